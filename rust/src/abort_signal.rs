@@ -0,0 +1,88 @@
+use std::future::Future;
+#[cfg(all(target_arch = "wasm32", not(target_os = "emscripten")))]
+use {
+    futures::future::{select, Either},
+    js_sys::{Function, Object, Promise, Reflect},
+    wasm_bindgen::{JsCast, JsValue},
+    wasm_bindgen_futures::JsFuture,
+    crate::error::ErrorCode,
+};
+use crate::js_error::JsError;
+
+/// An `AbortSignal`, taken as the optional last argument of every async entry
+/// point.
+#[cfg(all(target_arch = "wasm32", not(target_os = "emscripten")))]
+pub type AbortSignal = Object;
+
+/// Native builds have no `AbortSignal`, entry points always get `None`.
+#[cfg(not(all(target_arch = "wasm32", not(target_os = "emscripten"))))]
+pub enum AbortSignal {}
+
+/// Runs `future` until it completes or `signal` aborts. On abort the future
+/// is dropped, which aborts its in-flight fetches. Work that never awaits,
+/// like script evaluation itself, can't be interrupted and is only prevented
+/// from starting.
+#[cfg(all(target_arch = "wasm32", not(target_os = "emscripten")))]
+pub(crate) async fn with_abort_signal<T, F>(signal: Option<AbortSignal>, future: F) -> Result<T, JsError>
+where
+    F: Future<Output = Result<T, JsError>>,
+{
+    let Some(signal) = signal else {
+        return future.await;
+    };
+    if is_aborted(&signal) {
+        return Err(aborted_error(&signal));
+    }
+    let mut listener = None;
+    let aborted = Promise::new(&mut |resolve, _| {
+        let options = Object::new();
+        let _ = Reflect::set(&options, &JsValue::from_str("once"), &JsValue::TRUE);
+        if let Some(add_event_listener) = signal_method(&signal, "addEventListener") {
+            let _ = add_event_listener.call3(&signal, &JsValue::from_str("abort"), &resolve, &options);
+        }
+        listener = Some(resolve);
+    });
+    let result = match select(Box::pin(future), JsFuture::from(aborted)).await {
+        Either::Left((result, _)) => result,
+        Either::Right(_) => Err(aborted_error(&signal)),
+    };
+    // a long-lived signal shared by many calls would otherwise keep every listener
+    if let (Some(listener), Some(remove_event_listener)) = (listener, signal_method(&signal, "removeEventListener")) {
+        let _ = remove_event_listener.call2(&signal, &JsValue::from_str("abort"), &listener);
+    }
+    result
+}
+
+#[cfg(not(all(target_arch = "wasm32", not(target_os = "emscripten"))))]
+pub(crate) async fn with_abort_signal<T, F>(_signal: Option<AbortSignal>, future: F) -> Result<T, JsError>
+where
+    F: Future<Output = Result<T, JsError>>,
+{
+    future.await
+}
+
+#[cfg(all(target_arch = "wasm32", not(target_os = "emscripten")))]
+fn signal_method(signal: &Object, name: &str) -> Option<Function> {
+    Reflect::get(signal, &JsValue::from_str(name))
+        .ok()
+        .and_then(|f| f.dyn_into::<Function>().ok())
+}
+
+#[cfg(all(target_arch = "wasm32", not(target_os = "emscripten")))]
+fn is_aborted(signal: &Object) -> bool {
+    Reflect::get(signal, &JsValue::from_str("aborted"))
+        .ok()
+        .and_then(|aborted| aborted.as_bool())
+        .unwrap_or(false)
+}
+
+#[cfg(all(target_arch = "wasm32", not(target_os = "emscripten")))]
+fn aborted_error(signal: &Object) -> JsError {
+    let reason = Reflect::get(signal, &JsValue::from_str("reason"))
+        .ok()
+        .and_then(|reason| reason.as_string());
+    match reason {
+        Some(reason) => ErrorCode::Aborted.error(&format!("Aborted: {}", reason)),
+        None => ErrorCode::Aborted.error("Aborted"),
+    }
+}
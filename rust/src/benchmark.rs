@@ -0,0 +1,132 @@
+use serde::Serialize;
+use crate::bingen::wasm_bindgen;
+use crate::diagnostics::memory_bytes;
+use crate::error::ErrorCode;
+use crate::js_error::JsError;
+use crate::schemas::to_versioned_json;
+use crate::time_utils::now_ms;
+
+// every run's time is kept for the percentiles
+const MAX_ITERATIONS: u32 = 100_000;
+
+/// Arguments of the `execute_tx_scripts` operation, `utxos` and
+/// `protocol_params` as the JSON `execute_tx_scripts` takes.
+#[cfg(feature = "evaluator")]
+#[derive(serde::Deserialize, Debug)]
+struct EvaluationInput {
+    tx_hex: String,
+    utxos: serde_json::Value,
+    protocol_params: serde_json::Value,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, Default)]
+pub struct BenchmarkStats {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub median: f64,
+    pub p95: f64,
+    pub total: f64,
+}
+
+/// `first_ms` is the first run on its own, it includes any lazy
+/// initialization the later runs don't pay for.
+#[derive(Serialize, Debug, Clone)]
+pub struct Benchmark {
+    pub operation: String,
+    pub iterations: u32,
+    pub input_bytes: usize,
+    pub first_ms: f64,
+    pub time_ms: BenchmarkStats,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_bytes: Option<u64>,
+}
+
+/// Runs `operation` on `input` `iterations` times and reports the wall time
+/// of the runs, for comparing browsers and attaching numbers to reports of
+/// slow inputs. Operations are `cbor_to_json`, `decode_address`,
+/// `decode_plutus_data`, `decode_native_script`, `decode_plutus_script`,
+/// `decode_plutus_program` taking a hex (or bech32 address) and
+/// `execute_tx_scripts` taking `{"tx_hex": "...", "utxos": [...],
+/// "protocol_params": {...}}`. Fails with the operation's error if a run fails,
+/// `iterations` is at most 100 000.
+#[wasm_bindgen]
+pub fn run_benchmark(operation: &str, input: &str, iterations: u32) -> Result<String, JsError> {
+    if iterations > MAX_ITERATIONS {
+        return Err(ErrorCode::InvalidInput.error(&format!(
+            "{} iterations is above the limit of {}",
+            iterations, MAX_ITERATIONS
+        )));
+    }
+    let run = operation_runner(operation, input)?;
+    let iterations = iterations.max(1);
+
+    let mut times = Vec::new();
+    for _ in 0..iterations {
+        let started_at = now_ms();
+        run()?;
+        times.push(now_ms() - started_at);
+    }
+
+    to_versioned_json(&Benchmark {
+        operation: operation.to_string(),
+        iterations,
+        input_bytes: input.len(),
+        first_ms: times[0],
+        time_ms: benchmark_stats(&mut times),
+        memory_bytes: memory_bytes(),
+    })
+}
+
+/// The operation as a closure, with its input parsed up front so parsing
+/// isn't timed.
+fn operation_runner<'a>(operation: &str, input: &'a str) -> Result<Box<dyn Fn() -> Result<(), JsError> + 'a>, JsError> {
+    let runner: Box<dyn Fn() -> Result<(), JsError> + 'a> = match operation {
+        "cbor_to_json" => Box::new(move || crate::cbor_to_json(input).map(drop)),
+        #[cfg(feature = "csl-decoders")]
+        "decode_address" => Box::new(move || crate::csl_decoders::decode_address_with_extended_info(input).map(drop)),
+        #[cfg(feature = "csl-decoders")]
+        "decode_plutus_data" => Box::new(move || crate::csl_decoders::decode_plutus_data(input, 1).map(drop)),
+        #[cfg(feature = "csl-decoders")]
+        "decode_native_script" => Box::new(move || crate::csl_decoders::decode_native_script_with_extended_info(input).map(drop)),
+        #[cfg(feature = "csl-decoders")]
+        "decode_plutus_script" => Box::new(move || crate::csl_decoders::decode_plutus_script_with_extended_info(input).map(drop)),
+        #[cfg(feature = "evaluator")]
+        "decode_plutus_program" => Box::new(move || crate::plutus::plutus_decoder::decode_plutus_program_uplc_json(input).map(drop)),
+        #[cfg(feature = "evaluator")]
+        "execute_tx_scripts" => {
+            let input: EvaluationInput = serde_json::from_str(input)
+                .map_err(|e| ErrorCode::InvalidInput.error(&format!("Invalid evaluation input: {}", e)))?;
+            let utxo_json = input.utxos.to_string();
+            let protocol_params_json = input.protocol_params.to_string();
+            Box::new(move || {
+                crate::plutus::execute_tx_scripts::execute_tx_scripts(&input.tx_hex, &utxo_json, &protocol_params_json)
+                    .map(drop)
+            })
+        }
+        other => {
+            return Err(ErrorCode::InvalidInput.error(&format!(
+                "Unknown or disabled benchmark operation: {}",
+                other
+            )))
+        }
+    };
+    Ok(runner)
+}
+
+fn benchmark_stats(times: &mut [f64]) -> BenchmarkStats {
+    if times.is_empty() {
+        return BenchmarkStats::default();
+    }
+    times.sort_by(f64::total_cmp);
+    let total: f64 = times.iter().sum();
+    let percentile = |p: f64| times[((times.len() - 1) as f64 * p).round() as usize];
+    BenchmarkStats {
+        min: times[0],
+        max: times[times.len() - 1],
+        mean: total / times.len() as f64,
+        median: percentile(0.5),
+        p95: percentile(0.95),
+        total,
+    }
+}
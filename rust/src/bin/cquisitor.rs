@@ -0,0 +1,154 @@
+use std::fs;
+use std::process::ExitCode;
+use clap::{Parser, Subcommand, ValueEnum};
+use cquisitor_wasm::csl_decoders::decode_address_with_extended_info;
+use cquisitor_wasm::plutus::execute_tx_scripts::{
+    execute_tx_scripts, execute_tx_scripts_for_specific_network, validate_tx,
+};
+use cquisitor_wasm::plutus::tx_decoder::decode_transaction_with_extended_info;
+use cquisitor_wasm::{cbor_to_json, NetworkType};
+use serde::Serialize;
+use serde_json::Value;
+
+/// Inspect Cardano CBOR and evaluate transaction scripts from the terminal.
+///
+/// Arguments that take hex or JSON also take `@path` to read them from a file.
+#[derive(Parser)]
+#[command(name = "cquisitor", version)]
+struct Cli {
+    /// Indent the JSON output.
+    #[arg(long, global = true)]
+    pretty: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Decode any CBOR to JSON, with the position of every item.
+    Cbor { hex: String },
+    /// Decode a transaction with the serialization lib and the target of
+    /// each redeemer.
+    Tx {
+        hex: String,
+        /// Koios `utxo_info` JSON of the inputs, adds the addresses spent from.
+        #[arg(long)]
+        utxos: Option<String>,
+    },
+    /// Decode a bech32 or hex address and its credentials.
+    Address { address: String },
+    /// Evaluate the scripts of a transaction, against Koios or local files.
+    Evaluate {
+        tx_hex: String,
+        #[arg(long, value_enum, default_value = "mainnet", conflicts_with_all = ["utxos", "protocol_params"])]
+        network: Network,
+        /// Koios API token.
+        #[arg(long, env = "KOIOS_API_TOKEN")]
+        api_token: Option<String>,
+        /// Koios `utxo_info` JSON of every input, evaluates offline with
+        /// `--protocol-params`.
+        #[arg(long, requires = "protocol_params")]
+        utxos: Option<String>,
+        /// Protocol params as Koios, Ogmios, Blockfrost or cardano-cli JSON.
+        #[arg(long, requires = "utxos")]
+        protocol_params: Option<String>,
+    },
+    /// Run the ledger phase one checks on a transaction, then its scripts.
+    Validate {
+        tx_hex: String,
+        #[arg(long)]
+        utxos: String,
+        #[arg(long)]
+        protocol_params: String,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Network {
+    Mainnet,
+    Preprod,
+    Preview,
+}
+
+impl From<Network> for NetworkType {
+    fn from(network: Network) -> Self {
+        match network {
+            Network::Mainnet => NetworkType::Mainnet,
+            Network::Preprod => NetworkType::TestnetPreprod,
+            Network::Preview => NetworkType::TestnetPreview,
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli.command).await {
+        Ok(output) => {
+            let printed = if cli.pretty {
+                serde_json::to_string_pretty(&output)
+            } else {
+                serde_json::to_string(&output)
+            };
+            println!("{}", printed.unwrap_or_else(|e| e.to_string()));
+            ExitCode::SUCCESS
+        }
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(command: Command) -> Result<Value, String> {
+    match command {
+        Command::Cbor { hex } => {
+            let json = cbor_to_json(&read_arg(&hex)?).map_err(|e| e.to_string())?;
+            parse_json(&json)
+        }
+        Command::Tx { hex, utxos } => {
+            let utxos = utxos.map(|utxos| read_arg(&utxos)).transpose()?.unwrap_or_default();
+            let json = decode_transaction_with_extended_info(&read_arg(&hex)?, &utxos).map_err(|e| e.to_string())?;
+            parse_json(&json)
+        }
+        Command::Address { address } => {
+            to_value(&decode_address_with_extended_info(&read_arg(&address)?).map_err(|e| e.to_string())?)
+        }
+        Command::Evaluate { tx_hex, network, api_token, utxos, protocol_params } => {
+            let tx_hex = read_arg(&tx_hex)?;
+            let evaluation = match (utxos, protocol_params) {
+                (Some(utxos), Some(protocol_params)) => {
+                    execute_tx_scripts(&tx_hex, &read_arg(&utxos)?, &read_arg(&protocol_params)?)
+                }
+                _ => execute_tx_scripts_for_specific_network(&tx_hex, network.into(), api_token, None, None).await,
+            };
+            to_value(&evaluation.map_err(|e| e.to_string())?)
+        }
+        Command::Validate { tx_hex, utxos, protocol_params } => {
+            let evaluation = validate_tx(&read_arg(&tx_hex)?, &read_arg(&utxos)?, &read_arg(&protocol_params)?)
+                .map_err(|e| e.to_string())?;
+            if evaluation.results.iter().any(|result| result.error.is_some()) {
+                return Err(serde_json::to_string(&evaluation).map_err(|e| e.to_string())?);
+            }
+            to_value(&evaluation)
+        }
+    }
+}
+
+/// The argument itself, or the trimmed content of the file for `@path`.
+fn read_arg(arg: &str) -> Result<String, String> {
+    match arg.strip_prefix('@') {
+        Some(path) => fs::read_to_string(path)
+            .map(|content| content.trim().to_string())
+            .map_err(|e| format!("Can't read {}: {}", path, e)),
+        None => Ok(arg.trim().to_string()),
+    }
+}
+
+fn parse_json(json: &str) -> Result<Value, String> {
+    serde_json::from_str(json).map_err(|e| e.to_string())
+}
+
+fn to_value<T: Serialize>(value: &T) -> Result<Value, String> {
+    serde_json::to_value(value).map_err(|e| e.to_string())
+}
@@ -0,0 +1,123 @@
+use cardano_serialization_lib::{Block, Header, VRFCert};
+use minicbor::Decoder;
+use pallas_crypto::hash::Hasher;
+use serde::Serialize;
+use crate::bingen::wasm_bindgen;
+use crate::error::ErrorCode;
+use crate::input::decode_hex;
+use crate::js_error::JsError;
+use crate::schemas::to_versioned_json;
+
+// Praos domain separation of the single VRF output
+const LEADER_PREFIX: &[u8] = b"L";
+const NONCE_PREFIX: &[u8] = b"N";
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DecodedVrfCert {
+    pub output: String,
+    pub proof: String,
+}
+
+/// The VRF parts of a block header. Praos headers (Babbage on) carry one
+/// `vrf_result` the leader and nonce values are derived from, TPraos headers
+/// (Shelley to Alonzo) a `leader_vrf` and a `nonce_vrf`. `leader_value` is
+/// the leader value as a fraction of its range, the slot leader check passes
+/// when it's below `1 - (1 - f)^sigma`.
+#[derive(Serialize, Debug, Clone)]
+pub struct HeaderVrf {
+    pub block_number: u32,
+    pub slot: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
+    pub issuer_vkey: String,
+    pub vrf_vkey: String,
+    pub protocol: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vrf_result: Option<DecodedVrfCert>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub leader_vrf: Option<DecodedVrfCert>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce_vrf: Option<DecodedVrfCert>,
+    pub leader_value_hex: String,
+    pub leader_value: f64,
+    pub nonce_value_hex: String,
+}
+
+/// Decodes the VRF certificates of a block header and derives the leader and
+/// nonce values from them. Takes a header, a block, or a block wrapped with
+/// its era tag as the node serves it.
+#[wasm_bindgen]
+pub fn decode_header_vrf(hex: &str) -> Result<String, JsError> {
+    let bytes = decode_hex(hex)?;
+    to_versioned_json(&header_vrf(&parse_header(&bytes)?)?)
+}
+
+pub(crate) fn parse_header(bytes: &[u8]) -> Result<Header, JsError> {
+    if let Ok(header) = Header::from_bytes(bytes.to_vec()) {
+        return Ok(header);
+    }
+    if let Ok(block) = Block::from_bytes(bytes.to_vec()) {
+        return Ok(block.header());
+    }
+    // [era, block]
+    let mut decoder = Decoder::new(bytes);
+    let unwrapped = decoder.array().and_then(|_| decoder.u64());
+    match unwrapped {
+        Ok(_) => Block::from_bytes(bytes[decoder.position()..].to_vec())
+            .map(|block| block.header())
+            .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding block: {:?}", e))),
+        Err(_) => Err(ErrorCode::DecodeFailure.error("Neither a block header nor a block")),
+    }
+}
+
+pub(crate) fn header_vrf(header: &Header) -> Result<HeaderVrf, JsError> {
+    let body = header.header_body();
+    let mut decoded = HeaderVrf {
+        block_number: body.block_number(),
+        slot: body.slot_bignum().to_str(),
+        prev_hash: body.prev_hash().map(|hash| hash.to_hex()),
+        issuer_vkey: hex::encode(body.issuer_vkey().public_key().as_bytes()),
+        vrf_vkey: body.vrf_vkey().to_hex(),
+        protocol: String::new(),
+        vrf_result: None,
+        leader_vrf: None,
+        nonce_vrf: None,
+        leader_value_hex: String::new(),
+        leader_value: 0.0,
+        nonce_value_hex: String::new(),
+    };
+
+    if let Some(vrf_result) = body.vrf_result_or_nothing() {
+        let output = vrf_result.output();
+        let leader_value = Hasher::<256>::hash(&[LEADER_PREFIX, &output].concat());
+        let nonce_value = Hasher::<256>::hash(&Hasher::<256>::hash(&[NONCE_PREFIX, &output].concat())[..]);
+        decoded.protocol = "Praos".to_string();
+        decoded.leader_value = fraction(&leader_value[..]);
+        decoded.leader_value_hex = leader_value.to_string();
+        decoded.nonce_value_hex = nonce_value.to_string();
+        decoded.vrf_result = Some(vrf_cert(&vrf_result));
+    } else if let (Some(leader_vrf), Some(nonce_vrf)) = (body.leader_vrf_or_nothing(), body.nonce_vrf_or_nothing()) {
+        let leader_output = leader_vrf.output();
+        decoded.protocol = "TPraos".to_string();
+        decoded.leader_value = fraction(&leader_output);
+        decoded.leader_value_hex = hex::encode(&leader_output);
+        decoded.nonce_value_hex = Hasher::<256>::hash(&nonce_vrf.output()).to_string();
+        decoded.leader_vrf = Some(vrf_cert(&leader_vrf));
+        decoded.nonce_vrf = Some(vrf_cert(&nonce_vrf));
+    } else {
+        return Err(ErrorCode::DecodeFailure.error("The header has no VRF certificates"));
+    }
+    Ok(decoded)
+}
+
+fn vrf_cert(cert: &VRFCert) -> DecodedVrfCert {
+    DecodedVrfCert {
+        output: hex::encode(cert.output()),
+        proof: hex::encode(cert.proof()),
+    }
+}
+
+/// Big-endian natural over `2^(8 * len)`.
+pub(crate) fn fraction(bytes: &[u8]) -> f64 {
+    bytes.iter().rev().fold(0.0, |acc, byte| (acc + *byte as f64) / 256.0)
+}
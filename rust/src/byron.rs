@@ -0,0 +1,237 @@
+use cardano_serialization_lib::ByronAddress;
+use minicbor::data::{Tag, Type};
+use minicbor::decode::Error as CborError;
+use minicbor::Decoder;
+use pallas_crypto::hash::Hasher;
+use serde::Serialize;
+use serde_json::Value;
+use crate::bingen::wasm_bindgen;
+use crate::cbor::cbor_decoder::{get_tokenizer_at, get_value, minicbor_to_js_error};
+use crate::error::ErrorCode;
+use crate::input::decode_hex;
+use crate::js_error::JsError;
+use crate::schemas::to_versioned_json;
+
+const BYRON_EPOCH_SLOTS: u64 = 21_600;
+const MAIN_BLOCK_ERA: u64 = 1;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ByronInput {
+    pub tx_hash: String,
+    pub index: u32,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ByronOutput {
+    pub address: String,
+    pub amount: u64,
+}
+
+/// `kind` is `PkWitness`, `RedeemWitness` or `Unknown(<type>)`, the key and
+/// signature are those of the known kinds.
+#[derive(Serialize, Debug, Clone)]
+pub struct ByronWitness {
+    pub kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+/// A Byron `TxAux`, the tx and its witnesses. `attributes` is the CBOR JSON of
+/// the attributes map, empty on nearly every tx.
+#[derive(Serialize, Debug, Clone)]
+pub struct DecodedByronTx {
+    pub tx_hash: String,
+    pub inputs: Vec<ByronInput>,
+    pub outputs: Vec<ByronOutput>,
+    pub total_output: u64,
+    pub attributes: Value,
+    pub witnesses: Vec<ByronWitness>,
+}
+
+/// A Byron main block. `difficulty` is the chain length, the block number.
+#[derive(Serialize, Debug, Clone)]
+pub struct DecodedByronBlock {
+    pub hash: String,
+    pub protocol_magic: u32,
+    pub prev_hash: String,
+    pub epoch: u64,
+    pub slot_in_epoch: u64,
+    pub slot: u64,
+    pub difficulty: u64,
+    pub issuer_public_key: String,
+    pub txs: Vec<DecodedByronTx>,
+}
+
+/// Decodes a Byron tx, the `TxAux` `[tx, witnesses]` or the bare tx.
+#[wasm_bindgen]
+pub fn decode_byron_tx_with_extended_info(hex: &str) -> Result<String, JsError> {
+    let bytes = decode_hex(hex)?;
+    let mut decoder = Decoder::new(&bytes);
+    to_versioned_json(&read_tx_aux(&mut decoder, &bytes).map_err(minicbor_to_js_error)?)
+}
+
+/// Decodes a Byron main block, bare or wrapped with its era tag as the node
+/// serves it. Epoch boundary blocks hold no txs and are not supported.
+#[wasm_bindgen]
+pub fn decode_byron_block_with_extended_info(hex: &str) -> Result<String, JsError> {
+    let bytes = decode_hex(hex)?;
+    to_versioned_json(&byron_block(&bytes)?)
+}
+
+pub(crate) fn byron_block(bytes: &[u8]) -> Result<DecodedByronBlock, JsError> {
+    let mut decoder = Decoder::new(bytes);
+    let len = decoder.array().map_err(minicbor_to_js_error)?;
+    if len == Some(2) {
+        let era = decoder.u64().map_err(minicbor_to_js_error)?;
+        if era != MAIN_BLOCK_ERA {
+            return Err(ErrorCode::Unsupported.error(&format!("Era {} is not a Byron main block", era)));
+        }
+        decoder.array().map_err(minicbor_to_js_error)?;
+    }
+    read_block(&mut decoder, bytes).map_err(minicbor_to_js_error)
+}
+
+fn read_block(decoder: &mut Decoder, bytes: &[u8]) -> Result<DecodedByronBlock, CborError> {
+    // header: [protocol_magic, prev_block, body_proof, consensus_data, extra_data]
+    let header_start = decoder.position();
+    decoder.array()?;
+    let protocol_magic = decoder.u32()?;
+    let prev_hash = hex::encode(decoder.bytes()?);
+    decoder.skip()?;
+    // consensus_data: [[epoch, slot], issuer, [difficulty], signature]
+    decoder.array()?;
+    decoder.array()?;
+    let epoch = decoder.u64()?;
+    let slot_in_epoch = decoder.u64()?;
+    let issuer_public_key = hex::encode(decoder.bytes()?);
+    decoder.array()?;
+    let difficulty = decoder.u64()?;
+    decoder.skip()?;
+    decoder.skip()?;
+    let header = &bytes[header_start..decoder.position()];
+    // the hash covers the header wrapped as [1, header]
+    let hash = Hasher::<256>::hash(&[&[0x82, MAIN_BLOCK_ERA as u8][..], header].concat()).to_string();
+
+    // body: [tx_payload, ssc_payload, dlg_payload, upd_payload]
+    decoder.array()?;
+    let mut txs = Vec::new();
+    read_items(decoder, |decoder| {
+        txs.push(read_tx_aux(decoder, bytes)?);
+        Ok(())
+    })?;
+
+    Ok(DecodedByronBlock {
+        hash,
+        protocol_magic,
+        prev_hash,
+        epoch,
+        slot_in_epoch,
+        slot: epoch * BYRON_EPOCH_SLOTS + slot_in_epoch,
+        difficulty,
+        issuer_public_key,
+        txs,
+    })
+}
+
+fn read_tx_aux(decoder: &mut Decoder, bytes: &[u8]) -> Result<DecodedByronTx, CborError> {
+    let start = decoder.position();
+    // a TxAux is [tx, witnesses], a bare tx [inputs, outputs, attributes]
+    let is_bare_tx = decoder.array()? != Some(2);
+    if is_bare_tx {
+        decoder.set_position(start);
+    }
+    let tx_start = decoder.position();
+    decoder.array()?;
+    let mut inputs = Vec::new();
+    read_items(decoder, |decoder| {
+        decoder.array()?;
+        let kind = decoder.u8()?;
+        if kind != 0 {
+            return Err(CborError::message(format!("Unknown input type {}", kind)));
+        }
+        let mut input = encoded_cbor(decoder)?;
+        input.array()?;
+        inputs.push(ByronInput { tx_hash: hex::encode(input.bytes()?), index: input.u32()? });
+        Ok(())
+    })?;
+    let mut outputs = Vec::new();
+    read_items(decoder, |decoder| {
+        decoder.array()?;
+        let address_start = decoder.position();
+        decoder.skip()?;
+        let address_bytes = &bytes[address_start..decoder.position()];
+        let address = ByronAddress::from_bytes(address_bytes.to_vec())
+            .map(|address| address.to_base58())
+            .unwrap_or_else(|_| hex::encode(address_bytes));
+        outputs.push(ByronOutput { address, amount: decoder.u64()? });
+        Ok(())
+    })?;
+    let attributes_start = decoder.position();
+    decoder.skip()?;
+    let tx_hash = Hasher::<256>::hash(&bytes[tx_start..decoder.position()]).to_string();
+    let attributes = get_value(get_tokenizer_at(&bytes[..decoder.position()], attributes_start))
+        .ok()
+        .and_then(|value| value.as_array().and_then(|values| values.first().cloned()))
+        .unwrap_or(Value::Null);
+
+    let mut witnesses = Vec::new();
+    if !is_bare_tx {
+        read_items(decoder, |decoder| {
+            decoder.array()?;
+            let kind = decoder.u8()?;
+            let mut witness = ByronWitness { kind: format!("Unknown({})", kind), public_key: None, signature: None };
+            if kind == 0 || kind == 2 {
+                let mut inner = encoded_cbor(decoder)?;
+                inner.array()?;
+                witness.kind = if kind == 0 { "PkWitness" } else { "RedeemWitness" }.to_string();
+                witness.public_key = Some(hex::encode(inner.bytes()?));
+                witness.signature = Some(hex::encode(inner.bytes()?));
+            } else {
+                decoder.skip()?;
+            }
+            witnesses.push(witness);
+            Ok(())
+        })?;
+    }
+
+    Ok(DecodedByronTx {
+        tx_hash,
+        total_output: outputs.iter().map(|output| output.amount).sum(),
+        inputs,
+        outputs,
+        attributes,
+        witnesses,
+    })
+}
+
+/// Calls `read` on each item of a definite or indefinite array.
+fn read_items<'b>(
+    decoder: &mut Decoder<'b>,
+    mut read: impl FnMut(&mut Decoder<'b>) -> Result<(), CborError>,
+) -> Result<(), CborError> {
+    let len = decoder.array()?;
+    let mut index = 0;
+    loop {
+        match len {
+            Some(len) if index >= len => return Ok(()),
+            None if decoder.datatype()? == Type::Break => {
+                decoder.set_position(decoder.position() + 1);
+                return Ok(());
+            }
+            _ => {}
+        }
+        read(decoder)?;
+        index += 1;
+    }
+}
+
+/// Decoder over the CBOR inside a tag 24 bytestring.
+fn encoded_cbor<'b>(decoder: &mut Decoder<'b>) -> Result<Decoder<'b>, CborError> {
+    // tag 24, Byron wraps nested CBOR in it
+    if decoder.tag()? != Tag::Cbor {
+        return Err(CborError::message("Expected tag 24"));
+    }
+    Ok(Decoder::new(decoder.bytes()?))
+}
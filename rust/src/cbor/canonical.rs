@@ -0,0 +1,263 @@
+use serde::Serialize;
+use crate::bingen::wasm_bindgen;
+use crate::cbor::cbor_encoder::{
+    push_head, MAJOR_ARRAY, MAJOR_BYTES, MAJOR_MAP, MAJOR_NEGATIVE, MAJOR_SIMPLE, MAJOR_TAG, MAJOR_TEXT,
+    MAJOR_UNSIGNED,
+};
+use crate::error::ErrorCode;
+use crate::input::decode_hex;
+use crate::js_error::JsError;
+use crate::schemas::to_versioned_json;
+
+const INDEFINITE: u8 = 31;
+const BREAK: u8 = 0xff;
+// deeper nesting is reported rather than overflowing the stack
+const MAX_DEPTH: usize = 512;
+
+/// One rewrite of the input, `offset` and `length` locate the rewritten
+/// bytes in it: the whole item for an indefinite length or unsorted map keys,
+/// the head for a non-minimal one.
+#[derive(Serialize, Debug, Clone)]
+pub struct CanonicalFix {
+    pub offset: usize,
+    pub length: usize,
+    pub rule: String,
+    pub description: String,
+}
+
+/// `fixed_hex` is the input with every fix applied, equal to the input when
+/// it was already `canonical`.
+#[derive(Serialize, Debug, Clone)]
+pub struct CanonicalReport {
+    pub canonical: bool,
+    pub fixes: Vec<CanonicalFix>,
+    pub fixed_hex: String,
+}
+
+/// Rewrites CBOR in its canonical form (RFC 8949 §4.2.1 core deterministic
+/// encoding) and lists what was rewritten where: indefinite-length arrays,
+/// maps, byte and text strings become definite, strings joining their
+/// chunks, ints, lengths and tags written with a wider argument than their
+/// value needs (24 as `18 18` is fine, as `19 00 18` it isn't) get the
+/// shortest one, and map entries are sorted by the bytes of their encoded
+/// keys. Hashes are over the bytes as sent, so this shows what a serializer
+/// has to change to match one computed over the canonical form.
+#[wasm_bindgen]
+pub fn canonicalize_cbor(hex: &str) -> Result<String, JsError> {
+    let bytes = decode_hex(hex)?;
+    to_versioned_json(&canonical_report(&bytes)?)
+}
+
+pub(crate) fn canonical_report(bytes: &[u8]) -> Result<CanonicalReport, JsError> {
+    let mut canonicalizer = Canonicalizer { bytes, fixes: Vec::new() };
+    let mut fixed = Vec::with_capacity(bytes.len());
+    let end = canonicalizer.item(0, &mut fixed, 0)?;
+    if end != bytes.len() {
+        return Err(ErrorCode::DecodeFailure.error(&format!("Trailing bytes after offset {}", end)));
+    }
+    let mut fixes = canonicalizer.fixes;
+    fixes.sort_by_key(|fix| fix.offset);
+    Ok(CanonicalReport { canonical: fixes.is_empty(), fixes, fixed_hex: hex::encode(fixed) })
+}
+
+pub(crate) struct Head {
+    pub(crate) major: u8,
+    /// `None` for an indefinite length or a break.
+    pub(crate) argument: Option<u64>,
+    pub(crate) length: usize,
+}
+
+pub(crate) fn read_head(bytes: &[u8], offset: usize) -> Result<Head, JsError> {
+    let first = *bytes.get(offset).ok_or_else(|| truncated(offset))?;
+    let (major, info) = (first >> 5, first & 0x1f);
+    let size = match info {
+        0..=23 | INDEFINITE => 0,
+        24 => 1,
+        25 => 2,
+        26 => 4,
+        27 => 8,
+        _ => {
+            return Err(ErrorCode::DecodeFailure.error(&format!("Reserved additional info {} at offset {}", info, offset)))
+        }
+    };
+    let argument_bytes = bytes.get(offset + 1..offset + 1 + size).ok_or_else(|| truncated(offset))?;
+    let argument = match info {
+        0..=23 => Some(info as u64),
+        INDEFINITE => None,
+        _ => Some(argument_bytes.iter().fold(0u64, |acc, byte| acc << 8 | *byte as u64)),
+    };
+    Ok(Head { major, argument, length: 1 + size })
+}
+
+fn truncated(offset: usize) -> JsError {
+    ErrorCode::DecodeFailure.error(&format!("Unexpected end of input in the item at offset {}", offset))
+}
+
+struct Canonicalizer<'b> {
+    bytes: &'b [u8],
+    fixes: Vec<CanonicalFix>,
+}
+
+impl<'b> Canonicalizer<'b> {
+    /// Writes the canonical form of the item at `offset` to `out` and returns
+    /// the offset past it.
+    fn item(&mut self, offset: usize, out: &mut Vec<u8>, depth: usize) -> Result<usize, JsError> {
+        if depth > MAX_DEPTH {
+            return Err(ErrorCode::InputTooLarge.error(&format!("Nesting deeper than {} at offset {}", MAX_DEPTH, offset)));
+        }
+        let head = read_head(self.bytes, offset)?;
+        let mut position = offset + head.length;
+        match (head.major, head.argument) {
+            (MAJOR_BYTES | MAJOR_TEXT, None) => {
+                let mut content = Vec::new();
+                let mut chunks = 0;
+                while self.peek(position)? != BREAK {
+                    let chunk = read_head(self.bytes, position)?;
+                    let length = match chunk.argument {
+                        Some(length) if chunk.major == head.major => length as usize,
+                        _ => {
+                            return Err(ErrorCode::DecodeFailure.error(&format!(
+                                "Invalid chunk at offset {} of the indefinite string at offset {}",
+                                position, offset
+                            )))
+                        }
+                    };
+                    content.extend(self.slice(position + chunk.length, length)?);
+                    position += chunk.length + length;
+                    chunks += 1;
+                }
+                position += 1;
+                push_head(out, head.major, content.len() as u64);
+                out.extend(content);
+                let kind = if head.major == MAJOR_BYTES { "byte string" } else { "text string" };
+                self.fix(offset, position, "IndefiniteLength", format!("indefinite {} of {} chunks joined into one definite {}", kind, chunks, kind));
+            }
+            (MAJOR_BYTES | MAJOR_TEXT, Some(length)) => {
+                self.head(offset, &head, out);
+                out.extend(self.slice(position, length as usize)?);
+                position += length as usize;
+            }
+            (MAJOR_ARRAY, None) => {
+                let mut items = Vec::new();
+                let mut count = 0u64;
+                while self.peek(position)? != BREAK {
+                    position = self.item(position, &mut items, depth + 1)?;
+                    count += 1;
+                }
+                position += 1;
+                push_head(out, head.major, count);
+                out.extend(items);
+                self.fix(offset, position, "IndefiniteLength", format!("indefinite array of {} entries written with a definite length", count));
+            }
+            (MAJOR_ARRAY, Some(count)) => {
+                self.head(offset, &head, out);
+                for _ in 0..count {
+                    position = self.item(position, out, depth + 1)?;
+                }
+            }
+            (MAJOR_MAP, None) => {
+                let mut entries = Vec::new();
+                while self.peek(position)? != BREAK {
+                    position = self.entry(position, &mut entries, depth)?;
+                }
+                position += 1;
+                push_head(out, head.major, entries.len() as u64);
+                self.fix(offset, position, "IndefiniteLength", format!("indefinite map of {} entries written with a definite length", entries.len()));
+                self.sorted_entries(offset, position, entries, out);
+            }
+            (MAJOR_MAP, Some(count)) => {
+                self.head(offset, &head, out);
+                let mut entries = Vec::new();
+                for _ in 0..count {
+                    position = self.entry(position, &mut entries, depth)?;
+                }
+                self.sorted_entries(offset, position, entries, out);
+            }
+            (MAJOR_TAG, Some(_)) => {
+                self.head(offset, &head, out);
+                position = self.item(position, out, depth + 1)?;
+            }
+            (MAJOR_SIMPLE, None) => {
+                return Err(ErrorCode::DecodeFailure.error(&format!("Unexpected break at offset {}", offset)));
+            }
+            (_, None) => {
+                return Err(ErrorCode::DecodeFailure.error(&format!("Invalid indefinite length at offset {}", offset)));
+            }
+            // ints, simple values and floats, whose argument is the value
+            (_, Some(_)) => self.head(offset, &head, out),
+        }
+        Ok(position)
+    }
+
+    /// Reads the map entry at `offset` into `entries`, key and value in their
+    /// canonical form, and returns the offset past it.
+    fn entry(&mut self, offset: usize, entries: &mut Vec<(Vec<u8>, Vec<u8>)>, depth: usize) -> Result<usize, JsError> {
+        let mut key = Vec::new();
+        let position = self.item(offset, &mut key, depth + 1)?;
+        let mut value = Vec::new();
+        let position = self.item(position, &mut value, depth + 1)?;
+        entries.push((key, value));
+        Ok(position)
+    }
+
+    /// Writes the entries of the map spanning `offset..end` sorted by the
+    /// bytes of their encoded keys, bytewise lexicographic as RFC 8949 §4.2.1
+    /// orders them.
+    fn sorted_entries(&mut self, offset: usize, end: usize, mut entries: Vec<(Vec<u8>, Vec<u8>)>, out: &mut Vec<u8>) {
+        if entries.windows(2).any(|pair| pair[0].0 > pair[1].0) {
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            self.fix(offset, end, "MapKeyOrder", format!("map of {} entries reordered by their encoded keys", entries.len()));
+        }
+        for (key, value) in entries {
+            out.extend(key);
+            out.extend(value);
+        }
+    }
+
+    /// Writes the head of a definite item with the shortest argument. Simple
+    /// values and floats are copied, a float's width is its precision.
+    fn head(&mut self, offset: usize, head: &Head, out: &mut Vec<u8>) {
+        let start = out.len();
+        match head.argument {
+            Some(argument) if head.major != MAJOR_SIMPLE => push_head(out, head.major, argument),
+            _ => out.extend(&self.bytes[offset..offset + head.length]),
+        }
+        let minimal_length = out.len() - start;
+        if minimal_length < head.length {
+            let argument = head.argument.unwrap_or_default();
+            let kind = match head.major {
+                MAJOR_UNSIGNED => "unsigned int",
+                MAJOR_NEGATIVE => "negative int",
+                MAJOR_BYTES => "byte string length",
+                MAJOR_TEXT => "text string length",
+                MAJOR_ARRAY => "array length",
+                MAJOR_MAP => "map length",
+                _ => "tag",
+            };
+            self.fix(
+                offset,
+                offset + head.length,
+                "NonMinimalEncoding",
+                format!(
+                    "{} {} written in {} bytes where {} suffice",
+                    kind, argument, head.length, minimal_length
+                ),
+            );
+        }
+    }
+
+    fn peek(&self, offset: usize) -> Result<u8, JsError> {
+        self.bytes.get(offset).copied().ok_or_else(|| truncated(offset))
+    }
+
+    fn slice(&self, offset: usize, length: usize) -> Result<&'b [u8], JsError> {
+        offset
+            .checked_add(length)
+            .and_then(|end| self.bytes.get(offset..end))
+            .ok_or_else(|| truncated(offset))
+    }
+
+    fn fix(&mut self, offset: usize, end: usize, rule: &str, description: String) {
+        self.fixes.push(CanonicalFix { offset, length: end - offset, rule: rule.to_string(), description });
+    }
+}
@@ -1,13 +1,17 @@
 use std::convert::TryInto;
 
-use minicbor::data::Tag;
+use minicbor::data::{Tag, Type};
 use minicbor::decode::{ExtendedToken, ExtendedTokenizer};
 use minicbor::decode::Token;
 use minicbor::decode::Decoder;
 use minicbor::decode::Error as CborError;
 use serde_json::{Number, Value};
+use crate::error::ErrorCode;
 use crate::js_error::JsError;
 
+// RFC 9090 rational, [numerator, denominator], the protocol params use it
+const RATIONAL_TAG: u64 = 30;
+
 #[derive(Clone, Debug)]
 pub struct CborPos {
     offset: usize,
@@ -39,7 +43,7 @@ impl CborCollection {
             Token::Array(len) => Ok(CborCollection::Array(Value::Array(Vec::new()), Some(len as usize), 0, pos.clone(), pos)),
             Token::Map(len) => Ok(CborCollection::Map(Value::Array(Vec::new()), None, Some(len as usize), 0, pos.clone(), pos)),
             Token::Tag(tag) => Ok(CborCollection::Tag(None, tag, pos.clone(), pos)),
-            _ => Err(JsError::new("Invalid token")),
+            _ => Err(ErrorCode::DecodeFailure.error("Invalid token")),
         }
     }
 
@@ -48,10 +52,10 @@ impl CborCollection {
             CborCollection::Array(array, len, count, _, total_size) => {
                 if let Some(len) = len {
                     if *count >= *len {
-                        return Err(JsError::new("Array is full"));
+                        return Err(ErrorCode::DecodeFailure.error("Array is full"));
                     }
                 }
-                let array = array.as_array_mut().ok_or(JsError::new("Invalid array"))?;
+                let array = array.as_array_mut().ok_or_else(|| ErrorCode::DecodeFailure.error("Invalid array"))?;
                 array.push(new_value);
                 if !finalizer {
                     *count += 1;
@@ -62,17 +66,20 @@ impl CborCollection {
             CborCollection::Map(map, key, len, count, _, total_size) => {
                 if let Some(len) = len {
                     if *count >= *len {
-                        return Err(JsError::new("Map is full"));
+                        return Err(ErrorCode::DecodeFailure.error("Map is full"));
                     }
                 }
 
-                if key.is_none() {
-                    *key = Some(new_value.clone());
-                    return Ok(());
-                }
+                let map_key = match key.take() {
+                    Some(map_key) => map_key,
+                    None => {
+                        *key = Some(new_value);
+                        return Ok(());
+                    }
+                };
 
-                let map = map.as_array_mut().ok_or(JsError::new("Invalid array"))?;
-                map.push(build_map_value(key.clone().unwrap(), new_value));
+                let map = map.as_array_mut().ok_or_else(|| ErrorCode::DecodeFailure.error("Invalid array"))?;
+                map.push(build_map_value(map_key, new_value));
                 if !finalizer {
                     *count += 1;
                 }
@@ -82,7 +89,7 @@ impl CborCollection {
             },
             CborCollection::Tag(value, _, _, total_size) => {
                 if let Some(_) = value {
-                    return Err(JsError::new("Tag already has a value"));
+                    return Err(ErrorCode::DecodeFailure.error("Tag already has a value"));
                 }
                 *value = Some(new_value);
                 *total_size = extend_pos(total_size, value_pos);
@@ -134,13 +141,18 @@ impl CborCollection {
             },
             CborCollection::Tag(value, tag, pos, full_struct_pos) => {
                 let mut map = serde_json::Map::new();
-                let value = value.clone().ok_or(JsError::new("Tag has no value"))?;
+                let value = value.clone().ok_or_else(|| ErrorCode::DecodeFailure.error("Tag has no value"))?;
                 let position_info = cbor_pos_to_value(&pos);
                 let full_position_info = cbor_pos_to_value(&full_struct_pos);
                 map.insert(String::from("type"), Value::String(String::from("tag")));
                 map.insert(String::from("position_info"), position_info);
                 map.insert(String::from("struct_position_info"), full_position_info);
                 map.insert(String::from("tag"), Value::String(get_tag_name(&tag)));
+                if tag == Tag::Unassigned(RATIONAL_TAG) {
+                    if let Some(rational) = rational_value(&value) {
+                        map.insert(String::from("rational"), rational);
+                    }
+                }
                 map.insert(String::from("value"), value);
                 Ok(Value::Object(map))
             },
@@ -164,6 +176,38 @@ impl CborCollection {
     }
 }
 
+/// `numerator/denominator` and its value of a tag 30 array of two ints with a
+/// positive denominator, as `smart_data` renders Plutus rationals.
+fn rational_value(value: &Value) -> Option<Value> {
+    let int = |item: &Value| -> Option<i128> {
+        let value = item.get("value")?;
+        value
+            .as_i64()
+            .map(i128::from)
+            .or_else(|| value.as_u64().map(i128::from))
+            .or_else(|| value.as_str().and_then(|text| text.parse().ok()))
+    };
+    let items: Vec<&Value> = value
+        .get("values")?
+        .as_array()?
+        .iter()
+        .filter(|item| item.get("type").and_then(Value::as_str) != Some("Break"))
+        .collect();
+    let [numerator, denominator] = items.as_slice() else {
+        return None;
+    };
+    let (numerator, denominator) = (int(numerator)?, int(denominator)?);
+    if denominator <= 0 {
+        return None;
+    }
+    Some(serde_json::json!({
+        "numerator": numerator.to_string(),
+        "denominator": denominator.to_string(),
+        "text": format!("{}/{}", numerator, denominator),
+        "ratio": numerator as f64 / denominator as f64,
+    }))
+}
+
 pub fn extend_pos(struct_pos: &CborPos, value_pos: &CborPos) -> CborPos {
     let value_end = value_pos.offset + value_pos.length;
     let struct_end = struct_pos.offset + struct_pos.length;
@@ -181,59 +225,146 @@ pub fn get_tokenizer(data: &[u8]) -> ExtendedTokenizer {
     Decoder::new(data).into()
 }
 
-pub fn get_value(tokenizer: ExtendedTokenizer) -> Result<Value, JsError> {
-    let mut collections = Vec::<CborCollection>::new();
-    let root_collection = CborCollection::new_array();
-    collections.push(root_collection);
+/// Calls `visit` with each key of the body map of a tx (or of a bare body)
+/// and the decoder positioned at its value. `visit` returns whether it read
+/// the value, values it didn't read are skipped. Reading the raw map keeps
+/// what full decoders reject or normalize, like duplicate set entries.
+pub(crate) fn visit_tx_body<'b>(
+    tx_bytes: &'b [u8],
+    visit: impl FnMut(u64, &mut Decoder<'b>) -> Result<bool, CborError>,
+) -> Result<(), CborError> {
+    let mut decoder = Decoder::new(tx_bytes);
+    if decoder.datatype()? == Type::Array {
+        decoder.array()?;
+    }
+    visit_map(&mut decoder, visit)
+}
 
+/// Same as `visit_tx_body` for the witness set map of a full tx.
+pub(crate) fn visit_tx_witness_set<'b>(
+    tx_bytes: &'b [u8],
+    visit: impl FnMut(u64, &mut Decoder<'b>) -> Result<bool, CborError>,
+) -> Result<(), CborError> {
+    let mut decoder = Decoder::new(tx_bytes);
+    decoder.array()?;
+    decoder.skip()?;
+    visit_map(&mut decoder, visit)
+}
+
+fn visit_map<'b>(
+    decoder: &mut Decoder<'b>,
+    mut visit: impl FnMut(u64, &mut Decoder<'b>) -> Result<bool, CborError>,
+) -> Result<(), CborError> {
+    let entries = decoder.map()?;
+    let mut read = 0;
+    loop {
+        match entries {
+            Some(len) if read >= len => break,
+            None if decoder.datatype()? == Type::Break => break,
+            _ => {}
+        }
+        let key = decoder.u64()?;
+        if !visit(key, decoder)? {
+            decoder.skip()?;
+        }
+        read += 1;
+    }
+    Ok(())
+}
+
+/// Tokenizer over `data` starting at `position`, token offsets stay relative
+/// to the start of `data`.
+pub fn get_tokenizer_at(data: &[u8], position: usize) -> ExtendedTokenizer {
+    let mut decoder = Decoder::new(data);
+    decoder.set_position(position);
+    decoder.into()
+}
+
+pub fn get_value(tokenizer: ExtendedTokenizer) -> Result<Value, JsError> {
+    let mut state = CborDecodeState::new();
     for token in tokenizer {
         let token = token.map_err(|err|  minicbor_to_js_error(err))?;
+        state.push_token(&token)?;
+    }
+    state.finish()
+}
 
+/// Collections still open while decoding, so decoding can stop after any
+/// token and resume from `position` later.
+#[derive(Clone, Debug)]
+pub struct CborDecodeState {
+    collections: Vec<CborCollection>,
+    position: usize,
+}
+
+impl CborDecodeState {
+    pub fn new() -> CborDecodeState {
+        CborDecodeState {
+            collections: vec![CborCollection::new_array()],
+            position: 0,
+        }
+    }
+
+    /// Offset of the first byte not decoded yet.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn push_token(&mut self, token: &ExtendedToken) -> Result<(), JsError> {
         let token_pos = CborPos {
             offset: token.offset,
             length: token.length,
         };
+        self.position = token.offset + token.length;
 
-        collections = collapse_collections(collections)?;
+        let mut collections = collapse_collections(std::mem::take(&mut self.collections))?;
 
         if is_collection_finished(&token.token) {
-            let mut last_collection = collections.pop().unwrap();
-            let finalizer = extended_token_to_value(&token, &token_pos)?;
+            // the root collection is never closed by a break
+            if collections.len() < 2 {
+                return Err(ErrorCode::DecodeFailure.error("Unexpected break outside of an indefinite collection"));
+            }
+            let mut last_collection = pop_collection(&mut collections)?;
+            let finalizer = extended_token_to_value(token, &token_pos)?;
             last_collection.add_value(finalizer, &token_pos, true)?;
             let collection_pos = last_collection.get_full_pos();
-            collections.last_mut().unwrap().add_value(
+            last_collection_mut(&mut collections)?.add_value(
                 last_collection.to_value()?,
                 &collection_pos,
                 false)?;
-            continue;
+        } else if is_token_collection(&token.token) {
+            collections.push(CborCollection::new_collection(token)?);
+        } else {
+            let new_value = extended_token_to_value(token, &token_pos)?;
+            last_collection_mut(&mut collections)?.add_value(new_value, &token_pos, false)?;
         }
 
-        collections = collapse_collections(collections)?;
+        self.collections = collections;
+        Ok(())
+    }
 
-        if is_token_collection(&token.token) {
-            let new_collection = CborCollection::new_collection(&token)?;
-            collections.push(new_collection);
-            continue;
+    pub fn finish(self) -> Result<Value, JsError> {
+        let mut collections = collapse_collections(self.collections)?;
+
+        if collections.len() != 1 {
+            return Err(ErrorCode::DecodeFailure.error("Invalid CBOR"));
         }
 
-        let new_value = extended_token_to_value(&token, &token_pos)?;
-        collections.last_mut().unwrap().add_value(new_value, &token_pos, false)?;
+        Ok(pop_collection(&mut collections)?.to_simple_value())
     }
+}
 
-    collections = collapse_collections(collections)?;
-
-    if collections.len() != 1 {
-        return Err(JsError::new("Invalid CBOR"));
+impl Default for CborDecodeState {
+    fn default() -> Self {
+        CborDecodeState::new()
     }
-
-    Ok(collections.pop().unwrap().to_simple_value())
 }
 
 pub fn collapse_collections(mut collections: Vec<CborCollection>) -> Result<Vec<CborCollection>, JsError> {
-    while collections.last().unwrap().is_collection_finished() {
-        let last_collection = collections.pop().unwrap();
+    while collections.len() > 1 && last_collection_mut(&mut collections)?.is_collection_finished() {
+        let last_collection = pop_collection(&mut collections)?;
         let collection_pos = last_collection.get_full_pos();
-        collections.last_mut().unwrap().add_value(
+        last_collection_mut(&mut collections)?.add_value(
             last_collection.to_value()?,
             &collection_pos,
             false)?;
@@ -241,6 +372,14 @@ pub fn collapse_collections(mut collections: Vec<CborCollection>) -> Result<Vec<
     Ok(collections)
 }
 
+fn pop_collection(collections: &mut Vec<CborCollection>) -> Result<CborCollection, JsError> {
+    collections.pop().ok_or_else(|| ErrorCode::DecodeFailure.error("Invalid CBOR structure"))
+}
+
+fn last_collection_mut(collections: &mut [CborCollection]) -> Result<&mut CborCollection, JsError> {
+    collections.last_mut().ok_or_else(|| ErrorCode::DecodeFailure.error("Invalid CBOR structure"))
+}
+
 pub fn is_token_collection(token: &Token) -> bool {
     match token {
         Token::Array(_) => true,
@@ -286,9 +425,39 @@ pub fn extended_token_to_value(token: &ExtendedToken, pos: &CborPos) -> Result<V
     map.insert(String::from("position_info"), position_info);
     map.insert(String::from("type"), token_type);
     map.insert(String::from("value"), token_value);
+    if let Token::Bytes(bytes) = &token.token {
+        if let Some((text, confidence)) = guess_text(bytes) {
+            map.insert(String::from("text_guess"), serde_json::json!({ "text": text, "confidence": confidence }));
+        }
+    }
     Ok(Value::Object(map))
 }
 
+// below this share of printable ASCII bytes a string isn't guessed to be text
+const MIN_PRINTABLE_SHARE: f64 = 0.75;
+
+/// Text a byte string likely holds, as asset names and metadata often do.
+/// `high` confidence when it's printable UTF-8 with a letter or digit in it,
+/// `low` when only most bytes are printable ASCII, the others shown as `.`.
+pub(crate) fn guess_text(bytes: &[u8]) -> Option<(String, &'static str)> {
+    if bytes.len() < 2 {
+        return None;
+    }
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        let printable = text.chars().all(|c| !c.is_control() || c == '\n' || c == '\t');
+        if printable && text.chars().any(char::is_alphanumeric) {
+            return Some((text.to_string(), "high"));
+        }
+    }
+    let is_printable = |byte: &u8| (0x20..0x7f).contains(byte);
+    let printable = bytes.iter().filter(|byte| is_printable(byte)).count();
+    if (printable as f64) < bytes.len() as f64 * MIN_PRINTABLE_SHARE || !bytes.iter().any(u8::is_ascii_alphanumeric) {
+        return None;
+    }
+    let text = bytes.iter().map(|byte| if is_printable(byte) { *byte as char } else { '.' }).collect();
+    Some((text, "low"))
+}
+
 pub fn token_to_value(token: &Token) -> Result<Value, JsError> {
     match *token {
         Token::Null => Ok(Value::Null),
@@ -301,16 +470,35 @@ pub fn token_to_value(token: &Token) -> Result<Value, JsError> {
         Token::I16(i) => Ok(Value::Number(i.into())),
         Token::I32(i) => Ok(Value::Number(i.into())),
         Token::I64(i) => Ok(Value::Number(i.into())),
-        Token::Int(i) => Ok(Value::Number(<minicbor::data::Int as TryInto<u64>>::try_into(i).unwrap().into())),
-        Token::F16(f) => Ok(Value::Number(Number::from_f64(f.into()).unwrap())),
-        Token::F32(f) => Ok(Value::Number(Number::from_f64(f.into()).unwrap())),
-        Token::F64(f) => Ok(Value::Number(Number::from_f64(f.into()).unwrap())),
+        Token::Int(i) => Ok(int_to_value(i)),
+        Token::F16(f) => Ok(float_to_value(f.into())),
+        Token::F32(f) => Ok(float_to_value(f.into())),
+        Token::F64(f) => Ok(float_to_value(f)),
         Token::Bytes(b) => Ok(Value::String(hex::encode(b))),
         Token::String(t) => Ok(Value::String(t.to_string())),
         Token::Simple(s) => Ok(Value::Number(s.into())),
         Token::Undefined => Ok(Value::Null),
         Token::Break => Ok(Value::Null),
-        _ => Err(JsError::new("Token is not a value")),
+        _ => Err(ErrorCode::DecodeFailure.error("Token is not a value")),
+    }
+}
+
+/// Ints beyond the i64/u64 range (down to -2^64) are rendered as strings.
+fn int_to_value(i: minicbor::data::Int) -> Value {
+    if let Ok(u) = <minicbor::data::Int as TryInto<u64>>::try_into(i) {
+        return Value::Number(u.into());
+    }
+    if let Ok(n) = <minicbor::data::Int as TryInto<i64>>::try_into(i) {
+        return Value::Number(n.into());
+    }
+    Value::String(i128::from(i).to_string())
+}
+
+/// NaN and infinities have no JSON number, they are rendered as strings.
+fn float_to_value(f: f64) -> Value {
+    match Number::from_f64(f) {
+        Some(n) => Value::Number(n),
+        None => Value::String(f.to_string()),
     }
 }
 
@@ -367,15 +555,15 @@ pub fn get_tag_name(tag: &Tag) -> String {
 }
 
 pub fn to_js_error(error: CborError) -> JsError {
-    JsError::new(&format!("{:?}", error))
+    ErrorCode::DecodeFailure.error(&format!("{:?}", error))
 }
 
 pub fn minicbor_to_js_error(error: minicbor::decode::Error) -> JsError {
-    JsError::new(&format!("{:?}", error))
+    ErrorCode::DecodeFailure.error(&format!("{:?}", error))
 }
 
 pub fn fromhex_to_js_error(error: hex::FromHexError) -> JsError {
-    JsError::new(&format!("{:?}", error))
+    ErrorCode::InvalidHex.error(&format!("{:?}", error))
 }
 
 pub fn build_map_value(key: Value, value: Value) -> Value {
@@ -0,0 +1,50 @@
+// Minimal canonical CBOR writing for the few places that build bytes to hash
+// or to hand back, where the exact encoding matters.
+
+pub(crate) const MAJOR_UNSIGNED: u8 = 0;
+pub(crate) const MAJOR_NEGATIVE: u8 = 1;
+pub(crate) const MAJOR_BYTES: u8 = 2;
+pub(crate) const MAJOR_TEXT: u8 = 3;
+pub(crate) const MAJOR_ARRAY: u8 = 4;
+pub(crate) const MAJOR_MAP: u8 = 5;
+pub(crate) const MAJOR_TAG: u8 = 6;
+pub(crate) const MAJOR_SIMPLE: u8 = 7;
+
+/// Shortest head of major type `major` with argument `value`.
+pub(crate) fn push_head(out: &mut Vec<u8>, major: u8, value: u64) {
+    let major = major << 5;
+    match value {
+        0..=23 => out.push(major | value as u8),
+        24..=0xff => out.extend([major | 24, value as u8]),
+        0x100..=0xffff => {
+            out.push(major | 25);
+            out.extend((value as u16).to_be_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            out.push(major | 26);
+            out.extend((value as u32).to_be_bytes());
+        }
+        _ => {
+            out.push(major | 27);
+            out.extend(value.to_be_bytes());
+        }
+    }
+}
+
+pub(crate) fn push_int(out: &mut Vec<u8>, value: i64) {
+    if value >= 0 {
+        push_head(out, MAJOR_UNSIGNED, value as u64);
+    } else {
+        push_head(out, MAJOR_NEGATIVE, !value as u64);
+    }
+}
+
+pub(crate) fn push_text(out: &mut Vec<u8>, text: &str) {
+    push_head(out, MAJOR_TEXT, text.len() as u64);
+    out.extend(text.as_bytes());
+}
+
+pub(crate) fn push_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    push_head(out, MAJOR_BYTES, bytes.len() as u64);
+    out.extend(bytes);
+}
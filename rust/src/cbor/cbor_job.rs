@@ -0,0 +1,99 @@
+use serde_json::Value;
+use crate::bingen::wasm_bindgen;
+use crate::cbor::cbor_decoder::{get_tokenizer_at, minicbor_to_js_error, CborDecodeState};
+use crate::cbor::text_strings::{mask_invalid_text, render_invalid_text, InvalidText};
+use crate::error::ErrorCode;
+use crate::input::decode_hex;
+use crate::js_error::JsError;
+
+/// `cbor_to_json` split into steps, so a large CBOR can be decoded a chunk
+/// of tokens at a time between frames instead of blocking the main thread.
+///
+/// ```js
+/// const job = CborToJsonJob.start(hex);
+/// while (!job.poll(10000)) await new Promise(requestAnimationFrame);
+/// const json = job.result();
+/// ```
+#[wasm_bindgen]
+pub struct CborToJsonJob {
+    cbor: Vec<u8>,
+    invalid_text: Vec<InvalidText>,
+    state: Option<CborDecodeState>,
+    value: Option<Value>,
+    failed: bool,
+}
+
+#[wasm_bindgen]
+impl CborToJsonJob {
+    pub fn start(cbor_hex: &str) -> Result<CborToJsonJob, JsError> {
+        let cbor = decode_hex(cbor_hex)?;
+        let (masked, invalid_text) = mask_invalid_text(&cbor);
+        Ok(CborToJsonJob {
+            cbor: masked.into_owned(),
+            invalid_text,
+            state: Some(CborDecodeState::new()),
+            value: None,
+            failed: false,
+        })
+    }
+
+    /// Decodes up to `max_tokens` more tokens, returns true once the whole
+    /// input is decoded. After an error the job stays failed.
+    pub fn poll(&mut self, max_tokens: u32) -> Result<bool, JsError> {
+        let state = match self.state.take() {
+            Some(state) => state,
+            None if self.failed => return Err(failed_error()),
+            None => return Ok(true),
+        };
+        match self.decode_chunk(state, max_tokens) {
+            Ok(Ok(value)) => self.value = Some(value),
+            Ok(Err(state)) => self.state = Some(state),
+            Err(error) => {
+                self.failed = true;
+                return Err(error);
+            }
+        }
+        Ok(self.value.is_some())
+    }
+
+    /// Share of the input decoded so far, from 0 to 1.
+    pub fn progress(&self) -> f64 {
+        match &self.state {
+            Some(state) if !self.cbor.is_empty() => state.position() as f64 / self.cbor.len() as f64,
+            _ => 1.0,
+        }
+    }
+
+    /// Same JSON as `cbor_to_json`, once `poll` returned true.
+    pub fn result(&self) -> Result<String, JsError> {
+        match &self.value {
+            Some(value) => Ok(value.to_string()),
+            None if self.failed => Err(failed_error()),
+            None => Err(ErrorCode::InvalidInput.error("Decoding isn't finished, poll until it returns true")),
+        }
+    }
+}
+
+impl CborToJsonJob {
+    /// The decoded value once the input is exhausted, else the state to
+    /// resume from.
+    fn decode_chunk(&self, mut state: CborDecodeState, max_tokens: u32) -> Result<Result<Value, CborDecodeState>, JsError> {
+        let mut tokenizer = get_tokenizer_at(&self.cbor, state.position());
+        for _ in 0..max_tokens {
+            match tokenizer.next() {
+                Some(token) => state.push_token(&token.map_err(minicbor_to_js_error)?)?,
+                None => break,
+            }
+        }
+        if state.position() >= self.cbor.len() {
+            let mut value = state.finish()?;
+            render_invalid_text(&mut value, &self.invalid_text, &self.cbor);
+            return Ok(Ok(value));
+        }
+        Ok(Err(state))
+    }
+}
+
+fn failed_error() -> JsError {
+    ErrorCode::DecodeFailure.error("Decoding failed, the poll that failed returned the reason")
+}
@@ -1 +1,5 @@
-pub(crate) mod cbor_decoder;
\ No newline at end of file
+pub(crate) mod cbor_decoder;
+pub(crate) mod cbor_job;
+pub(crate) mod cbor_encoder;
+pub(crate) mod canonical;
+pub(crate) mod text_strings;
@@ -0,0 +1,134 @@
+use std::borrow::Cow;
+use serde_json::{json, Value};
+use crate::cbor::canonical::read_head;
+use crate::cbor::cbor_decoder::{get_tokenizer, get_value};
+use crate::cbor::cbor_encoder::{MAJOR_ARRAY, MAJOR_BYTES, MAJOR_MAP, MAJOR_TAG, MAJOR_TEXT};
+use crate::js_error::JsError;
+
+const BREAK: u8 = 0xff;
+// a text head turns into a byte string head by clearing this bit of major 3
+const TEXT_TO_BYTES: u8 = (MAJOR_TEXT ^ MAJOR_BYTES) << 5;
+
+/// A definite text string that isn't valid UTF-8. `sequences` are the
+/// offset and length of each invalid byte sequence, offsets are absolute.
+#[derive(Debug, Clone)]
+pub(crate) struct InvalidText {
+    pub(crate) offset: usize,
+    pub(crate) sequences: Vec<(usize, usize)>,
+}
+
+/// `cbor_to_json` of `bytes`. Text strings that aren't UTF-8, which the
+/// tokenizer rejects, are read as byte strings and rendered back as text with
+/// replacement characters, their `raw_hex` and the `invalid_utf8` sequences.
+pub(crate) fn decode_cbor_value(bytes: &[u8]) -> Result<Value, JsError> {
+    let (masked, invalid) = mask_invalid_text(bytes);
+    let mut value = get_value(get_tokenizer(&masked))?;
+    render_invalid_text(&mut value, &invalid, bytes);
+    Ok(value)
+}
+
+/// `bytes` with the heads of invalid text strings turned into byte string
+/// heads, and where they are. Scanning stops at the first malformed item,
+/// the tokenizer reports it.
+pub(crate) fn mask_invalid_text(bytes: &[u8]) -> (Cow<'_, [u8]>, Vec<InvalidText>) {
+    let mut invalid = Vec::new();
+    let mut offset = 0;
+    while offset < bytes.len() {
+        match scan(bytes, offset, &mut invalid) {
+            Some(end) => offset = end,
+            None => break,
+        }
+    }
+    if invalid.is_empty() {
+        return (Cow::Borrowed(bytes), invalid);
+    }
+    let mut masked = bytes.to_vec();
+    for text in &invalid {
+        masked[text.offset] &= !TEXT_TO_BYTES;
+    }
+    (Cow::Owned(masked), invalid)
+}
+
+/// Offset past the item at `offset`, `None` when it's malformed.
+fn scan(bytes: &[u8], offset: usize, invalid: &mut Vec<InvalidText>) -> Option<usize> {
+    let head = read_head(bytes, offset).ok()?;
+    let mut position = offset + head.length;
+    match (head.major, head.argument) {
+        (MAJOR_BYTES | MAJOR_TEXT, Some(length)) => {
+            let end = position.checked_add(length as usize)?;
+            let content = bytes.get(position..end)?;
+            if head.major == MAJOR_TEXT {
+                let sequences = invalid_sequences(content, position);
+                if !sequences.is_empty() {
+                    invalid.push(InvalidText { offset, sequences });
+                }
+            }
+            position = end;
+        }
+        (MAJOR_BYTES | MAJOR_TEXT | MAJOR_ARRAY | MAJOR_MAP, None) => {
+            while *bytes.get(position)? != BREAK {
+                position = scan(bytes, position, invalid)?;
+            }
+            position += 1;
+        }
+        (MAJOR_ARRAY | MAJOR_MAP, Some(count)) => {
+            let items = if head.major == MAJOR_MAP { count.saturating_mul(2) } else { count };
+            for _ in 0..items {
+                position = scan(bytes, position, invalid)?;
+            }
+        }
+        (MAJOR_TAG, Some(_)) => position = scan(bytes, position, invalid)?,
+        (_, Some(_)) => {}
+        (_, None) => return None,
+    }
+    Some(position)
+}
+
+/// Offset and length of each invalid sequence of `content`, which starts at
+/// `start` in the input.
+fn invalid_sequences(content: &[u8], start: usize) -> Vec<(usize, usize)> {
+    let mut sequences = Vec::new();
+    let mut rest = content;
+    let mut offset = start;
+    while let Err(error) = std::str::from_utf8(rest) {
+        let valid = error.valid_up_to();
+        // `None` when the input ends mid-character
+        let length = error.error_len().unwrap_or(rest.len() - valid);
+        sequences.push((offset + valid, length));
+        rest = &rest[valid + length..];
+        offset += valid + length;
+    }
+    sequences
+}
+
+/// Turns the byte string tokens of `invalid` texts back into strings.
+pub(crate) fn render_invalid_text(value: &mut Value, invalid: &[InvalidText], bytes: &[u8]) {
+    if invalid.is_empty() {
+        return;
+    }
+    match value {
+        Value::Array(items) => items.iter_mut().for_each(|item| render_invalid_text(item, invalid, bytes)),
+        Value::Object(obj) => {
+            let offset = obj.get("position_info").and_then(|pos| pos.get("offset")).and_then(Value::as_u64);
+            let text = offset.and_then(|offset| invalid.iter().find(|text| text.offset as u64 == offset));
+            match text {
+                Some(text) if obj.get("type").and_then(Value::as_str) == Some("Bytes") => {
+                    let raw = obj.get("value").and_then(Value::as_str).and_then(|hex| hex::decode(hex).ok()).unwrap_or_default();
+                    obj.insert("type".to_string(), json!("String"));
+                    obj.insert("value".to_string(), json!(String::from_utf8_lossy(&raw)));
+                    obj.insert("raw_hex".to_string(), json!(hex::encode(&raw)));
+                    let sequences: Vec<Value> = text
+                        .sequences
+                        .iter()
+                        .map(|(offset, length)| {
+                            json!({ "offset": offset, "length": length, "hex": hex::encode(&bytes[*offset..offset + length]) })
+                        })
+                        .collect();
+                    obj.insert("invalid_utf8".to_string(), Value::Array(sequences));
+                }
+                _ => obj.values_mut().for_each(|item| render_invalid_text(item, invalid, bytes)),
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,338 @@
+use cardano_serialization_lib::{Address, ByronAddress, Ed25519Signature, PublicKey};
+use minicbor::data::Type;
+use minicbor::Decoder;
+use pallas_crypto::hash::Hasher;
+use serde::Serialize;
+use crate::bingen::wasm_bindgen;
+use crate::cbor::cbor_decoder::minicbor_to_js_error;
+use crate::error::ErrorCode;
+use crate::input::decode_hex;
+use crate::js_error::JsError;
+use crate::metadata::{seek_label, seek_metadata, MetadataIssue};
+use crate::schemas::to_versioned_json;
+
+// CIP-36 registration {61284: {1: delegations, 2: stake key, 3: address, 4: nonce, 5?: purpose}}
+// and its witness {61285: {1: signature}}
+const REGISTRATION_LABEL: u64 = 61284;
+const WITNESS_LABEL: u64 = 61285;
+const DELEGATIONS_KEY: u64 = 1;
+const STAKE_KEY_KEY: u64 = 2;
+const ADDRESS_KEY: u64 = 3;
+const NONCE_KEY: u64 = 4;
+const VOTING_PURPOSE_KEY: u64 = 5;
+const SIGNATURE_KEY: u64 = 1;
+const KEY_LEN: usize = 32;
+const SIGNATURE_LEN: usize = 64;
+
+/// A voting key and its share of the voting power, the single key of a
+/// CIP-15 registration has weight 1.
+#[derive(Serialize, Debug, Clone)]
+pub struct Cip36Delegation {
+    pub voting_key: String,
+    pub weight: u64,
+}
+
+/// A decoded voting registration. `format` is `cip36` for a list of
+/// delegations and `cip15` for a single voting key. The signature is checked
+/// against `signed_hash`, the blake2b-256 of `{61284: registration}` as
+/// encoded in the metadata.
+#[derive(Serialize, Debug, Clone)]
+pub struct Cip36Registration {
+    pub valid: bool,
+    pub format: String,
+    pub delegations: Vec<Cip36Delegation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stake_public_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stake_key_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payment_address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<u64>,
+    pub voting_purpose: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    pub signed_hash: String,
+    pub signature_valid: bool,
+    pub issues: Vec<MetadataIssue>,
+}
+
+/// Decodes a Catalyst voting registration (CIP-36, or CIP-15 with a single
+/// voting key) from a tx, its auxiliary data or the bare metadata map,
+/// checks its structure and verifies the label 61285 signature against the
+/// stake key. Errors when there's no label 61284.
+#[wasm_bindgen]
+pub fn decode_cip36_registration(hex: &str) -> Result<String, JsError> {
+    let bytes = decode_hex(hex)?;
+    to_versioned_json(&cip36_registration(&bytes)?)
+}
+
+pub(crate) fn cip36_registration(bytes: &[u8]) -> Result<Cip36Registration, JsError> {
+    let registration_bytes = label_bytes(bytes, REGISTRATION_LABEL)?
+        .ok_or_else(|| ErrorCode::NotFound.error("The metadata has no label 61284"))?;
+    let mut registration = read_registration(registration_bytes).map_err(minicbor_to_js_error)?;
+
+    // the signed bytes are the label entry as encoded, re-encoding could change them
+    let mut signed = vec![0xa1, 0x19, 0xef, 0x64];
+    signed.extend_from_slice(registration_bytes);
+    let signed_hash = Hasher::<256>::hash(&signed);
+    registration.signed_hash = signed_hash.to_string();
+
+    match label_bytes(bytes, WITNESS_LABEL)? {
+        Some(witness) => {
+            let signature = read_signature(witness, &mut registration.issues).map_err(minicbor_to_js_error)?;
+            if let Some(signature) = &signature {
+                registration.signature = Some(hex::encode(signature));
+            }
+            let stake_key = registration.stake_public_key.as_ref().and_then(|key| hex::decode(key).ok());
+            if let (Some(signature), Some(stake_key)) = (signature, stake_key) {
+                registration.signature_valid = match (PublicKey::from_bytes(&stake_key), Ed25519Signature::from_bytes(signature)) {
+                    (Ok(key), Ok(signature)) => key.verify(signed_hash.as_ref(), &signature),
+                    _ => false,
+                };
+                if !registration.signature_valid {
+                    issue(
+                        &mut registration.issues,
+                        format!("{}/{}", WITNESS_LABEL, SIGNATURE_KEY),
+                        "InvalidSignature",
+                        "The signature doesn't verify against the stake key, the registration is ignored.",
+                    );
+                }
+            }
+        }
+        None => issue(
+            &mut registration.issues,
+            WITNESS_LABEL.to_string(),
+            "MissingWitness",
+            "Label 61285 with the stake key signature is missing.",
+        ),
+    }
+    registration.valid = registration.issues.is_empty();
+    Ok(registration)
+}
+
+/// The encoded value of a metadata label, `None` when it isn't there.
+fn label_bytes(bytes: &[u8], label: u64) -> Result<Option<&[u8]>, JsError> {
+    let mut decoder = Decoder::new(bytes);
+    if !seek_metadata(&mut decoder).map_err(minicbor_to_js_error)?
+        || !seek_label(&mut decoder, label).map_err(minicbor_to_js_error)?
+    {
+        return Ok(None);
+    }
+    let start = decoder.position();
+    decoder.skip().map_err(minicbor_to_js_error)?;
+    Ok(Some(&bytes[start..decoder.position()]))
+}
+
+fn read_registration(bytes: &[u8]) -> Result<Cip36Registration, minicbor::decode::Error> {
+    let mut registration = Cip36Registration {
+        valid: false,
+        format: "cip36".to_string(),
+        delegations: Vec::new(),
+        stake_public_key: None,
+        stake_key_hash: None,
+        payment_address: None,
+        nonce: None,
+        voting_purpose: 0,
+        signature: None,
+        signed_hash: String::new(),
+        signature_valid: false,
+        issues: Vec::new(),
+    };
+    let issues = &mut registration.issues;
+    let label = REGISTRATION_LABEL.to_string();
+    let mut decoder = Decoder::new(bytes);
+    if !matches!(decoder.datatype()?, Type::Map | Type::MapIndef) {
+        issue(issues, label, "NotAMap", "Label 61284 must hold a map.");
+        return Ok(registration);
+    }
+
+    let mut found = Vec::new();
+    for_each_entry(&mut decoder, |decoder, key| {
+        let path = format!("{}/{}", label, key);
+        found.push(key);
+        match key {
+            DELEGATIONS_KEY if decoder.datatype()? == Type::Bytes => {
+                registration.format = "cip15".to_string();
+                if let Some(key) = read_key(decoder, &path, KEY_LEN, issues)? {
+                    registration.delegations.push(Cip36Delegation { voting_key: hex::encode(key), weight: 1 });
+                }
+            }
+            DELEGATIONS_KEY if matches!(decoder.datatype()?, Type::Array | Type::ArrayIndef) => {
+                let mut index = 0;
+                for_each_item(decoder, |decoder| {
+                    let path = format!("{}/{}", path, index);
+                    index += 1;
+                    let start = decoder.position();
+                    if decoder.datatype()? != Type::Array || decoder.array()? != Some(2) {
+                        issue(issues, path, "InvalidDelegation", "A delegation must be [voting key, weight].");
+                        decoder.set_position(start);
+                        return Ok(());
+                    }
+                    let voting_key = read_key(decoder, &path, KEY_LEN, issues)?;
+                    let weight = read_uint(decoder, &path, issues)?;
+                    if let (Some(key), Some(weight)) = (voting_key, weight) {
+                        registration.delegations.push(Cip36Delegation { voting_key: hex::encode(key), weight });
+                    }
+                    Ok(())
+                })?;
+                if registration.delegations.is_empty() {
+                    issue(issues, path, "NoDelegations", "The registration delegates to no voting key.");
+                } else if registration.delegations.iter().all(|d| d.weight == 0) {
+                    issue(issues, path, "ZeroWeights", "Every delegation has weight 0, no voting power is assigned.");
+                }
+            }
+            DELEGATIONS_KEY => {
+                issue(issues, path, "InvalidDelegations", "Key 1 must be a voting key or a list of delegations.");
+                decoder.skip()?;
+            }
+            STAKE_KEY_KEY => {
+                if let Some(key) = read_key(decoder, &path, KEY_LEN, issues)? {
+                    registration.stake_key_hash = Some(Hasher::<224>::hash(&key).to_string());
+                    registration.stake_public_key = Some(hex::encode(key));
+                }
+            }
+            ADDRESS_KEY => {
+                if decoder.datatype()? != Type::Bytes {
+                    issue(issues, path, "InvalidAddress", "Key 3 must be the address bytes.");
+                    decoder.skip()?;
+                    return Ok(());
+                }
+                let address_bytes = decoder.bytes()?.to_vec();
+                match Address::from_bytes(address_bytes.clone()) {
+                    Ok(address) if ByronAddress::from_address(&address).is_none() => {
+                        registration.payment_address = Some(address.to_bech32(None).unwrap_or_else(|_| address.to_hex()));
+                    }
+                    _ => {
+                        issue(issues, path, "InvalidAddress", "Key 3 must be a Shelley address, rewards are paid to it.");
+                        registration.payment_address = Some(hex::encode(address_bytes));
+                    }
+                }
+            }
+            NONCE_KEY => registration.nonce = read_uint(decoder, &path, issues)?,
+            VOTING_PURPOSE_KEY => registration.voting_purpose = read_uint(decoder, &path, issues)?.unwrap_or_default(),
+            _ => decoder.skip()?,
+        }
+        Ok(())
+    })?;
+
+    let required = [
+        (DELEGATIONS_KEY, "voting key or delegations"),
+        (STAKE_KEY_KEY, "stake key"),
+        (ADDRESS_KEY, "payment address"),
+        (NONCE_KEY, "nonce"),
+    ];
+    for (key, name) in required {
+        if !found.contains(&key) {
+            issue(issues, format!("{}/{}", label, key), "MissingField", &format!("Key {} with the {} is missing.", key, name));
+        }
+    }
+    Ok(registration)
+}
+
+fn read_signature(bytes: &[u8], issues: &mut Vec<MetadataIssue>) -> Result<Option<Vec<u8>>, minicbor::decode::Error> {
+    let label = WITNESS_LABEL.to_string();
+    let mut decoder = Decoder::new(bytes);
+    if !matches!(decoder.datatype()?, Type::Map | Type::MapIndef) {
+        issue(issues, label, "NotAMap", "Label 61285 must hold a map.");
+        return Ok(None);
+    }
+    let mut signature = None;
+    for_each_entry(&mut decoder, |decoder, key| {
+        if key == SIGNATURE_KEY {
+            signature = read_key(decoder, &format!("{}/{}", label, key), SIGNATURE_LEN, issues)?;
+        } else {
+            decoder.skip()?;
+        }
+        Ok(())
+    })?;
+    if signature.is_none() && !issues.iter().any(|i| i.path.starts_with(&label)) {
+        issue(issues, format!("{}/{}", label, SIGNATURE_KEY), "MissingField", "Key 1 with the signature is missing.");
+    }
+    Ok(signature)
+}
+
+/// Calls `f` with the value of each unsigned int key of a map, other keys
+/// are skipped with their value.
+fn for_each_entry(
+    decoder: &mut Decoder,
+    mut f: impl FnMut(&mut Decoder, u64) -> Result<(), minicbor::decode::Error>,
+) -> Result<(), minicbor::decode::Error> {
+    let entries = decoder.map()?;
+    let mut read = 0;
+    loop {
+        match entries {
+            Some(len) if read >= len => return Ok(()),
+            None if decoder.datatype()? == Type::Break => {
+                decoder.set_position(decoder.position() + 1);
+                return Ok(());
+            }
+            _ => {}
+        }
+        read += 1;
+        if matches!(decoder.datatype()?, Type::U8 | Type::U16 | Type::U32 | Type::U64) {
+            let key = decoder.u64()?;
+            f(decoder, key)?;
+        } else {
+            decoder.skip()?;
+            decoder.skip()?;
+        }
+    }
+}
+
+fn for_each_item(
+    decoder: &mut Decoder,
+    mut f: impl FnMut(&mut Decoder) -> Result<(), minicbor::decode::Error>,
+) -> Result<(), minicbor::decode::Error> {
+    let len = decoder.array()?;
+    let mut index = 0;
+    loop {
+        match len {
+            Some(len) if index >= len => return Ok(()),
+            None if decoder.datatype()? == Type::Break => {
+                decoder.set_position(decoder.position() + 1);
+                return Ok(());
+            }
+            _ => {}
+        }
+        index += 1;
+        let start = decoder.position();
+        f(decoder)?;
+        // a malformed item is reported and skipped whole
+        if decoder.position() == start {
+            decoder.skip()?;
+        }
+    }
+}
+
+fn read_key(
+    decoder: &mut Decoder,
+    path: &str,
+    len: usize,
+    issues: &mut Vec<MetadataIssue>,
+) -> Result<Option<Vec<u8>>, minicbor::decode::Error> {
+    if decoder.datatype()? != Type::Bytes {
+        issue(issues, path.to_string(), "NotBytes", &format!("Expected {} bytes.", len));
+        decoder.skip()?;
+        return Ok(None);
+    }
+    let bytes = decoder.bytes()?;
+    if bytes.len() != len {
+        issue(issues, path.to_string(), "InvalidLength", &format!("Expected {} bytes, got {}.", len, bytes.len()));
+        return Ok(None);
+    }
+    Ok(Some(bytes.to_vec()))
+}
+
+fn read_uint(decoder: &mut Decoder, path: &str, issues: &mut Vec<MetadataIssue>) -> Result<Option<u64>, minicbor::decode::Error> {
+    if !matches!(decoder.datatype()?, Type::U8 | Type::U16 | Type::U32 | Type::U64) {
+        issue(issues, path.to_string(), "NotAnUnsignedInt", "Expected an unsigned integer.");
+        decoder.skip()?;
+        return Ok(None);
+    }
+    decoder.u64().map(Some)
+}
+
+fn issue(issues: &mut Vec<MetadataIssue>, path: String, rule: &str, message: &str) {
+    issues.push(MetadataIssue { path, rule: rule.to_string(), message: message.to_string() });
+}
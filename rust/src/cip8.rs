@@ -0,0 +1,297 @@
+use cardano_serialization_lib::{Address, Ed25519Signature, PrivateKey, PublicKey};
+use minicbor::data::{Tag, Type};
+use minicbor::Decoder;
+use pallas_crypto::hash::Hasher;
+use serde::Serialize;
+use crate::bingen::wasm_bindgen;
+use crate::cbor::cbor_decoder::minicbor_to_js_error;
+use crate::cbor::cbor_encoder::{push_bytes, push_head, push_int, push_text, MAJOR_ARRAY, MAJOR_MAP, MAJOR_SIMPLE};
+use crate::error::ErrorCode;
+use crate::input::decode_hex;
+use crate::js_error::JsError;
+use crate::schemas::to_versioned_json;
+
+// COSE labels used by CIP-30 signData, RFC 8152 sections 7 and 8
+const ALG_LABEL: i64 = 1;
+const EDDSA_ALG: i64 = -8;
+const KTY_LABEL: i64 = 1;
+const KEY_ALG_LABEL: i64 = 3;
+const OKP_KTY: i64 = 1;
+const CRV_LABEL: i64 = -1;
+const ED25519_CRV: i64 = 6;
+const X_LABEL: i64 = -2;
+const ADDRESS_HEADER: &str = "address";
+const HASHED_HEADER: &str = "hashed";
+const SIGN1_CONTEXT: &str = "Signature1";
+const SIGN1_TAG: u64 = 18;
+const SIMPLE_FALSE: u64 = 20;
+
+/// The CIP-30 `DataSignature`, both fields hex CBOR.
+#[derive(Serialize, Debug, Clone)]
+pub struct Cip8DataSignature {
+    pub signature: String,
+    pub key: String,
+}
+
+/// A checked `COSE_Sign1`. `payload` is as signed, the blake2b-224 of the
+/// message when `hashed`.
+#[derive(Serialize, Debug, Clone)]
+pub struct Cip8Verification {
+    pub valid: bool,
+    pub signature_valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub algorithm: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payload: Option<String>,
+    pub hashed: bool,
+    pub issues: Vec<String>,
+}
+
+/// Signs `payload_hex` the way a CIP-30 wallet's `signData` does, for
+/// testing verification without a wallet. The secret key is hex of a 32
+/// byte or 64 byte extended ed25519 key, or its `ed25519_sk`/`ed25519e_sk`
+/// bech32. `address` (bech32 or hex) goes into the protected header.
+#[wasm_bindgen]
+pub fn sign_cip8_message(secret_key: &str, payload_hex: &str, address: Option<String>) -> Result<String, JsError> {
+    let key = read_secret_key(secret_key)?;
+    let payload = decode_hex(payload_hex)?;
+    let address = address.as_deref().map(read_address).transpose()?;
+    to_versioned_json(&cip8_sign(&key, &payload, address.as_deref()))
+}
+
+/// Verifies a CIP-30 `DataSignature`. `payload_hex` is the message the
+/// caller expects, it's hashed before comparing when the signature is over
+/// its hash, and it stands in for a detached payload.
+#[wasm_bindgen]
+pub fn verify_cip8_signature(signature_hex: &str, key_hex: &str, payload_hex: Option<String>) -> Result<String, JsError> {
+    let signature = decode_hex(signature_hex)?;
+    let key = decode_hex(key_hex)?;
+    let payload = payload_hex.as_deref().map(decode_hex).transpose()?;
+    to_versioned_json(&cip8_verify(&signature, &key, payload.as_deref())?)
+}
+
+pub(crate) fn cip8_sign(key: &PrivateKey, payload: &[u8], address: Option<&[u8]>) -> Cip8DataSignature {
+    let mut protected = Vec::new();
+    push_head(&mut protected, MAJOR_MAP, 1 + address.is_some() as u64);
+    push_int(&mut protected, ALG_LABEL);
+    push_int(&mut protected, EDDSA_ALG);
+    if let Some(address) = address {
+        push_text(&mut protected, ADDRESS_HEADER);
+        push_bytes(&mut protected, address);
+    }
+    let signature = key.sign(&sig_structure(&protected, payload)).to_bytes();
+
+    let mut sign1 = Vec::new();
+    push_head(&mut sign1, MAJOR_ARRAY, 4);
+    push_bytes(&mut sign1, &protected);
+    push_head(&mut sign1, MAJOR_MAP, 1);
+    push_text(&mut sign1, HASHED_HEADER);
+    push_head(&mut sign1, MAJOR_SIMPLE, SIMPLE_FALSE);
+    push_bytes(&mut sign1, payload);
+    push_bytes(&mut sign1, &signature);
+
+    let mut cose_key = Vec::new();
+    push_head(&mut cose_key, MAJOR_MAP, 4);
+    for (label, value) in [(KTY_LABEL, OKP_KTY), (KEY_ALG_LABEL, EDDSA_ALG), (CRV_LABEL, ED25519_CRV)] {
+        push_int(&mut cose_key, label);
+        push_int(&mut cose_key, value);
+    }
+    push_int(&mut cose_key, X_LABEL);
+    push_bytes(&mut cose_key, &key.to_public().as_bytes());
+
+    Cip8DataSignature { signature: hex::encode(sign1), key: hex::encode(cose_key) }
+}
+
+pub(crate) fn cip8_verify(sign1: &[u8], cose_key: &[u8], expected: Option<&[u8]>) -> Result<Cip8Verification, JsError> {
+    let mut verification = Cip8Verification {
+        valid: false,
+        signature_valid: false,
+        algorithm: None,
+        public_key: None,
+        key_hash: None,
+        address: None,
+        payload: None,
+        hashed: false,
+        issues: Vec::new(),
+    };
+
+    let mut decoder = Decoder::new(sign1);
+    let (protected, payload, signature) = read_sign1(&mut decoder, &mut verification).map_err(minicbor_to_js_error)?;
+    read_protected(&protected, &mut verification).map_err(minicbor_to_js_error)?;
+    let public_key = read_cose_key(cose_key, &mut verification).map_err(minicbor_to_js_error)?;
+
+    match verification.algorithm {
+        Some(EDDSA_ALG) => {}
+        Some(algorithm) => verification.issues.push(format!("The algorithm is {}, CIP-30 signs with EdDSA (-8).", algorithm)),
+        None => verification.issues.push("The protected header has no algorithm.".to_string()),
+    }
+    match &payload {
+        Some(payload) => verification.payload = Some(hex::encode(payload)),
+        None if expected.is_none() => verification.issues.push("The payload is detached, pass it to verify.".to_string()),
+        None => {}
+    }
+    let payload = payload.or_else(|| expected.map(|message| signed_payload(message, verification.hashed)));
+    if let (Some(payload), Some(expected)) = (&payload, expected) {
+        if *payload != signed_payload(expected, verification.hashed) {
+            verification.issues.push("The signed payload differs from the expected one.".to_string());
+        }
+    }
+
+    if let (Some(payload), Some(public_key)) = (payload, public_key) {
+        verification.signature_valid = Ed25519Signature::from_bytes(signature)
+            .map(|signature| public_key.verify(&sig_structure(&protected, &payload), &signature))
+            .unwrap_or(false);
+        if !verification.signature_valid {
+            verification.issues.push("The signature doesn't verify against the key.".to_string());
+        }
+    }
+    verification.valid = verification.issues.is_empty();
+    Ok(verification)
+}
+
+/// `["Signature1", protected, external_aad, payload]`, the bytes the key signs.
+fn sig_structure(protected: &[u8], payload: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    push_head(&mut out, MAJOR_ARRAY, 4);
+    push_text(&mut out, SIGN1_CONTEXT);
+    push_bytes(&mut out, protected);
+    push_bytes(&mut out, &[]);
+    push_bytes(&mut out, payload);
+    out
+}
+
+fn signed_payload(message: &[u8], hashed: bool) -> Vec<u8> {
+    if hashed {
+        Hasher::<224>::hash(message).to_vec()
+    } else {
+        message.to_vec()
+    }
+}
+
+/// The protected header bytes, the payload unless detached and the signature.
+fn read_sign1(
+    decoder: &mut Decoder,
+    verification: &mut Cip8Verification,
+) -> Result<(Vec<u8>, Option<Vec<u8>>, Vec<u8>), minicbor::decode::Error> {
+    if decoder.datatype()? == Type::Tag && decoder.tag()? != Tag::Unassigned(SIGN1_TAG) {
+        return Err(minicbor::decode::Error::message("Unexpected tag, expected a COSE_Sign1"));
+    }
+    if decoder.array()? != Some(4) {
+        return Err(minicbor::decode::Error::message("a COSE_Sign1 is an array of 4"));
+    }
+    let protected = decoder.bytes()?.to_vec();
+    let entries = decoder.map()?.unwrap_or_default();
+    for _ in 0..entries {
+        let hashed = if decoder.datatype()? == Type::String {
+            decoder.str()? == HASHED_HEADER
+        } else {
+            decoder.skip()?;
+            false
+        };
+        if hashed && decoder.datatype()? == Type::Bool {
+            verification.hashed = decoder.bool()?;
+        } else {
+            decoder.skip()?;
+        }
+    }
+    let payload = match decoder.datatype()? {
+        Type::Null => {
+            decoder.null()?;
+            None
+        }
+        _ => Some(decoder.bytes()?.to_vec()),
+    };
+    Ok((protected, payload, decoder.bytes()?.to_vec()))
+}
+
+fn read_protected(protected: &[u8], verification: &mut Cip8Verification) -> Result<(), minicbor::decode::Error> {
+    if protected.is_empty() {
+        return Ok(());
+    }
+    let mut decoder = Decoder::new(protected);
+    let entries = decoder.map()?.unwrap_or_default();
+    for _ in 0..entries {
+        // the guards read the key, the arms its value
+        match decoder.datatype()? {
+            Type::String if decoder.str()? == ADDRESS_HEADER => {
+                let bytes = decoder.bytes()?.to_vec();
+                verification.address = Some(match Address::from_bytes(bytes.clone()) {
+                    Ok(address) => address.to_bech32(None).unwrap_or_else(|_| address.to_hex()),
+                    Err(_) => {
+                        verification.issues.push("The address header isn't a valid address.".to_string());
+                        hex::encode(bytes)
+                    }
+                });
+            }
+            Type::String => decoder.skip()?,
+            _ if decoder.i64()? == ALG_LABEL => verification.algorithm = Some(decoder.i64()?),
+            _ => decoder.skip()?,
+        }
+    }
+    Ok(())
+}
+
+fn read_cose_key(cose_key: &[u8], verification: &mut Cip8Verification) -> Result<Option<PublicKey>, minicbor::decode::Error> {
+    let mut decoder = Decoder::new(cose_key);
+    let entries = decoder.map()?.unwrap_or_default();
+    let mut public_key = None;
+    for _ in 0..entries {
+        if decoder.datatype()? == Type::String {
+            decoder.skip()?;
+            decoder.skip()?;
+            continue;
+        }
+        match decoder.i64()? {
+            X_LABEL => public_key = Some(decoder.bytes()?.to_vec()),
+            CRV_LABEL => {
+                if decoder.i64()? != ED25519_CRV {
+                    verification.issues.push("The key curve isn't Ed25519 (6).".to_string());
+                }
+            }
+            _ => decoder.skip()?,
+        }
+    }
+    let Some(public_key) = public_key else {
+        verification.issues.push("The COSE_Key has no public key (-2).".to_string());
+        return Ok(None);
+    };
+    verification.public_key = Some(hex::encode(&public_key));
+    match PublicKey::from_bytes(&public_key) {
+        Ok(key) => {
+            verification.key_hash = Some(key.hash().to_hex());
+            Ok(Some(key))
+        }
+        Err(_) => {
+            verification.issues.push("The public key isn't 32 bytes.".to_string());
+            Ok(None)
+        }
+    }
+}
+
+fn read_secret_key(secret_key: &str) -> Result<PrivateKey, JsError> {
+    let secret_key = secret_key.trim();
+    let key = if secret_key.starts_with("ed25519") {
+        PrivateKey::from_bech32(secret_key)
+    } else {
+        let bytes = decode_hex(secret_key)?;
+        match bytes.len() {
+            32 => PrivateKey::from_normal_bytes(&bytes),
+            64 => PrivateKey::from_extended_bytes(&bytes),
+            len => return Err(ErrorCode::InvalidInput.error(&format!("Expected a 32 or 64 byte secret key, got {} bytes", len))),
+        }
+    };
+    key.map_err(|e| ErrorCode::InvalidInput.error(&format!("Invalid secret key: {:?}", e)))
+}
+
+fn read_address(address: &str) -> Result<Vec<u8>, JsError> {
+    Address::from_bech32(address)
+        .or_else(|_| Address::from_hex(address))
+        .map(|address| address.to_bytes())
+        .map_err(|e| ErrorCode::InvalidInput.error(&format!("Invalid address: {:?}", e)))
+}
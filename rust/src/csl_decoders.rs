@@ -14,69 +14,442 @@ use cardano_serialization_lib::{
 use cardano_serialization_lib::{PlutusData, PlutusDatumSchema, PlutusScript};
 use cardano_serialization_lib::hash_plutus_data;
 
+use serde::Serialize;
 use serde_json::{Number, Value};
 use crate::bingen::wasm_bindgen;
+use crate::input::{decode_hex, sanitize_hex};
 
+use crate::error::ErrorCode;
 use crate::js_error::JsError;
+use crate::schemas::{to_versioned_json, SCHEMA_VERSION};
+use crate::smart_data::{guess_type, smart_render};
 
-#[wasm_bindgen]
-pub fn decode_address_with_extended_info(hex_or_bech32: &str) -> Result<String, JsError> {
-    let address =
-        Address::from_bech32(hex_or_bech32).map_or_else(
-            |_| Address::from_hex(hex_or_bech32)
-                .map_err(|e| JsError::new(&format!("Error decoding address: {:?}", e))),
-            |address| Ok(address))?;
+/// Address in bech32 with its kind and credentials. `extended_data` is empty
+/// for address kinds CSL can't break down.
+#[derive(Serialize, Debug, Clone)]
+#[cfg_attr(all(target_arch = "wasm32", not(target_os = "emscripten")), derive(tsify::Tsify), tsify(into_wasm_abi))]
+pub struct DecodedAddress {
+    pub schema_version: u32,
+    pub address: String,
+    pub extended_data: AddressDetails,
+}
 
-    let mut obj = serde_json::Map::new();
-    obj.insert("address".to_string(), Value::String(address.to_bech32(None)
-        .map_err(|e| JsError::new(&format!("Error encoding address: {:?}", e)))?));
+#[derive(Serialize, Debug, Clone, Default)]
+#[cfg_attr(all(target_arch = "wasm32", not(target_os = "emscripten")), derive(tsify::Tsify))]
+pub struct AddressDetails {
+    /// `BaseAddress`, `EnterpriseAddress`, `PointerAddress`, `RewardAddress`
+    /// or `ByronAddress`.
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub address_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(all(target_arch = "wasm32", not(target_os = "emscripten")), tsify(type = "Record<string, any>"))]
+    pub internal_data: Option<Value>,
+}
 
-    let mut extended_data_obj = serde_json::Map::new();
+#[wasm_bindgen]
+pub fn decode_address_with_extended_info(hex_or_bech32: &str) -> Result<DecodedAddress, JsError> {
+    let address = match Address::from_bech32(hex_or_bech32.trim()) {
+        Ok(address) => address,
+        Err(_) => Address::from_hex(sanitize_hex(hex_or_bech32)?)
+            .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding address: {:?}", e)))?,
+    };
+
+    let bech32 = address.to_bech32(None)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error encoding address: {:?}", e)))?;
 
     let network_id = address.network_id()
-        .map_err(|e| JsError::new(&format!("Error getting network id: {:?}", e)))?;
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error getting network id: {:?}", e)))?;
 
-    if let Some(base_address) = BaseAddress::from_address(&address) {
-        extended_data_obj.insert("type".to_string(), Value::String("BaseAddress".to_string()));
-        extended_data_obj.insert("internal_data".to_string(), base_address_to_json(&base_address, network_id));
+    let details = if let Some(base_address) = BaseAddress::from_address(&address) {
+        Some(("BaseAddress", base_address_to_json(&base_address, network_id)))
     } else if let Some(enterprise_address) = EnterpriseAddress::from_address(&address) {
-        extended_data_obj.insert("type".to_string(), Value::String("EnterpriseAddress".to_string()));
-        extended_data_obj.insert("internal_data".to_string(), enterprise_address_to_json(&enterprise_address, network_id));
+        Some(("EnterpriseAddress", enterprise_address_to_json(&enterprise_address, network_id)))
     } else if let Some(pointer_address) = PointerAddress::from_address(&address) {
-        extended_data_obj.insert("type".to_string(), Value::String("PointerAddress".to_string()));
-        extended_data_obj.insert("internal_data".to_string(), pointer_address_to_json(&pointer_address, network_id));
+        Some(("PointerAddress", pointer_address_to_json(&pointer_address, network_id)))
     } else if let Some(reward_address) = RewardAddress::from_address(&address) {
-        extended_data_obj.insert("type".to_string(), Value::String("RewardAddress".to_string()));
-        extended_data_obj.insert("internal_data".to_string(), reward_address_to_json(&reward_address, network_id));
+        Some(("RewardAddress", reward_address_to_json(&reward_address, network_id)))
     } else if let Some(byron_address) = ByronAddress::from_address(&address) {
-        extended_data_obj.insert("type".to_string(), Value::String("ByronAddress".to_string()));
-        extended_data_obj.insert("internal_data".to_string(), byron_address_to_json(&byron_address, network_id));
+        Some(("ByronAddress", byron_address_to_json(&byron_address, network_id)))
+    } else {
+        None
+    };
+
+    let extended_data = details
+        .map(|(address_type, internal_data)| AddressDetails {
+            address_type: Some(address_type.to_string()),
+            internal_data: Some(internal_data),
+        })
+        .unwrap_or_default();
+
+    Ok(DecodedAddress { schema_version: SCHEMA_VERSION, address: bech32, extended_data })
+}
+
+/// The address in another encoding. `explanation` says what the conversion
+/// did, the bytes never change, only how they're written.
+#[derive(Serialize, Debug, Clone)]
+pub struct ConvertedAddress {
+    pub address: String,
+    pub input_format: String,
+    pub target_format: String,
+    pub address_type: String,
+    pub explanation: String,
+}
+
+/// Converts an address between `hex`, `bech32` and `base58`. Shelley
+/// addresses have no base58 form and Byron ones no bech32 form, asking for
+/// either is an error.
+#[wasm_bindgen]
+pub fn convert_address(input: &str, target_format: &str) -> Result<String, JsError> {
+    to_versioned_json(&address_conversion(input, target_format)?)
+}
+
+pub(crate) fn address_conversion(input: &str, target_format: &str) -> Result<ConvertedAddress, JsError> {
+    let input = input.trim();
+    let (address, input_format) = if let Ok(address) = Address::from_bech32(input) {
+        (address, "bech32")
+    } else if let Ok(byron) = ByronAddress::from_base58(input) {
+        (byron.to_address(), "base58")
+    } else {
+        let address = Address::from_hex(sanitize_hex(input)?)
+            .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Not a hex, bech32 or base58 address: {:?}", e)))?;
+        (address, "hex")
+    };
+    let address_type = address_kind(&address);
+    let byron = ByronAddress::from_address(&address);
+
+    let converted = match target_format {
+        "hex" => address.to_hex(),
+        "bech32" => {
+            if byron.is_some() {
+                return Err(ErrorCode::Unsupported.error("Byron addresses have no bech32 form, use base58"));
+            }
+            address.to_bech32(None)
+                .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error encoding address: {:?}", e)))?
+        }
+        "base58" => match &byron {
+            Some(byron) => byron.to_base58(),
+            None => return Err(ErrorCode::Unsupported.error(&format!("{} has no base58 form, use bech32", address_type))),
+        },
+        _ => return Err(ErrorCode::InvalidInput.error(&format!("Invalid target format: {}, expected hex, bech32 or base58", target_format))),
+    };
+
+    let explanation = if input_format == target_format {
+        format!("The {} was already {}, re-encoded unchanged", address_type, target_format)
+    } else {
+        let mut explanation = format!("Re-encoded the {} bytes of a {} from {} to {}", address.to_bytes().len(), address_type, input_format, target_format);
+        if target_format == "bech32" {
+            let prefix = converted.rsplit_once('1').map(|(prefix, _)| prefix).unwrap_or_default();
+            explanation.push_str(&format!(", the {} prefix follows from its type and network", prefix));
+        }
+        explanation
+    };
+
+    Ok(ConvertedAddress {
+        address: converted,
+        input_format: input_format.to_string(),
+        target_format: target_format.to_string(),
+        address_type: address_type.to_string(),
+        explanation,
+    })
+}
+
+fn address_kind(address: &Address) -> &'static str {
+    if BaseAddress::from_address(address).is_some() {
+        "BaseAddress"
+    } else if EnterpriseAddress::from_address(address).is_some() {
+        "EnterpriseAddress"
+    } else if PointerAddress::from_address(address).is_some() {
+        "PointerAddress"
+    } else if RewardAddress::from_address(address).is_some() {
+        "RewardAddress"
+    } else if ByronAddress::from_address(address).is_some() {
+        "ByronAddress"
+    } else {
+        "Address"
     }
+}
 
-    obj.insert("extended_data".to_string(), Value::Object(extended_data_obj));
+/// An address of a stake credential's family in both encodings,
+/// `payment_credential` is set for base addresses.
+#[derive(Serialize, Debug, Clone)]
+pub struct FamilyAddress {
+    pub address_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payment_credential: Option<Value>,
+    pub bech32: String,
+    pub hex: String,
+}
 
-    Ok(Value::Object(obj).to_string())
+/// The reward address of a stake credential and the base addresses pairing
+/// it with each payment credential. `seen_addresses` is only set by the
+/// on-chain lookup.
+#[derive(Serialize, Debug, Clone)]
+pub struct StakeAddressFamily {
+    pub network_id: u8,
+    pub stake_credential: Value,
+    pub stake_credential_bech32: String,
+    pub addresses: Vec<FamilyAddress>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seen_addresses: Option<Vec<String>>,
+}
+
+/// Derives the addresses of a stake credential: its reward address and a
+/// base address for each of `payment_credentials_json`, a JSON list. Either
+/// credential is given as an address (the matching part is used), a
+/// `stake_vkh`/`addr_vkh`/`script` bech32 or a hex key hash.
+/// `network_id`, mainnet by default, only applies when `stake` isn't an
+/// address.
+#[wasm_bindgen]
+pub fn expand_stake_credential(stake: &str, payment_credentials_json: &str, network_id: Option<u8>) -> Result<String, JsError> {
+    let payment_credentials: Vec<String> = if payment_credentials_json.trim().is_empty() {
+        Vec::new()
+    } else {
+        serde_json::from_str(payment_credentials_json).map_err(|e| ErrorCode::InvalidInput.error(&e.to_string()))?
+    };
+    to_versioned_json(&stake_address_family(stake, &payment_credentials, network_id)?)
+}
+
+pub(crate) fn stake_address_family(stake: &str, payment_credentials: &[String], network_id: Option<u8>) -> Result<StakeAddressFamily, JsError> {
+    let (stake_credential, address_network) = parse_credential(stake, true)?;
+    let network_id = address_network.or(network_id).unwrap_or(1);
+    let stake_credential_bech32 = match (stake_credential.to_keyhash(), stake_credential.to_scripthash()) {
+        (Some(key_hash), _) => key_hash.to_bech32("stake_vkh"),
+        (_, Some(script_hash)) => script_hash.to_bech32("script"),
+        _ => unreachable!("a credential is a key or a script hash"),
+    }
+    .map_err(|e| ErrorCode::DecodeFailure.error(&format!("{:?}", e)))?;
+
+    let mut addresses = vec![family_address(RewardAddress::new(network_id, &stake_credential).to_address(), None)?];
+    for payment in payment_credentials {
+        let (payment_credential, _) = parse_credential(payment, false)?;
+        let address = BaseAddress::new(network_id, &payment_credential, &stake_credential).to_address();
+        addresses.push(family_address(address, Some(stake_cred_to_json(&payment_credential)))?);
+    }
+    Ok(StakeAddressFamily {
+        network_id,
+        stake_credential: stake_cred_to_json(&stake_credential),
+        stake_credential_bech32,
+        addresses,
+        seen_addresses: None,
+    })
+}
+
+fn family_address(address: Address, payment_credential: Option<Value>) -> Result<FamilyAddress, JsError> {
+    Ok(FamilyAddress {
+        address_type: address_kind(&address).to_string(),
+        payment_credential,
+        bech32: address.to_bech32(None)
+            .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error encoding address: {:?}", e)))?,
+        hex: address.to_hex(),
+    })
+}
+
+/// The stake or payment credential of an address, with its network id, or
+/// a credential given as a hash.
+fn parse_credential(input: &str, stake: bool) -> Result<(Credential, Option<u8>), JsError> {
+    let input = input.trim();
+    let address = Address::from_bech32(input).ok().or_else(|| {
+        sanitize_hex(input).ok().filter(|hex| hex.len() != 56).and_then(|hex| Address::from_hex(hex).ok())
+    });
+    if let Some(address) = address {
+        let credential = if stake {
+            RewardAddress::from_address(&address)
+                .map(|reward| reward.payment_cred())
+                .or_else(|| BaseAddress::from_address(&address).map(|base| base.stake_cred()))
+        } else {
+            BaseAddress::from_address(&address)
+                .map(|base| base.payment_cred())
+                .or_else(|| EnterpriseAddress::from_address(&address).map(|enterprise| enterprise.payment_cred()))
+                .or_else(|| PointerAddress::from_address(&address).map(|pointer| pointer.payment_cred()))
+        };
+        let credential = credential.ok_or_else(|| {
+            let part = if stake { "stake" } else { "payment" };
+            ErrorCode::InvalidInput.error(&format!("Address {} has no {} credential", input, part))
+        })?;
+        let network_id = address.network_id().map_err(|e| ErrorCode::DecodeFailure.error(&format!("{:?}", e)))?;
+        return Ok((credential, Some(network_id)));
+    }
+
+    let credential = if input.starts_with("script1") {
+        ScriptHash::from_bech32(input).map(|hash| Credential::from_scripthash(&hash))
+    } else if input.starts_with("stake_vkh1") || input.starts_with("addr_vkh1") {
+        Ed25519KeyHash::from_bech32(input).map(|hash| Credential::from_keyhash(&hash))
+    } else {
+        Ed25519KeyHash::from_hex(sanitize_hex(input)?).map(|hash| Credential::from_keyhash(&hash))
+    };
+    credential
+        .map(|credential| (credential, None))
+        .map_err(|e| ErrorCode::InvalidInput.error(&format!("Not an address or credential hash: {} ({:?})", input, e)))
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DecodedAsset {
+    pub policy_id: String,
+    pub asset_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub asset_name_text: Option<String>,
+    pub quantity: u64,
+}
+
+/// `kind` is `hash` for a datum hash, `inline` for an inline datum, whose
+/// `hash` is then computed and `cbor` set.
+#[derive(Serialize, Debug, Clone)]
+pub struct DecodedDatumOption {
+    pub kind: String,
+    pub hash: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cbor: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DecodedScriptRef {
+    pub script_hash: String,
+    pub language: String,
+    pub size: usize,
+}
+
+/// A tx output. `format` is `legacy` for the array form, which can only
+/// carry a datum hash, `post_alonzo` for the map form Babbage introduced.
+/// Byron addresses are given in base58, others in bech32.
+#[derive(Serialize, Debug, Clone)]
+pub struct DecodedOutput {
+    pub format: String,
+    pub address: String,
+    pub address_type: String,
+    pub coin: u64,
+    pub assets: Vec<DecodedAsset>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub datum: Option<DecodedDatumOption>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub script_ref: Option<DecodedScriptRef>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DecodedUtxo {
+    pub tx_hash: String,
+    pub index: u32,
+    pub output: DecodedOutput,
+}
+
+/// Decodes a standalone `TransactionOutput`, legacy or post-Alonzo.
+#[wasm_bindgen]
+pub fn decode_transaction_output_with_extended_info(hex: &str) -> Result<String, JsError> {
+    let bytes = decode_hex(hex)?;
+    to_versioned_json(&decode_output(&bytes)?)
+}
+
+/// Decodes a `TransactionUnspentOutput`, the `[input, output]` pair wallets
+/// return from CIP-30 `getUtxos`.
+#[wasm_bindgen]
+pub fn decode_utxo_with_extended_info(hex: &str) -> Result<String, JsError> {
+    let bytes = decode_hex(hex)?;
+    let utxo = TransactionUnspentOutput::from_bytes(bytes.clone())
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding UTxO: {:?}", e)))?;
+    // the output's own bytes, for its format
+    let mut decoder = minicbor::Decoder::new(&bytes);
+    decoder.array().and_then(|_| decoder.skip()).map_err(crate::cbor::cbor_decoder::minicbor_to_js_error)?;
+    let input = utxo.input();
+    to_versioned_json(&DecodedUtxo {
+        tx_hash: input.transaction_id().to_hex(),
+        index: input.index(),
+        output: output_details(&utxo.output(), &bytes[decoder.position()..]),
+    })
+}
+
+pub(crate) fn decode_output(bytes: &[u8]) -> Result<DecodedOutput, JsError> {
+    let output = TransactionOutput::from_bytes(bytes.to_vec())
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding output: {:?}", e)))?;
+    Ok(output_details(&output, bytes))
+}
+
+/// `bytes` are the output as encoded, starting with its array or map head.
+pub(crate) fn output_details(output: &TransactionOutput, bytes: &[u8]) -> DecodedOutput {
+    const MAJOR_MAP: u8 = 5;
+    let format = match bytes.first() {
+        Some(head) if head >> 5 == MAJOR_MAP => "post_alonzo",
+        _ => "legacy",
+    };
+    let address = output.address();
+    let address_text = match ByronAddress::from_address(&address) {
+        Some(byron) => byron.to_base58(),
+        None => address.to_bech32(None).unwrap_or_else(|_| address.to_hex()),
+    };
+    let amount = output.amount();
+    let mut assets = Vec::new();
+    if let Some(multiasset) = amount.multiasset() {
+        let policies = multiasset.keys();
+        for i in 0..policies.len() {
+            let policy = policies.get(i);
+            let Some(policy_assets) = multiasset.get(&policy) else {
+                continue;
+            };
+            let names = policy_assets.keys();
+            for j in 0..names.len() {
+                let name = names.get(j);
+                let quantity = policy_assets.get(&name).map(|q| q.to_str().parse().unwrap_or_default()).unwrap_or_default();
+                assets.push(DecodedAsset {
+                    policy_id: policy.to_hex(),
+                    asset_name: hex::encode(name.name()),
+                    asset_name_text: crate::smart_data::printable_text(&name.name()),
+                    quantity,
+                });
+            }
+        }
+    }
+    let datum = match (output.plutus_data(), output.data_hash()) {
+        (Some(data), _) => Some(DecodedDatumOption {
+            kind: "inline".to_string(),
+            hash: hash_plutus_data(&data).to_hex(),
+            cbor: Some(data.to_hex()),
+        }),
+        (None, Some(hash)) => Some(DecodedDatumOption { kind: "hash".to_string(), hash: hash.to_hex(), cbor: None }),
+        (None, None) => None,
+    };
+    let script_ref = output.script_ref().and_then(|script_ref| {
+        if let Some(native) = script_ref.native_script() {
+            Some(DecodedScriptRef { script_hash: native.hash().to_hex(), language: "Native".to_string(), size: native.to_bytes().len() })
+        } else {
+            script_ref.plutus_script().map(|plutus| DecodedScriptRef {
+                script_hash: plutus.hash().to_hex(),
+                language: format!("{:?}", plutus.language_version().kind()),
+                size: plutus.bytes().len(),
+            })
+        }
+    });
+
+    DecodedOutput {
+        format: format.to_string(),
+        address: address_text,
+        address_type: address_kind(&address).to_string(),
+        coin: amount.coin().to_str().parse().unwrap_or_default(),
+        assets,
+        datum,
+        script_ref,
+    }
 }
 
 #[wasm_bindgen]
 pub fn decode_native_script_with_extended_info(hex: &str) -> Result<String, JsError> {
-    let script = NativeScript::from_hex(hex)
-        .map_err(|e| JsError::new(&format!("Error decoding script: {:?}", e)))?;
+    let script = NativeScript::from_hex(sanitize_hex(hex)?)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding script: {:?}", e)))?;
     let hash = script.hash();
 
-    let script_obj = Value::from_str(&script.to_json().unwrap()).unwrap();
+    let script_json = script.to_json()
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error converting script to JSON: {:?}", e)))?;
+    let script_obj = Value::from_str(&script_json)
+        .map_err(|e| ErrorCode::Internal.error(&format!("Invalid script JSON: {}", e)))?;
 
     let mut obj = serde_json::Map::new();
     obj.insert("script_hash".to_string(), Value::String(hash.to_hex()));
     obj.insert("script".to_string(), script_obj);
 
-    Ok(Value::Object(obj).to_string())
+    to_versioned_json(&Value::Object(obj))
 }
 
 #[wasm_bindgen]
 pub fn decode_plutus_script_with_extended_info(hex: &str) -> Result<String, JsError> {
-    let script = PlutusScript::from_hex(hex)
-        .map_err(|e| JsError::new(&format!("Error decoding script: {:?}", e)))?;
+    let script = PlutusScript::from_hex(sanitize_hex(hex)?)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding script: {:?}", e)))?;
     let hash = script.hash();
 
     let script_obj = Value::String(script.to_hex());
@@ -85,28 +458,130 @@ pub fn decode_plutus_script_with_extended_info(hex: &str) -> Result<String, JsEr
     obj.insert("script_hash".to_string(), Value::String(hash.to_hex()));
     obj.insert("script".to_string(), script_obj);
 
-    Ok(Value::Object(obj).to_string())
+    to_versioned_json(&Value::Object(obj))
 }
 
+/// Decodes data with one of the CSL datum schemas (0 basic, 1 detailed) or,
+/// with schema 2, the smart rendering of `smart_data` meant for reading.
 #[wasm_bindgen]
 pub fn decode_plutus_data(hex: &str, schema: u32) -> Result<String, JsError> {
-    let data = PlutusData::from_hex(hex)
-        .map_err(|e| JsError::new(&format!("Error decoding data: {:?}", e)))?;
+    let data = PlutusData::from_hex(sanitize_hex(hex)?)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding data: {:?}", e)))?;
     let hash = hash_plutus_data(&data);
 
     let schema = match schema {
-        0 => PlutusDatumSchema::BasicConversions,
-        1 => PlutusDatumSchema::DetailedSchema,
-        _ => return Err(JsError::new(&format!("Invalid schema: {}", schema))),
+        0 => Some(PlutusDatumSchema::BasicConversions),
+        1 => Some(PlutusDatumSchema::DetailedSchema),
+        2 => None,
+        _ => return Err(ErrorCode::InvalidInput.error(&format!("Invalid schema: {}", schema))),
     };
 
-    let data_obj = Value::from_str(&data.to_json(schema).unwrap()).unwrap();
+    let data_obj = match schema {
+        Some(schema) => {
+            let data_json = data.to_json(schema)
+                .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error converting data to JSON: {:?}", e)))?;
+            Value::from_str(&data_json)
+                .map_err(|e| ErrorCode::Internal.error(&format!("Invalid data JSON: {}", e)))?
+        }
+        None => smart_render(&data),
+    };
 
     let mut obj = serde_json::Map::new();
     obj.insert("data_hash".to_string(), Value::String(hash.to_hex()));
     obj.insert("data".to_string(), data_obj);
 
-    Ok(Value::Object(obj).to_string())
+    to_versioned_json(&Value::Object(obj))
+}
+
+/// Guesses the Aiken type a datum was encoded from, for reading the datums
+/// of protocols whose blueprint isn't published.
+#[wasm_bindgen]
+pub fn guess_plutus_data_type(hex: &str) -> Result<String, JsError> {
+    let data = PlutusData::from_hex(sanitize_hex(hex)?)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding data: {:?}", e)))?;
+    to_versioned_json(&guess_type(&[data]))
+}
+
+/// A certificate by its CDDL name and tag. Tags 0 to 6 are the Shelley
+/// certificates, which Conway still accepts, 7 on the Conway ones. Conway's
+/// `reg_cert` and `unreg_cert` restate `stake_registration` and
+/// `stake_deregistration` with the deposit spelled out, the legacy forms take
+/// the protocol params `key_deposit` implicitly. `refund` is the amount a
+/// deregistration states it gets back.
+#[derive(Serialize, Debug, Clone)]
+pub struct CertificateForm {
+    pub name: &'static str,
+    pub tag: u8,
+    pub era: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deposit: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refund: Option<u64>,
+}
+
+/// Decodes a certificate with its form, telling the legacy registrations
+/// from the Conway ones with explicit deposits.
+#[wasm_bindgen]
+pub fn decode_certificate_with_extended_info(hex: &str) -> Result<String, JsError> {
+    let cert = Certificate::from_hex(sanitize_hex(hex)?)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding certificate: {:?}", e)))?;
+    let cert_json = cert.to_json()
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error converting certificate to JSON: {:?}", e)))?;
+    let cert_obj = Value::from_str(&cert_json)
+        .map_err(|e| ErrorCode::Internal.error(&format!("Invalid certificate JSON: {}", e)))?;
+
+    let mut obj = serde_json::Map::new();
+    obj.insert("form".to_string(), serde_json::to_value(certificate_form(&cert))
+        .map_err(|e| ErrorCode::Internal.error(&e.to_string()))?);
+    obj.insert("certificate".to_string(), cert_obj);
+
+    to_versioned_json(&Value::Object(obj))
+}
+
+pub(crate) fn certificate_form(cert: &Certificate) -> CertificateForm {
+    let coin = |value: &BigNum| value.to_str().parse::<u64>().unwrap_or_default();
+    let (name, tag, deposit, refund) = if let Some(reg) = cert.as_stake_registration() {
+        match reg.coin() {
+            Some(deposit) => ("reg_cert", 7, Some(coin(&deposit)), None),
+            None => ("stake_registration", 0, None, None),
+        }
+    } else if let Some(dereg) = cert.as_stake_deregistration() {
+        match dereg.coin() {
+            Some(refund) => ("unreg_cert", 8, None, Some(coin(&refund))),
+            None => ("stake_deregistration", 1, None, None),
+        }
+    } else if cert.as_stake_delegation().is_some() {
+        ("stake_delegation", 2, None, None)
+    } else if cert.as_pool_registration().is_some() {
+        ("pool_registration", 3, None, None)
+    } else if cert.as_pool_retirement().is_some() {
+        ("pool_retirement", 4, None, None)
+    } else if cert.as_genesis_key_delegation().is_some() {
+        ("genesis_key_delegation", 5, None, None)
+    } else if cert.as_move_instantaneous_rewards_cert().is_some() {
+        ("move_instantaneous_rewards_cert", 6, None, None)
+    } else if cert.as_vote_delegation().is_some() {
+        ("vote_deleg_cert", 9, None, None)
+    } else if cert.as_stake_and_vote_delegation().is_some() {
+        ("stake_vote_deleg_cert", 10, None, None)
+    } else if let Some(reg) = cert.as_stake_registration_and_delegation() {
+        ("stake_reg_deleg_cert", 11, Some(coin(&reg.coin())), None)
+    } else if let Some(reg) = cert.as_vote_registration_and_delegation() {
+        ("vote_reg_deleg_cert", 12, Some(coin(&reg.coin())), None)
+    } else if let Some(reg) = cert.as_stake_vote_registration_and_delegation() {
+        ("stake_vote_reg_deleg_cert", 13, Some(coin(&reg.coin())), None)
+    } else if cert.as_committee_hot_auth().is_some() {
+        ("auth_committee_hot_cert", 14, None, None)
+    } else if cert.as_committee_cold_resign().is_some() {
+        ("resign_committee_cold_cert", 15, None, None)
+    } else if let Some(reg) = cert.as_drep_registration() {
+        ("reg_drep_cert", 16, Some(coin(&reg.coin())), None)
+    } else if let Some(dereg) = cert.as_drep_deregistration() {
+        ("unreg_drep_cert", 17, None, Some(coin(&dereg.coin())))
+    } else {
+        ("update_drep_cert", 18, None, None)
+    };
+    CertificateForm { name, tag, era: if tag < 7 { "Shelley" } else { "Conway" }, deposit, refund }
 }
 
 fn base_address_to_json(addr: &BaseAddress, network_id: u8) -> Value {
@@ -159,7 +634,7 @@ fn bytes_to_string(bytes: &[u8]) -> String {
     bytes.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join("")
 }
 
-fn stake_cred_to_json(creds: &Credential) -> Value {
+pub(crate) fn stake_cred_to_json(creds: &Credential) -> Value {
     let mut obj = serde_json::Map::new();
     match creds.kind() {
         CredKind::Key => {
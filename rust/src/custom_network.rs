@@ -0,0 +1,200 @@
+use cardano_serialization_lib::Transaction;
+use reqwest::Url;
+use serde::Deserialize;
+use serde_json::json;
+use uplc::tx::SlotConfig;
+use crate::bingen::wasm_bindgen;
+use crate::era_history::{era_of_slot, tx_reference_slot, EraSummary};
+use crate::error::{CquisitorError, ErrorCode};
+use crate::js_error::JsError;
+use crate::koios_client::config::{ClientOptions, HttpOptions};
+use crate::koios_client::network_type::NetworkType as KoiosNetworkType;
+use crate::plutus::evaluation_result::TxEvaluation;
+use crate::plutus::execute_tx_scripts::{execute_tx_scripts_with_provider, SlotConfigDefinition};
+use crate::provider::fallback::{FallbackConfig, FallbackProvider};
+use crate::provider::hydra::HydraProvider;
+use crate::provider::koios::KoiosProvider;
+use crate::provider::kupo::KupoProvider;
+use crate::provider::ogmios::OgmiosProvider;
+use crate::provider::request_log::sanitize_url;
+use crate::provider::utxorpc::UtxoRpcProvider;
+use crate::provider::Provider;
+use crate::tx_checks::network_issues;
+use crate::abort_signal::{with_abort_signal, AbortSignal};
+
+/// Where a custom network's chain data comes from. Every provider takes
+/// optional `headers` and `proxy_url`, see [`HttpOptions`].
+#[derive(Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub(crate) enum ProviderConfig {
+    Koios {
+        url: String,
+        #[serde(flatten)]
+        http: HttpOptions,
+    },
+    Ogmios {
+        url: String,
+        #[serde(flatten)]
+        http: HttpOptions,
+    },
+    Kupo {
+        url: String,
+        ogmios_url: String,
+        #[serde(flatten)]
+        http: HttpOptions,
+    },
+    Utxorpc {
+        url: String,
+        #[serde(flatten)]
+        http: HttpOptions,
+    },
+    Hydra {
+        url: String,
+        #[serde(flatten)]
+        http: HttpOptions,
+    },
+    Fallback(FallbackConfig),
+}
+
+impl ProviderConfig {
+    /// Kind and URL, e.g. `ogmios http://localhost:1337`, secrets redacted.
+    pub(crate) fn label(&self) -> String {
+        let (kind, url) = match self {
+            ProviderConfig::Koios { url, .. } => ("koios", url),
+            ProviderConfig::Ogmios { url, .. } => ("ogmios", url),
+            ProviderConfig::Kupo { url, .. } => ("kupo", url),
+            ProviderConfig::Utxorpc { url, .. } => ("utxorpc", url),
+            ProviderConfig::Hydra { url, .. } => ("hydra", url),
+            ProviderConfig::Fallback(config) => return format!("fallback ({} providers)", config.providers.len()),
+        };
+        let url = Url::parse(url).map(|url| sanitize_url(&url)).unwrap_or_else(|_| url.clone());
+        format!("{} {}", kind, url)
+    }
+
+    pub(crate) fn build(&self, api_token: &Option<String>) -> Result<Box<dyn Provider>, JsError> {
+        let provider: Box<dyn Provider> = match self {
+            ProviderConfig::Koios { url, http } => {
+                let options = ClientOptions {
+                    http: http.clone(),
+                    ..ClientOptions::default()
+                };
+                Box::new(KoiosProvider::new(KoiosNetworkType::Custom(url.clone()), api_token.clone()).with_options(options))
+            }
+            ProviderConfig::Ogmios { url, http } => Box::new(OgmiosProvider::new(url).with_http_options(http.clone())),
+            ProviderConfig::Kupo { url, ogmios_url, http } => {
+                let ogmios = OgmiosProvider::new(ogmios_url).with_http_options(http.clone());
+                Box::new(KupoProvider::new(url, ogmios).with_http_options(http.clone()))
+            }
+            ProviderConfig::Utxorpc { url, http } => Box::new(UtxoRpcProvider::new(url).with_http_options(http.clone())),
+            ProviderConfig::Hydra { url, http } => Box::new(HydraProvider::new(url).with_http_options(http.clone())),
+            ProviderConfig::Fallback(config) => Box::new(FallbackProvider::from_config(config, api_token)?),
+        };
+        Ok(provider)
+    }
+}
+
+/// User-defined network such as a private testnet or a Yaci devnet, e.g.
+///
+/// ```json
+/// {
+///   "name": "devnet",
+///   "network_id": 0,
+///   "provider": { "type": "ogmios", "url": "http://localhost:1337" },
+///   "slot_config": { "zero_time": 1700000000000, "zero_slot": 0, "slot_length": 1000 }
+/// }
+/// ```
+///
+/// Without `slot_config` the entry of `era_history` the tx's validity interval
+/// falls in is used. With `network_id` a tx whose body or addresses are of
+/// another network is rejected before anything is fetched.
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct CustomNetwork {
+    pub(crate) name: String,
+    pub(crate) network_id: Option<u8>,
+    pub(crate) provider: ProviderConfig,
+    pub(crate) slot_config: Option<SlotConfigDefinition>,
+    #[serde(default)]
+    pub(crate) era_history: Vec<EraSummary>,
+}
+
+impl CustomNetwork {
+    pub(crate) fn from_json(network_json: &str) -> Result<Self, JsError> {
+        serde_json::from_str(network_json)
+            .map_err(|e| ErrorCode::InvalidInput.error(&format!("Invalid network definition: {}", e)))
+    }
+
+    /// Slot config of the era `reference_slot` falls in, or of the last era.
+    pub(crate) fn slot_config(&self, reference_slot: Option<u64>) -> Result<SlotConfig, JsError> {
+        if let Some(config) = self.slot_config {
+            return Ok(SlotConfig::from(config));
+        }
+        let era = match reference_slot {
+            Some(slot) => era_of_slot(&self.era_history, slot),
+            None => self.era_history.iter().max_by_key(|era| era.start_slot),
+        };
+        era.map(EraSummary::slot_config)
+            .ok_or_else(|| {
+                ErrorCode::InvalidInput.error(&format!("Network {} defines neither slot_config nor era_history", self.name))
+            })
+    }
+}
+
+/// Same as `execute_tx_scripts_for_specific_network` for a network described
+/// by a JSON definition, see [`CustomNetwork`]. `api_token` is optional and
+/// only used by Koios providers.
+#[wasm_bindgen(catch)]
+pub async fn execute_tx_scripts_for_custom_network(
+    tx_hex: &str,
+    network_json: &str,
+    api_token: Option<String>,
+    signal: Option<AbortSignal>,
+) -> Result<TxEvaluation, JsError> {
+    with_abort_signal(signal, async move {
+        let network = CustomNetwork::from_json(network_json)?;
+        let slot_config = network.slot_config(tx_reference_slot(tx_hex))?;
+        if let Ok(tx) = Transaction::from_hex(tx_hex) {
+            let issues = network_issues(&tx, network.network_id);
+            if !issues.is_empty() {
+                let message = format!("The transaction doesn't belong to network {}", network.name);
+                return Err(CquisitorError::new(ErrorCode::InvalidInput, &message)
+                    .with_details(json!({ "issues": issues }))
+                    .into());
+            }
+        }
+        match &network.provider {
+            ProviderConfig::Koios { url, http } => {
+                let options = ClientOptions {
+                    http: http.clone(),
+                    ..ClientOptions::default()
+                };
+                let provider = KoiosProvider::new(KoiosNetworkType::Custom(url.clone()), api_token.clone()).with_options(options);
+                execute_tx_scripts_with_provider(tx_hex, &provider, &slot_config).await
+            }
+            ProviderConfig::Ogmios { url, http } => {
+                let provider = OgmiosProvider::new(url).with_http_options(http.clone());
+                execute_tx_scripts_with_provider(tx_hex, &provider, &slot_config).await
+            }
+            ProviderConfig::Kupo { url, ogmios_url, http } => {
+                let ogmios = OgmiosProvider::new(ogmios_url).with_http_options(http.clone());
+                let provider = KupoProvider::new(url, ogmios).with_http_options(http.clone());
+                execute_tx_scripts_with_provider(tx_hex, &provider, &slot_config).await
+            }
+            ProviderConfig::Utxorpc { url, http } => {
+                let provider = UtxoRpcProvider::new(url).with_http_options(http.clone());
+                execute_tx_scripts_with_provider(tx_hex, &provider, &slot_config).await
+            }
+            ProviderConfig::Hydra { url, http } => {
+                let provider = HydraProvider::new(url).with_http_options(http.clone());
+                execute_tx_scripts_with_provider(tx_hex, &provider, &slot_config).await
+            }
+            ProviderConfig::Fallback(config) => {
+                let mut provider = FallbackProvider::from_config(config, &api_token)?;
+                if config.health_check {
+                    provider.prefer_healthy().await;
+                }
+                execute_tx_scripts_with_provider(tx_hex, &provider, &slot_config).await
+            }
+        }
+    })
+    .await
+}
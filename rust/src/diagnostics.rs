@@ -0,0 +1,99 @@
+// only the evaluator records phases so far
+#![cfg_attr(not(feature = "evaluator"), allow(dead_code))]
+
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use serde::{Deserialize, Serialize};
+use crate::bingen::wasm_bindgen;
+use crate::time_utils::now_ms;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static ROUND_TRIPS: AtomicU32 = AtomicU32::new(0);
+
+/// Attaches `diagnostics` to evaluation responses from now on, off by default.
+#[wasm_bindgen]
+pub fn set_diagnostics_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Wall time of one step of an operation, e.g. `decode` or `evaluate`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(all(target_arch = "wasm32", not(target_os = "emscripten")), derive(tsify::Tsify))]
+pub struct PhaseTiming {
+    pub phase: String,
+    pub duration_ms: f64,
+}
+
+/// Where the time of a call went. `memory_bytes` is the size of the WASM
+/// linear memory when the call finished, which only grows and so is its peak;
+/// it's absent natively. `provider_round_trips` counts the HTTP requests sent
+/// while the call ran, including those of calls running concurrently.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(all(target_arch = "wasm32", not(target_os = "emscripten")), derive(tsify::Tsify))]
+pub struct ExecutionDiagnostics {
+    pub total_ms: f64,
+    pub phases: Vec<PhaseTiming>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_bytes: Option<u64>,
+    pub provider_round_trips: u32,
+}
+
+/// Times the phases of one call, started when the call starts.
+pub(crate) struct DiagnosticsRecorder {
+    started_at: f64,
+    phase_started_at: f64,
+    round_trips_at_start: u32,
+    phases: Vec<PhaseTiming>,
+}
+
+impl DiagnosticsRecorder {
+    pub(crate) fn start() -> DiagnosticsRecorder {
+        let now = now_ms();
+        DiagnosticsRecorder {
+            started_at: now,
+            phase_started_at: now,
+            round_trips_at_start: ROUND_TRIPS.load(Ordering::Relaxed),
+            phases: vec![],
+        }
+    }
+
+    /// Ends `phase`, which ran since the previous phase ended.
+    pub(crate) fn phase(&mut self, phase: &str) {
+        let now = now_ms();
+        self.phases.push(PhaseTiming {
+            phase: phase.to_string(),
+            duration_ms: now - self.phase_started_at,
+        });
+        self.phase_started_at = now;
+    }
+
+    /// The diagnostics, `None` unless enabled with `set_diagnostics_enabled`.
+    pub(crate) fn finish(self) -> Option<ExecutionDiagnostics> {
+        if !ENABLED.load(Ordering::Relaxed) {
+            return None;
+        }
+        Some(self.finish_always())
+    }
+
+    pub(crate) fn finish_always(self) -> ExecutionDiagnostics {
+        ExecutionDiagnostics {
+            total_ms: now_ms() - self.started_at,
+            phases: self.phases,
+            memory_bytes: memory_bytes(),
+            provider_round_trips: ROUND_TRIPS.load(Ordering::Relaxed).wrapping_sub(self.round_trips_at_start),
+        }
+    }
+}
+
+pub(crate) fn count_round_trip() {
+    ROUND_TRIPS.fetch_add(1, Ordering::Relaxed);
+}
+
+#[cfg(all(target_arch = "wasm32", not(target_os = "emscripten")))]
+pub(crate) fn memory_bytes() -> Option<u64> {
+    Some(core::arch::wasm32::memory_size(0) as u64 * 65536)
+}
+
+#[cfg(not(all(target_arch = "wasm32", not(target_os = "emscripten"))))]
+pub(crate) fn memory_bytes() -> Option<u64> {
+    None
+}
@@ -0,0 +1,268 @@
+use cardano_serialization_lib::Transaction;
+use serde::{Deserialize, Serialize};
+use uplc::tx::SlotConfig;
+use crate::bingen::wasm_bindgen;
+use crate::genesis::{GenesisParams, BYRON_SLOT_LENGTH_MS};
+use crate::error::ErrorCode;
+use crate::js_error::JsError;
+use crate::schemas::to_versioned_json;
+use crate::logger::{log_debug, log_warn};
+use crate::netwrok_type::NetworkType;
+use crate::plutus::execute_tx_scripts::SlotConfigDefinition;
+use crate::provider::cache::{instance_storage, CachedProvider};
+use crate::provider::koios::KoiosProvider;
+use crate::provider::Provider;
+use crate::abort_signal::{with_abort_signal, AbortSignal};
+
+/// Start of an era with a constant slot length, times in POSIX milliseconds.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub(crate) struct EraSummary {
+    pub(crate) start_slot: u64,
+    pub(crate) start_time: u64,
+    pub(crate) slot_length: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) start_epoch: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) epoch_length: Option<u64>,
+}
+
+impl EraSummary {
+    pub(crate) fn slot_config(&self) -> SlotConfig {
+        SlotConfig {
+            zero_time: self.start_time,
+            zero_slot: self.start_slot,
+            slot_length: self.slot_length,
+        }
+    }
+}
+
+/// The era `slot` falls in, eras sorted by start slot. Slots before the first
+/// era belong to the first one, whose slot config is the best guess for them;
+/// `slot_to_posix` doesn't extrapolate and rejects them.
+pub(crate) fn era_of_slot(eras: &[EraSummary], slot: u64) -> Option<&EraSummary> {
+    eras.iter()
+        .filter(|era| era.start_slot <= slot)
+        .max_by_key(|era| era.start_slot)
+        .or_else(|| eras.iter().min_by_key(|era| era.start_slot))
+}
+
+/// POSIX time in milliseconds of `slot`, an error for slots before the era
+/// history or whose time doesn't fit a u64.
+pub(crate) fn slot_to_posix(eras: &[EraSummary], slot: u64) -> Result<u64, JsError> {
+    let era = era_of_slot(eras, slot).ok_or_else(|| ErrorCode::InvalidInput.error("The era history is empty"))?;
+    let slots = slot
+        .checked_sub(era.start_slot)
+        .ok_or_else(|| ErrorCode::InvalidInput.error(&format!("Slot {} is before the era history", slot)))?;
+    slots
+        .checked_mul(era.slot_length as u64)
+        .and_then(|elapsed| elapsed.checked_add(era.start_time))
+        .ok_or_else(|| ErrorCode::InvalidInput.error(&format!("Slot {} is out of the POSIX time range", slot)))
+}
+
+/// Slot containing the POSIX time in milliseconds `time`, an error for times
+/// before the era history or slots that don't fit a u64.
+pub(crate) fn posix_to_slot(eras: &[EraSummary], time: u64) -> Result<u64, JsError> {
+    let era = eras
+        .iter()
+        .filter(|era| era.start_time <= time)
+        .max_by_key(|era| era.start_time)
+        .ok_or_else(|| ErrorCode::InvalidInput.error(&format!("Time {} is before the era history", time)))?;
+    era.start_slot
+        .checked_add((time - era.start_time) / (era.slot_length as u64).max(1))
+        .ok_or_else(|| ErrorCode::InvalidInput.error(&format!("Time {} is out of the slot range", time)))
+}
+
+/// Byron and Shelley-onwards eras from the genesis. Slot lengths haven't
+/// changed since Shelley on any public network, so one era covers the rest.
+pub(crate) fn eras_from_genesis(genesis: &GenesisParams, shelley_epoch: u64) -> Vec<EraSummary> {
+    let (shelley_slot, shelley_time) = genesis.shelley_start(shelley_epoch);
+    let mut eras = Vec::new();
+    if shelley_epoch > 0 {
+        eras.push(EraSummary {
+            start_slot: 0,
+            start_time: genesis.system_start,
+            slot_length: BYRON_SLOT_LENGTH_MS as u32,
+            start_epoch: Some(0),
+            epoch_length: Some(genesis.byron_epoch_length()),
+        });
+    }
+    eras.push(EraSummary {
+        start_slot: shelley_slot,
+        start_time: shelley_time,
+        slot_length: genesis.slot_length as u32,
+        start_epoch: Some(shelley_epoch),
+        epoch_length: Some(genesis.epoch_length),
+    });
+    eras
+}
+
+/// Era history from the provider if it has one (Ogmios), derived from the
+/// genesis otherwise, and the network's built-in slot config as a last resort.
+pub(crate) async fn network_era_history<P: Provider>(provider: &P, network: &NetworkType) -> Vec<EraSummary> {
+    match provider.get_era_summaries().await {
+        Ok(eras) if !eras.is_empty() => return eras,
+        Ok(_) => log_debug!("provider has no era summaries, deriving them from the genesis"),
+        Err(_) => log_debug!("provider can't list era summaries, deriving them from the genesis"),
+    }
+    match provider.get_genesis().await.and_then(|genesis| GenesisParams::from_koios(&genesis)) {
+        Ok(genesis) => eras_from_genesis(&genesis, network.shelley_transition_epoch()),
+        Err(_) => {
+            log_warn!("no genesis for {}, using the built-in slot config", network.name());
+            let slot_config = network.slot_config();
+            vec![EraSummary {
+                start_slot: slot_config.zero_slot,
+                start_time: slot_config.zero_time,
+                slot_length: slot_config.slot_length,
+                start_epoch: None,
+                epoch_length: None,
+            }]
+        }
+    }
+}
+
+/// Slot the script context's time range depends on: the validity start, or
+/// the TTL when there's no start. `None` when the tx has neither.
+pub(crate) fn tx_reference_slot(tx_hex: &str) -> Option<u64> {
+    let body = Transaction::from_hex(tx_hex).ok()?.body();
+    body.validity_start_interval_bignum()
+        .or_else(|| body.ttl_bignum())
+        .and_then(|slot| slot.to_str().parse().ok())
+}
+
+/// Slot config of the era the tx's validity interval falls in, so txs from
+/// before a slot length change get the right POSIX times.
+pub(crate) fn slot_config_for_tx(eras: &[EraSummary], tx_hex: &str) -> Option<SlotConfig> {
+    let era = match tx_reference_slot(tx_hex) {
+        Some(slot) => era_of_slot(eras, slot),
+        None => eras.iter().max_by_key(|era| era.start_slot),
+    };
+    era.map(EraSummary::slot_config)
+}
+
+/// Slot config for evaluating `tx_hex` on `network`.
+pub(crate) async fn network_slot_config<P: Provider>(provider: &P, network: &NetworkType, tx_hex: &str) -> SlotConfig {
+    let eras = network_era_history(provider, network).await;
+    slot_config_for_tx(&eras, tx_hex).unwrap_or_else(|| network.slot_config())
+}
+
+/// Era history of the network behind a provider that isn't tied to a
+/// built-in network: its era summaries (Ogmios), or the eras derived from its
+/// genesis, the Shelley transition epoch told by the network magic and 0 for
+/// networks that start in Shelley. `None` when the provider has neither.
+pub(crate) async fn provider_era_history<P: Provider>(provider: &P) -> Option<Vec<EraSummary>> {
+    match provider.get_era_summaries().await {
+        Ok(eras) if !eras.is_empty() => return Some(eras),
+        Ok(_) => log_debug!("provider has no era summaries, deriving them from the genesis"),
+        Err(_) => log_debug!("provider can't list era summaries, deriving them from the genesis"),
+    }
+    let genesis = provider.get_genesis().await.and_then(|genesis| GenesisParams::from_koios(&genesis)).ok()?;
+    let shelley_epoch = NetworkType::from_magic(genesis.network_magic).map_or(0, |network| network.shelley_transition_epoch());
+    Some(eras_from_genesis(&genesis, shelley_epoch))
+}
+
+/// Slot config for evaluating `tx_hex` through a provider that isn't tied to
+/// a built-in network. `slot_config_json`, see [`SlotConfigDefinition`], wins
+/// over the provider's era history and is required for providers that have
+/// none, e.g. a Hydra head.
+pub(crate) async fn provider_slot_config<P: Provider>(
+    provider: &P,
+    tx_hex: &str,
+    slot_config_json: Option<&str>,
+) -> Result<SlotConfig, JsError> {
+    if let Some(slot_config_json) = slot_config_json {
+        return SlotConfigDefinition::from_json(slot_config_json).map(SlotConfig::from);
+    }
+    provider_era_history(provider)
+        .await
+        .and_then(|eras| slot_config_for_tx(&eras, tx_hex))
+        .ok_or_else(|| {
+            ErrorCode::InvalidInput.error("The provider has neither era summaries nor a genesis, pass a slot config")
+        })
+}
+
+/// Era history of a network as JSON: `[{ start_slot, start_time, slot_length,
+/// start_epoch, epoch_length }]`, times in POSIX milliseconds.
+#[wasm_bindgen(catch)]
+pub async fn get_era_history(
+    network: NetworkType,
+    api_token: Option<String>,
+    signal: Option<AbortSignal>,
+) -> Result<String, JsError> {
+    with_abort_signal(signal, async move {
+        let namespace = network.name();
+        let provider = CachedProvider::new(KoiosProvider::new(network.clone().into(), api_token), instance_storage(), namespace);
+        let eras = network_era_history(&provider, &network).await;
+        to_versioned_json(&eras)
+    })
+    .await
+}
+
+/// POSIX time in milliseconds of `slot`, given the era history JSON
+/// `get_era_history` returns.
+#[wasm_bindgen]
+pub fn slot_to_posix_time(slot: u64, era_history_json: &str) -> Result<u64, JsError> {
+    let eras = parse_era_history(era_history_json)?;
+    slot_to_posix(&eras, slot)
+}
+
+/// Slot containing the POSIX time in milliseconds, given the era history JSON
+/// `get_era_history` returns.
+#[wasm_bindgen]
+pub fn posix_time_to_slot(time: u64, era_history_json: &str) -> Result<u64, JsError> {
+    let eras = parse_era_history(era_history_json)?;
+    posix_to_slot(&eras, time)
+}
+
+fn parse_era_history(era_history_json: &str) -> Result<Vec<EraSummary>, JsError> {
+    serde_json::from_str(era_history_json).map_err(|e| ErrorCode::InvalidInput.error(&format!("Invalid era history: {}", e)))
+}
+
+/// Milliseconds since the epoch of an ISO 8601 UTC time as Ogmios prints
+/// it, e.g. `2017-09-23T21:44:51Z`.
+pub(crate) fn parse_utc_time_ms(time: &str) -> Option<u64> {
+    let (date, rest) = time.trim().trim_end_matches('Z').split_once('T')?;
+    let mut date_parts = date.split('-').map(|part| part.parse::<i64>());
+    let (year, month, day) = (date_parts.next()?.ok()?, date_parts.next()?.ok()?, date_parts.next()?.ok()?);
+    let (clock, fraction) = rest.split_once('.').unwrap_or((rest, ""));
+    let mut clock_parts = clock.split(':').map(|part| part.parse::<i64>());
+    let (hour, minute, second) = (clock_parts.next()?.ok()?, clock_parts.next()?.ok()?, clock_parts.next()?.ok()?);
+    let millis: i64 = format!("{:0<3}", fraction.chars().take(3).collect::<String>()).parse().ok()?;
+
+    // days since 1970-01-01 of a proleptic Gregorian date
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let year_of_era = y - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    let ms = ((days * 24 + hour) * 60 + minute) * 60_000 + second * 1000 + millis;
+    u64::try_from(ms).ok()
+}
+
+/// ISO 8601 UTC time of milliseconds since the epoch, the inverse of
+/// `parse_utc_time_ms`, e.g. `2017-09-23T21:44:51Z`.
+pub(crate) fn format_utc_time_ms(ms: u64) -> String {
+    let ms = ms as i64;
+    let days = ms.div_euclid(86_400_000);
+    let ms_of_day = ms.rem_euclid(86_400_000);
+
+    // proleptic Gregorian date of days since 1970-01-01
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    let (hour, minute, second, millis) =
+        (ms_of_day / 3_600_000, ms_of_day / 60_000 % 60, ms_of_day / 1000 % 60, ms_of_day % 1000);
+    if millis == 0 {
+        format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", year, month, day, hour, minute, second)
+    } else {
+        format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z", year, month, day, hour, minute, second, millis)
+    }
+}
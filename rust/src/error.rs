@@ -0,0 +1,83 @@
+use serde::Serialize;
+use serde_json::Value;
+use crate::js_error::JsError;
+use crate::schemas::SCHEMA_VERSION;
+
+/// Machine-readable reason of a failure, stable across releases so frontends
+/// can branch on it instead of on the message.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// An argument isn't valid hex.
+    InvalidHex,
+    /// CBOR, a transaction, script, datum or address that doesn't decode.
+    DecodeFailure,
+    /// JSON arguments or options that don't have the expected shape.
+    InvalidInput,
+    /// An argument above the size limit, `details` holds its `size` and the
+    /// `limit`.
+    InputTooLarge,
+    /// Transaction of an era or kind that can't be evaluated.
+    UnsupportedTransaction,
+    /// Inputs the provider couldn't resolve, `details.utxos` lists them.
+    MissingUtxo,
+    /// Scripts neither attached nor found by the provider, `details.scripts`
+    /// lists their hashes.
+    MissingScript,
+    /// The transaction fails ledger phase one checks before any script runs.
+    PhaseOneFailure,
+    NotFound,
+    /// The selected provider doesn't support the query.
+    Unsupported,
+    /// A provider request failed, `details` holds the provider error kind,
+    /// endpoint, status and whether it's retryable.
+    ProviderError,
+    Timeout,
+    Aborted,
+    Internal,
+}
+
+impl ErrorCode {
+    pub(crate) fn error(self, message: &str) -> JsError {
+        CquisitorError::new(self, message).to_js_error()
+    }
+}
+
+/// Error every public function fails with. It reaches JS as a `JsError`
+/// whose message is this struct as JSON, `JSON.parse(error.message).code`
+/// tells failures apart.
+#[derive(Serialize, Debug, Clone)]
+pub struct CquisitorError {
+    pub schema_version: u32,
+    pub code: ErrorCode,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<Value>,
+}
+
+impl CquisitorError {
+    pub(crate) fn new(code: ErrorCode, message: &str) -> Self {
+        CquisitorError {
+            schema_version: SCHEMA_VERSION,
+            code,
+            message: message.to_string(),
+            details: None,
+        }
+    }
+
+    pub(crate) fn with_details(mut self, details: Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    pub(crate) fn to_js_error(&self) -> JsError {
+        let message = serde_json::to_string(self).unwrap_or_else(|_| self.message.clone());
+        JsError::new(&message)
+    }
+}
+
+impl From<CquisitorError> for JsError {
+    fn from(error: CquisitorError) -> Self {
+        error.to_js_error()
+    }
+}
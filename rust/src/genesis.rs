@@ -0,0 +1,325 @@
+use pallas_crypto::hash::Hasher;
+use serde::Serialize;
+use serde_json::{json, Value};
+use crate::bingen::wasm_bindgen;
+use crate::era_history::parse_utc_time_ms;
+use crate::error::ErrorCode;
+use crate::js_error::JsError;
+use crate::schemas::to_versioned_json;
+use crate::netwrok_type::NetworkType;
+use crate::provider::cache::{instance_storage, CachedProvider};
+use crate::provider::koios::KoiosProvider;
+use crate::provider::Provider;
+use crate::abort_signal::{with_abort_signal, AbortSignal};
+
+// Byron slots are 20s and a Byron epoch is 10k slots, neither is in the
+// Shelley genesis
+pub(crate) const BYRON_SLOT_LENGTH_MS: u64 = 20_000;
+const BYRON_EPOCH_LENGTH_FACTOR: u64 = 10;
+// Praos needs the stability window of 3k/f slots to fit in an epoch
+const STABILITY_WINDOW_FACTOR: f64 = 3.0;
+
+/// Shelley genesis parameters, times in POSIX milliseconds.
+#[derive(Serialize, Debug, Clone)]
+pub struct GenesisParams {
+    pub network_magic: u64,
+    pub network_id: String,
+    pub system_start: u64,
+    pub epoch_length: u64,
+    pub slot_length: u64,
+    pub security_param: u64,
+    pub active_slot_coeff: f64,
+    pub slots_per_kes_period: u64,
+    pub max_kes_evolutions: u64,
+}
+
+impl GenesisParams {
+    /// Koios returns numbers as strings and times in seconds.
+    pub(crate) fn from_koios(value: &Value) -> Result<Self, JsError> {
+        let number = |field: &str| -> Result<f64, JsError> {
+            let raw = value
+                .get(field)
+                .ok_or_else(|| ErrorCode::ProviderError.error(&format!("Genesis has no {}", field)))?;
+            raw.as_f64()
+                .or_else(|| raw.as_str().and_then(|s| s.parse().ok()))
+                .ok_or_else(|| ErrorCode::ProviderError.error(&format!("Invalid genesis {}: {}", field, raw)))
+        };
+        Ok(GenesisParams {
+            network_magic: number("networkmagic")? as u64,
+            network_id: value.get("networkid").and_then(Value::as_str).unwrap_or_default().to_string(),
+            system_start: number("systemstart")? as u64 * 1000,
+            epoch_length: number("epochlength")? as u64,
+            slot_length: (number("slotlength")? * 1000.0) as u64,
+            security_param: number("securityparam")? as u64,
+            active_slot_coeff: number("activeslotcoeff")?,
+            slots_per_kes_period: number("slotsperkesperiod")? as u64,
+            max_kes_evolutions: number("maxkesrevolutions")? as u64,
+        })
+    }
+
+    /// The genesis file has times as UTC strings and slot lengths in seconds.
+    pub(crate) fn from_shelley_genesis(value: &Value) -> Option<Self> {
+        let uint = |field: &str| value.get(field).and_then(Value::as_u64);
+        Some(GenesisParams {
+            network_magic: uint("networkMagic")?,
+            network_id: value.get("networkId")?.as_str()?.to_string(),
+            system_start: parse_utc_time_ms(value.get("systemStart")?.as_str()?)?,
+            epoch_length: uint("epochLength")?,
+            slot_length: (value.get("slotLength")?.as_f64()? * 1000.0).round() as u64,
+            security_param: uint("securityParam")?,
+            active_slot_coeff: value.get("activeSlotsCoeff")?.as_f64()?,
+            slots_per_kes_period: uint("slotsPerKESPeriod")?,
+            max_kes_evolutions: uint("maxKESEvolutions")?,
+        })
+    }
+
+    pub(crate) fn byron_epoch_length(&self) -> u64 {
+        BYRON_EPOCH_LENGTH_FACTOR * self.security_param
+    }
+
+    /// First slot and its time of the Shelley era, after `shelley_epoch` Byron epochs.
+    pub(crate) fn shelley_start(&self, shelley_epoch: u64) -> (u64, u64) {
+        let slot = shelley_epoch * self.byron_epoch_length();
+        (slot, self.system_start + slot * BYRON_SLOT_LENGTH_MS)
+    }
+
+    pub(crate) fn epoch_of_slot(&self, slot: u64, shelley_epoch: u64) -> u64 {
+        let (shelley_slot, _) = self.shelley_start(shelley_epoch);
+        if slot < shelley_slot {
+            slot / self.byron_epoch_length().max(1)
+        } else {
+            shelley_epoch + (slot - shelley_slot) / self.epoch_length.max(1)
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct GenesisSummary {
+    #[serde(flatten)]
+    pub genesis: GenesisParams,
+    pub byron_epoch_length: u64,
+    pub shelley_start_epoch: u64,
+    pub shelley_start_slot: u64,
+    pub shelley_start_time: u64,
+    pub epoch_duration: u64,
+    pub current_epoch: u64,
+}
+
+/// Genesis parameters of a network with the constants derived from them:
+/// the Shelley era start, epoch duration and the epoch of the chain tip.
+#[wasm_bindgen(catch)]
+pub async fn get_genesis(
+    network: NetworkType,
+    api_token: Option<String>,
+    signal: Option<AbortSignal>,
+) -> Result<String, JsError> {
+    with_abort_signal(signal, async move {
+        let shelley_epoch = network.shelley_transition_epoch();
+        let namespace = network.name();
+        let provider = CachedProvider::new(KoiosProvider::new(network.into(), api_token), instance_storage(), namespace);
+        let genesis = GenesisParams::from_koios(&provider.get_genesis().await?)?;
+        let tip = provider.get_chain_tip().await?;
+        let (shelley_start_slot, shelley_start_time) = genesis.shelley_start(shelley_epoch);
+        let summary = GenesisSummary {
+            byron_epoch_length: genesis.byron_epoch_length(),
+            shelley_start_epoch: shelley_epoch,
+            shelley_start_slot,
+            shelley_start_time,
+            epoch_duration: genesis.epoch_length * genesis.slot_length,
+            current_epoch: genesis.epoch_of_slot(tip.abs_slot, shelley_epoch),
+            genesis,
+        };
+        to_versioned_json(&summary)
+    })
+    .await
+}
+
+/// A field of a genesis file the node would reject or misbehave with.
+#[derive(Serialize, Debug, Clone)]
+pub struct GenesisIssue {
+    pub field: String,
+    pub rule: String,
+    pub message: String,
+}
+
+/// `hash` is the blake2b-256 of the file as given, the one the node config
+/// pins. `params` and `custom_network` are set for a valid Shelley genesis,
+/// the latter with the slot config of a network starting in Shelley.
+#[derive(Serialize, Debug, Clone)]
+pub struct GenesisFileReport {
+    pub kind: String,
+    pub hash: String,
+    pub valid: bool,
+    pub issues: Vec<GenesisIssue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub params: Option<GenesisParams>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_network: Option<Value>,
+}
+
+#[derive(Clone, Copy)]
+enum FieldKind {
+    Object,
+    Array,
+    UInt,
+    PositiveInt,
+    PositiveNumber,
+    UnitInterval,
+    UtcTime,
+}
+
+const SHELLEY_FIELDS: &[(&str, FieldKind)] = &[
+    ("activeSlotsCoeff", FieldKind::UnitInterval),
+    ("epochLength", FieldKind::PositiveInt),
+    ("genDelegs", FieldKind::Object),
+    ("initialFunds", FieldKind::Object),
+    ("maxKESEvolutions", FieldKind::PositiveInt),
+    ("maxLovelaceSupply", FieldKind::PositiveInt),
+    ("networkMagic", FieldKind::UInt),
+    ("protocolParams", FieldKind::Object),
+    ("securityParam", FieldKind::PositiveInt),
+    ("slotLength", FieldKind::PositiveNumber),
+    ("slotsPerKESPeriod", FieldKind::PositiveInt),
+    ("systemStart", FieldKind::UtcTime),
+    ("updateQuorum", FieldKind::UInt),
+];
+
+const ALONZO_FIELDS: &[(&str, FieldKind)] = &[
+    ("lovelacePerUTxOWord", FieldKind::UInt),
+    ("executionPrices", FieldKind::Object),
+    ("maxTxExUnits", FieldKind::Object),
+    ("maxBlockExUnits", FieldKind::Object),
+    ("maxValueSize", FieldKind::PositiveInt),
+    ("collateralPercentage", FieldKind::PositiveInt),
+    ("maxCollateralInputs", FieldKind::PositiveInt),
+    ("costModels", FieldKind::Object),
+];
+
+const CONWAY_FIELDS: &[(&str, FieldKind)] = &[
+    ("poolVotingThresholds", FieldKind::Object),
+    ("dRepVotingThresholds", FieldKind::Object),
+    ("committeeMinSize", FieldKind::UInt),
+    ("committeeMaxTermLength", FieldKind::PositiveInt),
+    ("govActionLifetime", FieldKind::PositiveInt),
+    ("govActionDeposit", FieldKind::UInt),
+    ("dRepDeposit", FieldKind::UInt),
+    ("dRepActivity", FieldKind::PositiveInt),
+    ("minFeeRefScriptCostPerByte", FieldKind::UInt),
+    ("plutusV3CostModel", FieldKind::Array),
+    ("constitution", FieldKind::Object),
+    ("committee", FieldKind::Object),
+];
+
+/// Checks a Shelley, Alonzo or Conway genesis file, telling them apart by
+/// their fields, and computes its hash. Pass the file content unchanged, a
+/// reformatted file has another hash.
+#[wasm_bindgen]
+pub fn inspect_genesis_file(json: &str) -> Result<String, JsError> {
+    to_versioned_json(&genesis_file_report(json)?)
+}
+
+pub(crate) fn genesis_file_report(json: &str) -> Result<GenesisFileReport, JsError> {
+    let value: Value = serde_json::from_str(json)
+        .map_err(|e| ErrorCode::InvalidInput.error(&format!("Invalid genesis JSON: {}", e)))?;
+    let has = |fields: &[&str]| fields.iter().any(|field| value.get(field).is_some());
+    let (kind, fields) = if has(&["systemStart", "networkMagic", "genDelegs"]) {
+        ("Shelley", SHELLEY_FIELDS)
+    } else if has(&["poolVotingThresholds", "dRepVotingThresholds", "constitution"]) {
+        ("Conway", CONWAY_FIELDS)
+    } else if has(&["costModels", "lovelacePerUTxOWord", "executionPrices"]) {
+        ("Alonzo", ALONZO_FIELDS)
+    } else {
+        return Err(ErrorCode::Unsupported.error("Not a Shelley, Alonzo or Conway genesis file"));
+    };
+
+    let mut issues = Vec::new();
+    for (field, field_kind) in fields {
+        check_field(&value, field, *field_kind, &mut issues);
+    }
+    let mut params = None;
+    let mut custom_network = None;
+    if kind == "Shelley" {
+        shelley_issues(&value, &mut issues);
+        if issues.is_empty() {
+            params = GenesisParams::from_shelley_genesis(&value);
+            custom_network = params.as_ref().map(|params| {
+                json!({
+                    "network_id": if params.network_id == "Mainnet" { 1 } else { 0 },
+                    "slot_config": {
+                        "zero_time": params.system_start,
+                        "zero_slot": 0,
+                        "slot_length": params.slot_length,
+                    },
+                })
+            });
+        }
+    }
+
+    Ok(GenesisFileReport {
+        kind: kind.to_string(),
+        hash: Hasher::<256>::hash(json.as_bytes()).to_string(),
+        valid: issues.is_empty(),
+        issues,
+        params,
+        custom_network,
+    })
+}
+
+fn check_field(value: &Value, field: &str, kind: FieldKind, issues: &mut Vec<GenesisIssue>) {
+    let mut issue = |rule: &str, message: String| {
+        issues.push(GenesisIssue { field: field.to_string(), rule: rule.to_string(), message })
+    };
+    let Some(raw) = value.get(field) else {
+        issue("MissingField", format!("{} is required", field));
+        return;
+    };
+    let (ok, expected) = match kind {
+        FieldKind::Object => (raw.is_object(), "an object"),
+        FieldKind::Array => (raw.is_array(), "an array"),
+        FieldKind::UInt => (raw.is_u64(), "a non-negative integer"),
+        FieldKind::PositiveInt => (raw.as_u64().is_some_and(|n| n > 0), "a positive integer"),
+        FieldKind::PositiveNumber => (raw.as_f64().is_some_and(|n| n > 0.0), "a positive number"),
+        FieldKind::UnitInterval => (raw.as_f64().is_some_and(|n| n > 0.0 && n <= 1.0), "a number in (0, 1]"),
+        FieldKind::UtcTime => (raw.as_str().and_then(parse_utc_time_ms).is_some(), "a UTC time like 2022-10-25T00:00:00Z"),
+    };
+    if !ok {
+        issue("InvalidField", format!("{} must be {}, got {}", field, expected, raw));
+    }
+}
+
+/// Shelley checks across fields, run after each field checked out alone.
+fn shelley_issues(value: &Value, issues: &mut Vec<GenesisIssue>) {
+    let mut issue = |field: &str, rule: &str, message: String| {
+        issues.push(GenesisIssue { field: field.to_string(), rule: rule.to_string(), message })
+    };
+    match value.get("networkId").and_then(Value::as_str) {
+        Some("Mainnet") | Some("Testnet") => {}
+        _ => issue("networkId", "InvalidField", "networkId must be \"Mainnet\" or \"Testnet\"".to_string()),
+    }
+    if value.get("networkMagic").and_then(Value::as_u64).is_some_and(|magic| magic > u32::MAX as u64) {
+        issue("networkMagic", "InvalidField", "networkMagic must fit in 32 bits".to_string());
+    }
+
+    let number = |field: &str| value.get(field).and_then(Value::as_f64);
+    if let (Some(k), Some(f), Some(epoch_length)) = (number("securityParam"), number("activeSlotsCoeff"), number("epochLength")) {
+        let stability_window = (STABILITY_WINDOW_FACTOR * k / f).ceil();
+        if f > 0.0 && epoch_length < stability_window {
+            issue(
+                "epochLength",
+                "EpochTooShort",
+                format!("epochLength {} is below the stability window 3k/f = {}", epoch_length, stability_window),
+            );
+        }
+    }
+    let delegates = value.get("genDelegs").and_then(Value::as_object).map(|delegs| delegs.len() as u64);
+    let quorum = value.get("updateQuorum").and_then(Value::as_u64);
+    if let (Some(delegates), Some(quorum)) = (delegates, quorum) {
+        if quorum > delegates {
+            issue(
+                "updateQuorum",
+                "QuorumUnreachable",
+                format!("updateQuorum {} is more than the {} genesis delegates", quorum, delegates),
+            );
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use cardano_serialization_lib::{
+    Anchor, Committee, Constitution, Credentials, GovernanceAction, GovernanceActionId, Transaction,
+};
+use serde::Serialize;
+use serde_json::Value;
+use crate::bingen::wasm_bindgen;
+use crate::csl_decoders::stake_cred_to_json;
+use crate::error::ErrorCode;
+use crate::input::sanitize_hex;
+use crate::js_error::JsError;
+use crate::schemas::to_versioned_json;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DecodedAnchor {
+    pub url: String,
+    pub data_hash: String,
+}
+
+/// `guardrails_script_hash` is the script every treasury withdrawal and
+/// parameter change is checked against once the constitution is enacted.
+#[derive(Serialize, Debug, Clone)]
+pub struct DecodedConstitution {
+    pub anchor: DecodedAnchor,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub guardrails_script_hash: Option<String>,
+}
+
+/// A committee member added or kept, `term_epoch` is the last epoch of its term.
+#[derive(Serialize, Debug, Clone)]
+pub struct CommitteeMember {
+    pub credential: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub term_epoch: Option<u32>,
+}
+
+/// Share of committee members that must vote yes, e.g. `2/3`.
+#[derive(Serialize, Debug, Clone)]
+pub struct QuorumThreshold {
+    pub numerator: String,
+    pub denominator: String,
+    pub ratio: f64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DecodedCommitteeUpdate {
+    pub members_to_remove: Vec<Value>,
+    pub members_to_add: Vec<CommitteeMember>,
+    pub quorum_threshold: QuorumThreshold,
+}
+
+/// One proposal of the tx. `previous_action` is the enacted action of the
+/// same purpose this one builds on, `tx_hash#index`. `constitution` and
+/// `committee_update` are set for their action types, `action` holds the
+/// CSL JSON of the other types.
+#[derive(Serialize, Debug, Clone)]
+pub struct DecodedProposal {
+    pub index: usize,
+    pub action_type: String,
+    pub deposit: String,
+    pub reward_account: String,
+    pub anchor: DecodedAnchor,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_action: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub constitution: Option<DecodedConstitution>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub committee_update: Option<DecodedCommitteeUpdate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<Value>,
+}
+
+/// The proposals of a tx in order.
+#[derive(Serialize, Debug, Clone)]
+pub struct DecodedProposals {
+    pub proposals: Vec<DecodedProposal>,
+}
+
+/// What a proposal's action type adds, see [`DecodedProposal`].
+#[derive(Default)]
+struct DecodedAction {
+    previous_action: Option<String>,
+    constitution: Option<DecodedConstitution>,
+    committee_update: Option<DecodedCommitteeUpdate>,
+    action: Option<Value>,
+}
+
+/// Decodes the governance proposals of a tx, with `NewConstitution` and
+/// `UpdateCommittee` actions broken down into their anchor, guardrails
+/// script, members with their terms and quorum threshold.
+#[wasm_bindgen]
+pub fn decode_governance_proposals(tx_hex: &str) -> Result<String, JsError> {
+    let tx = Transaction::from_hex(sanitize_hex(tx_hex)?)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding transaction: {:?}", e)))?;
+    let mut decoded = Vec::new();
+    if let Some(proposals) = tx.body().voting_proposals() {
+        for index in 0..proposals.len() {
+            let proposal = proposals.get(index);
+            let reward_account = proposal
+                .reward_account()
+                .to_address()
+                .to_bech32(None)
+                .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error encoding reward account: {:?}", e)))?;
+            let governance_action = proposal.governance_action();
+            let action = decode_action(index, &governance_action)?;
+            decoded.push(DecodedProposal {
+                index,
+                action_type: format!("{:?}", governance_action.kind()),
+                deposit: proposal.deposit().to_str(),
+                reward_account,
+                anchor: decode_anchor(&proposal.anchor()),
+                previous_action: action.previous_action,
+                constitution: action.constitution,
+                committee_update: action.committee_update,
+                action: action.action,
+            });
+        }
+    }
+    to_versioned_json(&DecodedProposals { proposals: decoded })
+}
+
+fn decode_action(index: usize, action: &GovernanceAction) -> Result<DecodedAction, JsError> {
+    if let Some(new_constitution) = action.as_new_constitution_action() {
+        return Ok(DecodedAction {
+            previous_action: new_constitution.gov_action_id().map(|id| action_id(&id)),
+            constitution: Some(decode_constitution(&new_constitution.constitution())),
+            ..Default::default()
+        });
+    }
+    if let Some(update_committee) = action.as_new_committee_action() {
+        return Ok(DecodedAction {
+            previous_action: update_committee.gov_action_id().map(|id| action_id(&id)),
+            committee_update: Some(DecodedCommitteeUpdate {
+                members_to_remove: credentials_to_json(&update_committee.members_to_remove()),
+                members_to_add: committee_members(&update_committee.committee()),
+                quorum_threshold: quorum_threshold(&update_committee.committee()),
+            }),
+            ..Default::default()
+        });
+    }
+    let json = action
+        .to_json()
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error converting proposal {} to JSON: {:?}", index, e)))?;
+    let action = serde_json::from_str(&json).map_err(|e| ErrorCode::Internal.error(&format!("Invalid proposal JSON: {}", e)))?;
+    Ok(DecodedAction { action: Some(action), ..Default::default() })
+}
+
+fn decode_anchor(anchor: &Anchor) -> DecodedAnchor {
+    DecodedAnchor {
+        url: anchor.url().url(),
+        data_hash: anchor.anchor_data_hash().to_hex(),
+    }
+}
+
+fn decode_constitution(constitution: &Constitution) -> DecodedConstitution {
+    DecodedConstitution {
+        anchor: decode_anchor(&constitution.anchor()),
+        guardrails_script_hash: constitution.script_hash().map(|hash| hash.to_hex()),
+    }
+}
+
+fn committee_members(committee: &Committee) -> Vec<CommitteeMember> {
+    let keys = committee.members_keys();
+    (0..keys.len())
+        .map(|i| {
+            let credential = keys.get(i);
+            CommitteeMember {
+                term_epoch: committee.get_member_epoch(&credential),
+                credential: stake_cred_to_json(&credential),
+            }
+        })
+        .collect()
+}
+
+fn quorum_threshold(committee: &Committee) -> QuorumThreshold {
+    let threshold = committee.quorum_threshold();
+    let numerator = threshold.numerator().to_str();
+    let denominator = threshold.denominator().to_str();
+    let ratio = match (numerator.parse::<f64>(), denominator.parse::<f64>()) {
+        (Ok(n), Ok(d)) if d != 0.0 => n / d,
+        _ => 0.0,
+    };
+    QuorumThreshold { numerator, denominator, ratio }
+}
+
+fn credentials_to_json(credentials: &Credentials) -> Vec<Value> {
+    (0..credentials.len()).map(|i| stake_cred_to_json(&credentials.get(i))).collect()
+}
+
+fn action_id(id: &GovernanceActionId) -> String {
+    format!("{}#{}", id.transaction_id().to_hex(), id.index())
+}
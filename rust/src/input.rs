@@ -0,0 +1,49 @@
+use serde_json::json;
+use crate::error::{CquisitorError, ErrorCode};
+use crate::js_error::JsError;
+
+/// Largest hex argument accepted, in decoded bytes. Far above any tx, script
+/// or datum, it only stops pasted garbage from exhausting WASM memory.
+pub const MAX_INPUT_BYTES: usize = 16 * 1024 * 1024;
+
+/// Hex argument with surrounding whitespace and a `0x` prefix removed,
+/// failing with `invalid_hex` on empty input, odd length or a non hex digit
+/// and with `input_too_large` above `MAX_INPUT_BYTES`.
+pub(crate) fn sanitize_hex(input: &str) -> Result<&str, JsError> {
+    let trimmed = input.trim();
+    let hex = trimmed
+        .strip_prefix("0x")
+        .or_else(|| trimmed.strip_prefix("0X"))
+        .unwrap_or(trimmed);
+    if hex.is_empty() {
+        return Err(ErrorCode::InvalidHex.error("Hex input is empty"));
+    }
+    if hex.len() / 2 > MAX_INPUT_BYTES {
+        let message = format!("Hex input of {} bytes exceeds the limit of {} bytes", hex.len() / 2, MAX_INPUT_BYTES);
+        return Err(CquisitorError::new(ErrorCode::InputTooLarge, &message)
+            .with_details(json!({ "size": hex.len() / 2, "limit": MAX_INPUT_BYTES }))
+            .into());
+    }
+    // offsets are reported in the argument as given, prefix and whitespace included
+    let offset = input.len() - input.trim_start().len() + (trimmed.len() - hex.len());
+    if let Some((position, c)) = hex.char_indices().find(|(_, c)| !c.is_ascii_hexdigit()) {
+        return Err(ErrorCode::InvalidHex.error(&format!(
+            "Invalid hex character {:?} at position {}",
+            c,
+            offset + position
+        )));
+    }
+    if hex.len() % 2 != 0 {
+        return Err(ErrorCode::InvalidHex.error(&format!(
+            "Hex input has odd length {}, every byte takes two digits",
+            hex.len()
+        )));
+    }
+    Ok(hex)
+}
+
+/// Bytes of a hex argument, sanitized as in `sanitize_hex`.
+pub(crate) fn decode_hex(input: &str) -> Result<Vec<u8>, JsError> {
+    let hex = sanitize_hex(input)?;
+    hex::decode(hex).map_err(|e| ErrorCode::InvalidHex.error(&e.to_string()))
+}
@@ -32,4 +32,20 @@ impl std::fmt::Display for JsError {
 }
 
 #[cfg(not(all(target_arch = "wasm32", not(target_os = "emscripten"))))]
-impl std::error::Error for JsError {}
\ No newline at end of file
+impl std::error::Error for JsError {}
+
+/// Message of `error`, the [`CquisitorError`](crate::error::CquisitorError)
+/// JSON for the errors raised here.
+#[cfg(all(feature = "providers", target_arch = "wasm32", not(target_os = "emscripten")))]
+pub(crate) fn error_message(error: JsError) -> String {
+    let value = wasm_bindgen::JsValue::from(error);
+    js_sys::Reflect::get(&value, &wasm_bindgen::JsValue::from_str("message"))
+        .ok()
+        .and_then(|message| message.as_string())
+        .unwrap_or_default()
+}
+
+#[cfg(all(feature = "providers", not(all(target_arch = "wasm32", not(target_os = "emscripten")))))]
+pub(crate) fn error_message(error: JsError) -> String {
+    error.msg
+}
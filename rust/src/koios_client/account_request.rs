@@ -0,0 +1,53 @@
+use serde_json::Value;
+use crate::js_error::JsError;
+use crate::koios_client::config::KoiosConfig;
+use crate::koios_client::error_mapper::to_js_error;
+use crate::koios_client::models::{AccountInfoRequest, AccountInfoResponse, ApiError, ApiResult};
+use crate::koios_client::request::send_with_retry;
+use crate::koios_client::response_schema::parse_items;
+
+pub(crate) async fn get_account_info(
+    stake_addresses: &Vec<String>,
+    config: &KoiosConfig,
+) -> Result<Vec<AccountInfoResponse>, JsError> {
+    let request = AccountInfoRequest {
+        stake_addresses: stake_addresses.clone(),
+    };
+
+    let url = config.build_url("account_info");
+
+    let response = send_with_retry(config, "get_account_info.send", |client| {
+        client.post(url.as_str()).json(&request)
+    })
+    .await?;
+
+    let body: Value = response
+        .json()
+        .await
+        .map_err(|err| to_js_error(err, "get_account_info.parse"))?;
+
+    parse_items(body, "get_account_info.parse", "account_info", 0)
+}
+
+pub(crate) async fn get_account_addresses(
+    stake_addresses: &Vec<String>,
+    config: &KoiosConfig,
+) -> Result<Vec<Value>, JsError> {
+    let request = AccountInfoRequest {
+        stake_addresses: stake_addresses.clone(),
+    };
+
+    let url = config.build_url("account_addresses");
+
+    let response = send_with_retry(config, "get_account_addresses.send", |client| {
+        client.post(url.as_str()).json(&request)
+    })
+    .await?;
+
+    let addresses: ApiResult<Vec<Value>> = response
+        .json()
+        .await
+        .map_err(|err| to_js_error(err, "get_account_addresses.parse"))?;
+
+    addresses.map_err(|err: ApiError| err.to_js_error())
+}
@@ -0,0 +1,45 @@
+use serde_json::Value;
+use crate::js_error::JsError;
+use crate::koios_client::config::KoiosConfig;
+use crate::koios_client::error_mapper::to_js_error;
+use crate::koios_client::models::{AddressInfoRequest, AddressUtxosRequest, ApiError, ApiResult, UtxoInfoResponse};
+use crate::koios_client::request::{fetch_all_pages, send_with_retry};
+
+/// Live UTxOs at the addresses, in the same shape as `utxo_info`.
+pub(crate) async fn get_address_utxos(
+    addresses: &Vec<String>,
+    config: &KoiosConfig,
+) -> Result<Vec<UtxoInfoResponse>, JsError> {
+    let request = AddressUtxosRequest {
+        addresses: addresses.clone(),
+        extended: true,
+    };
+    let url = config.build_url("address_utxos");
+    fetch_all_pages(config, "get_address_utxos", &url, |client, page_url| {
+        client.post(page_url).json(&request)
+    })
+    .await
+}
+
+pub(crate) async fn get_address_info(
+    addresses: &Vec<String>,
+    config: &KoiosConfig,
+) -> Result<Vec<Value>, JsError> {
+    let request = AddressInfoRequest {
+        addresses: addresses.clone(),
+    };
+
+    let url = config.build_url("address_info");
+
+    let response = send_with_retry(config, "get_address_info.send", |client| {
+        client.post(url.as_str()).json(&request)
+    })
+    .await?;
+
+    let infos: ApiResult<Vec<Value>> = response
+        .json()
+        .await
+        .map_err(|err| to_js_error(err, "get_address_info.parse"))?;
+
+    infos.map_err(|err: ApiError| err.to_js_error())
+}
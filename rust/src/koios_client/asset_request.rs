@@ -0,0 +1,30 @@
+use serde_json::Value;
+use crate::js_error::JsError;
+use crate::koios_client::config::KoiosConfig;
+use crate::koios_client::error_mapper::to_js_error;
+use crate::koios_client::models::{ApiError, ApiResult, AssetInfoRequest};
+use crate::koios_client::request::send_with_retry;
+
+/// Koios `asset_info` items for `(policy_id, asset_name_hex)` pairs.
+pub(crate) async fn get_asset_info(
+    assets: &Vec<(String, String)>,
+    config: &KoiosConfig,
+) -> Result<Vec<Value>, JsError> {
+    let request = AssetInfoRequest {
+        asset_list: assets.clone(),
+    };
+
+    let url = config.build_url("asset_info");
+
+    let response = send_with_retry(config, "get_asset_info.send", |client| {
+        client.post(url.as_str()).json(&request)
+    })
+    .await?;
+
+    let infos: ApiResult<Vec<Value>> = response
+        .json()
+        .await
+        .map_err(|err| to_js_error(err, "get_asset_info.parse"))?;
+
+    infos.map_err(|err: ApiError| err.to_js_error())
+}
@@ -0,0 +1,123 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use reqwest::RequestBuilder;
+use serde::Deserialize;
+use crate::error::ErrorCode;
+use crate::js_error::JsError;
+use crate::koios_client::network_type::NetworkType;
+use crate::koios_client::rate_limit::{RateLimitConfig, TokenBucket};
+
+/// Retries for transient failures: 5xx responses, timeouts and dropped
+/// connections. The delay doubles after every attempt up to `max_backoff_ms`.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub(crate) struct RetryConfig {
+    pub(crate) max_attempts: u32,
+    pub(crate) initial_backoff_ms: u64,
+    pub(crate) max_backoff_ms: u64,
+    pub(crate) retry_on_server_error: bool,
+    pub(crate) retry_on_timeout: bool,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        RetryConfig {
+            max_attempts: 3,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 8000,
+            retry_on_server_error: true,
+            retry_on_timeout: true,
+        }
+    }
+}
+
+/// Extra headers for every provider request, e.g. for an API gateway, and a
+/// prefix put in front of every request URL to go through a CORS proxy, e.g.
+/// `https://proxy.example.com/` requests `https://proxy.example.com/https://api.koios.rest/...`.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub(crate) struct HttpOptions {
+    pub(crate) headers: BTreeMap<String, String>,
+    pub(crate) proxy_url: Option<String>,
+}
+
+impl HttpOptions {
+    pub(crate) fn url(&self, url: &str) -> String {
+        match &self.proxy_url {
+            Some(proxy_url) if !proxy_url.is_empty() => format!("{}{}", proxy_url, url),
+            _ => url.to_string(),
+        }
+    }
+
+    pub(crate) fn apply(&self, request: RequestBuilder) -> RequestBuilder {
+        self.headers
+            .iter()
+            .fold(request, |request, (name, value)| request.header(name.as_str(), value.as_str()))
+    }
+}
+
+/// Client options callers can pass as JSON, all fields are optional.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub(crate) struct ClientOptions {
+    pub(crate) retry: RetryConfig,
+    /// Max UTxO references per `utxo_info` request.
+    pub(crate) utxo_batch_size: usize,
+    /// Max requests in flight when a query is split into several requests.
+    pub(crate) max_concurrent_requests: usize,
+    pub(crate) rate_limit: Option<RateLimitConfig>,
+    #[serde(flatten)]
+    pub(crate) http: HttpOptions,
+}
+
+impl Default for ClientOptions {
+    fn default() -> Self {
+        ClientOptions {
+            retry: RetryConfig::default(),
+            utxo_batch_size: 100,
+            max_concurrent_requests: 4,
+            rate_limit: None,
+            http: HttpOptions::default(),
+        }
+    }
+}
+
+impl ClientOptions {
+    pub(crate) fn from_json(options_json: &str) -> Result<Self, JsError> {
+        if options_json.trim().is_empty() {
+            return Ok(ClientOptions::default());
+        }
+        serde_json::from_str(options_json)
+            .map_err(|e| ErrorCode::InvalidInput.error(&format!("Invalid client options: {}", e)))
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct KoiosConfig {
+    pub(crate) network_type: NetworkType,
+    /// Koios' public tier works without a token, so it's only sent when set.
+    pub(crate) api_token: Option<String>,
+    pub(crate) options: ClientOptions,
+    /// Shared by every request made with this config, clones included.
+    pub(crate) rate_limiter: Option<Rc<RefCell<TokenBucket>>>,
+}
+
+impl KoiosConfig {
+    pub(crate) fn new(network_type: NetworkType, api_token: Option<String>, options: ClientOptions) -> Self {
+        KoiosConfig {
+            network_type,
+            api_token: api_token.filter(|token| !token.trim().is_empty()),
+            rate_limiter: options
+                .rate_limit
+                .as_ref()
+                .map(|rate_limit| Rc::new(RefCell::new(TokenBucket::new(rate_limit)))),
+            options,
+        }
+    }
+
+    /// Koios endpoint URL, through the proxy if one is configured.
+    pub(crate) fn build_url(&self, endpoint: &str) -> String {
+        self.options.http.url(&self.network_type.build_url(endpoint))
+    }
+}
@@ -0,0 +1,30 @@
+use serde_json::Value;
+use crate::js_error::JsError;
+use crate::koios_client::config::KoiosConfig;
+use crate::koios_client::error_mapper::to_js_error;
+use crate::koios_client::models::{DatumInfoRequest, DatumInfoResponse};
+use crate::koios_client::request::send_with_retry;
+use crate::koios_client::response_schema::parse_items;
+
+pub(crate) async fn get_datums(
+    datum_hashes: &Vec<String>,
+    config: &KoiosConfig,
+) -> Result<Vec<DatumInfoResponse>, JsError> {
+    let request = DatumInfoRequest {
+        datum_hashes: datum_hashes.clone(),
+    };
+
+    let url = config.build_url("datum_info");
+
+    let response = send_with_retry(config, "get_datums.send", |client| {
+        client.post(url.as_str()).json(&request)
+    })
+    .await?;
+
+    let body: Value = response
+        .json()
+        .await
+        .map_err(|err| to_js_error(err, "get_datums.parse"))?;
+
+    parse_items(body, "get_datums.parse", "datum_info", 0)
+}
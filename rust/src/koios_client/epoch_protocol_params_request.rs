@@ -1,36 +1,32 @@
+use serde_json::Value;
+use crate::error::ErrorCode;
 use crate::js_error::JsError;
+use crate::koios_client::config::KoiosConfig;
 use crate::koios_client::error_mapper::to_js_error;
-use crate::koios_client::models::{ApiResult, EpochParamResponse};
-use crate::koios_client::network_type::NetworkType;
-use reqwest::Client;
+use crate::koios_client::models::EpochParamResponse;
+use crate::koios_client::request::send_with_retry;
+use crate::koios_client::response_schema::parse_items;
 
 pub(crate) async fn get_epoch_protocol_params(
     epoch: u64,
-    network_type: NetworkType,
-    api_token: &str
+    config: &KoiosConfig,
 ) -> Result<EpochParamResponse, JsError> {
-    let client = Client::new();
-    let url = network_type.build_url(format!("epoch_params?_epoch_no={}", epoch).as_str());
+    let url = config.build_url(format!("epoch_params?_epoch_no={}", epoch).as_str());
 
-    let response = client
-        .get(url)
-        .header("Accept", "application/json")
-        // .header("Content-Type", "application/json")
-        .bearer_auth(api_token)
-        .send()
-        .await
-        .map_err(|err| to_js_error(err, "get_epoch_protocol_params.send"))?;
+    let response = send_with_retry(config, "get_epoch_protocol_params.send", |client| {
+        client.get(url.as_str())
+    })
+    .await?;
 
-    let api_result: Vec<EpochParamResponse> = response
-        .error_for_status()
-        .map_err(|err| to_js_error(err, "get_epoch_protocol_params.status"))?
+    let body: Value = response
         .json()
         .await
         .map_err(|err| to_js_error(err, "get_epoch_protocol_params.parse"))?;
+    let api_result: Vec<EpochParamResponse> = parse_items(body, "get_epoch_protocol_params.parse", "epoch_params", 0)?;
 
     let pp = api_result.first().cloned().map_or_else(
         || {
-            Err(JsError::new("No epoch protocol params found"))
+            Err(ErrorCode::NotFound.error("No epoch protocol params found"))
         },
         |x| Ok(x),
     )?;
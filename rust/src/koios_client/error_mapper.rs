@@ -1,6 +1,120 @@
-use reqwest::Error;
+use reqwest::{Error, Response, StatusCode};
+use serde::Serialize;
+use crate::error::{CquisitorError, ErrorCode};
 use crate::js_error::JsError;
 
+// provider error pages can be large HTML documents, only their start is kept
+pub(crate) const MAX_BODY_LEN: usize = 2000;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ProviderErrorKind {
+    /// 401 or 403, the API token is missing, invalid or lacks access
+    Auth,
+    /// 429
+    RateLimited,
+    NotFound,
+    /// any other 4xx
+    BadRequest,
+    /// 5xx
+    Server,
+    Timeout,
+    /// the request didn't reach the provider, including CORS rejections
+    Network,
+    /// the response doesn't have the expected shape
+    Decode,
+    /// an error the provider reported in a successful response
+    Provider,
+}
+
+/// Failed provider request. It reaches JS as a `provider_error`
+/// [`CquisitorError`] with this struct as `details`, so UIs can tell auth
+/// failures, rate limits and bad requests apart.
+#[derive(Serialize, Debug, Clone)]
+pub(crate) struct ProviderError {
+    pub(crate) kind: ProviderErrorKind,
+    /// Request that failed, e.g. `get_utxos` or an Ogmios method.
+    pub(crate) endpoint: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) status: Option<u16>,
+    /// Response body as the provider sent it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) body: Option<String>,
+    pub(crate) message: String,
+    pub(crate) retryable: bool,
+    /// Delay the provider asked for before retrying, from `Retry-After`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) retry_after_ms: Option<u64>,
+}
+
+impl ProviderError {
+    pub(crate) fn new(kind: ProviderErrorKind, endpoint: &str, message: &str) -> Self {
+        ProviderError {
+            kind,
+            endpoint: endpoint.to_string(),
+            status: None,
+            body: None,
+            message: message.to_string(),
+            retry_after_ms: None,
+            retryable: matches!(
+                kind,
+                ProviderErrorKind::RateLimited
+                    | ProviderErrorKind::Server
+                    | ProviderErrorKind::Timeout
+                    | ProviderErrorKind::Network
+            ),
+        }
+    }
+
+    pub(crate) fn from_status(status: StatusCode, endpoint: &str, body: Option<String>) -> Self {
+        let kind = match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => ProviderErrorKind::Auth,
+            StatusCode::TOO_MANY_REQUESTS => ProviderErrorKind::RateLimited,
+            StatusCode::NOT_FOUND => ProviderErrorKind::NotFound,
+            status if status.is_server_error() => ProviderErrorKind::Server,
+            _ => ProviderErrorKind::BadRequest,
+        };
+        let mut error = ProviderError::new(kind, endpoint, &format!("HTTP {}", status));
+        error.status = Some(status.as_u16());
+        error.body = body.map(|body| body.chars().take(MAX_BODY_LEN).collect());
+        error
+    }
+
+    pub(crate) fn from_reqwest(e: &Error, endpoint: &str) -> Self {
+        if let Some(status) = e.status() {
+            let mut error = ProviderError::from_status(status, endpoint, None);
+            error.message = e.to_string();
+            return error;
+        }
+        let kind = if e.is_timeout() {
+            ProviderErrorKind::Timeout
+        } else if e.is_decode() {
+            ProviderErrorKind::Decode
+        } else {
+            ProviderErrorKind::Network
+        };
+        ProviderError::new(kind, endpoint, &e.to_string())
+    }
+
+    pub(crate) fn to_js_error(&self) -> JsError {
+        let message = format!("{}, {}", self.endpoint, self.message);
+        let mut error = CquisitorError::new(ErrorCode::ProviderError, &message);
+        error.details = serde_json::to_value(self).ok();
+        error.to_js_error()
+    }
+}
+
 pub(crate) fn to_js_error(e: Error, location: &'static str) -> JsError {
-    JsError::new(&format!("{}, {:?}", location, e))
-}
\ No newline at end of file
+    ProviderError::from_reqwest(&e, location).to_js_error()
+}
+
+/// Passes successful responses through, turns any other into a
+/// [`ProviderError`] carrying the status and the body the provider sent.
+pub(crate) async fn check_status(response: Response, location: &str) -> Result<Response, JsError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response.text().await.ok().filter(|body| !body.is_empty());
+    Err(ProviderError::from_status(status, location, body).to_js_error())
+}
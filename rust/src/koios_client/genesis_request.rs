@@ -0,0 +1,28 @@
+use serde_json::Value;
+use crate::error::ErrorCode;
+use crate::js_error::JsError;
+use crate::koios_client::config::KoiosConfig;
+use crate::koios_client::error_mapper::to_js_error;
+use crate::koios_client::models::ApiResult;
+use crate::koios_client::request::send_with_retry;
+
+/// Koios `genesis`, numbers come as strings.
+pub(crate) async fn get_genesis(config: &KoiosConfig) -> Result<Value, JsError> {
+    let url = config.build_url("genesis");
+
+    let response = send_with_retry(config, "get_genesis.send", |client| {
+        client.get(url.as_str())
+    })
+    .await?;
+
+    let api_result: ApiResult<Vec<Value>> = response
+        .json()
+        .await
+        .map_err(|err| to_js_error(err, "get_genesis.parse"))?;
+
+    api_result
+        .map_err(|err| err.to_js_error())?
+        .into_iter()
+        .next()
+        .ok_or_else(|| ErrorCode::ProviderError.error("get_genesis, empty response"))
+}
@@ -0,0 +1,54 @@
+use serde_json::Value;
+use crate::js_error::JsError;
+use crate::koios_client::config::KoiosConfig;
+use crate::koios_client::error_mapper::to_js_error;
+use crate::koios_client::models::{ApiError, ApiResult, DrepInfoRequest};
+use crate::koios_client::request::{fetch_all_pages, send_with_retry};
+
+pub(crate) async fn get_proposal_list(config: &KoiosConfig) -> Result<Vec<Value>, JsError> {
+    let url = config.build_url("proposal_list");
+    fetch_all_pages(config, "get_proposal_list", &url, |client, page_url| client.get(page_url)).await
+}
+
+/// Votes cast on a governance action, `proposal_id` in CIP-129 bech32.
+pub(crate) async fn get_proposal_votes(proposal_id: &str, config: &KoiosConfig) -> Result<Vec<Value>, JsError> {
+    let url = format!(
+        "{}?_proposal_id={}",
+        config.build_url("proposal_votes"),
+        proposal_id
+    );
+    fetch_all_pages(config, "get_proposal_votes", &url, |client, page_url| client.get(page_url)).await
+}
+
+pub(crate) async fn get_drep_info(drep_ids: &Vec<String>, config: &KoiosConfig) -> Result<Vec<Value>, JsError> {
+    post_drep_ids("drep_info", "get_drep_info", drep_ids, config).await
+}
+
+pub(crate) async fn get_drep_metadata(drep_ids: &Vec<String>, config: &KoiosConfig) -> Result<Vec<Value>, JsError> {
+    post_drep_ids("drep_metadata", "get_drep_metadata", drep_ids, config).await
+}
+
+async fn post_drep_ids(
+    endpoint: &str,
+    location: &'static str,
+    drep_ids: &Vec<String>,
+    config: &KoiosConfig,
+) -> Result<Vec<Value>, JsError> {
+    let request = DrepInfoRequest {
+        drep_ids: drep_ids.clone(),
+    };
+
+    let url = config.build_url(endpoint);
+
+    let response = send_with_retry(config, location, |client| {
+        client.post(url.as_str()).json(&request)
+    })
+    .await?;
+
+    let infos: ApiResult<Vec<Value>> = response
+        .json()
+        .await
+        .map_err(|err| to_js_error(err, location))?;
+
+    infos.map_err(|err: ApiError| err.to_js_error())
+}
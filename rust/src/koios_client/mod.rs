@@ -1,6 +1,37 @@
 pub mod models;
+#[cfg(feature = "providers")]
 pub(crate) mod utxo_request;
+#[cfg(feature = "providers")]
 pub(crate) mod query_chain_tip_request;
+#[cfg(feature = "providers")]
 pub(crate) mod network_type;
+#[cfg(feature = "providers")]
 pub(crate) mod epoch_protocol_params_request;
-pub(crate) mod error_mapper;
\ No newline at end of file
+#[cfg(feature = "providers")]
+pub(crate) mod error_mapper;
+#[cfg(feature = "providers")]
+pub(crate) mod datum_request;
+#[cfg(feature = "providers")]
+pub(crate) mod script_request;
+#[cfg(feature = "providers")]
+pub(crate) mod config;
+#[cfg(feature = "providers")]
+pub(crate) mod request;
+#[cfg(feature = "providers")]
+pub(crate) mod response_schema;
+#[cfg(feature = "providers")]
+pub(crate) mod rate_limit;
+#[cfg(feature = "providers")]
+pub(crate) mod tx_request;
+#[cfg(feature = "providers")]
+pub(crate) mod address_request;
+#[cfg(feature = "providers")]
+pub(crate) mod account_request;
+#[cfg(feature = "providers")]
+pub(crate) mod asset_request;
+#[cfg(feature = "providers")]
+pub(crate) mod pool_request;
+#[cfg(feature = "providers")]
+pub(crate) mod governance_request;
+#[cfg(feature = "providers")]
+pub(crate) mod genesis_request;
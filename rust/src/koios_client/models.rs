@@ -1,6 +1,10 @@
+// most models are only read by the provider requests
+#![cfg_attr(not(feature = "providers"), allow(dead_code))]
 use std::fmt::format;
 use serde::{Deserialize, Serialize};
 use crate::js_error::JsError;
+#[cfg(feature = "providers")]
+use crate::koios_client::error_mapper::{ProviderError, ProviderErrorKind};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub (crate) struct UtxoInfoRequest {
@@ -10,13 +14,13 @@ pub (crate) struct UtxoInfoRequest {
     pub(crate) extended: bool,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub (crate) struct InlineDatum {
     pub(crate) bytes: String,
     pub(crate) value: serde_json::Value,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub (crate) struct ReferenceScript {
     pub(crate) hash: String,
     pub(crate) size: u64,
@@ -26,7 +30,7 @@ pub (crate) struct ReferenceScript {
     pub(crate) value: Option<serde_json::Value>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub (crate) struct Asset {
     pub(crate) policy_id: String,
     pub(crate) asset_name: Option<String>,
@@ -35,7 +39,7 @@ pub (crate) struct Asset {
     pub(crate) quantity: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub(crate) struct UtxoInfoResponse {
     pub(crate) tx_hash: String,
     pub(crate) tx_index: u64,
@@ -53,6 +57,133 @@ pub(crate) struct UtxoInfoResponse {
     pub(crate) is_spent: bool,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct DatumInfoRequest {
+    #[serde(rename = "_datum_hashes")]
+    pub(crate) datum_hashes: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct DatumInfoResponse {
+    pub(crate) datum_hash: String,
+    pub(crate) creation_tx_hash: Option<String>,
+    pub(crate) value: Option<serde_json::Value>,
+    pub(crate) bytes: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct ScriptInfoRequest {
+    #[serde(rename = "_script_hashes")]
+    pub(crate) script_hashes: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct ScriptInfoResponse {
+    pub(crate) script_hash: String,
+    pub(crate) creation_tx_hash: Option<String>,
+    #[serde(rename = "type")]
+    pub(crate) script_type: String,
+    pub(crate) value: Option<serde_json::Value>,
+    pub(crate) bytes: Option<String>,
+    pub(crate) size: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct AddressUtxosRequest {
+    #[serde(rename = "_addresses")]
+    pub(crate) addresses: Vec<String>,
+    #[serde(rename = "_extended")]
+    pub(crate) extended: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct AddressInfoRequest {
+    #[serde(rename = "_addresses")]
+    pub(crate) addresses: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct AccountInfoRequest {
+    #[serde(rename = "_stake_addresses")]
+    pub(crate) stake_addresses: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct AccountInfoResponse {
+    pub(crate) stake_address: String,
+    pub(crate) status: Option<String>,
+    pub(crate) delegated_pool: Option<String>,
+    pub(crate) delegated_drep: Option<String>,
+    pub(crate) total_balance: Option<String>,
+    pub(crate) utxo: Option<String>,
+    pub(crate) rewards: Option<String>,
+    pub(crate) withdrawals: Option<String>,
+    pub(crate) rewards_available: Option<String>,
+    pub(crate) deposit: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct AssetInfoRequest {
+    /// `[policy_id, asset_name_hex]` pairs
+    #[serde(rename = "_asset_list")]
+    pub(crate) asset_list: Vec<(String, String)>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct PoolInfoRequest {
+    #[serde(rename = "_pool_bech32_ids")]
+    pub(crate) pool_ids: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct DrepInfoRequest {
+    #[serde(rename = "_drep_ids")]
+    pub(crate) drep_ids: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct TxCborRequest {
+    #[serde(rename = "_tx_hashes")]
+    pub(crate) tx_hashes: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct TxCborResponse {
+    pub(crate) tx_hash: String,
+    pub(crate) block_hash: Option<String>,
+    pub(crate) block_height: Option<u64>,
+    pub(crate) epoch_no: Option<u64>,
+    pub(crate) absolute_slot: Option<u64>,
+    pub(crate) tx_timestamp: Option<u64>,
+    pub(crate) cbor: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct TxStatusResponse {
+    pub(crate) tx_hash: String,
+    pub(crate) num_confirmations: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct TxInfoRequest {
+    #[serde(rename = "_tx_hashes")]
+    pub(crate) tx_hashes: Vec<String>,
+    #[serde(rename = "_inputs")]
+    pub(crate) inputs: bool,
+    #[serde(rename = "_metadata")]
+    pub(crate) metadata: bool,
+    #[serde(rename = "_assets")]
+    pub(crate) assets: bool,
+    #[serde(rename = "_withdrawals")]
+    pub(crate) withdrawals: bool,
+    #[serde(rename = "_certs")]
+    pub(crate) certs: bool,
+    #[serde(rename = "_scripts")]
+    pub(crate) scripts: bool,
+    #[serde(rename = "_bytecode")]
+    pub(crate) bytecode: bool,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub(crate) struct QueryChainTipResponse {
     pub(crate) hash: String,
@@ -63,7 +194,7 @@ pub(crate) struct QueryChainTipResponse {
     pub(crate) block_time: u64,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub(crate) struct CostModels {
     #[serde(rename = "PlutusV1")]
     pub(crate) plutus_v1: Option<Vec<i64>>,
@@ -73,7 +204,7 @@ pub(crate) struct CostModels {
     pub(crate) plutus_v3: Option<Vec<i64>>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub (crate) struct EpochParamResponse {
     pub(crate) epoch_no: u64,
     pub(crate) min_fee_a: Option<u64>,
@@ -139,9 +270,13 @@ pub(crate) struct  ApiError {
     pub(crate) error: Option<String>,
 }
 
+#[cfg(feature = "providers")]
 impl ApiError {
     pub (crate) fn to_js_error(&self) -> JsError {
-        JsError::new(&format!("ApiError: {:?}", self))
+        let message = self.message.clone().or_else(|| self.error.clone()).unwrap_or_else(|| "Koios error".to_string());
+        let mut error = ProviderError::new(ProviderErrorKind::Provider, "koios", &message);
+        error.body = serde_json::to_string(self).ok();
+        error.to_js_error()
     }
 }
 
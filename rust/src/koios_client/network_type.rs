@@ -1,8 +1,11 @@
 
+#[derive(Clone)]
 pub(crate) enum NetworkType {
     Mainnet,
     TestnetPreprod,
     TestnetPreview,
+    /// Base URL of a self-hosted Koios instance or a proxy in front of one.
+    Custom(String),
 }
 
 impl NetworkType {
@@ -11,11 +14,12 @@ impl NetworkType {
             NetworkType::Mainnet => "https://api.koios.rest/api/v1/",
             NetworkType::TestnetPreprod => "https://preprod.koios.rest/api/v1/",
             NetworkType::TestnetPreview => "https://preview.koios.rest/api/v1/",
+            NetworkType::Custom(url) => url,
         }
     }
 
     pub(crate) fn build_url(&self, endpoint: &str) -> String {
-        format!("{}{}", self.get_url(), endpoint)
+        format!("{}/{}", self.get_url().trim_end_matches('/'), endpoint)
     }
 }
 
@@ -0,0 +1,29 @@
+use serde_json::Value;
+use crate::js_error::JsError;
+use crate::koios_client::config::KoiosConfig;
+use crate::koios_client::error_mapper::to_js_error;
+use crate::koios_client::models::{ApiError, ApiResult, PoolInfoRequest};
+use crate::koios_client::request::send_with_retry;
+
+pub(crate) async fn get_pool_info(
+    pool_ids: &Vec<String>,
+    config: &KoiosConfig,
+) -> Result<Vec<Value>, JsError> {
+    let request = PoolInfoRequest {
+        pool_ids: pool_ids.clone(),
+    };
+
+    let url = config.build_url("pool_info");
+
+    let response = send_with_retry(config, "get_pool_info.send", |client| {
+        client.post(url.as_str()).json(&request)
+    })
+    .await?;
+
+    let infos: ApiResult<Vec<Value>> = response
+        .json()
+        .await
+        .map_err(|err| to_js_error(err, "get_pool_info.parse"))?;
+
+    infos.map_err(|err: ApiError| err.to_js_error())
+}
@@ -1,32 +1,26 @@
+use serde_json::Value;
 use crate::js_error::JsError;
+use crate::koios_client::config::KoiosConfig;
 use crate::koios_client::error_mapper::to_js_error;
-use crate::koios_client::models::{ApiResult, QueryChainTipResponse};
-use crate::koios_client::network_type::NetworkType;
-use reqwest::Client;
+use crate::koios_client::models::QueryChainTipResponse;
+use crate::koios_client::request::send_with_retry;
+use crate::koios_client::response_schema::parse_items;
 
 pub(crate) async fn get_chain_tip(
-    network_type: NetworkType,
-    api_token: &str,
+    config: &KoiosConfig,
 ) -> Result<QueryChainTipResponse, JsError> {
-    let client = Client::new();
-    let url = network_type.build_url("tip");
+    let url = config.build_url("tip");
 
-    let response = client
-        .get(url)
-        .header("Accept", "application/json")
-        // .header("Content-Type", "application/json")
-        .bearer_auth(api_token)
-        .send()
-        .await
-        .map_err(|err| to_js_error(err, "get_chain_tip.send"))?;
+    let response = send_with_retry(config, "get_chain_tip.send", |client| {
+        client.get(url.as_str())
+    })
+    .await?;
 
-    let api_result: ApiResult<Vec<QueryChainTipResponse>> = response
-        .error_for_status()
-        .map_err(|err| to_js_error(err, "get_chain_tip.status"))?
+    let body: Value = response
         .json()
         .await
         .map_err(|err| to_js_error(err, "get_chain_tip.parse"))?;
 
-    let chain_tip = api_result.map_err(|err| err.to_js_error())?;
+    let chain_tip: Vec<QueryChainTipResponse> = parse_items(body, "get_chain_tip.parse", "tip", 0)?;
     Ok(chain_tip.first().cloned().unwrap_or_default())
 }
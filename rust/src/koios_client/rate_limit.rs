@@ -0,0 +1,48 @@
+use serde::Deserialize;
+use crate::time_utils::now_ms;
+
+/// Client-side limit so bursts of lookups stay under the provider's quota.
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub(crate) struct RateLimitConfig {
+    pub(crate) requests_per_second: f64,
+    #[serde(default = "default_burst")]
+    pub(crate) burst: u32,
+}
+
+fn default_burst() -> u32 {
+    1
+}
+
+pub(crate) struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_ms: f64,
+    last_refill: f64,
+}
+
+impl TokenBucket {
+    pub(crate) fn new(config: &RateLimitConfig) -> Self {
+        let capacity = config.burst.max(1) as f64;
+        TokenBucket {
+            capacity,
+            tokens: capacity,
+            refill_per_ms: config.requests_per_second.max(f64::MIN_POSITIVE) / 1000.0,
+            last_refill: now_ms(),
+        }
+    }
+
+    /// Takes a token and returns how many milliseconds the caller has to wait
+    /// before sending. The token is reserved right away, so concurrent callers
+    /// queue up behind each other instead of all waking at once.
+    pub(crate) fn acquire(&mut self) -> u64 {
+        let now = now_ms();
+        self.tokens = (self.tokens + (now - self.last_refill) * self.refill_per_ms).min(self.capacity);
+        self.last_refill = now;
+        self.tokens -= 1.0;
+        if self.tokens >= 0.0 {
+            0
+        } else {
+            (-self.tokens / self.refill_per_ms).ceil() as u64
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use reqwest::header::{HeaderValue, RETRY_AFTER};
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use serde_json::Value;
+use crate::js_error::JsError;
+use crate::koios_client::config::KoiosConfig;
+use crate::koios_client::error_mapper::{check_status, to_js_error, ProviderError};
+use crate::koios_client::response_schema::{endpoint_name, parse_items, ResponseSchema};
+use crate::logger::log_warn;
+use crate::provider::request_log::send_logged;
+use crate::time_utils::sleep_ms;
+
+// Koios (PostgREST) never returns more than 1000 rows per response
+const KOIOS_PAGE_SIZE: usize = 1000;
+
+/// Sends the request built by `build`, retrying per the config's retry policy
+/// and honouring `Retry-After` on 429s. Waits for the rate limiter first.
+/// `build` is called again for every attempt since a request can't be reused.
+pub(crate) async fn send_with_retry<F>(
+    config: &KoiosConfig,
+    location: &'static str,
+    build: F,
+) -> Result<Response, JsError>
+where
+    F: Fn(&Client) -> RequestBuilder,
+{
+    let client = Client::new();
+    let retry = &config.options.retry;
+    let mut backoff = retry.initial_backoff_ms;
+    let mut attempt = 1;
+    loop {
+        if let Some(rate_limiter) = &config.rate_limiter {
+            let wait = rate_limiter.borrow_mut().acquire();
+            if wait > 0 {
+                sleep_ms(wait).await;
+            }
+        }
+        let mut request = config.options.http.apply(build(&client).header("Accept", "application/json"));
+        if let Some(api_token) = &config.api_token {
+            request = request.bearer_auth(api_token);
+        }
+        let result = send_logged(&client, request, location, attempt).await;
+        let retry_after = match &result {
+            Ok(response) if response.status() == StatusCode::TOO_MANY_REQUESTS => {
+                Some(retry_after_ms(response.headers().get(RETRY_AFTER)))
+            }
+            _ => None,
+        };
+        let retryable = match &result {
+            Ok(_) if retry_after.is_some() => true,
+            Ok(response) => retry.retry_on_server_error && response.status().is_server_error(),
+            Err(err) => retry.retry_on_timeout && is_transient(err),
+        };
+        if !retryable || attempt >= retry.max_attempts {
+            let response = result.map_err(|err| to_js_error(err, location))?;
+            if let Some(retry_after) = retry_after {
+                let body = response.text().await.ok().filter(|body| !body.is_empty());
+                let mut error = ProviderError::from_status(StatusCode::TOO_MANY_REQUESTS, location, body);
+                error.message = format!(
+                    "rate limited by provider (HTTP 429) after {} attempts, retry after {}",
+                    attempt,
+                    retry_after.map_or("an unspecified delay".to_string(), |ms| format!("{}ms", ms))
+                );
+                error.retry_after_ms = retry_after;
+                return Err(error.to_js_error());
+            }
+            return check_status(response, location).await;
+        }
+        let delay = retry_after.flatten().unwrap_or(backoff);
+        match &result {
+            Ok(response) => log_warn!("{} got HTTP {}, retrying in {} ms", location, response.status(), delay),
+            Err(err) => log_warn!("{} failed: {}, retrying in {} ms", location, err, delay),
+        }
+        sleep_ms(delay).await;
+        backoff = backoff.saturating_mul(2).min(retry.max_backoff_ms);
+        attempt += 1;
+    }
+}
+
+/// Whether a failed request is worth sending again: it timed out or never
+/// reached the server. Other errors (bad URL, redirect loops, undecodable
+/// bodies) fail the same way on every attempt.
+#[cfg(not(all(target_arch = "wasm32", not(target_os = "emscripten"))))]
+fn is_transient(err: &reqwest::Error) -> bool {
+    err.is_timeout() || err.is_connect()
+}
+
+/// reqwest has no `is_connect` on wasm, a rejected `fetch` (the browser's
+/// connection failure) surfaces as a request error there.
+#[cfg(all(target_arch = "wasm32", not(target_os = "emscripten")))]
+fn is_transient(err: &reqwest::Error) -> bool {
+    err.is_timeout() || err.is_request()
+}
+
+/// `Retry-After` in milliseconds. Only the delay-seconds form is understood,
+/// the HTTP-date form falls back to the regular backoff.
+fn retry_after_ms(header: Option<&HeaderValue>) -> Option<u64> {
+    header?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(|seconds| seconds * 1000)
+}
+
+/// Fetches every page of a list endpoint, following `offset`/`limit` until a
+/// short page or the total from `Content-Range` says there is nothing left.
+pub(crate) async fn fetch_all_pages<T, F>(
+    config: &KoiosConfig,
+    location: &'static str,
+    url: &str,
+    build: F,
+) -> Result<Vec<T>, JsError>
+where
+    T: ResponseSchema,
+    F: Fn(&Client, &str) -> RequestBuilder,
+{
+    let separator = if url.contains('?') { '&' } else { '?' };
+    let mut items = Vec::new();
+    let mut offset = 0;
+    loop {
+        let page_url = format!("{}{}offset={}&limit={}", url, separator, offset, KOIOS_PAGE_SIZE);
+        let response = send_with_retry(config, location, |client| build(client, &page_url)).await?;
+        let total = content_range_total(response.headers().get("content-range"));
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|err| to_js_error(err, location))?;
+        let page: Vec<T> = parse_items(body, location, endpoint_name(url), offset)?;
+
+        let page_len = page.len();
+        items.extend(page);
+        offset += page_len;
+        let has_more = page_len == KOIOS_PAGE_SIZE && total.is_none_or(|total| offset < total);
+        if !has_more {
+            return Ok(items);
+        }
+    }
+}
+
+/// Total row count from a `Content-Range: 0-999/1500` header, `*` means unknown.
+fn content_range_total(header: Option<&HeaderValue>) -> Option<usize> {
+    header?
+        .to_str()
+        .ok()?
+        .rsplit('/')
+        .next()?
+        .parse()
+        .ok()
+}
@@ -0,0 +1,294 @@
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use crate::js_error::JsError;
+use crate::koios_client::error_mapper::{ProviderError, ProviderErrorKind, MAX_BODY_LEN};
+use crate::koios_client::models::{AccountInfoResponse, ApiError, DatumInfoResponse, EpochParamResponse, QueryChainTipResponse, ScriptInfoResponse, TxCborResponse, TxStatusResponse, UtxoInfoResponse};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum JsonType {
+    String,
+    /// A non-negative integer.
+    Integer,
+    Number,
+    Bool,
+    Object,
+    Array,
+}
+
+impl JsonType {
+    fn matches(self, value: &Value) -> bool {
+        match self {
+            JsonType::String => value.is_string(),
+            JsonType::Integer => value.is_u64(),
+            JsonType::Number => value.is_number(),
+            JsonType::Bool => value.is_boolean(),
+            JsonType::Object => value.is_object(),
+            JsonType::Array => value.is_array(),
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            JsonType::String => "a string",
+            JsonType::Integer => "a non-negative integer",
+            JsonType::Number => "a number",
+            JsonType::Bool => "a boolean",
+            JsonType::Object => "an object",
+            JsonType::Array => "an array",
+        }
+    }
+}
+
+/// A field of a response item. `fields` are those of an object, or of every
+/// object in an array.
+#[derive(Debug)]
+pub(crate) struct Field {
+    name: &'static str,
+    json_type: JsonType,
+    nullable: bool,
+    fields: &'static [Field],
+}
+
+impl Field {
+    const fn required(name: &'static str, json_type: JsonType) -> Field {
+        Field { name, json_type, nullable: false, fields: &[] }
+    }
+
+    /// May also be null or absent.
+    const fn optional(name: &'static str, json_type: JsonType) -> Field {
+        Field { name, json_type, nullable: true, fields: &[] }
+    }
+
+    const fn with_fields(mut self, fields: &'static [Field]) -> Field {
+        self.fields = fields;
+        self
+    }
+}
+
+/// The fields a response model relies on, checked before deserializing so a
+/// provider changing its responses gets a targeted error instead of serde's.
+pub(crate) trait ResponseSchema: DeserializeOwned {
+    const FIELDS: &'static [Field];
+}
+
+impl ResponseSchema for Value {
+    const FIELDS: &'static [Field] = &[];
+}
+
+const ASSET: &[Field] = &[
+    Field::required("policy_id", JsonType::String),
+    Field::optional("asset_name", JsonType::String),
+    Field::required("quantity", JsonType::String),
+];
+
+const INLINE_DATUM: &[Field] = &[Field::required("bytes", JsonType::String)];
+
+const REFERENCE_SCRIPT: &[Field] = &[
+    Field::required("hash", JsonType::String),
+    Field::required("size", JsonType::Integer),
+    Field::required("type", JsonType::String),
+    Field::required("bytes", JsonType::String),
+];
+
+impl ResponseSchema for UtxoInfoResponse {
+    const FIELDS: &'static [Field] = &[
+        Field::required("tx_hash", JsonType::String),
+        Field::required("tx_index", JsonType::Integer),
+        Field::required("address", JsonType::String),
+        Field::required("value", JsonType::String),
+        Field::optional("stake_address", JsonType::String),
+        Field::optional("payment_cred", JsonType::String),
+        Field::required("epoch_no", JsonType::Integer),
+        Field::optional("block_height", JsonType::Integer),
+        Field::required("block_time", JsonType::Integer),
+        Field::optional("datum_hash", JsonType::String),
+        Field::optional("inline_datum", JsonType::Object).with_fields(INLINE_DATUM),
+        Field::optional("reference_script", JsonType::Object).with_fields(REFERENCE_SCRIPT),
+        Field::optional("asset_list", JsonType::Array).with_fields(ASSET),
+        Field::required("is_spent", JsonType::Bool),
+    ];
+}
+
+impl ResponseSchema for DatumInfoResponse {
+    const FIELDS: &'static [Field] = &[
+        Field::required("datum_hash", JsonType::String),
+        Field::optional("creation_tx_hash", JsonType::String),
+        Field::required("bytes", JsonType::String),
+    ];
+}
+
+impl ResponseSchema for ScriptInfoResponse {
+    const FIELDS: &'static [Field] = &[
+        Field::required("script_hash", JsonType::String),
+        Field::optional("creation_tx_hash", JsonType::String),
+        Field::required("type", JsonType::String),
+        Field::optional("bytes", JsonType::String),
+        Field::optional("size", JsonType::Integer),
+    ];
+}
+
+impl ResponseSchema for TxCborResponse {
+    const FIELDS: &'static [Field] = &[
+        Field::required("tx_hash", JsonType::String),
+        Field::optional("block_hash", JsonType::String),
+        Field::optional("block_height", JsonType::Integer),
+        Field::optional("epoch_no", JsonType::Integer),
+        Field::optional("absolute_slot", JsonType::Integer),
+        Field::optional("tx_timestamp", JsonType::Integer),
+        Field::required("cbor", JsonType::String),
+    ];
+}
+
+impl ResponseSchema for TxStatusResponse {
+    const FIELDS: &'static [Field] = &[
+        Field::required("tx_hash", JsonType::String),
+        Field::optional("num_confirmations", JsonType::Integer),
+    ];
+}
+
+impl ResponseSchema for QueryChainTipResponse {
+    const FIELDS: &'static [Field] = &[
+        Field::required("hash", JsonType::String),
+        Field::required("epoch_no", JsonType::Integer),
+        Field::required("abs_slot", JsonType::Integer),
+        Field::required("epoch_slot", JsonType::Integer),
+        Field::optional("block_no", JsonType::Integer),
+        Field::required("block_time", JsonType::Integer),
+    ];
+}
+
+impl ResponseSchema for AccountInfoResponse {
+    const FIELDS: &'static [Field] = &[
+        Field::required("stake_address", JsonType::String),
+        Field::optional("status", JsonType::String),
+        Field::optional("delegated_pool", JsonType::String),
+        Field::optional("delegated_drep", JsonType::String),
+        Field::optional("total_balance", JsonType::String),
+    ];
+}
+
+const COST_MODELS: &[Field] = &[
+    Field::optional("PlutusV1", JsonType::Array),
+    Field::optional("PlutusV2", JsonType::Array),
+    Field::optional("PlutusV3", JsonType::Array),
+];
+
+// only what evaluation and fee checks read, serde reports the rest
+impl ResponseSchema for EpochParamResponse {
+    const FIELDS: &'static [Field] = &[
+        Field::required("epoch_no", JsonType::Integer),
+        Field::required("block_hash", JsonType::String),
+        Field::optional("min_fee_a", JsonType::Integer),
+        Field::optional("min_fee_b", JsonType::Integer),
+        Field::optional("max_tx_size", JsonType::Integer),
+        Field::optional("key_deposit", JsonType::String),
+        Field::optional("pool_deposit", JsonType::String),
+        Field::optional("protocol_major", JsonType::Integer),
+        Field::optional("cost_models", JsonType::Object).with_fields(COST_MODELS),
+        Field::optional("price_mem", JsonType::Number),
+        Field::optional("price_step", JsonType::Number),
+        Field::optional("max_tx_ex_mem", JsonType::Integer),
+        Field::optional("max_tx_ex_steps", JsonType::Integer),
+        Field::optional("max_val_size", JsonType::Integer),
+        Field::optional("collateral_percent", JsonType::Integer),
+        Field::optional("max_collateral_inputs", JsonType::Integer),
+        Field::optional("coins_per_utxo_size", JsonType::String),
+        Field::optional("min_fee_ref_script_cost_per_byte", JsonType::Number),
+    ];
+}
+
+/// Checks every item of a Koios list response against `T`'s schema, then
+/// deserializes them. Item numbers count from `offset`, the position of the
+/// page. A Koios error object becomes its provider error.
+pub(crate) fn parse_items<T: ResponseSchema>(
+    body: Value,
+    location: &str,
+    endpoint: &str,
+    offset: usize,
+) -> Result<Vec<T>, JsError> {
+    let items = match body {
+        Value::Array(items) => items,
+        Value::Object(_) => {
+            return match serde_json::from_value::<ApiError>(body) {
+                Ok(error) => Err(error.to_js_error()),
+                Err(_) => Err(invalid(location, &format!("Koios {} response is an object, expected an array", endpoint), None)),
+            };
+        }
+        other => {
+            let message = format!("Koios {} response is {}, expected an array", endpoint, json_type_of(&other));
+            return Err(invalid(location, &message, None));
+        }
+    };
+    items
+        .into_iter()
+        .enumerate()
+        .map(|(index, item)| {
+            let context = format!("Koios {} item {}", endpoint, offset + index);
+            if let Err(message) = check_fields(&item, T::FIELDS, "") {
+                return Err(invalid(location, &format!("{} {}", context, message), Some(item.to_string())));
+            }
+            let body = item.to_string();
+            serde_json::from_value(item).map_err(|e| invalid(location, &format!("{}: {}", context, e), Some(body)))
+        })
+        .collect()
+}
+
+/// The endpoint name of a Koios URL, e.g. `utxo_info`.
+pub(crate) fn endpoint_name(url: &str) -> &str {
+    let path = url.split('?').next().unwrap_or(url);
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+fn check_fields(item: &Value, fields: &[Field], prefix: &str) -> Result<(), String> {
+    let Some(object) = item.as_object() else {
+        return Err(format!("{}is {}, expected an object", describe_prefix(prefix), json_type_of(item)));
+    };
+    for field in fields {
+        let path = format!("{}{}", prefix, field.name);
+        let value = match object.get(field.name) {
+            None | Some(Value::Null) if field.nullable => continue,
+            None => return Err(format!("missing `{}`", path)),
+            Some(value) => value,
+        };
+        if !field.json_type.matches(value) {
+            return Err(format!("`{}` is {}, expected {}", path, json_type_of(value), field.json_type.describe()));
+        }
+        if field.fields.is_empty() {
+            continue;
+        }
+        match value {
+            Value::Array(elements) => {
+                for (index, element) in elements.iter().enumerate() {
+                    check_fields(element, field.fields, &format!("{}[{}].", path, index))?;
+                }
+            }
+            _ => check_fields(value, field.fields, &format!("{}.", path))?,
+        }
+    }
+    Ok(())
+}
+
+fn describe_prefix(prefix: &str) -> String {
+    match prefix.strip_suffix('.') {
+        Some(path) => format!("`{}` ", path),
+        None => String::new(),
+    }
+}
+
+fn json_type_of(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(number) if number.is_u64() => "a non-negative integer",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+fn invalid(location: &str, message: &str, item: Option<String>) -> JsError {
+    let mut error = ProviderError::new(ProviderErrorKind::Decode, location, message);
+    error.body = item.map(|item| item.chars().take(MAX_BODY_LEN).collect());
+    error.to_js_error()
+}
@@ -0,0 +1,86 @@
+use serde_json::Value;
+use crate::js_error::JsError;
+use crate::koios_client::config::KoiosConfig;
+use crate::koios_client::error_mapper::to_js_error;
+use crate::koios_client::models::{ApiError, ApiResult, TxCborRequest, TxCborResponse, TxInfoRequest, TxStatusResponse};
+use crate::koios_client::request::send_with_retry;
+use crate::koios_client::response_schema::parse_items;
+
+pub(crate) async fn get_tx_cbor(
+    tx_hashes: &Vec<String>,
+    config: &KoiosConfig,
+) -> Result<Vec<TxCborResponse>, JsError> {
+    let request = TxCborRequest {
+        tx_hashes: tx_hashes.clone(),
+    };
+
+    let url = config.build_url("tx_cbor");
+
+    let response = send_with_retry(config, "get_tx_cbor.send", |client| {
+        client.post(url.as_str()).json(&request)
+    })
+    .await?;
+
+    let body: Value = response
+        .json()
+        .await
+        .map_err(|err| to_js_error(err, "get_tx_cbor.parse"))?;
+
+    parse_items(body, "get_tx_cbor.parse", "tx_cbor", 0)
+}
+
+/// Full `tx_info` objects with every optional section enabled. They're passed
+/// through as JSON since the shape is large and only ever shown to the user.
+pub(crate) async fn get_tx_info(
+    tx_hashes: &Vec<String>,
+    config: &KoiosConfig,
+) -> Result<Vec<Value>, JsError> {
+    let request = TxInfoRequest {
+        tx_hashes: tx_hashes.clone(),
+        inputs: true,
+        metadata: true,
+        assets: true,
+        withdrawals: true,
+        certs: true,
+        scripts: true,
+        bytecode: true,
+    };
+
+    let url = config.build_url("tx_info");
+
+    let response = send_with_retry(config, "get_tx_info.send", |client| {
+        client.post(url.as_str()).json(&request)
+    })
+    .await?;
+
+    let txs: ApiResult<Vec<Value>> = response
+        .json()
+        .await
+        .map_err(|err| to_js_error(err, "get_tx_info.parse"))?;
+
+    txs.map_err(|err: ApiError| err.to_js_error())
+}
+
+/// Confirmation counts, `None` for transactions Koios hasn't seen on chain.
+pub(crate) async fn get_tx_status(
+    tx_hashes: &Vec<String>,
+    config: &KoiosConfig,
+) -> Result<Vec<TxStatusResponse>, JsError> {
+    let request = TxCborRequest {
+        tx_hashes: tx_hashes.clone(),
+    };
+
+    let url = config.build_url("tx_status");
+
+    let response = send_with_retry(config, "get_tx_status.send", |client| {
+        client.post(url.as_str()).json(&request)
+    })
+    .await?;
+
+    let body: Value = response
+        .json()
+        .await
+        .map_err(|err| to_js_error(err, "get_tx_status.parse"))?;
+
+    parse_items(body, "get_tx_status.parse", "tx_status", 0)
+}
@@ -1,46 +1,35 @@
+use futures::stream::{self, StreamExt, TryStreamExt};
 use crate::js_error::JsError;
-use crate::koios_client::error_mapper::to_js_error;
-use crate::koios_client::models::{ApiError, ApiResult, UtxoInfoRequest, UtxoInfoResponse};
-use crate::koios_client::network_type::NetworkType;
-use itertools::Itertools;
-use reqwest::Client;
+use crate::koios_client::config::KoiosConfig;
+use crate::koios_client::models::{UtxoInfoRequest, UtxoInfoResponse};
+use crate::koios_client::request::fetch_all_pages;
 
+/// Resolves UTxO references, split into batches of `utxo_batch_size` since
+/// Koios rejects oversized request bodies. Up to `max_concurrent_requests`
+/// batches are fetched at once, results keep the order of `inputs`.
 pub(crate) async fn get_utxos(
     inputs: &Vec<String>,
-    network_type: NetworkType,
-    api_token: &str
+    config: &KoiosConfig,
 ) -> Result<Vec<UtxoInfoResponse>, JsError> {
-    let client = Client::new();
-    let mut inputs_request = UtxoInfoRequest {
-        utxo_refs: vec![],
-        extended: true,
-    };
+    let url = config.build_url("utxo_info");
 
-    for input in inputs {
-        inputs_request.utxo_refs.push(input.clone());
-    }
+    let batches: Vec<Vec<Option<UtxoInfoResponse>>> = stream::iter(inputs.chunks(config.options.utxo_batch_size.max(1)))
+        .map(|batch| {
+            let inputs_request = UtxoInfoRequest {
+                utxo_refs: batch.to_vec(),
+                extended: true,
+            };
+            let url = &url;
+            async move {
+                fetch_all_pages(config, "get_utxos", url, |client, page_url| {
+                    client.post(page_url).json(&inputs_request)
+                })
+                .await
+            }
+        })
+        .buffered(config.options.max_concurrent_requests.max(1))
+        .try_collect()
+        .await?;
 
-    let url = network_type.build_url("utxo_info");
-
-    let response = client
-        .post(url)
-        .json(&inputs_request)
-        .bearer_auth(api_token)
-        .header("Accept", "application/json")
-        // .header("Content-Type", "application/json")
-        .send()
-        .await
-        .map_err(|err| to_js_error(err, "get_utxos.send"))?;
-
-    let utxo_infos: ApiResult<Vec<Option<UtxoInfoResponse>>> = response
-        .error_for_status()
-        .map_err(|err| to_js_error(err, "get_utxos.status"))?
-        .json()
-        .await
-        .map_err(|err| to_js_error(err, "get_utxos.parse"))?;
-
-    let result = utxo_infos
-        .map_err(|err: ApiError| err.to_js_error())?
-        .into_iter().filter_map(|x| x).collect_vec();
-    Ok(result)
+    Ok(batches.into_iter().flatten().flatten().collect())
 }
@@ -0,0 +1,99 @@
+// only the evaluator and providers log so far
+#![cfg_attr(not(feature = "evaluator"), allow(dead_code))]
+
+use std::sync::atomic::{AtomicU8, Ordering};
+use crate::bingen::wasm_bindgen;
+
+/// Most verbose messages that get through, `Off` silences everything.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Off = 0,
+    Error = 1,
+    Warn = 2,
+    Info = 3,
+    Debug = 4,
+}
+
+// silent in browsers until enabled, natively the `log` logger does the filtering
+#[cfg(all(target_arch = "wasm32", not(target_os = "emscripten")))]
+static LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Off as u8);
+#[cfg(not(all(target_arch = "wasm32", not(target_os = "emscripten"))))]
+static LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Debug as u8);
+
+/// Logs evaluation and provider internals up to `level` to the browser console
+/// (`console.error`, `console.warn`, `console.info`, `console.debug`), or to
+/// the `log` logger natively.
+#[wasm_bindgen]
+pub fn set_log_level(level: LogLevel) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+pub(crate) fn enabled(level: LogLevel) -> bool {
+    level != LogLevel::Off && level as u8 <= LEVEL.load(Ordering::Relaxed)
+}
+
+#[cfg(all(target_arch = "wasm32", not(target_os = "emscripten")))]
+pub(crate) fn write(level: LogLevel, target: &str, message: &str) {
+    use wasm_bindgen::prelude::wasm_bindgen;
+
+    #[wasm_bindgen]
+    extern "C" {
+        #[wasm_bindgen(js_namespace = console, js_name = error)]
+        fn console_error(message: &str);
+        #[wasm_bindgen(js_namespace = console, js_name = warn)]
+        fn console_warn(message: &str);
+        #[wasm_bindgen(js_namespace = console, js_name = info)]
+        fn console_info(message: &str);
+        #[wasm_bindgen(js_namespace = console, js_name = debug)]
+        fn console_debug(message: &str);
+    }
+
+    let line = format!("[cquisitor {}] {}", target, message);
+    match level {
+        LogLevel::Off => {}
+        LogLevel::Error => console_error(&line),
+        LogLevel::Warn => console_warn(&line),
+        LogLevel::Info => console_info(&line),
+        LogLevel::Debug => console_debug(&line),
+    }
+}
+
+#[cfg(not(all(target_arch = "wasm32", not(target_os = "emscripten"))))]
+pub(crate) fn write(level: LogLevel, target: &str, message: &str) {
+    let level = match level {
+        LogLevel::Off => return,
+        LogLevel::Error => log::Level::Error,
+        LogLevel::Warn => log::Level::Warn,
+        LogLevel::Info => log::Level::Info,
+        LogLevel::Debug => log::Level::Debug,
+    };
+    log::log!(target: target, level, "{}", message);
+}
+
+macro_rules! log_at {
+    ($level:expr, $($arg:tt)*) => {
+        if $crate::logger::enabled($level) {
+            $crate::logger::write($level, module_path!(), &format!($($arg)*));
+        }
+    };
+}
+
+macro_rules! log_error {
+    ($($arg:tt)*) => { $crate::logger::log_at!($crate::logger::LogLevel::Error, $($arg)*) };
+}
+
+macro_rules! log_warn {
+    ($($arg:tt)*) => { $crate::logger::log_at!($crate::logger::LogLevel::Warn, $($arg)*) };
+}
+
+macro_rules! log_info {
+    ($($arg:tt)*) => { $crate::logger::log_at!($crate::logger::LogLevel::Info, $($arg)*) };
+}
+
+macro_rules! log_debug {
+    ($($arg:tt)*) => { $crate::logger::log_at!($crate::logger::LogLevel::Debug, $($arg)*) };
+}
+
+#[allow(unused_imports)]
+pub(crate) use {log_at, log_debug, log_error, log_info, log_warn};
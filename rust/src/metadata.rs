@@ -0,0 +1,392 @@
+use minicbor::data::{Tag, Type};
+use minicbor::Decoder;
+use serde::Serialize;
+use crate::bingen::wasm_bindgen;
+use crate::cbor::cbor_decoder::{get_tag_name, minicbor_to_js_error};
+use crate::cbor::cbor_encoder::{push_head, push_text, MAJOR_ARRAY, MAJOR_MAP, MAJOR_UNSIGNED};
+use crate::error::ErrorCode;
+use crate::input::decode_hex;
+use crate::js_error::JsError;
+use crate::schemas::to_versioned_json;
+
+const MAX_METADATUM_LEN: usize = 64;
+// auxiliary data of Alonzo and later, a map with the metadata at key 0
+const AUXILIARY_DATA_TAG: u64 = 259;
+const AUXILIARY_METADATA_KEY: u64 = 0;
+// CIP-20 transaction messages: {674: {"msg": [text]}}
+const CIP20_LABEL: u64 = 674;
+const CIP20_MSG_KEY: &str = "msg";
+
+/// A metadatum the ledger rejects, `path` is the label followed by the map
+/// keys and list indexes down to it, e.g. `674/msg/0`.
+#[derive(Serialize, Debug, Clone)]
+pub struct MetadataIssue {
+    pub path: String,
+    pub rule: String,
+    pub message: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct MetadataReport {
+    pub valid: bool,
+    pub labels: Vec<u64>,
+    pub issues: Vec<MetadataIssue>,
+}
+
+/// The lines of a CIP-20 message, `issues` what doesn't follow the CIP.
+#[derive(Serialize, Debug, Clone)]
+pub struct Cip20Message {
+    pub messages: Vec<String>,
+    pub issues: Vec<MetadataIssue>,
+}
+
+/// Checks tx metadata against the ledger rules: labels are unsigned ints,
+/// metadatums are maps, lists, ints, and bytes or text of at most 64 bytes,
+/// with no floats, tags or simple values. Takes a tx, its auxiliary data or
+/// the bare metadata map, all read raw as full decoders reject what's checked.
+#[wasm_bindgen]
+pub fn validate_metadata(hex: &str) -> Result<String, JsError> {
+    let bytes = decode_hex(hex)?;
+    to_versioned_json(&metadata_report(&bytes)?)
+}
+
+pub(crate) fn metadata_report(bytes: &[u8]) -> Result<MetadataReport, JsError> {
+    let mut decoder = Decoder::new(bytes);
+    let mut labels = Vec::new();
+    let mut issues = Vec::new();
+    if seek_metadata(&mut decoder).map_err(minicbor_to_js_error)? {
+        check_metadata(&mut decoder, &mut labels, &mut issues).map_err(minicbor_to_js_error)?;
+    }
+    Ok(MetadataReport { valid: issues.is_empty(), labels, issues })
+}
+
+/// Reads the CIP-20 message (label 674) of a tx, its auxiliary data or the
+/// bare metadata map. Errors when there's no label 674.
+#[wasm_bindgen]
+pub fn decode_cip20_message(hex: &str) -> Result<String, JsError> {
+    let bytes = decode_hex(hex)?;
+    to_versioned_json(&cip20_message(&bytes)?)
+}
+
+pub(crate) fn cip20_message(bytes: &[u8]) -> Result<Cip20Message, JsError> {
+    let mut decoder = Decoder::new(bytes);
+    if !seek_metadata(&mut decoder).map_err(minicbor_to_js_error)? || !seek_label(&mut decoder, CIP20_LABEL).map_err(minicbor_to_js_error)? {
+        return Err(ErrorCode::NotFound.error("The metadata has no label 674"));
+    }
+    read_cip20(&mut decoder).map_err(minicbor_to_js_error)
+}
+
+/// Metadata (a map, hex) of a CIP-20 message with the given text, a line per
+/// `msg` entry and lines longer than 64 bytes split at character boundaries.
+#[wasm_bindgen]
+pub fn compose_cip20_message(text: &str) -> Result<String, JsError> {
+    let lines = cip20_lines(text);
+    if lines.is_empty() {
+        return Err(ErrorCode::InvalidInput.error("The message is empty"));
+    }
+    let mut out = Vec::new();
+    push_head(&mut out, MAJOR_MAP, 1);
+    push_head(&mut out, MAJOR_UNSIGNED, CIP20_LABEL);
+    push_head(&mut out, MAJOR_MAP, 1);
+    push_text(&mut out, CIP20_MSG_KEY);
+    push_head(&mut out, MAJOR_ARRAY, lines.len() as u64);
+    for line in &lines {
+        push_text(&mut out, line);
+    }
+    Ok(hex::encode(out))
+}
+
+pub(crate) fn cip20_lines(text: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    for line in text.lines() {
+        let mut chunk = String::new();
+        for c in line.chars() {
+            if chunk.len() + c.len_utf8() > MAX_METADATUM_LEN {
+                lines.push(std::mem::take(&mut chunk));
+            }
+            chunk.push(c);
+        }
+        lines.push(chunk);
+    }
+    // trailing empty lines carry nothing
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    lines
+}
+
+/// Moves the decoder from the start of the metadata map to the value of
+/// `label`, false when there's none.
+pub(crate) fn seek_label(decoder: &mut Decoder, label: u64) -> Result<bool, minicbor::decode::Error> {
+    let entries = decoder.map()?;
+    let mut read = 0;
+    loop {
+        match entries {
+            Some(len) if read >= len => return Ok(false),
+            None if decoder.datatype()? == Type::Break => return Ok(false),
+            _ => {}
+        }
+        if matches!(decoder.datatype()?, Type::U8 | Type::U16 | Type::U32 | Type::U64) {
+            if decoder.u64()? == label {
+                return Ok(true);
+            }
+        } else {
+            decoder.skip()?;
+        }
+        decoder.skip()?;
+        read += 1;
+    }
+}
+
+fn read_cip20(decoder: &mut Decoder) -> Result<Cip20Message, minicbor::decode::Error> {
+    let mut messages = Vec::new();
+    let mut issues = Vec::new();
+    let issue = |issues: &mut Vec<MetadataIssue>, path: String, rule: &str, message: &str| {
+        issues.push(MetadataIssue { path, rule: rule.to_string(), message: message.to_string() })
+    };
+    let label = CIP20_LABEL.to_string();
+    if !matches!(decoder.datatype()?, Type::Map | Type::MapIndef) {
+        issue(&mut issues, label, "NotAMap", "Label 674 must hold a map with a msg key.");
+        return Ok(Cip20Message { messages, issues });
+    }
+    let entries = decoder.map()?;
+    let mut read = 0;
+    let mut found = false;
+    loop {
+        match entries {
+            Some(len) if read >= len => break,
+            None if decoder.datatype()? == Type::Break => {
+                decoder.set_position(decoder.position() + 1);
+                break;
+            }
+            _ => {}
+        }
+        read += 1;
+        let is_msg = if decoder.datatype()? == Type::String {
+            decoder.str()? == CIP20_MSG_KEY
+        } else {
+            decoder.skip()?;
+            false
+        };
+        if !is_msg {
+            decoder.skip()?;
+            continue;
+        }
+        found = true;
+        let path = format!("{}/{}", label, CIP20_MSG_KEY);
+        if !matches!(decoder.datatype()?, Type::Array | Type::ArrayIndef) {
+            issue(&mut issues, path, "NotAList", "msg must be a list of strings, even for a single line.");
+            decoder.skip()?;
+            continue;
+        }
+        let len = decoder.array()?;
+        let mut index = 0;
+        loop {
+            match len {
+                Some(len) if index >= len => break,
+                None if decoder.datatype()? == Type::Break => {
+                    decoder.set_position(decoder.position() + 1);
+                    break;
+                }
+                _ => {}
+            }
+            let line_path = format!("{}/{}", path, index);
+            index += 1;
+            if decoder.datatype()? != Type::String {
+                issue(&mut issues, line_path, "NotAString", "msg entries must be strings.");
+                decoder.skip()?;
+                continue;
+            }
+            let line = decoder.str()?;
+            if line.len() > MAX_METADATUM_LEN {
+                issue(&mut issues, line_path, "StringTooLong", "Lines are limited to 64 bytes, split longer ones.");
+            }
+            messages.push(line.to_string());
+        }
+    }
+    if !found {
+        issue(&mut issues, label, "MissingMsg", "Label 674 has no msg key.");
+    }
+    Ok(Cip20Message { messages, issues })
+}
+
+/// Moves the decoder to the metadata map of a tx or auxiliary data, false
+/// when there's none.
+pub(crate) fn seek_metadata(decoder: &mut Decoder) -> Result<bool, minicbor::decode::Error> {
+    if decoder.datatype()? == Type::Array {
+        let len = decoder.array()?;
+        if len.is_none_or(|len| len >= 3) {
+            // tx: body, witness set, optional is_valid flag, auxiliary data
+            decoder.skip()?;
+            decoder.skip()?;
+            if decoder.datatype()? == Type::Bool {
+                decoder.skip()?;
+            }
+            if decoder.datatype()? == Type::Null {
+                return Ok(false);
+            }
+            return seek_metadata(decoder);
+        }
+        // Allegra/Mary auxiliary data: [metadata, scripts]
+        return Ok(true);
+    }
+    if decoder.datatype()? == Type::Tag {
+        if decoder.tag()? != Tag::Unassigned(AUXILIARY_DATA_TAG) {
+            return Err(minicbor::decode::Error::message("Unexpected tag, expected auxiliary data"));
+        }
+        let entries = decoder.map()?;
+        let mut read = 0;
+        loop {
+            match entries {
+                Some(len) if read >= len => return Ok(false),
+                None if decoder.datatype()? == Type::Break => return Ok(false),
+                _ => {}
+            }
+            if decoder.u64()? == AUXILIARY_METADATA_KEY {
+                return Ok(true);
+            }
+            decoder.skip()?;
+            read += 1;
+        }
+    }
+    Ok(true)
+}
+
+fn check_metadata(
+    decoder: &mut Decoder,
+    labels: &mut Vec<u64>,
+    issues: &mut Vec<MetadataIssue>,
+) -> Result<(), minicbor::decode::Error> {
+    let entries = decoder.map()?;
+    let mut read = 0;
+    loop {
+        match entries {
+            Some(len) if read >= len => break,
+            None if decoder.datatype()? == Type::Break => {
+                decoder.set_position(decoder.position() + 1);
+                break;
+            }
+            _ => {}
+        }
+        let path = match decoder.datatype()? {
+            Type::U8 | Type::U16 | Type::U32 | Type::U64 => {
+                let label = decoder.u64()?;
+                labels.push(label);
+                label.to_string()
+            }
+            _ => {
+                let path = format!("{{{}}}", read);
+                issues.push(MetadataIssue {
+                    path: path.clone(),
+                    rule: "InvalidLabel".to_string(),
+                    message: "Metadata labels must be unsigned integers.".to_string(),
+                });
+                decoder.skip()?;
+                path
+            }
+        };
+        check_metadatum(decoder, &path, issues)?;
+        read += 1;
+    }
+    Ok(())
+}
+
+fn check_metadatum(
+    decoder: &mut Decoder,
+    path: &str,
+    issues: &mut Vec<MetadataIssue>,
+) -> Result<(), minicbor::decode::Error> {
+    let issue = |issues: &mut Vec<MetadataIssue>, rule: &str, message: String| {
+        issues.push(MetadataIssue { path: path.to_string(), rule: rule.to_string(), message })
+    };
+    match decoder.datatype()? {
+        Type::U8 | Type::U16 | Type::U32 | Type::U64 | Type::I8 | Type::I16 | Type::I32 | Type::I64 | Type::Int => {
+            decoder.skip()?;
+        }
+        Type::Bytes | Type::BytesIndef => {
+            let mut len = 0;
+            for chunk in decoder.bytes_iter()? {
+                len += chunk?.len();
+            }
+            if len > MAX_METADATUM_LEN {
+                issue(issues, "BytesTooLong", format!("{} bytes, at most {} are allowed.", len, MAX_METADATUM_LEN));
+            }
+        }
+        Type::String | Type::StringIndef => {
+            let mut len = 0;
+            for chunk in decoder.str_iter()? {
+                len += chunk?.len();
+            }
+            if len > MAX_METADATUM_LEN {
+                issue(
+                    issues,
+                    "StringTooLong",
+                    format!("{} bytes of UTF-8, at most {} are allowed; split it into a list.", len, MAX_METADATUM_LEN),
+                );
+            }
+        }
+        Type::Array | Type::ArrayIndef => {
+            let len = decoder.array()?;
+            let mut index = 0;
+            loop {
+                match len {
+                    Some(len) if index >= len => break,
+                    None if decoder.datatype()? == Type::Break => {
+                        decoder.set_position(decoder.position() + 1);
+                        break;
+                    }
+                    _ => {}
+                }
+                check_metadatum(decoder, &format!("{}/{}", path, index), issues)?;
+                index += 1;
+            }
+        }
+        Type::Map | Type::MapIndef => {
+            let len = decoder.map()?;
+            let mut index = 0;
+            loop {
+                match len {
+                    Some(len) if index >= len => break,
+                    None if decoder.datatype()? == Type::Break => {
+                        decoder.set_position(decoder.position() + 1);
+                        break;
+                    }
+                    _ => {}
+                }
+                let key_path = format!("{}/{}", path, key_name(decoder, index)?);
+                check_metadatum(decoder, &key_path, issues)?;
+                check_metadatum(decoder, &key_path, issues)?;
+                index += 1;
+            }
+        }
+        Type::F16 | Type::F32 | Type::F64 => {
+            issue(issues, "FloatNotAllowed", "Metadata can't hold floating point numbers.".to_string());
+            decoder.skip()?;
+        }
+        Type::Tag => {
+            let tag = decoder.tag()?;
+            issue(issues, "TagNotAllowed", format!("Metadata can't hold tagged values, found tag {}.", get_tag_name(&tag)));
+            decoder.skip()?;
+        }
+        other => {
+            issue(issues, "InvalidMetadatum", format!("Metadata can't hold {:?} values.", other));
+            decoder.skip()?;
+        }
+    }
+    Ok(())
+}
+
+/// The key as a path segment without consuming it: ints and short text as
+/// they are, other keys by their position in the map.
+fn key_name(decoder: &Decoder, index: u64) -> Result<String, minicbor::decode::Error> {
+    let mut probe = decoder.clone();
+    Ok(match probe.datatype()? {
+        Type::U8 | Type::U16 | Type::U32 | Type::U64 => probe.u64()?.to_string(),
+        Type::I8 | Type::I16 | Type::I32 | Type::I64 => probe.i64()?.to_string(),
+        Type::String => match probe.str() {
+            Ok(key) if key.len() <= MAX_METADATUM_LEN && !key.contains('/') => key.to_string(),
+            _ => format!("{{{}}}", index),
+        },
+        _ => format!("{{{}}}", index),
+    })
+}
@@ -0,0 +1,315 @@
+use std::collections::BTreeSet;
+use cardano_serialization_lib::{BigNum, NativeScript, NativeScripts};
+use serde::Serialize;
+use crate::bingen::wasm_bindgen;
+use crate::error::ErrorCode;
+use crate::input::sanitize_hex;
+use crate::js_error::JsError;
+use crate::schemas::to_versioned_json;
+#[cfg(feature = "providers")]
+use crate::NetworkType;
+
+// keeps `atLeast` over many scripts from blowing up, the branches past it are dropped
+const MAX_BRANCHES: usize = 1024;
+
+/// One way to satisfy a script: all `signers` sign and the tx validity
+/// interval lies within `[valid_from_slot, valid_until_slot)`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct Branch {
+    pub(crate) signers: BTreeSet<String>,
+    pub(crate) valid_from_slot: Option<u64>,
+    pub(crate) valid_until_slot: Option<u64>,
+}
+
+impl Branch {
+    fn signer(key_hash: String) -> Branch {
+        Branch { signers: BTreeSet::from([key_hash]), valid_from_slot: None, valid_until_slot: None }
+    }
+
+    fn any_time() -> Branch {
+        Branch { signers: BTreeSet::new(), valid_from_slot: None, valid_until_slot: None }
+    }
+
+    /// Both branches at once: their signers and the overlap of their windows.
+    fn and(&self, other: &Branch) -> Branch {
+        Branch {
+            signers: self.signers.union(&other.signers).cloned().collect(),
+            valid_from_slot: self.valid_from_slot.max(other.valid_from_slot),
+            valid_until_slot: match (self.valid_until_slot, other.valid_until_slot) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            },
+        }
+    }
+
+    pub(crate) fn satisfiable(&self) -> bool {
+        match (self.valid_from_slot, self.valid_until_slot) {
+            (Some(from), Some(until)) => from < until,
+            _ => true,
+        }
+    }
+}
+
+/// The branches of a script, each a conjunction of signers and time bounds;
+/// the script is satisfied when any branch is. `truncated` is set when
+/// `atLeast` nodes had more combinations than `MAX_BRANCHES`.
+pub(crate) fn script_branches(script: &NativeScript) -> (Vec<Branch>, bool) {
+    let mut truncated = false;
+    let mut branches = branches_of(script, &mut truncated);
+    branches.sort();
+    branches.dedup();
+    (branches, truncated)
+}
+
+fn branches_of(script: &NativeScript, truncated: &mut bool) -> Vec<Branch> {
+    if let Some(pubkey) = script.as_script_pubkey() {
+        return vec![Branch::signer(pubkey.addr_keyhash().to_hex())];
+    }
+    if let Some(all) = script.as_script_all() {
+        return all_of(&children(&all.native_scripts()), truncated);
+    }
+    if let Some(any) = script.as_script_any() {
+        let mut branches = Vec::new();
+        for child in children(&any.native_scripts()) {
+            branches.extend(branches_of(&child, truncated));
+        }
+        return cap(branches, truncated);
+    }
+    if let Some(n_of_k) = script.as_script_n_of_k() {
+        let scripts = children(&n_of_k.native_scripts());
+        let mut branches = Vec::new();
+        for combination in combinations(scripts.len(), n_of_k.n() as usize) {
+            let chosen: Vec<NativeScript> = combination.iter().map(|i| scripts[*i].clone()).collect();
+            branches.extend(all_of(&chosen, truncated));
+            if branches.len() > MAX_BRANCHES {
+                break;
+            }
+        }
+        return cap(branches, truncated);
+    }
+    if let Some(start) = script.as_timelock_start() {
+        let mut branch = Branch::any_time();
+        branch.valid_from_slot = Some(slot(&start.slot_bignum()));
+        return vec![branch];
+    }
+    if let Some(expiry) = script.as_timelock_expiry() {
+        let mut branch = Branch::any_time();
+        branch.valid_until_slot = Some(slot(&expiry.slot_bignum()));
+        return vec![branch];
+    }
+    Vec::new()
+}
+
+fn all_of(scripts: &[NativeScript], truncated: &mut bool) -> Vec<Branch> {
+    let mut branches = vec![Branch::any_time()];
+    for script in scripts {
+        let child_branches = branches_of(script, truncated);
+        let mut combined = Vec::new();
+        for branch in &branches {
+            for child in &child_branches {
+                combined.push(branch.and(child));
+            }
+        }
+        branches = cap(combined, truncated);
+    }
+    branches
+}
+
+fn cap(mut branches: Vec<Branch>, truncated: &mut bool) -> Vec<Branch> {
+    if branches.len() > MAX_BRANCHES {
+        branches.truncate(MAX_BRANCHES);
+        *truncated = true;
+    }
+    branches
+}
+
+/// Index sets of size `n` out of `0..k` in lexicographic order, none when `n > k`.
+pub(crate) fn combinations(k: usize, n: usize) -> Vec<Vec<usize>> {
+    if n > k {
+        return Vec::new();
+    }
+    let mut result = Vec::new();
+    let mut current: Vec<usize> = (0..n).collect();
+    loop {
+        result.push(current.clone());
+        if result.len() > MAX_BRANCHES {
+            return result;
+        }
+        let Some(i) = (0..n).rev().find(|&i| current[i] != i + k - n) else {
+            return result;
+        };
+        current[i] += 1;
+        for j in i + 1..n {
+            current[j] = current[j - 1] + 1;
+        }
+    }
+}
+
+fn children(scripts: &NativeScripts) -> Vec<NativeScript> {
+    (0..scripts.len()).map(|i| scripts.get(i)).collect()
+}
+
+fn slot(value: &BigNum) -> u64 {
+    value.to_str().parse().unwrap_or_default()
+}
+
+pub(crate) fn parse_native_script(hex: &str) -> Result<NativeScript, JsError> {
+    NativeScript::from_hex(sanitize_hex(hex)?)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding script: {:?}", e)))
+}
+
+/// When a branch can be used. `valid_until_slot` is exclusive, the tx must
+/// expire at or before it. The UTC times are set when a network is given.
+#[derive(Serialize, Debug, Clone)]
+pub struct TimelockWindow {
+    pub signers: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub valid_from_slot: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub valid_until_slot: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub valid_from_utc: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub valid_until_utc: Option<String>,
+    pub satisfiable: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct TimelockReport {
+    pub script_hash: String,
+    pub windows: Vec<TimelockWindow>,
+    pub truncated: bool,
+}
+
+/// Breaks a native script into the ways it can be satisfied, each with its
+/// signers and the slot window its `after`/`before` nodes leave, however they
+/// nest under `all`, `any` and `atLeast`. Branches whose bounds exclude each
+/// other are kept with `satisfiable: false`.
+#[wasm_bindgen]
+pub fn analyze_native_script_timelocks(hex: &str) -> Result<String, JsError> {
+    let script = parse_native_script(hex)?;
+    to_versioned_json(&timelock_report(&script, |_| None))
+}
+
+/// Same as `analyze_native_script_timelocks` with the slots converted to UTC
+/// with the Shelley slot config of `network`.
+#[cfg(feature = "providers")]
+#[wasm_bindgen]
+pub fn analyze_native_script_timelocks_for_network(hex: &str, network: NetworkType) -> Result<String, JsError> {
+    let script = parse_native_script(hex)?;
+    let slot_config = network.slot_config();
+    to_versioned_json(&timelock_report(&script, |slot| {
+        let time = slot_config.zero_time as i128
+            + (slot as i128 - slot_config.zero_slot as i128) * slot_config.slot_length as i128;
+        u64::try_from(time).ok().map(crate::era_history::format_utc_time_ms)
+    }))
+}
+
+pub(crate) fn timelock_report(script: &NativeScript, to_utc: impl Fn(u64) -> Option<String>) -> TimelockReport {
+    let (branches, truncated) = script_branches(script);
+    let windows = branches
+        .into_iter()
+        .map(|branch| TimelockWindow {
+            satisfiable: branch.satisfiable(),
+            valid_from_utc: branch.valid_from_slot.and_then(&to_utc),
+            valid_until_utc: branch.valid_until_slot.and_then(&to_utc),
+            signers: branch.signers.into_iter().collect(),
+            valid_from_slot: branch.valid_from_slot,
+            valid_until_slot: branch.valid_until_slot,
+        })
+        .collect();
+    TimelockReport { script_hash: script.hash().to_hex(), windows, truncated }
+}
+
+/// A minimal set of signers satisfying the script. `missing` are the signers
+/// of the set not among the available ones.
+#[derive(Serialize, Debug, Clone)]
+pub struct SignerCombination {
+    pub signers: Vec<String>,
+    pub available: Vec<String>,
+    pub missing: Vec<String>,
+    pub additional_needed: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub valid_from_slot: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub valid_until_slot: Option<u64>,
+}
+
+/// `fewest_additional` is the least signatures still to collect over all
+/// combinations, 0 when the available signers already satisfy the script.
+#[derive(Serialize, Debug, Clone)]
+pub struct QuorumReport {
+    pub script_hash: String,
+    pub combinations: Vec<SignerCombination>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fewest_additional: Option<usize>,
+    pub satisfied: bool,
+    pub truncated: bool,
+}
+
+/// Lists the minimal signer combinations that satisfy a native script and,
+/// given the key hashes that can sign (JSON array of hex), how many more
+/// signatures each one needs, fewest first. Combinations with impossible
+/// time bounds are left out, time bounds are otherwise ignored.
+#[wasm_bindgen]
+pub fn analyze_native_script_quorum(hex: &str, available_signers_json: &str) -> Result<String, JsError> {
+    let script = parse_native_script(hex)?;
+    let available: Vec<String> = serde_json::from_str(available_signers_json)
+        .map_err(|e| ErrorCode::InvalidInput.error(&format!("Invalid signer list: {}", e)))?;
+    let available: BTreeSet<String> = available.into_iter().map(|signer| signer.trim().to_lowercase()).collect();
+    to_versioned_json(&quorum_report(&script, &available))
+}
+
+pub(crate) fn quorum_report(script: &NativeScript, available: &BTreeSet<String>) -> QuorumReport {
+    let (branches, truncated) = script_branches(script);
+    let branches: Vec<Branch> = branches.into_iter().filter(Branch::satisfiable).collect();
+    let minimal: Vec<&Branch> = branches
+        .iter()
+        .filter(|branch| {
+            !branches.iter().any(|other| {
+                other.signers.is_subset(&branch.signers) && other.signers != branch.signers && window_contains(other, branch)
+            })
+        })
+        .collect();
+
+    let mut combinations: Vec<SignerCombination> = minimal
+        .into_iter()
+        .map(|branch| {
+            let (signed, missing): (Vec<String>, Vec<String>) =
+                branch.signers.iter().cloned().partition(|signer| available.contains(signer));
+            SignerCombination {
+                signers: branch.signers.iter().cloned().collect(),
+                available: signed,
+                additional_needed: missing.len(),
+                missing,
+                valid_from_slot: branch.valid_from_slot,
+                valid_until_slot: branch.valid_until_slot,
+            }
+        })
+        .collect();
+    combinations.sort_by_key(|combination| (combination.additional_needed, combination.signers.len()));
+    let fewest_additional = combinations.first().map(|combination| combination.additional_needed);
+
+    QuorumReport {
+        script_hash: script.hash().to_hex(),
+        satisfied: fewest_additional == Some(0),
+        fewest_additional,
+        combinations,
+        truncated,
+    }
+}
+
+/// Whether every tx that fits the window of `inner` fits the one of `outer`.
+fn window_contains(outer: &Branch, inner: &Branch) -> bool {
+    let from_ok = match (outer.valid_from_slot, inner.valid_from_slot) {
+        (None, _) => true,
+        (Some(outer), Some(inner)) => outer <= inner,
+        (Some(_), None) => false,
+    };
+    let until_ok = match (outer.valid_until_slot, inner.valid_until_slot) {
+        (None, _) => true,
+        (Some(outer), Some(inner)) => outer >= inner,
+        (Some(_), None) => false,
+    };
+    from_ok && until_ok
+}
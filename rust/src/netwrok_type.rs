@@ -10,4 +10,59 @@ pub enum NetworkType {
     Mainnet = 0,
     TestnetPreprod = 1,
     TestnetPreview = 2,
-}
\ No newline at end of file
+}
+
+impl NetworkType {
+    /// Short network name, used to namespace cached provider data.
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            NetworkType::Mainnet => "mainnet",
+            NetworkType::TestnetPreprod => "preprod",
+            NetworkType::TestnetPreview => "preview",
+        }
+    }
+
+    /// Network id of the addresses and tx bodies of the network.
+    pub(crate) fn network_id(&self) -> u8 {
+        match self {
+            NetworkType::Mainnet => 1,
+            NetworkType::TestnetPreprod | NetworkType::TestnetPreview => 0,
+        }
+    }
+
+    /// Built-in network of a genesis network magic.
+    pub(crate) fn from_magic(network_magic: u64) -> Option<NetworkType> {
+        match network_magic {
+            764824073 => Some(NetworkType::Mainnet),
+            1 => Some(NetworkType::TestnetPreprod),
+            2 => Some(NetworkType::TestnetPreview),
+            _ => None,
+        }
+    }
+
+    /// First Shelley epoch, every epoch before it is a Byron epoch.
+    pub(crate) fn shelley_transition_epoch(&self) -> u64 {
+        match self {
+            NetworkType::Mainnet => 208,
+            NetworkType::TestnetPreprod => 4,
+            NetworkType::TestnetPreview => 0,
+        }
+    }
+
+    /// Shelley-era slot to POSIX time mapping used for script validity ranges.
+    pub(crate) fn slot_config(&self) -> uplc::tx::SlotConfig {
+        match self {
+            NetworkType::Mainnet => uplc::tx::SlotConfig::default(),
+            NetworkType::TestnetPreprod => uplc::tx::SlotConfig {
+                slot_length: 1000,
+                zero_slot: 86400,
+                zero_time: 1655769600000,
+            },
+            NetworkType::TestnetPreview => uplc::tx::SlotConfig {
+                slot_length: 1000,
+                zero_slot: 0,
+                zero_time: 1666656000000,
+            },
+        }
+    }
+}
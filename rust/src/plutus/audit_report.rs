@@ -0,0 +1,326 @@
+use cardano_serialization_lib::{Transaction, TransactionInputs};
+use serde::Deserialize;
+use crate::bingen::wasm_bindgen;
+use crate::error::ErrorCode;
+use crate::input::decode_hex;
+use crate::js_error::JsError;
+use crate::koios_client::models::{EpochParamResponse, UtxoInfoResponse};
+use crate::plutus::deposits::deposit_report;
+use crate::plutus::datum_checks::datum_issues;
+use crate::plutus::evaluation_result::RedeemerEvaluation;
+use crate::plutus::fee_breakdown::fee_breakdown;
+use crate::plutus::tx_decoder::decode_transaction;
+use crate::plutus::tx_summary::tx_summary;
+use crate::protocol_params::adapters::parse_protocol_params;
+use crate::tx_checks::{input_set_issues, network_issues, TxCheckIssue};
+
+/// Share of the tx ex unit limits above which the budget is flagged.
+const BUDGET_WARNING_RATIO: f64 = 0.9;
+
+/// The results of any `execute_tx_scripts*` call.
+#[derive(Deserialize)]
+struct EvaluationInput {
+    results: Vec<RedeemerEvaluation>,
+}
+
+enum Block {
+    Paragraph(String),
+    List(Vec<String>),
+    Table(&'static [&'static str], Vec<Vec<String>>),
+    Code(String),
+}
+
+struct Section {
+    heading: &'static str,
+    blocks: Vec<Block>,
+}
+
+/// Writes a self-contained audit report of a tx, as `markdown` or `html`: a
+/// plain-language summary, the ledger checks, the script budgets, the fee and
+/// the decoded tx, with warnings for anything failing or left unchecked.
+/// `utxo_json` (Koios `utxo_info` JSON of the spent and referenced inputs),
+/// `protocol_params_json` and `evaluation_json` (the output of an
+/// `execute_tx_scripts*` call) may be empty, the checks needing them are
+/// then skipped.
+#[wasm_bindgen]
+pub fn generate_audit_report(
+    tx_hex: &str,
+    utxo_json: &str,
+    protocol_params_json: &str,
+    evaluation_json: &str,
+    format: &str,
+) -> Result<String, JsError> {
+    let utxos: Vec<UtxoInfoResponse> = if utxo_json.trim().is_empty() {
+        Vec::new()
+    } else {
+        serde_json::from_str(utxo_json).map_err(|e| ErrorCode::InvalidInput.error(&e.to_string()))?
+    };
+    let protocol_params = if protocol_params_json.trim().is_empty() {
+        None
+    } else {
+        Some(parse_protocol_params(protocol_params_json)?)
+    };
+    let evaluation = if evaluation_json.trim().is_empty() {
+        None
+    } else {
+        let input: EvaluationInput = serde_json::from_str(evaluation_json)
+            .map_err(|e| ErrorCode::InvalidInput.error(&format!("Invalid evaluation results: {}", e)))?;
+        Some(input.results)
+    };
+    let sections = report_sections(tx_hex, &utxos, protocol_params.as_ref(), evaluation.as_deref())?;
+    match format {
+        "markdown" => Ok(render_markdown(&sections)),
+        "html" => Ok(render_html(&sections)),
+        _ => Err(ErrorCode::InvalidInput.error(&format!("Invalid report format: {}, expected markdown or html", format))),
+    }
+}
+
+fn report_sections(
+    tx_hex: &str,
+    utxos: &[UtxoInfoResponse],
+    pp: Option<&EpochParamResponse>,
+    evaluation: Option<&[RedeemerEvaluation]>,
+) -> Result<Vec<Section>, JsError> {
+    let tx_bytes = decode_hex(tx_hex)?;
+    let tx = Transaction::from_bytes(tx_bytes.clone())
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding transaction: {:?}", e)))?;
+    let decoded = decode_transaction(tx_hex, utxos)?;
+    let summary = tx_summary(&tx, utxos)?;
+    let mut warnings = Vec::new();
+
+    let overview = vec![
+        format!("Transaction hash: `{}`", decoded.tx_hash),
+        format!("Size: {} bytes", tx_bytes.len()),
+        format!("Redeemers: {}", decoded.redeemers.len()),
+        format!("Report by cquisitor {}", env!("CARGO_PKG_VERSION")),
+    ];
+
+    let mut issues = input_set_issues(&tx_bytes)?;
+    issues.extend(network_issues(&tx, None));
+    let unresolved = unresolved_inputs(&tx, utxos);
+    if utxos.is_empty() {
+        warnings.push("No UTxOs were given: value balance, datums and spend targets are not checked.".to_string());
+    } else if !unresolved.is_empty() {
+        warnings.push(format!("Inputs missing from the given UTxOs, datums are not checked: {}.", unresolved.join(", ")));
+    } else {
+        issues.extend(datum_issues(&tx, utxos)?);
+    }
+
+    let mut fee_rows = Vec::new();
+    match pp {
+        Some(pp) => {
+            let fee = fee_breakdown(&tx_bytes, utxos.to_vec(), pp)?;
+            if fee.remainder < 0 {
+                issues.push(issue("FeeTooSmallUTxO", format!("The fee is {} lovelace below the minimum of {}.", -fee.remainder, fee.min_fee)));
+            }
+            fee_rows = vec![
+                row(&["Declared fee", &fee.declared_fee.to_string()]),
+                row(&["Size fee", &format!("{} ({} bytes)", fee.size_fee, fee.tx_size)]),
+                row(&["Constant fee", &fee.constant_fee.to_string()]),
+                row(&["Ex unit fee", &fee.ex_unit_fee.to_string()]),
+                row(&["Reference script fee", &format!("{} ({} bytes)", fee.reference_script_fee, fee.reference_script_size)]),
+                row(&["Minimum fee", &fee.min_fee.to_string()]),
+                row(&["Paid above the minimum", &fee.remainder.to_string()]),
+            ];
+            if let Some(balance) = deposit_report(&tx, utxos, pp)?.balance {
+                if !balance.balanced {
+                    let message = format!(
+                        "The tx consumes {} lovelace and produces {}, a difference of {}.",
+                        balance.consumed, balance.produced, balance.difference
+                    );
+                    issues.push(issue("ValueNotConservedUTxO", message));
+                }
+            }
+        }
+        None => warnings.push("No protocol params were given: the fee, deposits and budget limits are not checked.".to_string()),
+    }
+
+    let mut budget_rows = Vec::new();
+    match evaluation {
+        Some(results) => {
+            let mut total = (0u64, 0u64);
+            for redeemer in &decoded.redeemers {
+                let result = results
+                    .iter()
+                    .find(|r| r.redeemer_tag == redeemer.tag && r.redeemer_index == redeemer.index);
+                let (calculated, outcome) = match result {
+                    Some(RedeemerEvaluation { calculated_ex_units: Some(units), .. }) => {
+                        total = (total.0 + units.mem, total.1 + units.steps);
+                        if units.mem > redeemer.ex_units.mem || units.steps > redeemer.ex_units.steps {
+                            warnings.push(format!(
+                                "{} #{} declares fewer ex units than its script needs, the tx will fail phase 2.",
+                                redeemer.tag, redeemer.index
+                            ));
+                        }
+                        (format!("{} / {}", units.mem, units.steps), "passed".to_string())
+                    }
+                    Some(RedeemerEvaluation { error: Some(error), .. }) => {
+                        warnings.push(format!("{} #{} fails: {}", redeemer.tag, redeemer.index, error));
+                        ("-".to_string(), format!("failed: {}", error))
+                    }
+                    _ => ("-".to_string(), "not evaluated".to_string()),
+                };
+                budget_rows.push(vec![
+                    format!("{} #{}", redeemer.tag, redeemer.index),
+                    redeemer.target.clone(),
+                    format!("{} / {}", redeemer.ex_units.mem, redeemer.ex_units.steps),
+                    calculated,
+                    outcome,
+                ]);
+            }
+            if let Some(pp) = pp {
+                for (name, used, limit) in [("memory", total.0, pp.max_tx_ex_mem), ("steps", total.1, pp.max_tx_ex_steps)] {
+                    let Some(limit) = limit else {
+                        continue;
+                    };
+                    if used > limit {
+                        issues.push(issue("ExUnitsTooBigUTxO", format!("The scripts need {} {}, over the tx limit of {}.", used, name, limit)));
+                    } else if used as f64 > limit as f64 * BUDGET_WARNING_RATIO {
+                        warnings.push(format!("The scripts use {} of the {} {} a tx may use.", used, limit, name));
+                    }
+                }
+            }
+        }
+        None if !decoded.redeemers.is_empty() => {
+            warnings.push("No evaluation results were given: the scripts are not checked.".to_string());
+        }
+        None => {}
+    }
+    if !summary.missing_inputs.is_empty() {
+        warnings.push(format!("Value flows are unknown for inputs missing from the given UTxOs: {}.", summary.missing_inputs.join(", ")));
+    }
+
+    let validation = if issues.is_empty() {
+        Block::Paragraph("No ledger rule violations found by the checks that ran.".to_string())
+    } else {
+        let rows = issues.iter().map(|i| vec![i.rule.clone(), i.message.clone(), i.refs.join(", ")]).collect();
+        Block::Table(&["Rule", "Problem", "Refs"], rows)
+    };
+    let transaction = serde_json::to_string_pretty(&decoded.transaction)
+        .map_err(|e| ErrorCode::Internal.error(&format!("Error serializing transaction: {}", e)))?;
+
+    let mut sections = vec![
+        Section { heading: "Overview", blocks: vec![Block::List(overview)] },
+        Section { heading: "Summary", blocks: vec![Block::Paragraph(summary.summary)] },
+        Section { heading: "Validation", blocks: vec![validation] },
+    ];
+    if !budget_rows.is_empty() {
+        let headers: &[&str] = &["Redeemer", "Target", "Declared mem / steps", "Needed mem / steps", "Result"];
+        sections.push(Section { heading: "Script budgets", blocks: vec![Block::Table(headers, budget_rows)] });
+    }
+    if !fee_rows.is_empty() {
+        sections.push(Section { heading: "Fee", blocks: vec![Block::Table(&["Term", "Lovelace"], fee_rows)] });
+    }
+    let warnings = if warnings.is_empty() {
+        Block::Paragraph("None.".to_string())
+    } else {
+        Block::List(warnings)
+    };
+    sections.push(Section { heading: "Warnings", blocks: vec![warnings] });
+    sections.push(Section { heading: "Decoded transaction", blocks: vec![Block::Code(transaction)] });
+    Ok(sections)
+}
+
+/// Spent and referenced inputs that aren't among `utxos`.
+fn unresolved_inputs(tx: &Transaction, utxos: &[UtxoInfoResponse]) -> Vec<String> {
+    let body = tx.body();
+    let sets = [Some(body.inputs()), body.reference_inputs()];
+    let mut missing = Vec::new();
+    for inputs in sets.iter().flatten() {
+        missing.extend(input_refs(inputs).into_iter().filter(|key| {
+            !utxos.iter().any(|u| format!("{}#{}", u.tx_hash, u.tx_index) == *key)
+        }));
+    }
+    missing
+}
+
+fn input_refs(inputs: &TransactionInputs) -> Vec<String> {
+    (0..inputs.len())
+        .map(|i| inputs.get(i))
+        .map(|input| format!("{}#{}", input.transaction_id().to_hex(), input.index()))
+        .collect()
+}
+
+fn issue(rule: &str, message: String) -> TxCheckIssue {
+    TxCheckIssue { rule: rule.to_string(), message, refs: Vec::new() }
+}
+
+fn row(cells: &[&str]) -> Vec<String> {
+    cells.iter().map(|cell| cell.to_string()).collect()
+}
+
+fn render_markdown(sections: &[Section]) -> String {
+    let mut out = String::from("# Transaction audit report\n");
+    for section in sections {
+        out.push_str(&format!("\n## {}\n\n", section.heading));
+        for block in &section.blocks {
+            match block {
+                Block::Paragraph(text) => out.push_str(&format!("{}\n", text)),
+                Block::List(items) => items.iter().for_each(|item| out.push_str(&format!("- {}\n", item))),
+                Block::Table(headers, rows) => {
+                    out.push_str(&format!("| {} |\n", headers.join(" | ")));
+                    out.push_str(&format!("|{}\n", " --- |".repeat(headers.len())));
+                    for row in rows {
+                        let cells: Vec<String> = row.iter().map(|c| c.replace('|', "\\|").replace('\n', " ")).collect();
+                        out.push_str(&format!("| {} |\n", cells.join(" | ")));
+                    }
+                }
+                Block::Code(code) => out.push_str(&format!("```json\n{}\n```\n", code)),
+            }
+        }
+    }
+    out
+}
+
+fn render_html(sections: &[Section]) -> String {
+    let mut out = String::from(concat!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Transaction audit report</title>\n",
+        "<style>body{font-family:sans-serif;max-width:60rem;margin:2rem auto;padding:0 1rem}",
+        "table{border-collapse:collapse}th,td{border:1px solid #ccc;padding:.3rem .5rem;text-align:left}",
+        "pre{background:#f5f5f5;padding:1rem;overflow:auto}</style>\n</head>\n<body>\n",
+        "<h1>Transaction audit report</h1>\n",
+    ));
+    for section in sections {
+        out.push_str(&format!("<h2>{}</h2>\n", escape_html(section.heading)));
+        for block in &section.blocks {
+            match block {
+                Block::Paragraph(text) => out.push_str(&format!("<p>{}</p>\n", inline_code(text))),
+                Block::List(items) => {
+                    out.push_str("<ul>\n");
+                    items.iter().for_each(|item| out.push_str(&format!("<li>{}</li>\n", inline_code(item))));
+                    out.push_str("</ul>\n");
+                }
+                Block::Table(headers, rows) => {
+                    out.push_str("<table>\n<tr>");
+                    headers.iter().for_each(|h| out.push_str(&format!("<th>{}</th>", escape_html(h))));
+                    out.push_str("</tr>\n");
+                    for row in rows {
+                        out.push_str("<tr>");
+                        row.iter().for_each(|cell| out.push_str(&format!("<td>{}</td>", escape_html(cell))));
+                        out.push_str("</tr>\n");
+                    }
+                    out.push_str("</table>\n");
+                }
+                Block::Code(code) => out.push_str(&format!("<pre><code>{}</code></pre>\n", escape_html(code))),
+            }
+        }
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+/// Escapes the text and turns Markdown `code` spans into `<code>`.
+fn inline_code(text: &str) -> String {
+    escape_html(text)
+        .split('`')
+        .enumerate()
+        .map(|(i, part)| if i % 2 == 1 { format!("<code>{}</code>", part) } else { part.to_string() })
+        .collect()
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,107 @@
+use pallas_crypto::hash::Hasher;
+use serde::{Deserialize, Serialize};
+use crate::error::ErrorCode;
+use crate::js_error::JsError;
+
+#[derive(Deserialize, Debug, Default)]
+struct BlueprintPreamble {
+    #[serde(rename = "plutusVersion")]
+    plutus_version: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct BlueprintValidator {
+    title: String,
+    #[serde(rename = "compiledCode")]
+    compiled_code: Option<String>,
+    hash: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Blueprint {
+    #[serde(default)]
+    preamble: BlueprintPreamble,
+    validators: Vec<BlueprintValidator>,
+}
+
+/// Blueprint (CIP-57) validator matched to an on-chain script hash.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(all(target_arch = "wasm32", not(target_os = "emscripten")), derive(tsify::Tsify))]
+pub struct ValidatorAnnotation {
+    pub script_hash: String,
+    pub title: String,
+    pub purpose: Option<String>,
+}
+
+const PURPOSES: [&str; 6] = ["spend", "mint", "withdraw", "publish", "vote", "propose"];
+
+pub(crate) fn parse_blueprint(blueprint_json: &str) -> Result<Vec<ValidatorAnnotation>, JsError> {
+    let blueprint: Blueprint = serde_json::from_str(blueprint_json)
+        .map_err(|e| ErrorCode::InvalidInput.error(&format!("Invalid blueprint: {}", e)))?;
+    let language_tag = match blueprint.preamble.plutus_version.as_deref() {
+        Some("v1") => 1,
+        Some("v3") => 3,
+        _ => 2,
+    };
+
+    let mut annotations = Vec::new();
+    for validator in blueprint.validators {
+        let script_hash = match (validator.hash, validator.compiled_code) {
+            (Some(hash), _) => hash,
+            (None, Some(code)) => {
+                let bytes = hex::decode(&code).map_err(|e| {
+                    ErrorCode::InvalidInput.error(&format!("Invalid compiledCode of {}: {}", validator.title, e))
+                })?;
+                let mut hasher = Hasher::<224>::new();
+                hasher.input(&[language_tag]);
+                hasher.input(&bytes);
+                hex::encode(hasher.finalize())
+            }
+            (None, None) => continue,
+        };
+        // aiken >= 1.1 titles are `module.validator.purpose`
+        let purpose = validator
+            .title
+            .rsplit('.')
+            .next()
+            .filter(|last| PURPOSES.contains(last))
+            .map(|last| last.to_string());
+        annotations.push(ValidatorAnnotation {
+            script_hash,
+            title: validator.title,
+            purpose,
+        });
+    }
+    Ok(annotations)
+}
+
+/// Picks the validator for a script hash. Multi-purpose validators share one
+/// hash, so the entry whose purpose matches the redeemer tag wins.
+pub(crate) fn find_validator(
+    annotations: &[ValidatorAnnotation],
+    script_hash: &str,
+    redeemer_tag: Option<&str>,
+) -> Option<ValidatorAnnotation> {
+    let purpose = redeemer_tag.map(redeemer_tag_to_purpose);
+    let candidates: Vec<&ValidatorAnnotation> = annotations
+        .iter()
+        .filter(|a| a.script_hash == script_hash)
+        .collect();
+    candidates
+        .iter()
+        .find(|a| purpose.is_some() && a.purpose.as_deref() == purpose)
+        .or_else(|| candidates.first())
+        .map(|a| (*a).clone())
+}
+
+fn redeemer_tag_to_purpose(tag: &str) -> &'static str {
+    match tag {
+        "Spend" => "spend",
+        "Mint" => "mint",
+        "Reward" => "withdraw",
+        "Cert" => "publish",
+        "Vote" => "vote",
+        "Propose" => "propose",
+        _ => "",
+    }
+}
@@ -0,0 +1,140 @@
+use cardano_serialization_lib::{hash_plutus_data, Address, PlutusData, Transaction, TransactionInputs};
+use serde_json::json;
+use crate::bingen::wasm_bindgen;
+use crate::error::{CquisitorError, ErrorCode};
+use crate::input::sanitize_hex;
+use crate::js_error::JsError;
+use crate::koios_client::models::UtxoInfoResponse;
+use crate::schemas::to_versioned_json;
+use crate::tx_checks::{TxCheckIssue, TxCheckReport};
+
+/// Cross-checks the datums of the witness set against the datum hashes of the
+/// spent script UTxOs (Koios `utxo_info` JSON of every spent and referenced
+/// input), the way the ledger does: a datum hash of a spent script output must
+/// be witnessed, and a witnessed datum must be needed or hashed by an output
+/// of the tx or of a referenced UTxO. A missing datum paired with an
+/// unexpected one is reported as a mismatch, the datum was likely re-encoded.
+#[wasm_bindgen]
+pub fn check_datum_witnesses(tx_hex: &str, utxo_json: &str) -> Result<String, JsError> {
+    let tx = Transaction::from_hex(sanitize_hex(tx_hex)?)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding transaction: {:?}", e)))?;
+    let utxos: Vec<UtxoInfoResponse> =
+        serde_json::from_str(utxo_json).map_err(|e| ErrorCode::InvalidInput.error(&e.to_string()))?;
+    to_versioned_json(&TxCheckReport::new(datum_issues(&tx, &utxos)?))
+}
+
+pub(crate) fn datum_issues(tx: &Transaction, utxos: &[UtxoInfoResponse]) -> Result<Vec<TxCheckIssue>, JsError> {
+    let body = tx.body();
+    let inputs = resolve(&body.inputs(), utxos)?;
+    let reference_inputs = match body.reference_inputs() {
+        Some(reference_inputs) => resolve(&reference_inputs, utxos)?,
+        None => Vec::new(),
+    };
+
+    let mut issues = Vec::new();
+    // (input ref, datum hash) of spent script outputs holding a datum hash
+    let mut required = Vec::new();
+    let mut allowed = Vec::new();
+    let resolved = inputs.iter().map(|utxo| (utxo, true)).chain(reference_inputs.iter().map(|utxo| (utxo, false)));
+    for (utxo, is_spent) in resolved {
+        let utxo_ref = format!("{}#{}", utxo.tx_hash, utxo.tx_index);
+        if let (Some(inline_datum), Some(datum_hash)) = (&utxo.inline_datum, &utxo.datum_hash) {
+            let computed = PlutusData::from_hex(&inline_datum.bytes)
+                .map(|data| hash_plutus_data(&data).to_hex())
+                .map_err(|e| ErrorCode::InvalidInput.error(&format!("Invalid inline datum of UTxO {}: {:?}", utxo_ref, e)))?;
+            if &computed != datum_hash {
+                issues.push(TxCheckIssue {
+                    rule: "DatumHashMismatch".to_string(),
+                    message: format!("The inline datum hashes to {}, the UTxO states {}.", computed, datum_hash),
+                    refs: vec![utxo_ref],
+                });
+            }
+            continue;
+        }
+        let Some(datum_hash) = &utxo.datum_hash else {
+            continue;
+        };
+        if is_spent && is_script_address(&utxo.address) {
+            required.push((utxo_ref, datum_hash.clone()));
+        } else {
+            allowed.push(datum_hash.clone());
+        }
+    }
+    let outputs = body.outputs();
+    for i in 0..outputs.len() {
+        if let Some(data_hash) = outputs.get(i).data_hash() {
+            allowed.push(data_hash.to_hex());
+        }
+    }
+
+    let mut witnessed = Vec::new();
+    if let Some(datums) = tx.witness_set().plutus_data() {
+        for i in 0..datums.len() {
+            witnessed.push(hash_plutus_data(&datums.get(i)).to_hex());
+        }
+    }
+
+    let missing: Vec<&(String, String)> = required.iter().filter(|(_, hash)| !witnessed.contains(hash)).collect();
+    let extraneous: Vec<&String> = witnessed
+        .iter()
+        .filter(|hash| !allowed.contains(*hash) && !required.iter().any(|(_, required)| required == *hash))
+        .collect();
+
+    if missing.len() == 1 && extraneous.len() == 1 {
+        let (utxo_ref, expected) = missing[0];
+        issues.push(TxCheckIssue {
+            rule: "DatumHashMismatch".to_string(),
+            message: format!(
+                "UTxO {} needs datum {}, the witness set has datum {} instead.",
+                utxo_ref, expected, extraneous[0]
+            ),
+            refs: vec![utxo_ref.clone(), extraneous[0].clone()],
+        });
+        return Ok(issues);
+    }
+    if !missing.is_empty() {
+        issues.push(TxCheckIssue {
+            rule: "MissingRequiredDatums".to_string(),
+            message: format!(
+                "Datums of spent script UTxOs are not in the witness set: {}.",
+                missing.iter().map(|(_, hash)| hash.as_str()).collect::<Vec<_>>().join(", ")
+            ),
+            refs: missing.iter().map(|(utxo_ref, _)| utxo_ref.clone()).collect(),
+        });
+    }
+    if !extraneous.is_empty() {
+        issues.push(TxCheckIssue {
+            rule: "NotAllowedSupplementalDatums".to_string(),
+            message: "Witnessed datums are neither needed by a spent input nor hashed by an output.".to_string(),
+            refs: extraneous.into_iter().cloned().collect(),
+        });
+    }
+    Ok(issues)
+}
+
+fn resolve<'a>(inputs: &TransactionInputs, utxos: &'a [UtxoInfoResponse]) -> Result<Vec<&'a UtxoInfoResponse>, JsError> {
+    let mut resolved = Vec::new();
+    let mut missed = Vec::new();
+    for i in 0..inputs.len() {
+        let input = inputs.get(i);
+        let key = format!("{}#{}", input.transaction_id().to_hex(), input.index());
+        match utxos.iter().find(|u| format!("{}#{}", u.tx_hash, u.tx_index) == key) {
+            Some(utxo) => resolved.push(utxo),
+            None => missed.push(key),
+        }
+    }
+    if !missed.is_empty() {
+        let message = format!("UTxOs missing from the given list: {}", missed.join(", "));
+        return Err(CquisitorError::new(ErrorCode::MissingUtxo, &message)
+            .with_details(json!({ "utxos": missed }))
+            .into());
+    }
+    Ok(resolved)
+}
+
+fn is_script_address(address: &str) -> bool {
+    Address::from_bech32(address)
+        .ok()
+        .and_then(|address| address.payment_cred())
+        .is_some_and(|cred| cred.has_script_hash())
+}
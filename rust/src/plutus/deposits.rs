@@ -0,0 +1,226 @@
+use cardano_serialization_lib::{Certificate, Transaction};
+use serde::Serialize;
+use crate::bingen::wasm_bindgen;
+use crate::csl_decoders::certificate_form;
+use crate::error::ErrorCode;
+use crate::input::sanitize_hex;
+use crate::js_error::JsError;
+use crate::koios_client::models::{EpochParamResponse, UtxoInfoResponse};
+use crate::protocol_params::adapters::parse_protocol_params;
+use crate::schemas::to_versioned_json;
+
+/// A deposit a certificate or proposal takes, or a refund a deregistration
+/// gives back. `expected` is the amount the protocol params ask for, set when
+/// the tx states its own amount and it differs.
+#[derive(Serialize, Debug, Clone)]
+pub struct DepositEntry {
+    pub source: String,
+    pub index: usize,
+    pub kind: String,
+    pub amount: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected: Option<u64>,
+}
+
+/// Lovelace consumed and produced by the tx, deposits and refunds included.
+/// `difference` is consumed minus produced, a balanced tx has 0.
+#[derive(Serialize, Debug, Clone)]
+pub struct LovelaceBalance {
+    pub inputs: u64,
+    pub withdrawals: u64,
+    pub refunds: u64,
+    pub consumed: u64,
+    pub outputs: u64,
+    pub fee: u64,
+    pub deposits: u64,
+    pub donation: u64,
+    pub produced: u64,
+    pub difference: i128,
+    pub balanced: bool,
+}
+
+/// `balance` is absent when some inputs aren't among the given UTxOs,
+/// `missing_inputs` lists them.
+#[derive(Serialize, Debug, Clone)]
+pub struct DepositReport {
+    pub deposits: Vec<DepositEntry>,
+    pub refunds: Vec<DepositEntry>,
+    pub total_deposit: u64,
+    pub total_refund: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub balance: Option<LovelaceBalance>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub missing_inputs: Vec<String>,
+}
+
+enum DepositChange {
+    Deposit(&'static str, u64, Option<u64>),
+    Refund(&'static str, u64, Option<u64>),
+}
+
+/// Lists the deposits the certificates and proposals of a tx take and the
+/// refunds its deregistrations give back, and checks the lovelace balance
+/// with them. Pool registrations are counted as new pools, re-registering an
+/// existing pool takes no deposit; pool retirement refunds are paid at the
+/// epoch boundary, not by the tx.
+#[wasm_bindgen]
+pub fn calculate_deposits(tx_hex: &str, utxo_json: &str, protocol_params_json: &str) -> Result<String, JsError> {
+    let tx = Transaction::from_hex(sanitize_hex(tx_hex)?)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding transaction: {:?}", e)))?;
+    let utxos: Vec<UtxoInfoResponse> = if utxo_json.trim().is_empty() {
+        Vec::new()
+    } else {
+        serde_json::from_str(utxo_json).map_err(|e| ErrorCode::InvalidInput.error(&e.to_string()))?
+    };
+    let protocol_params = parse_protocol_params(protocol_params_json)?;
+    to_versioned_json(&deposit_report(&tx, &utxos, &protocol_params)?)
+}
+
+pub(crate) fn deposit_report(
+    tx: &Transaction,
+    utxos: &[UtxoInfoResponse],
+    pp: &EpochParamResponse,
+) -> Result<DepositReport, JsError> {
+    let body = tx.body();
+    let mut deposits = Vec::new();
+    let mut refunds = Vec::new();
+
+    if let Some(certs) = body.certs() {
+        for index in 0..certs.len() {
+            let change = match certificate_deposit(&certs.get(index), pp)? {
+                Some(change) => change,
+                None => continue,
+            };
+            let (list, kind, amount, expected) = match change {
+                DepositChange::Deposit(kind, amount, expected) => (&mut deposits, kind, amount, expected),
+                DepositChange::Refund(kind, amount, expected) => (&mut refunds, kind, amount, expected),
+            };
+            list.push(DepositEntry {
+                source: "certificate".to_string(),
+                index,
+                kind: kind.to_string(),
+                amount,
+                expected: expected.filter(|expected| *expected != amount),
+            });
+        }
+    }
+
+    if let Some(proposals) = body.voting_proposals() {
+        let expected = pp_lovelace(&pp.gov_action_deposit, "gov_action_deposit")?;
+        for index in 0..proposals.len() {
+            let amount = u64::from(proposals.get(index).deposit());
+            deposits.push(DepositEntry {
+                source: "proposal".to_string(),
+                index,
+                kind: "governance_action".to_string(),
+                amount,
+                expected: Some(expected).filter(|expected| *expected != amount),
+            });
+        }
+    }
+
+    let total_deposit = checked_sum(deposits.iter().map(|d| d.amount), "deposits")?;
+    let total_refund = checked_sum(refunds.iter().map(|r| r.amount), "refunds")?;
+
+    let mut missing_inputs = Vec::new();
+    let mut input_values = Vec::new();
+    let tx_inputs = body.inputs();
+    for i in 0..tx_inputs.len() {
+        let input = tx_inputs.get(i);
+        let key = format!("{}#{}", input.transaction_id().to_hex(), input.index());
+        match utxos.iter().find(|u| format!("{}#{}", u.tx_hash, u.tx_index) == key) {
+            Some(utxo) => {
+                input_values.push(
+                    utxo.value
+                        .parse::<u64>()
+                        .map_err(|e| ErrorCode::InvalidInput.error(&format!("Invalid value of UTxO {}: {}", key, e)))?,
+                );
+            }
+            None => missing_inputs.push(key),
+        }
+    }
+
+    let balance = if missing_inputs.is_empty() {
+        let inputs = checked_sum(input_values, "inputs")?;
+        let withdrawals = match body.withdrawals() {
+            Some(withdrawals) => {
+                let keys = withdrawals.keys();
+                checked_sum((0..keys.len()).filter_map(|i| withdrawals.get(&keys.get(i))).map(u64::from), "withdrawals")?
+            }
+            None => 0,
+        };
+        let outputs_list = body.outputs();
+        let outputs = checked_sum((0..outputs_list.len()).map(|i| u64::from(outputs_list.get(i).amount().coin())), "outputs")?;
+        let fee = u64::from(body.fee());
+        let donation = body.donation().map(u64::from).unwrap_or_default();
+        let consumed = checked_sum([inputs, withdrawals, total_refund], "consumed lovelace")?;
+        let produced = checked_sum([outputs, fee, total_deposit, donation], "produced lovelace")?;
+        Some(LovelaceBalance {
+            inputs,
+            withdrawals,
+            refunds: total_refund,
+            consumed,
+            outputs,
+            fee,
+            deposits: total_deposit,
+            donation,
+            produced,
+            difference: consumed as i128 - produced as i128,
+            balanced: consumed == produced,
+        })
+    } else {
+        None
+    };
+
+    Ok(DepositReport {
+        deposits,
+        refunds,
+        total_deposit,
+        total_refund,
+        balance,
+        missing_inputs,
+    })
+}
+
+/// Deposit or refund of a certificate, `None` for certificates that move no
+/// deposit. Kinds are the CDDL names, so a legacy `stake_registration` takes
+/// the protocol params value while a Conway `reg_cert` states its own and is
+/// checked against it.
+fn certificate_deposit(cert: &Certificate, pp: &EpochParamResponse) -> Result<Option<DepositChange>, JsError> {
+    let form = certificate_form(cert);
+    let key_deposit = || pp_lovelace(&pp.key_deposit, "key_deposit");
+    let change = match form.name {
+        "stake_registration" => DepositChange::Deposit(form.name, key_deposit()?, None),
+        "stake_deregistration" => DepositChange::Refund(form.name, key_deposit()?, None),
+        "reg_cert" | "stake_reg_deleg_cert" | "vote_reg_deleg_cert" | "stake_vote_reg_deleg_cert" => {
+            DepositChange::Deposit(form.name, form.deposit.unwrap_or_default(), Some(key_deposit()?))
+        }
+        "unreg_cert" => DepositChange::Refund(form.name, form.refund.unwrap_or_default(), Some(key_deposit()?)),
+        "pool_registration" => {
+            DepositChange::Deposit(form.name, pp_lovelace(&pp.pool_deposit, "pool_deposit")?, None)
+        }
+        "reg_drep_cert" => {
+            let expected = pp_lovelace(&pp.drep_deposit, "drep_deposit")?;
+            DepositChange::Deposit(form.name, form.deposit.unwrap_or_default(), Some(expected))
+        }
+        // refunds what was paid at registration, which may predate the current params
+        "unreg_drep_cert" => DepositChange::Refund(form.name, form.refund.unwrap_or_default(), None),
+        _ => return Ok(None),
+    };
+    Ok(Some(change))
+}
+
+fn pp_lovelace(value: &Option<String>, name: &str) -> Result<u64, JsError> {
+    value
+        .as_deref()
+        .ok_or_else(|| ErrorCode::InvalidInput.error(&format!("Protocol params have no {}", name)))?
+        .parse()
+        .map_err(|e| ErrorCode::InvalidInput.error(&format!("Invalid {} in protocol params: {}", name, e)))
+}
+
+fn checked_sum(values: impl IntoIterator<Item = u64>, name: &str) -> Result<u64, JsError> {
+    values
+        .into_iter()
+        .try_fold(0u64, u64::checked_add)
+        .ok_or_else(|| ErrorCode::InvalidInput.error(&format!("The {} of the tx overflow a u64", name)))
+}
@@ -0,0 +1,133 @@
+use crate::bingen::wasm_bindgen;
+use crate::error::ErrorCode;
+use crate::input::decode_hex;
+use crate::js_error::JsError;
+use crate::koios_client::models::{EpochParamResponse, UtxoInfoResponse};
+use crate::plutus::evaluation_limits::EvaluationLimits;
+use crate::plutus::evaluation_result::{EvaluationError, TxEvaluation};
+use crate::plutus::execute_tx_scripts::{
+    build_response_object, decode_conway_tx, eval_one_redeemer, response_utxo_to_pallas,
+    to_pallas_cost_models, tx_redeemers, SlotConfigDefinition,
+};
+use crate::protocol_params::adapters::parse_protocol_params;
+use crate::time_utils::now_ms;
+use pallas_primitives::conway::{CostMdls, Redeemer};
+use uplc::tx::{DataLookupTable, ResolvedInput, SlotConfig};
+
+/// `execute_tx_scripts_with_limits` split into steps, so the redeemers of a tx
+/// can be evaluated a few at a time between frames instead of blocking the
+/// main thread. A single redeemer still runs to completion once started, and
+/// `timeout_ms` counts the time spent in `poll` only.
+///
+/// ```js
+/// const job = EvaluationJob.start(txHex, utxoJson, paramsJson, slotConfigJson, limitsJson);
+/// while (!job.poll(1)) await new Promise(requestAnimationFrame);
+/// const evaluation = job.result();
+/// ```
+#[wasm_bindgen]
+pub struct EvaluationJob {
+    tx_bytes: Vec<u8>,
+    utxos: Vec<ResolvedInput>,
+    cost_models: CostMdls,
+    slot_config: SlotConfig,
+    limits: EvaluationLimits,
+    elapsed_ms: f64,
+    // in reverse order, so the next redeemer is popped off the end
+    pending: Vec<Redeemer>,
+    total: usize,
+    results: Vec<Result<(Redeemer, Redeemer), (Redeemer, EvaluationError)>>,
+}
+
+#[wasm_bindgen]
+impl EvaluationJob {
+    /// `slot_config_json` is `{ zero_time, zero_slot, slot_length }`, mainnet's
+    /// when not given, and `limits_json` the limits of `execute_tx_scripts_with_limits`.
+    pub fn start(
+        tx_hex: &str,
+        utxo_json: &str,
+        protocol_params_json: &str,
+        slot_config_json: Option<String>,
+        limits_json: Option<String>,
+    ) -> Result<EvaluationJob, JsError> {
+        let tx_bytes = decode_hex(tx_hex)?;
+        let mut pending = tx_redeemers(&decode_conway_tx(&tx_bytes)?);
+        pending.reverse();
+
+        let kios_utxos: Vec<UtxoInfoResponse> =
+            serde_json::from_str(utxo_json).map_err(|e| ErrorCode::InvalidInput.error(&e.to_string()))?;
+        let utxos = response_utxo_to_pallas(kios_utxos)?;
+        let kios_pp: EpochParamResponse = parse_protocol_params(protocol_params_json)?;
+        let slot_config = match slot_config_json {
+            Some(slot_config_json) => SlotConfig::from(SlotConfigDefinition::from_json(&slot_config_json)?),
+            None => SlotConfig::default(),
+        };
+        let limits = match limits_json {
+            Some(limits_json) => EvaluationLimits::from_json(&limits_json)?,
+            None => EvaluationLimits::default(),
+        };
+
+        Ok(EvaluationJob {
+            tx_bytes,
+            utxos,
+            cost_models: to_pallas_cost_models(&kios_pp),
+            slot_config,
+            limits,
+            elapsed_ms: 0.0,
+            total: pending.len(),
+            pending,
+            results: vec![],
+        })
+    }
+
+    /// Evaluates up to `max_redeemers` more redeemers, returns true once all
+    /// of them are evaluated.
+    pub fn poll(&mut self, max_redeemers: u32) -> Result<bool, JsError> {
+        if self.pending.is_empty() {
+            return Ok(true);
+        }
+        // the decoded tx borrows its bytes, so it is decoded again on each poll
+        let tx = decode_conway_tx(&self.tx_bytes)?;
+        let lookup_table = DataLookupTable::from_transaction(&tx, &self.utxos);
+        let budget = self.limits.initial_budget();
+        let started_at = now_ms();
+        for _ in 0..max_redeemers {
+            let redeemer = match self.pending.pop() {
+                Some(redeemer) => redeemer,
+                None => break,
+            };
+            if let Some(timeout_ms) = self.limits.timeout_ms {
+                if self.elapsed_ms + now_ms() - started_at > timeout_ms {
+                    self.results.push(Err((redeemer, EvaluationError::timeout(timeout_ms))));
+                    continue;
+                }
+            }
+            self.results.push(eval_one_redeemer(
+                &tx,
+                &self.utxos,
+                &self.slot_config,
+                &lookup_table,
+                Some(&self.cost_models),
+                &budget,
+                redeemer,
+            ));
+        }
+        self.elapsed_ms += now_ms() - started_at;
+        Ok(self.pending.is_empty())
+    }
+
+    /// Share of the redeemers evaluated so far, from 0 to 1.
+    pub fn progress(&self) -> f64 {
+        if self.total == 0 {
+            return 1.0;
+        }
+        self.results.len() as f64 / self.total as f64
+    }
+
+    /// Same evaluation as `execute_tx_scripts_with_limits`, once `poll` returned true.
+    pub fn result(&self) -> Result<TxEvaluation, JsError> {
+        if !self.pending.is_empty() {
+            return Err(ErrorCode::InvalidInput.error("Evaluation isn't finished, poll until it returns true"));
+        }
+        Ok(TxEvaluation::new(build_response_object(self.results.clone()), None))
+    }
+}
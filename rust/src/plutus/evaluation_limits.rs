@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+use uplc::machine::cost_model::ExBudget;
+use crate::error::ErrorCode;
+use crate::js_error::JsError;
+
+/// Caps applied while evaluating redeemers.
+///
+/// `max_mem`/`max_steps` bound the budget a single redeemer may spend, a
+/// looping script stops with a "budget exceeded" result instead of running
+/// until the ledger maximum. `timeout_ms` is a wall-clock budget for the whole
+/// evaluation; it is checked between redeemers since uplc can't be interrupted
+/// mid-script, so the step cap is what actually stops a runaway script.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct EvaluationLimits {
+    pub max_mem: Option<i64>,
+    pub max_steps: Option<i64>,
+    pub timeout_ms: Option<f64>,
+}
+
+impl EvaluationLimits {
+    pub(crate) fn from_json(limits_json: &str) -> Result<Self, JsError> {
+        serde_json::from_str(limits_json)
+            .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Invalid evaluation limits: {}", e)))
+    }
+
+    pub(crate) fn initial_budget(&self) -> ExBudget {
+        let default_budget = ExBudget::default();
+        ExBudget {
+            mem: self.max_mem.unwrap_or(default_budget.mem),
+            cpu: self.max_steps.unwrap_or(default_budget.cpu),
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use pallas_primitives::conway::{ExUnits, Redeemer, RedeemerTag};
+use serde::{Deserialize, Serialize};
+use uplc::machine::cost_model::ExBudget;
+use uplc::machine::Error as MachineError;
+use uplc::tx::error::Error;
+use crate::diagnostics::ExecutionDiagnostics;
+use crate::plutus::blueprint::ValidatorAnnotation;
+use crate::schemas::SCHEMA_VERSION;
+
+/// Execution budget of a redeemer, `mem` and `steps` as in the ledger `ExUnits`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(all(target_arch = "wasm32", not(target_os = "emscripten")), derive(tsify::Tsify))]
+pub struct EvaluationExUnits {
+    pub mem: u64,
+    pub steps: u64,
+}
+
+/// Why a redeemer failed to evaluate, `message` is the uplc error text.
+/// `budget_exceeded` is set when the script ran out of the allowed budget or
+/// the evaluation deadline passed before the redeemer was reached.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(all(target_arch = "wasm32", not(target_os = "emscripten")), derive(tsify::Tsify))]
+pub struct EvaluationError {
+    pub message: String,
+    pub budget_exceeded: bool,
+}
+
+impl EvaluationError {
+    pub(crate) fn from_uplc(err: &Error, budget: &ExBudget) -> Self {
+        match err {
+            Error::Machine(MachineError::OutOfExError(_), ..) => EvaluationError {
+                message: format!(
+                    "Budget exceeded at {} steps, {} mem",
+                    budget.cpu, budget.mem
+                ),
+                budget_exceeded: true,
+            },
+            _ => EvaluationError {
+                message: err.to_string(),
+                budget_exceeded: false,
+            },
+        }
+    }
+
+    pub(crate) fn timeout(timeout_ms: f64) -> Self {
+        EvaluationError {
+            message: format!("Evaluation timeout of {} ms exceeded before this redeemer", timeout_ms),
+            budget_exceeded: true,
+        }
+    }
+}
+
+/// Result of evaluating one redeemer of a transaction.
+///
+/// `calculated_ex_units` is present when the script succeeded, `error` when it
+/// failed; exactly one of them is set. `error` is the message, `error_details`
+/// the same failure with whether the budget ran out.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(all(target_arch = "wasm32", not(target_os = "emscripten")), derive(tsify::Tsify))]
+pub struct RedeemerEvaluation {
+    pub redeemer_tag: String,
+    pub redeemer_index: u32,
+    pub original_ex_units: EvaluationExUnits,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub calculated_ex_units: Option<EvaluationExUnits>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_details: Option<EvaluationError>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validator: Option<ValidatorAnnotation>,
+}
+
+/// Evaluation against a bundled protocol-parameter preset, `preset_epoch` is
+/// the epoch the preset snapshot was taken at.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(all(target_arch = "wasm32", not(target_os = "emscripten")), derive(tsify::Tsify), tsify(into_wasm_abi))]
+pub struct PresetEvaluation {
+    pub schema_version: u32,
+    pub preset: String,
+    pub preset_epoch: u64,
+    pub results: Vec<RedeemerEvaluation>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diagnostics: Option<ExecutionDiagnostics>,
+}
+
+/// A script used by the transaction, either attached to the witness set or
+/// provided by a reference input.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(all(target_arch = "wasm32", not(target_os = "emscripten")), derive(tsify::Tsify))]
+pub struct ScriptAnnotation {
+    pub script_hash: String,
+    pub language: String,
+    pub source: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validator: Option<ValidatorAnnotation>,
+}
+
+/// Evaluation annotated with validator names from a CIP-57 blueprint.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(all(target_arch = "wasm32", not(target_os = "emscripten")), derive(tsify::Tsify), tsify(into_wasm_abi))]
+pub struct BlueprintEvaluation {
+    pub schema_version: u32,
+    pub results: Vec<RedeemerEvaluation>,
+    pub scripts: Vec<ScriptAnnotation>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diagnostics: Option<ExecutionDiagnostics>,
+}
+
+/// Results of every redeemer of a transaction, in redeemer order.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(all(target_arch = "wasm32", not(target_os = "emscripten")), derive(tsify::Tsify), tsify(into_wasm_abi))]
+pub struct TxEvaluation {
+    pub schema_version: u32,
+    pub results: Vec<RedeemerEvaluation>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diagnostics: Option<ExecutionDiagnostics>,
+}
+
+impl TxEvaluation {
+    pub(crate) fn new(results: Vec<RedeemerEvaluation>, diagnostics: Option<ExecutionDiagnostics>) -> Self {
+        TxEvaluation {
+            schema_version: SCHEMA_VERSION,
+            results,
+            diagnostics,
+        }
+    }
+}
+
+impl From<ExUnits> for EvaluationExUnits {
+    fn from(ex_units: ExUnits) -> Self {
+        EvaluationExUnits {
+            mem: ex_units.mem,
+            steps: ex_units.steps,
+        }
+    }
+}
+
+impl RedeemerEvaluation {
+    pub(crate) fn success(redeemer: &Redeemer, new_redeemer: &Redeemer) -> Self {
+        RedeemerEvaluation {
+            redeemer_tag: redeemer_tag_to_string(&redeemer.tag),
+            redeemer_index: redeemer.index,
+            original_ex_units: redeemer.ex_units.into(),
+            calculated_ex_units: Some(new_redeemer.ex_units.into()),
+            error: None,
+            error_details: None,
+            validator: None,
+        }
+    }
+
+    pub(crate) fn failure(redeemer: &Redeemer, err: &EvaluationError) -> Self {
+        RedeemerEvaluation {
+            redeemer_tag: redeemer_tag_to_string(&redeemer.tag),
+            redeemer_index: redeemer.index,
+            original_ex_units: redeemer.ex_units.into(),
+            calculated_ex_units: None,
+            error: Some(err.message.clone()),
+            error_details: Some(err.clone()),
+            validator: None,
+        }
+    }
+}
+
+pub(crate) fn redeemer_tag_to_string(tag: &RedeemerTag) -> String {
+    match tag {
+        RedeemerTag::Spend => "Spend".to_string(),
+        RedeemerTag::Mint => "Mint".to_string(),
+        RedeemerTag::Cert => "Cert".to_string(),
+        RedeemerTag::Reward => "Reward".to_string(),
+        RedeemerTag::Propose => "Propose".to_string(),
+        RedeemerTag::Vote => "Vote".to_string(),
+    }
+}
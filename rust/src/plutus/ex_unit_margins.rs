@@ -0,0 +1,114 @@
+use cardano_serialization_lib::{BigNum, ExUnits, Redeemer, Redeemers, Transaction};
+use serde::{Deserialize, Serialize};
+use crate::bingen::wasm_bindgen;
+use crate::error::ErrorCode;
+use crate::input::sanitize_hex;
+use crate::js_error::JsError;
+use crate::plutus::evaluation_result::{EvaluationExUnits, RedeemerEvaluation};
+use crate::plutus::script_purpose::redeemer_tag_name;
+use crate::schemas::to_versioned_json;
+
+/// Ex units to set on a redeemer, the evaluated cost plus the margin rounded
+/// up. A redeemer whose evaluation failed keeps its original units and is
+/// `failed`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SuggestedExUnits {
+    pub redeemer_tag: String,
+    pub redeemer_index: u32,
+    pub original_ex_units: EvaluationExUnits,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub calculated_ex_units: Option<EvaluationExUnits>,
+    pub suggested_ex_units: EvaluationExUnits,
+    pub failed: bool,
+}
+
+/// `redeemers_cbor` is the witness set `redeemers` field with the suggested
+/// units, to splice into the tx. The body's `script_data_hash` covers the
+/// redeemers and has to be recomputed after.
+#[derive(Serialize, Debug, Clone)]
+pub struct ExUnitSuggestions {
+    pub margin_percent: f64,
+    pub redeemers: Vec<SuggestedExUnits>,
+    pub total: EvaluationExUnits,
+    pub redeemers_cbor: String,
+}
+
+#[derive(Deserialize)]
+struct EvaluationResults {
+    results: Vec<RedeemerEvaluation>,
+}
+
+/// Ex units for every redeemer of `tx_hex` from an evaluation of it (the
+/// output of any of the `execute_tx_scripts` functions) with
+/// `margin_percent` on top, e.g. 10 for +10%.
+#[wasm_bindgen]
+pub fn suggest_redeemer_ex_units(tx_hex: &str, evaluation_json: &str, margin_percent: f64) -> Result<String, JsError> {
+    let tx = Transaction::from_hex(sanitize_hex(tx_hex)?)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding transaction: {:?}", e)))?;
+    let evaluation: EvaluationResults = serde_json::from_str(evaluation_json)
+        .map_err(|e| ErrorCode::InvalidInput.error(&format!("Invalid evaluation: {}", e)))?;
+    to_versioned_json(&suggest_ex_units(&tx, &evaluation.results, margin_percent)?)
+}
+
+pub(crate) fn suggest_ex_units(
+    tx: &Transaction,
+    results: &[RedeemerEvaluation],
+    margin_percent: f64,
+) -> Result<ExUnitSuggestions, JsError> {
+    if !margin_percent.is_finite() || margin_percent < 0.0 {
+        return Err(ErrorCode::InvalidInput.error(&format!("Invalid margin: {}%", margin_percent)));
+    }
+    let redeemers = tx
+        .witness_set()
+        .redeemers()
+        .ok_or_else(|| ErrorCode::InvalidInput.error("Transaction has no redeemers"))?;
+
+    let mut suggestions = Vec::new();
+    let mut patched = Redeemers::new();
+    let mut total = EvaluationExUnits { mem: 0, steps: 0 };
+    for i in 0..redeemers.len() {
+        let redeemer = redeemers.get(i);
+        let tag = redeemer_tag_name(redeemer.tag().kind());
+        let index: u32 = redeemer.index().to_str().parse().unwrap_or_default();
+        let original = EvaluationExUnits {
+            mem: redeemer.ex_units().mem().to_str().parse().unwrap_or_default(),
+            steps: redeemer.ex_units().steps().to_str().parse().unwrap_or_default(),
+        };
+        let calculated = results
+            .iter()
+            .find(|result| result.redeemer_tag == tag && result.redeemer_index == index)
+            .and_then(|result| result.calculated_ex_units);
+        let suggested = match calculated {
+            Some(units) => EvaluationExUnits {
+                mem: with_margin(units.mem, margin_percent),
+                steps: with_margin(units.steps, margin_percent),
+            },
+            None => original,
+        };
+
+        total.mem += suggested.mem;
+        total.steps += suggested.steps;
+        let ex_units = ExUnits::new(&BigNum::from(suggested.mem), &BigNum::from(suggested.steps));
+        patched.add(&Redeemer::new(&redeemer.tag(), &redeemer.index(), &redeemer.data(), &ex_units));
+        suggestions.push(SuggestedExUnits {
+            redeemer_tag: tag.to_string(),
+            redeemer_index: index,
+            original_ex_units: original,
+            calculated_ex_units: calculated,
+            suggested_ex_units: suggested,
+            failed: calculated.is_none(),
+        });
+    }
+
+    Ok(ExUnitSuggestions {
+        margin_percent,
+        redeemers: suggestions,
+        total,
+        redeemers_cbor: patched.to_hex(),
+    })
+}
+
+fn with_margin(units: u64, margin_percent: f64) -> u64 {
+    // only the margin goes through floats, 1000 * 1.1 would round up to 1101
+    units + (units as f64 * margin_percent / 100.0).ceil() as u64
+}
@@ -1,41 +1,59 @@
-use std::collections::HashSet;
+use std::collections::BTreeMap;
 use crate::bingen::wasm_bindgen;
+use crate::error::ErrorCode;
+use crate::input::decode_hex;
 use crate::js_error::JsError;
-use crate::koios_client::epoch_protocol_params_request::get_epoch_protocol_params;
-use crate::koios_client::models::{EpochParamResponse, QueryChainTipResponse, UtxoInfoResponse};
-use crate::koios_client::query_chain_tip_request::get_chain_tip;
-use crate::koios_client::utxo_request::get_utxos;
-use crate::netwrok_type::NetworkType;
+use crate::koios_client::models::{EpochParamResponse, UtxoInfoResponse};
+use crate::plutus::evaluation_limits::EvaluationLimits;
+use crate::plutus::blueprint::{find_validator, parse_blueprint, ValidatorAnnotation};
+use crate::plutus::evaluation_result::{BlueprintEvaluation, EvaluationError, PresetEvaluation, RedeemerEvaluation, ScriptAnnotation, TxEvaluation};
+use crate::plutus::script_purpose::{resolve_redeemer_target, utxo_addresses};
+use crate::time_utils::now_ms;
+use crate::diagnostics::DiagnosticsRecorder;
+use crate::schemas::SCHEMA_VERSION;
+use crate::logger::{log_debug, log_warn};
+use crate::protocol_params::adapters::parse_protocol_params;
+use crate::protocol_params::presets::get_preset;
 use cardano_serialization_lib::Address;
-use itertools::Itertools;
+use serde::Deserialize;
 use pallas_codec::minicbor::Decode;
 use pallas_codec::utils::{Bytes, CborWrap, KeyValuePairs, NonEmptyKeyValuePairs, PositiveCoin};
 use pallas_crypto::hash::Hash;
 use pallas_primitives::conway::{AssetName, CostMdls, ExUnits, MintedTx, Multiasset, NativeScript, PlutusData, PlutusV1Script, PlutusV2Script, PlutusV3Script};
 use pallas_primitives::conway::{
-    PolicyId, PostAlonzoTransactionOutput, PseudoScript, Redeemer, RedeemerTag, ScriptRef,
+    PolicyId, PostAlonzoTransactionOutput, PseudoScript, Redeemer, ScriptRef,
     TransactionOutput,
 };
 use pallas_primitives::conway::DatumOption;
 use pallas_primitives::conway::Language::PlutusV3;
 use pallas_primitives::Fragment;
 use pallas_traverse::{Era, MultiEraTx};
-use serde_json::{Map, Number, Value};
 use uplc::machine::cost_model::ExBudget;
 use uplc::tx::error::Error;
 use uplc::tx::DataLookupTable;
 use uplc::tx::{eval, eval_phase_one, ResolvedInput, SlotConfig};
 use uplc::TransactionInput;
 
+#[cfg(feature = "providers")]
+use {
+    std::collections::HashSet,
+    serde_json::json,
+    crate::error::CquisitorError,
+    crate::koios_client::config::ClientOptions,
+    crate::koios_client::network_type::NetworkType as KoiosNetworkType,
+    crate::netwrok_type::NetworkType,
+    crate::era_history::{network_slot_config, provider_slot_config},
+    crate::provider::cache::{instance_storage, CachedProvider},
+    crate::provider::koios::KoiosProvider,
+    crate::provider::Provider,
+    crate::plutus::script_resolution::resolve_missing_scripts,
+    crate::abort_signal::{with_abort_signal, AbortSignal},
+};
+
 #[wasm_bindgen]
 pub fn get_utxo_list_from_tx(tx_hex: &str) -> Result<Vec<String>, JsError> {
-    let tx_bytes = hex::decode(tx_hex).map_err(|e| JsError::new(&e.to_string()))?;
-    let mtx = MultiEraTx::decode_for_era(Era::Conway, &tx_bytes)
-        .map_err(|e| JsError::new(&e.to_string()))?;
-    let tx = match mtx {
-        MultiEraTx::Conway(tx) => tx.into_owned(),
-        _ => return Err(JsError::new("Invalid transaction type")),
-    };
+    let tx_bytes = decode_hex(tx_hex)?;
+    let tx = decode_conway_tx(&tx_bytes)?;
 
     let mut all_inputs = Vec::new();
     for input in tx.transaction_body.inputs.iter() {
@@ -60,41 +78,284 @@ pub fn execute_tx_scripts(
     tx_hex: &str,
     utxo_json: &str,
     protocol_params_json: &str,
-) -> Result<String, JsError> {
-    let tx_bytes = hex::decode(tx_hex).map_err(|e| JsError::new(&e.to_string()))?;
-    let mtx = MultiEraTx::decode_for_era(Era::Conway, &tx_bytes)
-        .map_err(|e| JsError::new(&e.to_string()))?;
-    let tx = match mtx {
-        MultiEraTx::Conway(tx) => tx.into_owned(),
-        _ => return Err(JsError::new("Invalid transaction type")),
-    };
+) -> Result<TxEvaluation, JsError> {
+    evaluate(tx_hex, utxo_json, protocol_params_json, false, &EvaluationLimits::default())
+}
+
+/// Evaluates every redeemer of the tx against the UTxOs and the cost models of
+/// the protocol params, the pipeline every sync entry point shares.
+/// `phase_one` runs the ledger phase one checks first.
+fn evaluate(
+    tx_hex: &str,
+    utxo_json: &str,
+    protocol_params_json: &str,
+    phase_one: bool,
+    limits: &EvaluationLimits,
+) -> Result<TxEvaluation, JsError> {
+    let kios_pp: EpochParamResponse = parse_protocol_params(protocol_params_json)?;
+    let cost_models = to_pallas_cost_models(&kios_pp);
+    evaluate_with_cost_models(tx_hex, utxo_json, Some(&cost_models), phase_one, limits)
+}
+
+/// `evaluate` with the cost models given, uplc's built-in ones when `None`.
+fn evaluate_with_cost_models(
+    tx_hex: &str,
+    utxo_json: &str,
+    cost_models: Option<&CostMdls>,
+    phase_one: bool,
+    limits: &EvaluationLimits,
+) -> Result<TxEvaluation, JsError> {
+    let mut recorder = DiagnosticsRecorder::start();
+    let tx_bytes = decode_hex(tx_hex)?;
+    let tx = decode_conway_tx(&tx_bytes)?;
 
     let kios_utxos: Vec<UtxoInfoResponse> =
-        serde_json::from_str(utxo_json).map_err(|e| JsError::new(&e.to_string()))?;
+        serde_json::from_str(utxo_json).map_err(|e| ErrorCode::InvalidInput.error(&e.to_string()))?;
     let utxos = response_utxo_to_pallas(kios_utxos)?;
+    recorder.phase("decode");
     let slot_config: SlotConfig = SlotConfig::default();
-    let kios_pp: EpochParamResponse =
-        serde_json::from_str(protocol_params_json).map_err(|e| JsError::new(&e.to_string()))?;
-    let cost_models = to_pallas_cost_models(&kios_pp);
-    let exec_result = eval_all_redeemers(&tx, &utxos, Some(&cost_models), &slot_config, false)?;
+    let exec_result = eval_all_redeemers(&tx, &utxos, cost_models, &slot_config, phase_one, limits)?;
+    recorder.phase("evaluate");
+
+    Ok(TxEvaluation::new(build_response_object(exec_result), recorder.finish()))
+}
+
+/// Same as `execute_tx_scripts`, failing with a `phase_one_failure` error when
+/// the redeemers, scripts or datums of the tx don't pass the ledger phase one
+/// checks.
+#[wasm_bindgen]
+pub fn validate_tx(
+    tx_hex: &str,
+    utxo_json: &str,
+    protocol_params_json: &str,
+) -> Result<TxEvaluation, JsError> {
+    evaluate(tx_hex, utxo_json, protocol_params_json, true, &EvaluationLimits::default())
+}
 
-    return Ok(build_response_object(exec_result).to_string());
+/// Same as `execute_tx_scripts`, capping each redeemer's budget and the whole
+/// evaluation's wall-clock time with `limits_json`, see [`EvaluationLimits`].
+/// The timeout is only checked between redeemers, a single script runs until
+/// it finishes or exhausts its budget.
+#[wasm_bindgen]
+pub fn execute_tx_scripts_with_limits(
+    tx_hex: &str,
+    utxo_json: &str,
+    protocol_params_json: &str,
+    limits_json: &str,
+) -> Result<TxEvaluation, JsError> {
+    evaluate(tx_hex, utxo_json, protocol_params_json, false, &EvaluationLimits::from_json(limits_json)?)
 }
 
+/// Same as `execute_tx_scripts`, with every redeemer result and every script of
+/// the transaction annotated with its validator title from the blueprint.
+#[wasm_bindgen]
+pub fn execute_tx_scripts_with_blueprint(
+    tx_hex: &str,
+    utxo_json: &str,
+    protocol_params_json: &str,
+    blueprint_json: &str,
+) -> Result<BlueprintEvaluation, JsError> {
+    let validators = parse_blueprint(blueprint_json)?;
+    let evaluation = evaluate(tx_hex, utxo_json, protocol_params_json, false, &EvaluationLimits::default())?;
+
+    let csl_tx = cardano_serialization_lib::Transaction::from_bytes(decode_hex(tx_hex)?)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("{:?}", e)))?;
+    let kios_utxos: Vec<UtxoInfoResponse> =
+        serde_json::from_str(utxo_json).map_err(|e| ErrorCode::InvalidInput.error(&e.to_string()))?;
+    let utxo_addresses = utxo_addresses(&kios_utxos);
+    let scripts = collect_script_annotations(&csl_tx, &kios_utxos, &validators);
+
+    let mut results = evaluation.results;
+    for result in results.iter_mut() {
+        let target = resolve_redeemer_target(&csl_tx, &utxo_addresses, &result.redeemer_tag, result.redeemer_index);
+        result.validator = target
+            .script_hash
+            .and_then(|hash| find_validator(&validators, &hash, Some(&result.redeemer_tag)));
+    }
+
+    Ok(BlueprintEvaluation { schema_version: SCHEMA_VERSION, results, scripts, diagnostics: evaluation.diagnostics })
+}
+
+fn collect_script_annotations(
+    tx: &cardano_serialization_lib::Transaction,
+    utxos: &[UtxoInfoResponse],
+    validators: &[ValidatorAnnotation],
+) -> Vec<ScriptAnnotation> {
+    let mut scripts = Vec::new();
+    if let Some(plutus_scripts) = tx.witness_set().plutus_scripts() {
+        for i in 0..plutus_scripts.len() {
+            let script = plutus_scripts.get(i);
+            let script_hash = script.hash().to_hex();
+            scripts.push(ScriptAnnotation {
+                validator: find_validator(validators, &script_hash, None),
+                script_hash,
+                language: format!("{:?}", script.language_version().kind()),
+                source: "witness".to_string(),
+            });
+        }
+    }
+    for utxo in utxos {
+        if let Some(script) = &utxo.reference_script {
+            scripts.push(ScriptAnnotation {
+                validator: find_validator(validators, &script.hash, None),
+                script_hash: script.hash.clone(),
+                language: script.script_type.clone(),
+                source: format!("reference {}#{}", utxo.tx_hash, utxo.tx_index),
+            });
+        }
+    }
+    scripts
+}
+
+#[wasm_bindgen]
+pub fn execute_tx_scripts_with_preset(
+    tx_hex: &str,
+    utxo_json: &str,
+    preset_name: &str,
+) -> Result<PresetEvaluation, JsError> {
+    let preset_pp = get_preset(preset_name)?;
+    // presets don't carry cost models, uplc falls back to its built-in ones
+    let cost_models = preset_pp
+        .cost_models
+        .as_ref()
+        .map(|_| to_pallas_cost_models(&preset_pp));
+    let evaluation = evaluate_with_cost_models(tx_hex, utxo_json, cost_models.as_ref(), false, &EvaluationLimits::default())?;
+
+    Ok(PresetEvaluation {
+        schema_version: SCHEMA_VERSION,
+        preset: preset_name.to_string(),
+        preset_epoch: preset_pp.epoch_no,
+        results: evaluation.results,
+        diagnostics: evaluation.diagnostics,
+    })
+}
+
+/// Evaluates all redeemers of a tx against Koios, with the slot config of the
+/// era its validity interval falls in. The chain tip (for 20s), genesis, epoch params,
+/// datums and scripts are kept for the lifetime of the instance,
+/// `force_refresh` fetches them again.
+#[cfg(feature = "providers")]
 #[wasm_bindgen(catch)]
 pub async fn execute_tx_scripts_for_specific_network(
     tx_hex: &str,
     network: NetworkType,
-    api_token: &str,
-) -> Result<String, JsError> {
-    let tx_bytes = hex::decode(tx_hex).map_err(|e| JsError::new(&e.to_string()))?;
-    let mtx = MultiEraTx::decode_for_era(Era::Conway, &tx_bytes)
-        .map_err(|e| JsError::new(&e.to_string()))?;
-    let tx = match mtx {
-        MultiEraTx::Conway(tx) => tx.into_owned(),
-        _ => return Err(JsError::new("Invalid transaction type")),
+    api_token: Option<String>,
+    force_refresh: Option<bool>,
+    signal: Option<AbortSignal>,
+) -> Result<TxEvaluation, JsError> {
+    with_abort_signal(signal, async move {
+        let namespace = network.name();
+        let koios = KoiosProvider::new(network.clone().into(), api_token);
+        let provider = CachedProvider::new(koios, instance_storage(), namespace)
+            .with_force_refresh(force_refresh.unwrap_or(false));
+        let slot_config = network_slot_config(&provider, &network, tx_hex).await;
+        execute_tx_scripts_with_provider(tx_hex, &provider, &slot_config).await
+    })
+    .await
+}
+
+/// Same as `execute_tx_scripts_for_specific_network` with Koios client options
+/// as JSON, e.g. `{"retry": {"max_attempts": 5, "initial_backoff_ms": 1000}}`.
+#[cfg(feature = "providers")]
+#[wasm_bindgen(catch)]
+pub async fn execute_tx_scripts_for_specific_network_with_options(
+    tx_hex: &str,
+    network: NetworkType,
+    api_token: Option<String>,
+    options_json: &str,
+    force_refresh: Option<bool>,
+    signal: Option<AbortSignal>,
+) -> Result<TxEvaluation, JsError> {
+    with_abort_signal(signal, async move {
+        let options = ClientOptions::from_json(options_json)?;
+        let namespace = network.name();
+        let koios = KoiosProvider::new(network.clone().into(), api_token).with_options(options);
+        let provider = CachedProvider::new(koios, instance_storage(), namespace)
+            .with_force_refresh(force_refresh.unwrap_or(false));
+        let slot_config = network_slot_config(&provider, &network, tx_hex).await;
+        execute_tx_scripts_with_provider(tx_hex, &provider, &slot_config).await
+    })
+    .await
+}
+
+/// Same as `execute_tx_scripts_for_specific_network` with a required
+/// `signal`, kept for callers written before every entry point took one.
+/// Pending requests are aborted, an evaluation that already started runs to
+/// completion since it never yields.
+#[cfg(all(feature = "providers", target_arch = "wasm32", not(target_os = "emscripten")))]
+#[wasm_bindgen(catch)]
+pub async fn execute_tx_scripts_with_abort_signal(
+    tx_hex: &str,
+    network: NetworkType,
+    api_token: Option<String>,
+    signal: AbortSignal,
+) -> Result<TxEvaluation, JsError> {
+    execute_tx_scripts_for_specific_network(tx_hex, network, api_token, None, Some(signal)).await
+}
+
+/// Same as `execute_tx_scripts_for_specific_network`, against the Koios
+/// instance at `koios_url`, e.g. `https://koios.example.com/api/v1`. The slot
+/// config is derived from the instance's genesis.
+#[cfg(feature = "providers")]
+#[wasm_bindgen(catch)]
+pub async fn execute_tx_scripts_with_koios_url(
+    tx_hex: &str,
+    koios_url: &str,
+    api_token: Option<String>,
+    signal: Option<AbortSignal>,
+) -> Result<TxEvaluation, JsError> {
+    with_abort_signal(signal, async move {
+        let provider = KoiosProvider::new(KoiosNetworkType::Custom(koios_url.to_string()), api_token);
+        let slot_config = provider_slot_config(&provider, tx_hex, None).await?;
+        execute_tx_scripts_with_provider(tx_hex, &provider, &slot_config).await
+    })
+    .await
+}
+
+/// Evaluates all redeemers of a tx, resolving its inputs, the protocol params
+/// of the current epoch and the chain tip through `provider`.
+#[cfg(feature = "providers")]
+pub(crate) async fn execute_tx_scripts_with_provider<P: Provider>(
+    tx_hex: &str,
+    provider: &P,
+    slot_config: &SlotConfig,
+) -> Result<TxEvaluation, JsError> {
+    let mut recorder = DiagnosticsRecorder::start();
+    let tx_bytes = decode_hex(tx_hex)?;
+    let all_inputs = {
+        let tx = decode_conway_tx(&tx_bytes)?;
+        tx_input_refs(&tx)
     };
+    recorder.phase("decode");
+
+    // the tip doesn't depend on the inputs, nor the params on missing scripts
+    let (koios_utxos, chain_tip) = futures::join!(provider.get_utxos(&all_inputs), provider.get_chain_tip());
+    let koios_utxos = koios_utxos?;
+
+    check_missed_utxos(&all_inputs, &koios_utxos)?;
+    recorder.phase("fetch_utxos");
+
+    let epoch_number = chain_tip?.epoch_no;
+    let (tx_bytes, kios_pp) = futures::join!(
+        resolve_missing_scripts(tx_bytes, &koios_utxos, provider),
+        provider.get_protocol_params(epoch_number)
+    );
+    let tx_bytes = tx_bytes?;
+    let kios_pp = kios_pp?;
+    recorder.phase("fetch_scripts_and_params");
+    let tx = decode_conway_tx(&tx_bytes)?;
+    let utxos = response_utxo_to_pallas(koios_utxos)?;
+
+    let cost_models = to_pallas_cost_models(&kios_pp);
+    let exec_result = eval_all_redeemers(&tx, &utxos, Some(&cost_models), slot_config, false, &EvaluationLimits::default())?;
+    recorder.phase("evaluate");
 
+    Ok(TxEvaluation::new(build_response_object(exec_result), recorder.finish()))
+}
+
+/// Inputs, reference inputs and collaterals as `tx_hash#index`.
+#[cfg(feature = "providers")]
+fn tx_input_refs(tx: &MintedTx) -> Vec<String> {
     let mut all_inputs = Vec::new();
     for input in tx.transaction_body.inputs.iter() {
         all_inputs.push(input_to_request_format(input));
@@ -109,24 +370,10 @@ pub async fn execute_tx_scripts_for_specific_network(
             all_inputs.push(input_to_request_format(input));
         }
     }
-
-    let koios_utxos = get_utxos(&all_inputs, network.clone().into(), api_token).await?;
-
-    check_missed_utxos(&all_inputs, &koios_utxos)?;
-
-    let utxos = response_utxo_to_pallas(koios_utxos)?;
-    let slot_config = SlotConfig::default();
-
-    let epoch_number = get_chain_tip(network.clone().into(), api_token)
-        .await?
-        .epoch_no;
-    let kios_pp = get_epoch_protocol_params(epoch_number, network.into(), api_token).await?;
-    let cost_models = to_pallas_cost_models(&kios_pp);
-    let exec_result = eval_all_redeemers(&tx, &utxos, Some(&cost_models), &slot_config, false)?;
-
-    Ok(build_response_object(exec_result).to_string())
+    all_inputs
 }
 
+#[cfg(feature = "providers")]
 fn check_missed_utxos(
     request_utxos: &Vec<String>,
     utxos: &Vec<UtxoInfoResponse>,
@@ -141,87 +388,60 @@ fn check_missed_utxos(
         .cloned()
         .collect();
     if missed_utxos.len() > 0 {
-        return Err(JsError::new(&format!(
+        let message = format!(
             "Can't get these UTXOs from API, check the network type : {}",
             missed_utxos.join(", ")
-        )));
+        );
+        return Err(CquisitorError::new(ErrorCode::MissingUtxo, &message)
+            .with_details(json!({ "utxos": missed_utxos }))
+            .into());
     }
     Ok(())
 }
 
-fn build_response_object(
-    exec_result: Vec<Result<(Redeemer, Redeemer), (Redeemer, Error)>>,
-) -> Value {
-    let mut response = Vec::new();
-
-    for result in exec_result {
-        match result {
-            Ok((redeemer, new_redeemer)) => {
-                let mut redeemer_result = Map::new();
-                redeemer_result.insert(
-                    "original_ex_units".to_string(),
-                    exec_units_to_json(redeemer.ex_units),
-                );
-                redeemer_result.insert(
-                    "calculated_ex_units".to_string(),
-                    exec_units_to_json(new_redeemer.ex_units),
-                );
-                redeemer_result.insert("redeemer_index".to_string(), redeemer.index.into());
-                redeemer_result.insert(
-                    "redeemer_tag".to_string(),
-                    redeemer_tag_to_string(&redeemer.tag).into(),
-                );
-                response.push(Value::Object(redeemer_result));
-            }
-            Err((redeemer, err)) => {
-                let mut redeemer_result = Map::new();
-                redeemer_result.insert(
-                    "original_ex_units".to_string(),
-                    exec_units_to_json(redeemer.ex_units),
-                );
-                redeemer_result.insert("error".to_string(), err.to_string().into());
-                redeemer_result.insert("redeemer_index".to_string(), redeemer.index.into());
-                redeemer_result.insert(
-                    "redeemer_tag".to_string(),
-                    redeemer_tag_to_string(&redeemer.tag).into(),
-                );
-                response.push(Value::Object(redeemer_result));
-            }
-        }
-    }
+pub(crate) fn build_response_object(
+    exec_result: Vec<Result<(Redeemer, Redeemer), (Redeemer, EvaluationError)>>,
+) -> Vec<RedeemerEvaluation> {
+    exec_result
+        .iter()
+        .map(|result| match result {
+            Ok((redeemer, new_redeemer)) => RedeemerEvaluation::success(redeemer, new_redeemer),
+            Err((redeemer, err)) => RedeemerEvaluation::failure(redeemer, err),
+        })
+        .collect()
+}
 
-    return Value::Array(response);
+fn input_to_request_format(input: &TransactionInput) -> String {
+    return format!("{}#{}", hex::encode(input.transaction_id), input.index);
 }
 
-fn exec_units_to_json(exec_unit: ExUnits) -> Value {
-    let mut obj = Map::new();
-    obj.insert(
-        "steps".to_string(),
-        Value::Number(Number::from(exec_unit.steps)),
-    );
-    obj.insert(
-        "mem".to_string(),
-        Value::Number(Number::from(exec_unit.mem)),
-    );
-    Value::Object(obj)
+/// Slot to POSIX time mapping given by the caller, times in POSIX milliseconds,
+/// e.g. `{ "zero_time": 1700000000000, "zero_slot": 0, "slot_length": 1000 }`.
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub(crate) struct SlotConfigDefinition {
+    pub(crate) zero_time: u64,
+    pub(crate) zero_slot: u64,
+    pub(crate) slot_length: u32,
 }
 
-fn redeemer_tag_to_string(tag: &RedeemerTag) -> String {
-    match tag {
-        RedeemerTag::Spend => "Spend".to_string(),
-        RedeemerTag::Mint => "Mint".to_string(),
-        RedeemerTag::Cert => "Cert".to_string(),
-        RedeemerTag::Reward => "Reward".to_string(),
-        RedeemerTag::Propose => "Propose".to_string(),
-        RedeemerTag::Vote => "Vote".to_string(),
+impl SlotConfigDefinition {
+    pub(crate) fn from_json(slot_config_json: &str) -> Result<Self, JsError> {
+        serde_json::from_str(slot_config_json)
+            .map_err(|e| ErrorCode::InvalidInput.error(&format!("Invalid slot config: {}", e)))
     }
 }
 
-fn input_to_request_format(input: &TransactionInput) -> String {
-    return format!("{}#{}", hex::encode(input.transaction_id), input.index);
+impl From<SlotConfigDefinition> for SlotConfig {
+    fn from(config: SlotConfigDefinition) -> Self {
+        SlotConfig {
+            zero_time: config.zero_time,
+            zero_slot: config.zero_slot,
+            slot_length: config.slot_length,
+        }
+    }
 }
 
-fn to_pallas_cost_models(pp: &EpochParamResponse) -> CostMdls {
+pub(crate) fn to_pallas_cost_models(pp: &EpochParamResponse) -> CostMdls {
     CostMdls {
         plutus_v1: pp
             .cost_models
@@ -241,184 +461,233 @@ fn to_pallas_cost_models(pp: &EpochParamResponse) -> CostMdls {
     }
 }
 
-fn response_utxo_to_pallas(utxos: Vec<UtxoInfoResponse>) -> Result<Vec<ResolvedInput>, JsError> {
+/// Converts UTxOs, from a provider or the caller, to the evaluator's inputs.
+/// A malformed one is named along with the offending field instead of
+/// failing on a bare hex or length error.
+pub(crate) fn response_utxo_to_pallas(utxos: Vec<UtxoInfoResponse>) -> Result<Vec<ResolvedInput>, JsError> {
     let mut resolved_inputs = Vec::new();
     for utxo in utxos {
-        let tx_hash: [u8; 32] = hex::decode(&utxo.tx_hash)
-            .map_err(|e| JsError::new(&e.to_string()))?
-            .try_into()
-            .map_err(|e: Vec<u8>| JsError::new("incorrect len"))?;
-        let resolved_input = ResolvedInput {
-            input: TransactionInput {
-                transaction_id: Hash::from(tx_hash),
-                index: utxo.tx_index,
-            },
-            output: TransactionOutput::PostAlonzo(PostAlonzoTransactionOutput {
-                address: to_pallas_address(&utxo)?,
-                value: to_pallas_value(&utxo)?,
-                datum_option: to_pallas_datum(&utxo)?,
-                script_ref: to_pallas_script_ref(&utxo)?,
-            }),
-        };
+        let resolved_input = to_resolved_input(&utxo).map_err(|message| {
+            ErrorCode::DecodeFailure.error(&format!("UTxO {}#{}: {}", utxo.tx_hash, utxo.tx_index, message))
+        })?;
         resolved_inputs.push(resolved_input);
     }
 
     Ok(resolved_inputs)
 }
 
-fn to_pallas_script_ref(utxo: &UtxoInfoResponse) -> Result<Option<CborWrap<ScriptRef>>, JsError> {
+fn to_resolved_input(utxo: &UtxoInfoResponse) -> Result<ResolvedInput, String> {
+    let tx_hash: [u8; 32] = hex_field("tx_hash", &utxo.tx_hash, Some(32))?
+        .try_into()
+        .map_err(|_| "`tx_hash` is not 32 bytes".to_string())?;
+    let value = to_pallas_value(utxo)?;
+    let datum_option = to_pallas_datum(utxo)?;
+    let script_ref = to_pallas_script_ref(utxo)?;
+    let address = to_pallas_address(utxo)?;
+    Ok(ResolvedInput {
+        input: TransactionInput {
+            transaction_id: Hash::from(tx_hash),
+            index: utxo.tx_index,
+        },
+        output: TransactionOutput::PostAlonzo(PostAlonzoTransactionOutput {
+            address,
+            value,
+            datum_option,
+            script_ref,
+        }),
+    })
+}
+
+/// The bytes of a hex field, checked against `len` when given.
+fn hex_field(field: &str, value: &str, len: Option<usize>) -> Result<Vec<u8>, String> {
+    let bytes = hex::decode(value).map_err(|e| format!("`{}` is not hex: {}", field, e))?;
+    match len {
+        Some(len) if bytes.len() != len => Err(format!("`{}` is {} bytes, expected {}", field, bytes.len(), len)),
+        _ => Ok(bytes),
+    }
+}
+
+fn quantity_field(field: &str, value: &str) -> Result<u64, String> {
+    value
+        .parse::<u64>()
+        .map_err(|_| format!("`{}` {:?} is not a lovelace or token quantity", field, value))
+}
+
+fn to_pallas_script_ref(utxo: &UtxoInfoResponse) -> Result<Option<CborWrap<ScriptRef>>, String> {
     if let Some(script) = &utxo.reference_script {
-        let script_bytes = hex::decode(&script.bytes).map_err(|e| JsError::new(&e.to_string()))?;
+        let script_bytes = hex_field("reference_script.bytes", &script.bytes, None)?;
         let decoded_script = match script.script_type.as_str() {
-            "nativeScript" => Ok(PseudoScript::NativeScript(
+            "nativeScript" => PseudoScript::NativeScript(
                 NativeScript::decode_fragment(&script_bytes)
-                    .map_err(|e| JsError::new(&e.to_string()))?,
-            )),
-            "plutusV1" => Ok(PseudoScript::PlutusV1Script(PlutusV1Script(
-                script_bytes.into(),
-            ))),
-            "plutusV2" => Ok(PseudoScript::PlutusV2Script(PlutusV2Script(
-                script_bytes.into(),
-            ))),
-            "plutusV3" => Ok(PseudoScript::PlutusV3Script(PlutusV3Script(
-                script_bytes.into(),
-            ))),
-            _ => Err(JsError::new("Invalid script type")),
-        }?;
+                    .map_err(|e| format!("`reference_script.bytes` is not a native script: {}", e))?,
+            ),
+            "plutusV1" => PseudoScript::PlutusV1Script(PlutusV1Script(script_bytes.into())),
+            "plutusV2" => PseudoScript::PlutusV2Script(PlutusV2Script(script_bytes.into())),
+            "plutusV3" => PseudoScript::PlutusV3Script(PlutusV3Script(script_bytes.into())),
+            other => return Err(format!("`reference_script.type` {} is not a known script type", other)),
+        };
         Ok(Some(CborWrap(decoded_script)))
     } else {
         Ok(None)
     }
 }
 
-fn to_pallas_datum(utxo: &UtxoInfoResponse) -> Result<Option<DatumOption>, JsError> {
+fn to_pallas_datum(utxo: &UtxoInfoResponse) -> Result<Option<DatumOption>, String> {
     if let Some(datum) = &utxo.inline_datum {
-        let datum_bytes = hex::decode(&datum.bytes).map_err(|e| JsError::new(&e.to_string()))?;
+        let datum_bytes = hex_field("inline_datum.bytes", &datum.bytes, None)?;
         let datum = CborWrap(
-            PlutusData::decode_fragment(&datum_bytes).map_err(|e| JsError::new(&e.to_string()))?,
+            PlutusData::decode_fragment(&datum_bytes)
+                .map_err(|e| format!("`inline_datum.bytes` is not Plutus data: {}", e))?,
         );
         Ok(Some(DatumOption::Data(datum)))
     } else if let Some(datum_hash) = &utxo.datum_hash {
-        let datum_hash: [u8; 32] = hex::decode(datum_hash)
-            .map_err(|e| JsError::new(&e.to_string()))?
+        let datum_hash: [u8; 32] = hex_field("datum_hash", datum_hash, Some(32))?
             .try_into()
-            .map_err(|e: Vec<u8>| JsError::new("incorrect len"))?;
+            .map_err(|_| "`datum_hash` is not 32 bytes".to_string())?;
         Ok(Some(DatumOption::Hash(Hash::from(datum_hash))))
     } else {
         Ok(None)
     }
 }
 
-fn to_pallas_address(utxo: &UtxoInfoResponse) -> Result<Bytes, JsError> {
+fn to_pallas_address(utxo: &UtxoInfoResponse) -> Result<Bytes, String> {
     Address::from_bech32(&utxo.address)
-        .map_err(|_| JsError::new(&format!("Cannot convert address {}", utxo.address)))
-        .map(|a| a.to_bytes())
-        .map(|a| Bytes::from(a))
+        .map(|a| Bytes::from(a.to_bytes()))
+        .map_err(|_| format!("`address` {} is not a bech32 address", utxo.address))
 }
 
 fn to_pallas_value(
     utxo: &UtxoInfoResponse,
-) -> Result<pallas_primitives::conway::Value, JsError> {
-    let coins: u64 = utxo
-        .value
-        .parse()
-        .map_err(|e| JsError::new(&format!("{}", e)))?;
-    match to_pallas_multi_asset(utxo) {
-        Ok(Some(multi_asset)) => Ok(pallas_primitives::conway::Value::Multiasset(
-            coins,
-            multi_asset,
-        )),
-        Ok(None) => Ok(pallas_primitives::conway::Value::Coin(coins)),
-        Err(e) => Err(e),
+) -> Result<pallas_primitives::conway::Value, String> {
+    let coins = quantity_field("value", &utxo.value)?;
+    match to_pallas_multi_asset(utxo)? {
+        Some(multi_asset) => Ok(pallas_primitives::conway::Value::Multiasset(coins, multi_asset)),
+        None => Ok(pallas_primitives::conway::Value::Coin(coins)),
     }
 }
 
-fn to_pallas_multi_asset(utxo: &UtxoInfoResponse) -> Result<Option<Multiasset<PositiveCoin>>, JsError> {
+/// Policies and the asset names under each are sorted by their bytes, as in
+/// the ledger's value map, whatever order the provider listed them in.
+pub(crate) fn to_pallas_multi_asset(utxo: &UtxoInfoResponse) -> Result<Option<Multiasset<PositiveCoin>>, String> {
     if let Some(assets) = &utxo.asset_list {
-        let mut multi_asset = Vec::new();
-        for (policy, assets) in assets.iter().into_group_map_by(|a| &a.policy_id) {
-            let policy_id_bytes: [u8; 28] = hex::decode(policy)
-                .map_err(|e| JsError::new(&e.to_string()))?
+        let mut policies: BTreeMap<[u8; 28], BTreeMap<Vec<u8>, PositiveCoin>> = BTreeMap::new();
+        for (index, asset) in assets.iter().enumerate() {
+            let field = |name: &str| format!("asset_list[{}].{}", index, name);
+            let policy_id_bytes: [u8; 28] = hex_field(&field("policy_id"), &asset.policy_id, Some(28))?
                 .try_into()
-                .map_err(|e: Vec<u8>| JsError::new("incorrect len"))?;
-            let policy_id = PolicyId::from(policy_id_bytes);
-            let mut mapped_assets = Vec::new();
-            for asset in assets {
-                let asset_name = if let Some(asset_name) = &asset.asset_name {
-                    AssetName::from(
-                        hex::decode(asset_name).map_err(|e| JsError::new(&e.to_string()))?,
-                    )
-                } else {
-                    AssetName::from(Vec::new())
-                };
-                let asset_quantity: u64 = asset
-                    .quantity
-                    .parse()
-                    .map_err(|e| JsError::new(&format!("{}", e)))?;
-
-                if asset_quantity == 0 {
-                    continue
-                }
-                let coin = PositiveCoin::try_from(asset_quantity).map_err(
-                    |e| JsError::new(&format!("Cannot convert asset quantity: {}", e)),
-                )?;
-                mapped_assets.push((asset_name, coin));
+                .map_err(|_| format!("`{}` is not 28 bytes", field("policy_id")))?;
+            let policy_assets = policies.entry(policy_id_bytes).or_default();
+            let asset_name = match &asset.asset_name {
+                Some(asset_name) => hex_field(&field("asset_name"), asset_name, None)?,
+                None => Vec::new(),
+            };
+            let asset_quantity = quantity_field(&field("quantity"), &asset.quantity)?;
+
+            if asset_quantity == 0 {
+                continue
             }
-            multi_asset.push((policy_id, NonEmptyKeyValuePairs::Def(mapped_assets)));
+            let coin = PositiveCoin::try_from(asset_quantity)
+                .map_err(|e| format!("Cannot convert `{}`: {}", field("quantity"), e))?;
+            policy_assets.insert(asset_name, coin);
         }
+        let multi_asset = policies
+            .into_iter()
+            .filter(|(_, assets)| !assets.is_empty())
+            .map(|(policy_id, assets)| {
+                let assets = assets.into_iter().map(|(name, coin)| (AssetName::from(name), coin)).collect();
+                (PolicyId::from(policy_id), NonEmptyKeyValuePairs::Def(assets))
+            })
+            .collect();
         return Ok(Some(NonEmptyKeyValuePairs::Def(multi_asset)));
     }
 
     Ok(None)
 }
 
+/// Decodes a Conway transaction, the only era scripts are evaluated in.
+pub(crate) fn decode_conway_tx(tx_bytes: &[u8]) -> Result<MintedTx<'_>, JsError> {
+    let mtx = MultiEraTx::decode_for_era(Era::Conway, tx_bytes)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&e.to_string()))?;
+    match mtx {
+        MultiEraTx::Conway(tx) => Ok(tx.into_owned()),
+        _ => Err(ErrorCode::UnsupportedTransaction.error("Invalid transaction type")),
+    }
+}
+
+/// Evaluates every redeemer of `tx` within `limits`. Redeemers that would
+/// start after `limits.timeout_ms` fail with a timeout error instead; the
+/// check runs between redeemers, so one that started in time always finishes.
 fn eval_all_redeemers(
     tx: &MintedTx,
     utxos: &[ResolvedInput],
     cost_mdls: Option<&CostMdls>,
     slot_config: &SlotConfig,
     run_phase_one: bool,
-) -> Result<Vec<Result<(Redeemer, Redeemer), (Redeemer, Error)>>, JsError> {
-    let redeemers = tx.transaction_witness_set.redeemer.as_ref();
+    limits: &EvaluationLimits,
+) -> Result<Vec<Result<(Redeemer, Redeemer), (Redeemer, EvaluationError)>>, JsError> {
+    let redeemers = tx_redeemers(tx);
 
     let lookup_table = DataLookupTable::from_transaction(tx, utxos);
 
     if run_phase_one {
         // subset of phase 1 check on redeemers and scripts
-        eval_phase_one(tx, utxos, &lookup_table).map_err(|e| JsError::new(&e.to_string()))?;
+        eval_phase_one(tx, utxos, &lookup_table).map_err(|e| ErrorCode::PhaseOneFailure.error(&e.to_string()))?;
     }
 
-    match redeemers {
-        Some(rs) => {
-            let mut collected_redeemers = vec![];
-            let remaining_budget = ExBudget::default();
-            for (rKey, rValue) in rs.iter() {
-                let redeemer = Redeemer {
-                    tag: rKey.tag,
-                    index: rKey.index,
-                    data: rValue.data.clone(),
-                    ex_units: rValue.ex_units,
-                };
-                let result = eval::eval_redeemer(
-                    tx,
-                    utxos,
-                    slot_config,
-                    &redeemer,
-                    &lookup_table,
-                    cost_mdls,
-                    &remaining_budget,
-                );
-
-                match result {
-                    Ok(new_redeemer) => {
-                        collected_redeemers.push(Ok((redeemer.clone(), new_redeemer)))
-                    }
-                    Err(err) => collected_redeemers.push(Err((redeemer.clone(), err))),
-                }
+    let mut collected_redeemers = vec![];
+    let remaining_budget = limits.initial_budget();
+    let started_at = now_ms();
+    for redeemer in redeemers {
+        if let Some(timeout_ms) = limits.timeout_ms {
+            if now_ms() - started_at > timeout_ms {
+                log_warn!("evaluation timeout of {} ms exceeded, skipping {:?}:{}", timeout_ms, redeemer.tag, redeemer.index);
+                collected_redeemers.push(Err((redeemer, EvaluationError::timeout(timeout_ms))));
+                continue;
             }
+        }
+        collected_redeemers.push(eval_one_redeemer(tx, utxos, slot_config, &lookup_table, cost_mdls, &remaining_budget, redeemer));
+    }
+
+    Ok(collected_redeemers)
+}
 
-            Ok(collected_redeemers)
+/// Redeemers of the transaction in witness set order.
+pub(crate) fn tx_redeemers(tx: &MintedTx) -> Vec<Redeemer> {
+    match tx.transaction_witness_set.redeemer.as_ref() {
+        Some(rs) => rs
+            .iter()
+            .map(|(key, value)| Redeemer {
+                tag: key.tag,
+                index: key.index,
+                data: value.data.clone(),
+                ex_units: value.ex_units,
+            })
+            .collect(),
+        None => vec![],
+    }
+}
+
+pub(crate) fn eval_one_redeemer(
+    tx: &MintedTx,
+    utxos: &[ResolvedInput],
+    slot_config: &SlotConfig,
+    lookup_table: &DataLookupTable,
+    cost_mdls: Option<&CostMdls>,
+    budget: &ExBudget,
+    redeemer: Redeemer,
+) -> Result<(Redeemer, Redeemer), (Redeemer, EvaluationError)> {
+    match eval::eval_redeemer(tx, utxos, slot_config, &redeemer, lookup_table, cost_mdls, budget) {
+        Ok(new_redeemer) => {
+            log_debug!(
+                "{:?}:{} succeeded with {} mem, {} steps",
+                redeemer.tag, redeemer.index, new_redeemer.ex_units.mem, new_redeemer.ex_units.steps
+            );
+            Ok((redeemer, new_redeemer))
+        }
+        Err(err) => {
+            log_debug!("{:?}:{} failed: {}", redeemer.tag, redeemer.index, err);
+            let error = EvaluationError::from_uplc(&err, budget);
+            Err((redeemer, error))
         }
-        None => Ok(vec![]),
     }
 }
@@ -8,6 +8,7 @@ use uplc::{
     ast::{Constant, Program, Term, Type},
 };
 use blst::*;
+use crate::schemas::SCHEMA_VERSION;
 
 pub fn to_json_program(program: &Program<NamedDeBruijn>) -> String {
     let version = format!(
@@ -18,6 +19,7 @@ pub fn to_json_program(program: &Program<NamedDeBruijn>) -> String {
     let mut json = serde_json::Map::new();
     json.insert("version".to_string(), Value::String(version));
     json.insert("term".to_string(), to_json_term(&program.term));
+    program_json.insert("schema_version".to_string(), Value::from(SCHEMA_VERSION));
     program_json.insert("program".to_string(), Value::Object(json));
     json!(&program_json).to_string()
 }
@@ -97,33 +99,39 @@ fn to_json_constant(constant: &Constant) -> Value {
         }),
         Constant::Data(d) => json!({ "data": to_json_plutus_data(d) }),
         Constant::Bls12_381G1Element(p1) => {
-            json!({ "bls12_381_G1_element": json!({ "x": p1.x.l, "y": p1.y.l, "z": p1.z.l }) })
+            json!({ "bls12_381_G1_element": hex::encode(compress_g1(p1)) })
         }
         Constant::Bls12_381G2Element(p2) => {
-
-            json!({ "bls12_381_G2_element": json_blst_p2(p2) })
+            json!({ "bls12_381_G2_element": hex::encode(compress_g2(p2)) })
         }
-        Constant::Bls12_381MlResult(_) => panic!("cannot represent Bls12_381MlResult as json"),
+        // Miller loop results only exist during evaluation and have no serialized form
+        Constant::Bls12_381MlResult(_) => json!({ "bls12_381_mlresult": "<opaque>" }),
     }
 }
 
-fn json_blst_p2(p1: &blst_p2) -> Value {
-    json!({
-        "x": to_json_blst_fp2(&p1.x),
-        "y": to_json_blst_fp2(&p1.y),
-        "z": to_json_blst_fp2(&p1.z),
-    })
+/// Standard 48-byte compressed encoding of a G1 point, as used by Plutus.
+pub(crate) fn compress_g1(point: &blst_p1) -> [u8; 48] {
+    let mut out = [0u8; 48];
+    unsafe { blst_p1_compress(out.as_mut_ptr(), point) };
+    out
 }
 
-fn to_json_blst_fp2(fp2: &blst_fp2) -> Value {
-    let mut first_level = Vec::new();
-    for fp in &fp2.fp {
-        first_level.push(json!(fp.l));
-    }
-    Value::Array(first_level)
+/// Standard 96-byte compressed encoding of a G2 point, as used by Plutus.
+pub(crate) fn compress_g2(point: &blst_p2) -> [u8; 96] {
+    let mut out = [0u8; 96];
+    unsafe { blst_p2_compress(out.as_mut_ptr(), point) };
+    out
 }
 
-
+/// Constructor index encoded by a `Constr`: tags 121-127 stand for 0-6,
+/// 1280-1400 for 7-127 and tag 102 carries any other index explicitly.
+pub(crate) fn constructor_index(tag: u64, any_constructor: Option<u64>) -> u64 {
+    match tag {
+        121..=127 => tag - 121,
+        1280..=1400 => tag - 1280 + 7,
+        _ => any_constructor.unwrap_or_default(),
+    }
+}
 
 // This feels a little awkward here; not sure if it should be upstreamed to pallas
 fn to_json_plutus_data(data: &PlutusData) -> Value {
@@ -134,6 +142,7 @@ fn to_json_plutus_data(data: &PlutusData) -> Value {
             fields,
         }) => json!({
             "constr": {
+                "constructor_index": constructor_index(*tag, *any_constructor),
                 "tag": tag,
                 "any_constructor": any_constructor,
                 "fields": fields.iter().map(to_json_plutus_data).collect::<Vec<Value>>()
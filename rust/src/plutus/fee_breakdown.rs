@@ -0,0 +1,83 @@
+use cardano_serialization_lib::Transaction;
+use serde::Serialize;
+use crate::bingen::wasm_bindgen;
+use crate::error::ErrorCode;
+use crate::input::decode_hex;
+use crate::js_error::JsError;
+use crate::koios_client::models::{EpochParamResponse, UtxoInfoResponse};
+use crate::plutus::evaluation_result::EvaluationExUnits;
+use crate::plutus::script_inventory::{ex_unit_fee, min_fee, reference_script_fee_report};
+use crate::protocol_params::adapters::parse_protocol_params;
+use crate::schemas::to_versioned_json;
+
+/// The declared fee split into the terms of the Conway min fee:
+/// `min_fee_a * size + min_fee_b`, the redeemer ex units at the step and
+/// memory prices, and the tiered reference script fee. `remainder` is what the
+/// fee pays above the minimum, negative when it's too low. The size is of
+/// the tx as given, an unsigned tx grows by its vkey witnesses before submit.
+#[derive(Serialize, Debug, Clone)]
+pub struct FeeBreakdown {
+    pub declared_fee: u64,
+    pub tx_size: u64,
+    pub size_fee: u64,
+    pub constant_fee: u64,
+    pub ex_units: EvaluationExUnits,
+    pub ex_unit_fee: u64,
+    pub reference_script_size: u64,
+    pub reference_script_fee: u64,
+    pub min_fee: u64,
+    pub remainder: i64,
+    pub vkey_witnesses: usize,
+}
+
+/// Breaks down the fee of a tx with the protocol params, the spent and
+/// referenced UTxOs (Koios `utxo_info` JSON, may be empty) give the
+/// reference scripts.
+#[wasm_bindgen]
+pub fn get_fee_breakdown(tx_hex: &str, utxo_json: &str, protocol_params_json: &str) -> Result<String, JsError> {
+    let tx_bytes = decode_hex(tx_hex)?;
+    let utxos: Vec<UtxoInfoResponse> = if utxo_json.trim().is_empty() {
+        Vec::new()
+    } else {
+        serde_json::from_str(utxo_json).map_err(|e| ErrorCode::InvalidInput.error(&e.to_string()))?
+    };
+    let protocol_params = parse_protocol_params(protocol_params_json)?;
+    to_versioned_json(&fee_breakdown(&tx_bytes, utxos, &protocol_params)?)
+}
+
+pub(crate) fn fee_breakdown(tx_bytes: &[u8], utxos: Vec<UtxoInfoResponse>, pp: &EpochParamResponse) -> Result<FeeBreakdown, JsError> {
+    let tx = Transaction::from_bytes(tx_bytes.to_vec())
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding transaction: {:?}", e)))?;
+
+    let mut ex_units = EvaluationExUnits { mem: 0, steps: 0 };
+    if let Some(redeemers) = tx.witness_set().redeemers() {
+        for i in 0..redeemers.len() {
+            let units = redeemers.get(i).ex_units();
+            ex_units.mem += units.mem().to_str().parse::<u64>().unwrap_or_default();
+            ex_units.steps += units.steps().to_str().parse::<u64>().unwrap_or_default();
+        }
+    }
+    let ex_unit_fee = ex_unit_fee(pp, &ex_units);
+
+    let reference_scripts = reference_script_fee_report(&tx, utxos, pp.min_fee_ref_script_cost_per_byte.unwrap_or_default());
+
+    let tx_size = tx_bytes.len() as u64;
+    let size_fee = pp.min_fee_a.unwrap_or_default() * tx_size;
+    let constant_fee = pp.min_fee_b.unwrap_or_default();
+    let min_fee = min_fee(pp, tx_size, &ex_units, reference_scripts.total_size);
+    let declared_fee: u64 = tx.body().fee().to_str().parse().unwrap_or_default();
+
+    Ok(FeeBreakdown {
+        declared_fee,
+        tx_size,
+        size_fee,
+        constant_fee,
+        ex_units,
+        ex_unit_fee,
+        reference_script_size: reference_scripts.total_size,
+        reference_script_fee: reference_scripts.fee,
+        min_fee,
+        remainder: declared_fee as i64 - min_fee as i64,
+        vkey_witnesses: tx.witness_set().vkeys().map_or(0, |vkeys| vkeys.len()),
+    })
+}
@@ -1,3 +1,28 @@
 pub mod plutus_decoder;
 pub mod execute_tx_scripts;
+pub mod evaluation_result;
+pub mod evaluation_limits;
+pub mod ex_unit_margins;
+pub mod tx_rebalance;
+pub mod evaluation_job;
 mod explain;
+pub mod pretty_printer;
+pub mod optimizer;
+pub mod blueprint;
+pub(crate) mod script_purpose;
+pub mod script_compare;
+pub mod script_benchmark;
+pub mod tx_scripts;
+pub mod tx_decoder;
+pub mod script_inventory;
+pub mod fee_breakdown;
+pub mod deposits;
+pub mod value_flow;
+pub mod tx_summary;
+pub mod tx_intent;
+pub mod tx_witnesses;
+pub mod audit_report;
+pub mod script_integrity;
+pub mod datum_checks;
+#[cfg(feature = "providers")]
+pub(crate) mod script_resolution;
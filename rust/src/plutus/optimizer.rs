@@ -0,0 +1,123 @@
+use pallas_crypto::hash::Hasher;
+use pallas_primitives::conway::PlutusData;
+use pallas_primitives::Fragment;
+use serde::Serialize;
+use uplc::ast::{DeBruijn, Name, NamedDeBruijn, Program};
+use uplc::machine::cost_model::ExBudget;
+use uplc::optimize::aiken_optimize_and_intern;
+use crate::bingen::wasm_bindgen;
+use crate::error::ErrorCode;
+use crate::input::{decode_hex, sanitize_hex};
+use crate::js_error::JsError;
+use crate::schemas::to_versioned_json;
+use crate::plutus::evaluation_result::EvaluationExUnits;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct OptimizationReport {
+    pub optimized_program_hex: String,
+    pub original_size: usize,
+    pub optimized_size: usize,
+    pub size_delta: i64,
+    pub guarantee: OptimizationGuarantee,
+}
+
+/// Each test case applies the same arguments to the original and the
+/// optimized program and compares the blake2b-256 hash of the results.
+#[derive(Serialize, Debug, Clone)]
+pub struct OptimizationGuarantee {
+    pub all_equal: bool,
+    pub tests: Vec<OptimizationTest>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct OptimizationTest {
+    pub arguments: Vec<String>,
+    pub original_result_hash: String,
+    pub optimized_result_hash: String,
+    pub original_budget: EvaluationExUnits,
+    pub optimized_budget: EvaluationExUnits,
+    pub equal: bool,
+}
+
+/// Runs the aiken optimization passes over a script and reports how much
+/// smaller it gets. `test_arguments_json` is a list of argument lists, each
+/// argument a Plutus data CBOR hex, used to check both programs agree.
+#[wasm_bindgen]
+pub fn optimize_script(script_hex: &str, test_arguments_json: &str) -> Result<String, JsError> {
+    let test_arguments: Vec<Vec<String>> = serde_json::from_str(test_arguments_json)
+        .map_err(|e| ErrorCode::InvalidInput.error(&format!("Invalid test arguments: {}", e)))?;
+
+    let mut cbor_buffer = Vec::new();
+    let mut flat_buffer = Vec::new();
+    let program = Program::<DeBruijn>::from_hex(sanitize_hex(script_hex)?, &mut cbor_buffer, &mut flat_buffer)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&e.to_string()))?;
+    let original_size = program.to_flat()
+        .map_err(|e| ErrorCode::Internal.error(&e.to_string()))?
+        .len();
+
+    let original: Program<NamedDeBruijn> = program.into();
+    let named = Program::<Name>::try_from(original.clone())
+        .map_err(|e| ErrorCode::Internal.error(&e.to_string()))?;
+    let optimized = Program::<NamedDeBruijn>::try_from(aiken_optimize_and_intern(named))
+        .map_err(|e| ErrorCode::Internal.error(&e.to_string()))?;
+    let optimized_debruijn: Program<DeBruijn> = optimized.clone().into();
+    let optimized_size = optimized_debruijn.to_flat()
+        .map_err(|e| ErrorCode::Internal.error(&e.to_string()))?
+        .len();
+    let optimized_program_hex = optimized_debruijn.to_hex()
+        .map_err(|e| ErrorCode::Internal.error(&e.to_string()))?;
+
+    let mut tests = Vec::new();
+    for arguments in test_arguments {
+        let (original_result_hash, original_budget) = eval_with_arguments(&original, &arguments)?;
+        let (optimized_result_hash, optimized_budget) = eval_with_arguments(&optimized, &arguments)?;
+        tests.push(OptimizationTest {
+            equal: original_result_hash == optimized_result_hash,
+            arguments,
+            original_result_hash,
+            optimized_result_hash,
+            original_budget,
+            optimized_budget,
+        });
+    }
+
+    let report = OptimizationReport {
+        optimized_program_hex,
+        original_size,
+        optimized_size,
+        size_delta: optimized_size as i64 - original_size as i64,
+        guarantee: OptimizationGuarantee {
+            all_equal: tests.iter().all(|t| t.equal),
+            tests,
+        },
+    };
+    to_versioned_json(&report)
+}
+
+fn eval_with_arguments(
+    program: &Program<NamedDeBruijn>,
+    arguments: &[String],
+) -> Result<(String, EvaluationExUnits), JsError> {
+    let mut applied = program.clone();
+    for argument in arguments {
+        let bytes = decode_hex(argument)?;
+        let data = PlutusData::decode_fragment(&bytes)
+            .map_err(|e| ErrorCode::InvalidInput.error(&format!("Invalid test argument {}: {}", argument, e)))?;
+        applied = applied.apply_data(data);
+    }
+
+    let eval_result = applied.eval(ExBudget::default());
+    let cost = eval_result.cost();
+    let result = match eval_result.result() {
+        Ok(term) => term.to_pretty(),
+        Err(err) => format!("error: {}", err),
+    };
+    let hash = Hasher::<256>::hash(result.as_bytes());
+    Ok((
+        hex::encode(hash),
+        EvaluationExUnits {
+            mem: cost.mem.max(0) as u64,
+            steps: cost.cpu.max(0) as u64,
+        },
+    ))
+}
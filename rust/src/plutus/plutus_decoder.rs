@@ -1,13 +1,16 @@
 use uplc::ast::{DeBruijn, NamedDeBruijn, Program};
 use crate::bingen::wasm_bindgen;
+use crate::error::ErrorCode;
+use crate::input::{decode_hex, sanitize_hex};
 use crate::js_error::JsError;
+use crate::plutus::pretty_printer::{pretty_print_program, PrettyPrintOptions};
 
 #[wasm_bindgen]
 pub fn decode_plutus_program_uplc_json(hex: &str) -> Result<String, JsError> {
     let mut cbor_buffer = Vec::new();
     let mut flat_buffer = Vec::new();
-    let program = Program::<DeBruijn>::from_hex(hex, &mut cbor_buffer, &mut flat_buffer)
-        .map_err(|e| JsError::new(&e.to_string()))?;
+    let program = Program::<DeBruijn>::from_hex(sanitize_hex(hex)?, &mut cbor_buffer, &mut flat_buffer)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&e.to_string()))?;
 
     Ok(super::explain::to_json_program(&program.into()))
 }
@@ -16,7 +19,41 @@ pub fn decode_plutus_program_uplc_json(hex: &str) -> Result<String, JsError> {
 pub fn decode_plutus_program_pretty_uplc(hex: &str) -> Result<String, JsError> {
     let mut cbor_buffer = Vec::new();
     let mut flat_buffer = Vec::new();
-    let program = Program::<DeBruijn>::from_hex(hex, &mut cbor_buffer, &mut flat_buffer)
-        .map_err(|e| JsError::new(&e.to_string()))?;
+    let program = Program::<DeBruijn>::from_hex(sanitize_hex(hex)?, &mut cbor_buffer, &mut flat_buffer)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&e.to_string()))?;
     Ok(Program::<NamedDeBruijn>::from(program).to_pretty())
-}
\ No newline at end of file
+}
+#[wasm_bindgen]
+pub fn decode_plutus_program_pretty_uplc_with_options(hex: &str, options_json: &str) -> Result<String, JsError> {
+    let options: PrettyPrintOptions = serde_json::from_str(options_json)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Invalid pretty print options: {}", e)))?;
+    let mut cbor_buffer = Vec::new();
+    let mut flat_buffer = Vec::new();
+    let program = Program::<DeBruijn>::from_hex(sanitize_hex(hex)?, &mut cbor_buffer, &mut flat_buffer)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&e.to_string()))?;
+    Ok(pretty_print_program(&program.into(), &options))
+}
+
+/// Decodes a script that may be wrapped in any number of CBOR bytestring
+/// layers (on-chain scripts are wrapped once, cardano-cli envelopes twice).
+/// Returns the program together with the number of layers removed.
+pub(crate) fn decode_program_unwrapped(hex: &str) -> Result<(Program<DeBruijn>, usize), JsError> {
+    let bytes = decode_hex(hex)?;
+    decode_program_bytes_unwrapped(bytes).map_err(|e| ErrorCode::DecodeFailure.error(&e))
+}
+
+pub(crate) fn decode_program_bytes_unwrapped(mut bytes: Vec<u8>) -> Result<(Program<DeBruijn>, usize), String> {
+    let mut layers = 0;
+    loop {
+        let mut decoder = minicbor::Decoder::new(&bytes);
+        let inner = match decoder.bytes() {
+            Ok(inner) if decoder.position() == bytes.len() => inner.to_vec(),
+            _ => break,
+        };
+        bytes = inner;
+        layers += 1;
+    }
+    let program = Program::<DeBruijn>::from_flat(&bytes)
+        .map_err(|e| format!("Error decoding flat program: {}", e))?;
+    Ok((program, layers))
+}
@@ -0,0 +1,299 @@
+use pallas_primitives::conway::{self, Constr, PlutusData};
+use serde::{Deserialize, Serialize};
+use uplc::ast::{Constant, NamedDeBruijn, Program, Term, Type};
+use crate::plutus::explain::{compress_g1, compress_g2, constructor_index};
+
+const SMALL_CONSTANT_WIDTH: usize = 24;
+
+/// Layout options for [`pretty_print_program`].
+///
+/// `max_line_length` of 0 disables flattening so every term goes on its own
+/// line, which is what line-based diff tools work best with.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct PrettyPrintOptions {
+    pub indent: usize,
+    pub max_line_length: usize,
+    pub show_debruijn_indices: bool,
+    pub inline_small_constants: bool,
+}
+
+impl Default for PrettyPrintOptions {
+    fn default() -> Self {
+        PrettyPrintOptions {
+            indent: 2,
+            max_line_length: 80,
+            show_debruijn_indices: false,
+            inline_small_constants: true,
+        }
+    }
+}
+
+enum Doc {
+    Text(String),
+    Constant(String),
+    Group(String, Vec<Doc>, String),
+}
+
+pub fn pretty_print_program(program: &Program<NamedDeBruijn>, options: &PrettyPrintOptions) -> String {
+    let mut scope = Vec::new();
+    let mut fresh = 0;
+    let term = term_to_doc(&program.term, options, &mut scope, &mut fresh);
+    let version = format!(
+        "{}.{}.{}",
+        program.version.0, program.version.1, program.version.2
+    );
+    let doc = Doc::Group(format!("(program {}", version), vec![term], ")".to_string());
+    let mut out = String::new();
+    render(&doc, 0, options, &mut out);
+    out
+}
+
+/// Single-line rendering of a term with DeBruijn indices, used for snippets.
+pub(crate) fn term_to_flat_string(term: &Term<NamedDeBruijn>) -> String {
+    let options = PrettyPrintOptions {
+        max_line_length: usize::MAX,
+        show_debruijn_indices: true,
+        ..PrettyPrintOptions::default()
+    };
+    flat(&term_to_doc(term, &options, &mut Vec::new(), &mut 0))
+}
+
+fn term_to_doc(
+    term: &Term<NamedDeBruijn>,
+    options: &PrettyPrintOptions,
+    scope: &mut Vec<String>,
+    fresh: &mut usize,
+) -> Doc {
+    match term {
+        Term::Var(name) => Doc::Text(var_name(name, options, scope)),
+        Term::Delay(term) => Doc::Group(
+            "(delay".to_string(),
+            vec![term_to_doc(term, options, scope, fresh)],
+            ")".to_string(),
+        ),
+        Term::Lambda {
+            parameter_name,
+            body,
+        } => {
+            let param = if options.show_debruijn_indices {
+                format!("{}_0", parameter_name.text)
+            } else {
+                *fresh += 1;
+                format!("{}_{}", parameter_name.text, fresh)
+            };
+            scope.push(param.clone());
+            let body = term_to_doc(body, options, scope, fresh);
+            scope.pop();
+            Doc::Group(format!("(lam {}", param), vec![body], ")".to_string())
+        }
+        Term::Apply { function, argument } => {
+            // flatten nested applications into a single [f a b c]
+            let mut args = vec![argument.as_ref()];
+            let mut head = function.as_ref();
+            while let Term::Apply { function, argument } = head {
+                args.push(argument.as_ref());
+                head = function.as_ref();
+            }
+            let mut children = vec![term_to_doc(head, options, scope, fresh)];
+            for arg in args.into_iter().rev() {
+                children.push(term_to_doc(arg, options, scope, fresh));
+            }
+            Doc::Group("[".to_string(), children, "]".to_string())
+        }
+        Term::Constant(constant) => Doc::Constant(format!(
+            "(con {} {})",
+            type_to_string(&constant_type(constant)),
+            constant_to_string(constant)
+        )),
+        Term::Force(term) => Doc::Group(
+            "(force".to_string(),
+            vec![term_to_doc(term, options, scope, fresh)],
+            ")".to_string(),
+        ),
+        Term::Error => Doc::Text("(error)".to_string()),
+        Term::Builtin(builtin) => Doc::Text(format!("(builtin {})", builtin)),
+        Term::Constr { tag, fields } => Doc::Group(
+            format!("(constr {}", tag),
+            fields
+                .iter()
+                .map(|field| term_to_doc(field, options, scope, fresh))
+                .collect(),
+            ")".to_string(),
+        ),
+        Term::Case { constr, branches } => {
+            let mut children = vec![term_to_doc(constr, options, scope, fresh)];
+            for branch in branches {
+                children.push(term_to_doc(branch, options, scope, fresh));
+            }
+            Doc::Group("(case".to_string(), children, ")".to_string())
+        }
+    }
+}
+
+fn var_name(name: &NamedDeBruijn, options: &PrettyPrintOptions, scope: &[String]) -> String {
+    let index = name.index.inner();
+    if options.show_debruijn_indices {
+        return format!("{}_{}", name.text, index);
+    }
+    // DeBruijn indices are 1-based, 1 refers to the innermost binder
+    if index >= 1 && index <= scope.len() {
+        scope[scope.len() - index].clone()
+    } else {
+        format!("{}_free{}", name.text, index)
+    }
+}
+
+fn flat(doc: &Doc) -> String {
+    match doc {
+        Doc::Text(text) | Doc::Constant(text) => text.clone(),
+        Doc::Group(open, children, close) => {
+            let mut out = open.clone();
+            for (i, child) in children.iter().enumerate() {
+                if i > 0 || !open.ends_with('[') {
+                    out.push(' ');
+                }
+                out.push_str(&flat(child));
+            }
+            out.push_str(close);
+            out
+        }
+    }
+}
+
+fn is_small_constant(doc: &Doc) -> bool {
+    match doc {
+        Doc::Constant(text) => text.len() <= SMALL_CONSTANT_WIDTH && !text.contains('\n'),
+        _ => false,
+    }
+}
+
+/// Width of the flat rendering, or None once it exceeds `limit`, so deep
+/// programs aren't flattened over and over just to find out they don't fit.
+fn flat_width(doc: &Doc, limit: usize) -> Option<usize> {
+    match doc {
+        Doc::Text(text) | Doc::Constant(text) => Some(text.len()).filter(|len| *len <= limit),
+        Doc::Group(open, children, close) => {
+            let mut width = open.len() + close.len() + children.len();
+            for child in children {
+                if width > limit {
+                    return None;
+                }
+                width += flat_width(child, limit - width)?;
+            }
+            Some(width).filter(|width| *width <= limit)
+        }
+    }
+}
+
+fn render(doc: &Doc, level: usize, options: &PrettyPrintOptions, out: &mut String) {
+    let indent = level * options.indent;
+    let fits = options.max_line_length > indent
+        && flat_width(doc, options.max_line_length - indent).is_some();
+    match doc {
+        Doc::Group(open, children, close) if !fits => {
+            out.push_str(open);
+            let child_indent = " ".repeat((level + 1) * options.indent);
+            for child in children {
+                if options.inline_small_constants && is_small_constant(child) {
+                    out.push(' ');
+                    out.push_str(&flat(child));
+                    continue;
+                }
+                out.push('\n');
+                out.push_str(&child_indent);
+                render(child, level + 1, options, out);
+            }
+            out.push('\n');
+            out.push_str(&" ".repeat(indent));
+            out.push_str(close);
+        }
+        _ => out.push_str(&flat(doc)),
+    }
+}
+
+fn constant_type(constant: &Constant) -> Type {
+    match constant {
+        Constant::Integer(_) => Type::Integer,
+        Constant::ByteString(_) => Type::ByteString,
+        Constant::String(_) => Type::String,
+        Constant::Unit => Type::Unit,
+        Constant::Bool(_) => Type::Bool,
+        Constant::ProtoList(r#type, _) => Type::List(r#type.clone().into()),
+        Constant::ProtoPair(left, right, _, _) => Type::Pair(left.clone().into(), right.clone().into()),
+        Constant::Data(_) => Type::Data,
+        Constant::Bls12_381G1Element(_) => Type::Bls12_381G1Element,
+        Constant::Bls12_381G2Element(_) => Type::Bls12_381G2Element,
+        Constant::Bls12_381MlResult(_) => Type::Bls12_381MlResult,
+    }
+}
+
+fn type_to_string(term_type: &Type) -> String {
+    match term_type {
+        Type::Bool => "bool".to_string(),
+        Type::Integer => "integer".to_string(),
+        Type::String => "string".to_string(),
+        Type::ByteString => "bytestring".to_string(),
+        Type::Unit => "unit".to_string(),
+        Type::List(r#type) => format!("(list {})", type_to_string(r#type)),
+        Type::Pair(l, r) => format!("(pair {} {})", type_to_string(l), type_to_string(r)),
+        Type::Data => "data".to_string(),
+        Type::Bls12_381G1Element => "bls12_381_G1_element".to_string(),
+        Type::Bls12_381G2Element => "bls12_381_G2_element".to_string(),
+        Type::Bls12_381MlResult => "bls12_381_mlresult".to_string(),
+    }
+}
+
+pub(crate) fn constant_to_string(constant: &Constant) -> String {
+    match constant {
+        Constant::Integer(i) => i.to_string(),
+        Constant::ByteString(bs) => format!("#{}", hex::encode(bs)),
+        Constant::String(s) => format!("{:?}", s),
+        Constant::Unit => "()".to_string(),
+        Constant::Bool(b) => if *b { "True".to_string() } else { "False".to_string() },
+        Constant::ProtoList(_, items) => format!(
+            "[{}]",
+            items.iter().map(constant_to_string).collect::<Vec<_>>().join(", ")
+        ),
+        Constant::ProtoPair(_, _, left, right) => {
+            format!("({}, {})", constant_to_string(left), constant_to_string(right))
+        }
+        Constant::Data(d) => format!("({})", plutus_data_to_string(d)),
+        Constant::Bls12_381G1Element(p1) => format!("0x{}", hex::encode(compress_g1(p1))),
+        Constant::Bls12_381G2Element(p2) => format!("0x{}", hex::encode(compress_g2(p2))),
+        Constant::Bls12_381MlResult(_) => "<bls12_381_mlresult>".to_string(),
+    }
+}
+
+fn plutus_data_to_string(data: &PlutusData) -> String {
+    match data {
+        PlutusData::Constr(Constr {
+            tag,
+            any_constructor,
+            fields,
+        }) => {
+            format!(
+                "Constr {} [{}]",
+                constructor_index(*tag, *any_constructor),
+                fields.iter().map(plutus_data_to_string).collect::<Vec<_>>().join(", ")
+            )
+        }
+        PlutusData::Map(kvp) => format!(
+            "Map [{}]",
+            kvp.iter()
+                .map(|(k, v)| format!("({}, {})", plutus_data_to_string(k), plutus_data_to_string(v)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        PlutusData::BigInt(conway::BigInt::Int(i)) => {
+            format!("I {}", serde_json::to_string(i).unwrap_or_default())
+        }
+        PlutusData::BigInt(conway::BigInt::BigUInt(bs)) => format!("I 0x{}", bs),
+        PlutusData::BigInt(conway::BigInt::BigNInt(bs)) => format!("I -(1 + 0x{})", bs),
+        PlutusData::BoundedBytes(bs) => format!("B #{}", hex::encode(bs.to_vec())),
+        PlutusData::Array(a) => format!(
+            "List [{}]",
+            a.iter().map(plutus_data_to_string).collect::<Vec<_>>().join(", ")
+        ),
+    }
+}
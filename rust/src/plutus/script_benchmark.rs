@@ -0,0 +1,140 @@
+use pallas_primitives::conway::PlutusData;
+use pallas_primitives::Fragment;
+use serde::{Deserialize, Serialize};
+use uplc::ast::{NamedDeBruijn, Program};
+use uplc::machine::cost_model::ExBudget;
+use crate::bingen::wasm_bindgen;
+use crate::error::ErrorCode;
+use crate::input::decode_hex;
+use crate::js_error::JsError;
+use crate::schemas::to_versioned_json;
+use crate::plutus::plutus_decoder::decode_program_unwrapped;
+use crate::time_utils::now_ms;
+
+/// A set of arguments the script is applied to, typically datum, redeemer and
+/// script context for a spending validator. `runs` repeats the evaluation to
+/// get stable timings; the budget itself is deterministic.
+#[derive(Deserialize, Debug, Clone)]
+pub struct BenchmarkFixture {
+    pub name: String,
+    pub arguments: Vec<String>,
+    #[serde(default = "default_runs")]
+    pub runs: u32,
+}
+
+fn default_runs() -> u32 {
+    1
+}
+
+#[derive(Serialize, Debug, Clone, Copy, Default)]
+pub struct BudgetStats {
+    pub min: i64,
+    pub max: i64,
+    pub mean: f64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct FixtureBenchmark {
+    pub name: String,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub mem: BudgetStats,
+    pub steps: BudgetStats,
+    pub time_ms: TimeStats,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, Default)]
+pub struct TimeStats {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ScriptBenchmark {
+    pub fixtures: Vec<FixtureBenchmark>,
+    pub mem: BudgetStats,
+    pub steps: BudgetStats,
+}
+
+/// Evaluates a script against every fixture and reports the spent budget per
+/// fixture and across all of them, for tracking execution cost regressions
+/// between validator versions.
+#[wasm_bindgen]
+pub fn benchmark_script(script_hex: &str, fixtures_json: &str) -> Result<String, JsError> {
+    let fixtures: Vec<BenchmarkFixture> = serde_json::from_str(fixtures_json)
+        .map_err(|e| ErrorCode::InvalidInput.error(&format!("Invalid benchmark fixtures: {}", e)))?;
+    let (program, _) = decode_program_unwrapped(script_hex)?;
+    let program: Program<NamedDeBruijn> = program.into();
+
+    let mut results = Vec::new();
+    for fixture in fixtures {
+        results.push(run_fixture(&program, &fixture)?);
+    }
+
+    let benchmark = ScriptBenchmark {
+        mem: budget_stats(&results.iter().map(|r| r.mem.mean as i64).collect::<Vec<_>>()),
+        steps: budget_stats(&results.iter().map(|r| r.steps.mean as i64).collect::<Vec<_>>()),
+        fixtures: results,
+    };
+    to_versioned_json(&benchmark)
+}
+
+fn run_fixture(program: &Program<NamedDeBruijn>, fixture: &BenchmarkFixture) -> Result<FixtureBenchmark, JsError> {
+    let mut applied = program.clone();
+    for argument in &fixture.arguments {
+        let bytes = decode_hex(argument)?;
+        let data = PlutusData::decode_fragment(&bytes).map_err(|e| {
+            ErrorCode::InvalidInput.error(&format!("Invalid argument in fixture {}: {}", fixture.name, e))
+        })?;
+        applied = applied.apply_data(data);
+    }
+
+    let mut mem = Vec::new();
+    let mut steps = Vec::new();
+    let mut times = Vec::new();
+    let mut error = None;
+    for _ in 0..fixture.runs.max(1) {
+        let started_at = now_ms();
+        let eval_result = applied.clone().eval(ExBudget::default());
+        times.push(now_ms() - started_at);
+        let cost = eval_result.cost();
+        mem.push(cost.mem);
+        steps.push(cost.cpu);
+        if let Err(err) = eval_result.result() {
+            error = Some(err.to_string());
+        }
+    }
+
+    Ok(FixtureBenchmark {
+        name: fixture.name.clone(),
+        success: error.is_none(),
+        error,
+        mem: budget_stats(&mem),
+        steps: budget_stats(&steps),
+        time_ms: time_stats(&times),
+    })
+}
+
+fn budget_stats(values: &[i64]) -> BudgetStats {
+    if values.is_empty() {
+        return BudgetStats::default();
+    }
+    BudgetStats {
+        min: *values.iter().min().unwrap_or(&0),
+        max: *values.iter().max().unwrap_or(&0),
+        mean: values.iter().sum::<i64>() as f64 / values.len() as f64,
+    }
+}
+
+fn time_stats(values: &[f64]) -> TimeStats {
+    if values.is_empty() {
+        return TimeStats::default();
+    }
+    TimeStats {
+        min: values.iter().cloned().fold(f64::INFINITY, f64::min),
+        max: values.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+        mean: values.iter().sum::<f64>() / values.len() as f64,
+    }
+}
@@ -0,0 +1,145 @@
+use serde::Serialize;
+use uplc::ast::{NamedDeBruijn, Program, Term};
+use crate::bingen::wasm_bindgen;
+use crate::js_error::JsError;
+use crate::schemas::to_versioned_json;
+use crate::plutus::plutus_decoder::decode_program_unwrapped;
+use crate::plutus::pretty_printer::{constant_to_string, term_to_flat_string};
+
+const MAX_DIFFERENCES: usize = 50;
+const SNIPPET_LENGTH: usize = 120;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ScriptDifference {
+    pub path: String,
+    pub left: String,
+    pub right: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ScriptComparison {
+    pub alpha_equivalent: bool,
+    pub same_version: bool,
+    pub version_a: String,
+    pub version_b: String,
+    pub cbor_layers_a: usize,
+    pub cbor_layers_b: usize,
+    pub differences: Vec<ScriptDifference>,
+    pub differences_truncated: bool,
+}
+
+/// Compares two scripts structurally. Both are unwrapped from any CBOR layers
+/// and compared on their DeBruijn form, so variable names don't matter and
+/// equal trees are alpha-equivalent. Each difference carries the path from
+/// the root and a one-line snippet of both subterms.
+#[wasm_bindgen]
+pub fn compare_scripts(hex_a: &str, hex_b: &str) -> Result<String, JsError> {
+    let (program_a, layers_a) = decode_program_unwrapped(hex_a)?;
+    let (program_b, layers_b) = decode_program_unwrapped(hex_b)?;
+    let program_a: Program<NamedDeBruijn> = program_a.into();
+    let program_b: Program<NamedDeBruijn> = program_b.into();
+
+    let mut differences = Vec::new();
+    let mut truncated = false;
+    diff_terms(&program_a.term, &program_b.term, "program", &mut differences, &mut truncated);
+
+    let comparison = ScriptComparison {
+        alpha_equivalent: differences.is_empty(),
+        same_version: program_a.version == program_b.version,
+        version_a: version_to_string(&program_a),
+        version_b: version_to_string(&program_b),
+        cbor_layers_a: layers_a,
+        cbor_layers_b: layers_b,
+        differences,
+        differences_truncated: truncated,
+    };
+    to_versioned_json(&comparison)
+}
+
+fn version_to_string(program: &Program<NamedDeBruijn>) -> String {
+    format!("{}.{}.{}", program.version.0, program.version.1, program.version.2)
+}
+
+fn snippet(term: &Term<NamedDeBruijn>) -> String {
+    let text = term_to_flat_string(term);
+    if text.chars().count() > SNIPPET_LENGTH {
+        format!("{}...", text.chars().take(SNIPPET_LENGTH).collect::<String>())
+    } else {
+        text
+    }
+}
+
+fn diff_terms(
+    a: &Term<NamedDeBruijn>,
+    b: &Term<NamedDeBruijn>,
+    path: &str,
+    differences: &mut Vec<ScriptDifference>,
+    truncated: &mut bool,
+) {
+    if differences.len() >= MAX_DIFFERENCES {
+        *truncated = true;
+        return;
+    }
+    let report = |differences: &mut Vec<ScriptDifference>| {
+        differences.push(ScriptDifference {
+            path: path.to_string(),
+            left: snippet(a),
+            right: snippet(b),
+        })
+    };
+
+    match (a, b) {
+        (Term::Var(x), Term::Var(y)) => {
+            if x.index.inner() != y.index.inner() {
+                report(differences);
+            }
+        }
+        (Term::Delay(x), Term::Delay(y)) => {
+            diff_terms(x, y, &format!("{}/delay", path), differences, truncated)
+        }
+        (Term::Force(x), Term::Force(y)) => {
+            diff_terms(x, y, &format!("{}/force", path), differences, truncated)
+        }
+        (Term::Lambda { body: x, .. }, Term::Lambda { body: y, .. }) => {
+            diff_terms(x, y, &format!("{}/lam", path), differences, truncated)
+        }
+        (
+            Term::Apply { function: fx, argument: ax },
+            Term::Apply { function: fy, argument: ay },
+        ) => {
+            diff_terms(fx, fy, &format!("{}/fun", path), differences, truncated);
+            diff_terms(ax, ay, &format!("{}/arg", path), differences, truncated);
+        }
+        (Term::Constant(x), Term::Constant(y)) => {
+            if constant_to_string(x) != constant_to_string(y) || snippet(a) != snippet(b) {
+                report(differences);
+            }
+        }
+        (Term::Builtin(x), Term::Builtin(y)) => {
+            if x != y {
+                report(differences);
+            }
+        }
+        (Term::Error, Term::Error) => {}
+        (Term::Constr { tag: tx, fields: fx }, Term::Constr { tag: ty, fields: fy }) => {
+            if tx != ty || fx.len() != fy.len() {
+                report(differences);
+                return;
+            }
+            for (i, (x, y)) in fx.iter().zip(fy.iter()).enumerate() {
+                diff_terms(x, y, &format!("{}/constr[{}]", path, i), differences, truncated);
+            }
+        }
+        (Term::Case { constr: cx, branches: bx }, Term::Case { constr: cy, branches: by }) => {
+            diff_terms(cx, cy, &format!("{}/case", path), differences, truncated);
+            if bx.len() != by.len() {
+                report(differences);
+                return;
+            }
+            for (i, (x, y)) in bx.iter().zip(by.iter()).enumerate() {
+                diff_terms(x, y, &format!("{}/branch[{}]", path, i), differences, truncated);
+            }
+        }
+        _ => report(differences),
+    }
+}
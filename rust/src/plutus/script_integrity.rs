@@ -0,0 +1,213 @@
+use std::collections::{BTreeSet, HashSet};
+use cardano_serialization_lib::Transaction;
+use pallas_crypto::hash::Hasher;
+use serde::Serialize;
+use crate::bingen::wasm_bindgen;
+use crate::cbor::cbor_decoder::{minicbor_to_js_error, visit_tx_body, visit_tx_witness_set};
+use crate::cbor::cbor_encoder::{push_head, push_int, MAJOR_ARRAY, MAJOR_BYTES, MAJOR_MAP, MAJOR_UNSIGNED};
+use crate::error::ErrorCode;
+use crate::input::{decode_hex, sanitize_hex};
+use crate::js_error::JsError;
+use crate::koios_client::models::{EpochParamResponse, UtxoInfoResponse};
+use crate::plutus::script_purpose::redeemer_targets;
+use crate::plutus::tx_scripts::{collect_tx_scripts, ExtractedScript};
+use crate::protocol_params::adapters::parse_protocol_params;
+use crate::schemas::to_versioned_json;
+
+// Conway transaction body and witness set keys
+const SCRIPT_DATA_HASH_KEY: u64 = 11;
+const PLUTUS_DATA_KEY: u64 = 4;
+const REDEEMERS_KEY: u64 = 5;
+
+// empty map, stands in for the redeemers and language views of a tx with datums only
+const EMPTY_MAP: &[u8] = &[0xa0];
+
+/// The language views entry of one Plutus version. `key_hex` and `value_hex`
+/// are the exact bytes hashed: PlutusV1 keeps the bug of the original Alonzo
+/// encoding, its key is the language id wrapped in a bytestring and its value
+/// the indefinite-length cost model list wrapped in a bytestring again.
+#[derive(Serialize, Debug, Clone)]
+pub struct LanguageView {
+    pub language: String,
+    pub key_hex: String,
+    pub value_hex: String,
+    pub cost_model_len: usize,
+}
+
+/// The parts of the script integrity hash preimage, `redeemers || datums ||
+/// language_views`, each as it's hashed. `declared_hash` is the
+/// `script_data_hash` of the body, `computed_hash` blake2b-256 of `preimage_hex`.
+#[derive(Serialize, Debug, Clone)]
+pub struct ScriptIntegrity {
+    pub languages: Vec<String>,
+    pub language_views: Vec<LanguageView>,
+    pub language_views_hex: String,
+    pub redeemers_hex: String,
+    pub datums_hex: String,
+    pub preimage_hex: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub declared_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub computed_hash: Option<String>,
+    pub matches: bool,
+}
+
+/// Rebuilds the script integrity hash preimage of a tx from its raw
+/// redeemers and datums and the cost models of the protocol params, so a
+/// `script_data_hash` mismatch can be traced to the differing bytes. The
+/// languages are those of the Plutus scripts in the witness set and of the
+/// reference scripts the redeemers run, found in the given spent or
+/// referenced UTxOs (Koios `utxo_info` JSON, may be empty).
+#[wasm_bindgen]
+pub fn inspect_script_integrity(tx_hex: &str, utxo_json: &str, protocol_params_json: &str) -> Result<String, JsError> {
+    let tx_bytes = decode_hex(tx_hex)?;
+    let tx = Transaction::from_hex(sanitize_hex(tx_hex)?)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding transaction: {:?}", e)))?;
+    let utxos: Vec<UtxoInfoResponse> = if utxo_json.trim().is_empty() {
+        Vec::new()
+    } else {
+        serde_json::from_str(utxo_json).map_err(|e| ErrorCode::InvalidInput.error(&e.to_string()))?
+    };
+    let protocol_params = parse_protocol_params(protocol_params_json)?;
+
+    let languages = needed_languages(&tx, &collect_tx_scripts(&tx, &utxos), &utxos)?;
+    to_versioned_json(&script_integrity(&tx_bytes, &languages, &protocol_params)?)
+}
+
+/// Languages of the scripts the tx needs, the only ones the ledger hashes a
+/// language view for. Witness set scripts are always needed (the ledger
+/// rejects extraneous ones), reference scripts only when a redeemer runs them.
+/// When a redeemer's script can't be resolved (vote and propose redeemers,
+/// spends of UTxOs not given) every reference script is kept.
+pub(crate) fn needed_languages(
+    tx: &Transaction,
+    scripts: &[ExtractedScript],
+    utxos: &[UtxoInfoResponse],
+) -> Result<Vec<String>, JsError> {
+    let targets = redeemer_targets(tx, utxos)?;
+    let all_resolved = targets.iter().all(|target| target.script_hash.is_some());
+    let run: HashSet<String> = targets.into_iter().filter_map(|target| target.script_hash).collect();
+    let languages: BTreeSet<String> = scripts
+        .iter()
+        .filter(|script| script.source != "reference" || !all_resolved || run.contains(&script.script_hash))
+        .filter_map(|script| normalize_language(&script.language))
+        .collect();
+    Ok(languages.into_iter().collect())
+}
+
+pub(crate) fn script_integrity(
+    tx_bytes: &[u8],
+    languages: &[String],
+    pp: &EpochParamResponse,
+) -> Result<ScriptIntegrity, JsError> {
+    let mut declared_hash = None;
+    visit_tx_body(tx_bytes, |key, decoder| {
+        if key != SCRIPT_DATA_HASH_KEY {
+            return Ok(false);
+        }
+        declared_hash = Some(hex::encode(decoder.bytes()?));
+        Ok(true)
+    })
+    .map_err(minicbor_to_js_error)?;
+
+    let mut redeemers: &[u8] = &[];
+    let mut datums: &[u8] = &[];
+    visit_tx_witness_set(tx_bytes, |key, decoder| {
+        let start = decoder.position();
+        match key {
+            REDEEMERS_KEY | PLUTUS_DATA_KEY => decoder.skip()?,
+            _ => return Ok(false),
+        }
+        let raw = &tx_bytes[start..decoder.position()];
+        if key == REDEEMERS_KEY {
+            redeemers = raw;
+        } else {
+            datums = raw;
+        }
+        Ok(true)
+    })
+    .map_err(minicbor_to_js_error)?;
+
+    let mut entries = Vec::new();
+    for language in languages {
+        entries.push(language_view(language, pp)?);
+    }
+    // canonical map key order: shorter keys first, so V2 and V3 come before V1
+    entries.sort_by(|(a, _, _), (b, _, _)| (a.len(), a).cmp(&(b.len(), b)));
+    let mut language_views_bytes = Vec::new();
+    push_head(&mut language_views_bytes, MAJOR_MAP, entries.len() as u64);
+    let mut language_views = Vec::new();
+    for (key, value, view) in entries {
+        language_views_bytes.extend(&key);
+        language_views_bytes.extend(&value);
+        language_views.push(view);
+    }
+
+    let preimage = if redeemers.is_empty() && datums.is_empty() {
+        Vec::new()
+    } else if redeemers.is_empty() {
+        [EMPTY_MAP, datums, EMPTY_MAP].concat()
+    } else {
+        [redeemers, datums, &language_views_bytes].concat()
+    };
+    let computed_hash = (!preimage.is_empty()).then(|| Hasher::<256>::hash(&preimage).to_string());
+
+    Ok(ScriptIntegrity {
+        languages: languages.to_vec(),
+        language_views,
+        language_views_hex: hex::encode(&language_views_bytes),
+        redeemers_hex: hex::encode(redeemers),
+        datums_hex: hex::encode(datums),
+        preimage_hex: hex::encode(&preimage),
+        matches: declared_hash == computed_hash,
+        declared_hash,
+        computed_hash,
+    })
+}
+
+/// Key and value bytes of the language views entry of `language`.
+fn language_view(language: &str, pp: &EpochParamResponse) -> Result<(Vec<u8>, Vec<u8>, LanguageView), JsError> {
+    let cost_models = pp.cost_models.as_ref();
+    let (id, cost_model) = match language {
+        "PlutusV1" => (0, cost_models.and_then(|cm| cm.plutus_v1.as_ref())),
+        "PlutusV2" => (1, cost_models.and_then(|cm| cm.plutus_v2.as_ref())),
+        "PlutusV3" => (2, cost_models.and_then(|cm| cm.plutus_v3.as_ref())),
+        other => return Err(ErrorCode::Unsupported.error(&format!("Unknown Plutus language {}", other))),
+    };
+    let cost_model = cost_model
+        .ok_or_else(|| ErrorCode::InvalidInput.error(&format!("Protocol params have no {} cost model", language)))?;
+
+    let mut key = Vec::new();
+    let mut value = Vec::new();
+    if id == 0 {
+        let mut list = vec![0x9f];
+        cost_model.iter().for_each(|cost| push_int(&mut list, *cost));
+        list.push(0xff);
+        push_head(&mut key, MAJOR_BYTES, 1);
+        key.push(0);
+        push_head(&mut value, MAJOR_BYTES, list.len() as u64);
+        value.extend(list);
+    } else {
+        push_head(&mut key, MAJOR_UNSIGNED, id);
+        push_head(&mut value, MAJOR_ARRAY, cost_model.len() as u64);
+        cost_model.iter().for_each(|cost| push_int(&mut value, *cost));
+    }
+    let view = LanguageView {
+        language: language.to_string(),
+        key_hex: hex::encode(&key),
+        value_hex: hex::encode(&value),
+        cost_model_len: cost_model.len(),
+    };
+    Ok((key, value, view))
+}
+
+/// `PlutusV1`.. for Plutus scripts, also from the Koios script types of
+/// reference scripts that failed to decode, `None` for native scripts.
+pub(crate) fn normalize_language(language: &str) -> Option<String> {
+    match language {
+        "PlutusV1" | "plutusV1" => Some("PlutusV1".to_string()),
+        "PlutusV2" | "plutusV2" => Some("PlutusV2".to_string()),
+        "PlutusV3" | "plutusV3" => Some("PlutusV3".to_string()),
+        _ => None,
+    }
+}
@@ -0,0 +1,303 @@
+use std::collections::{BTreeMap, HashSet};
+use cardano_serialization_lib::{Transaction, TransactionInputs};
+use serde::Serialize;
+use crate::bingen::wasm_bindgen;
+use crate::error::ErrorCode;
+use crate::input::sanitize_hex;
+use crate::js_error::JsError;
+use crate::schemas::to_versioned_json;
+use crate::koios_client::models::{EpochParamResponse, UtxoInfoResponse};
+use crate::plutus::evaluation_result::EvaluationExUnits;
+use crate::plutus::tx_scripts::{collect_tx_scripts, ExtractedScript};
+use crate::protocol_params::adapters::parse_protocol_params;
+
+// Conway reference script fee: price grows by 1.2x every 25 KiB
+const REF_SCRIPT_SIZE_INCREMENT: u64 = 25_600;
+const REF_SCRIPT_MULTIPLIER: f64 = 1.2;
+// Conway `maxRefScriptSizePerTx`
+const MAX_REF_SCRIPT_SIZE_PER_TX: u64 = 204_800;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct InventoryEntry {
+    pub script_hash: String,
+    pub language: String,
+    pub source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub utxo: Option<String>,
+    pub size: usize,
+    /// Lovelace the script adds to the size-based fee when attached to the witness set.
+    pub attached_fee: u64,
+    /// Lovelace the script would cost as the only reference script of a tx.
+    pub referenced_fee: u64,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct LanguageSummary {
+    pub count: usize,
+    pub total_size: usize,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ScriptInventory {
+    pub scripts: Vec<InventoryEntry>,
+    pub languages: BTreeMap<String, LanguageSummary>,
+    pub attached_size: usize,
+    pub referenced_size: usize,
+    pub attached_fee: u64,
+    pub reference_script_fee: u64,
+}
+
+/// Lists the scripts a transaction uses with their sizes and what they cost in
+/// fees, attached or referenced. Only UTxOs the tx spends or references count
+/// towards the reference script size, like the ledger does.
+#[wasm_bindgen]
+pub fn get_script_inventory(tx_hex: &str, utxo_json: &str, protocol_params_json: &str) -> Result<String, JsError> {
+    let tx = Transaction::from_hex(sanitize_hex(tx_hex)?)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding transaction: {:?}", e)))?;
+    let utxos: Vec<UtxoInfoResponse> = if utxo_json.trim().is_empty() {
+        Vec::new()
+    } else {
+        serde_json::from_str(utxo_json).map_err(|e| ErrorCode::InvalidInput.error(&e.to_string()))?
+    };
+    let protocol_params = parse_protocol_params(protocol_params_json)?;
+    let min_fee_a = protocol_params.min_fee_a.unwrap_or_default();
+    let ref_script_price = protocol_params.min_fee_ref_script_cost_per_byte.unwrap_or_default();
+
+    let utxos = used_utxos(&tx, utxos);
+    let scripts: Vec<InventoryEntry> = collect_tx_scripts(&tx, &utxos)
+        .into_iter()
+        .map(|script| to_inventory_entry(script, min_fee_a, ref_script_price))
+        .collect();
+
+    let mut languages: BTreeMap<String, LanguageSummary> = BTreeMap::new();
+    for script in &scripts {
+        let summary = languages.entry(script.language.clone()).or_default();
+        summary.count += 1;
+        summary.total_size += script.size;
+    }
+    let attached_size: usize = scripts.iter().filter(|s| s.source == "witness").map(|s| s.size).sum();
+    let referenced_size: usize = scripts.iter().filter(|s| s.source == "reference").map(|s| s.size).sum();
+
+    let inventory = ScriptInventory {
+        attached_fee: attached_size as u64 * min_fee_a,
+        reference_script_fee: reference_script_fee(referenced_size as u64, ref_script_price),
+        scripts,
+        languages,
+        attached_size,
+        referenced_size,
+    };
+    to_versioned_json(&inventory)
+}
+
+/// A reference script the tx pays for, from a spent (`input`) or a
+/// referenced (`reference_input`) UTxO.
+#[derive(Serialize, Debug, Clone)]
+pub struct ReferenceScriptEntry {
+    pub utxo: String,
+    pub input_kind: String,
+    pub script_hash: String,
+    pub language: String,
+    pub size: usize,
+}
+
+/// One 25 KiB pricing tier, `price_per_byte` grown 1.2x per tier.
+#[derive(Serialize, Debug, Clone)]
+pub struct ReferenceScriptTier {
+    pub bytes: u64,
+    pub price_per_byte: f64,
+}
+
+/// What reference scripts add to the min fee. Every occurrence counts, the
+/// same script in two UTxOs is paid twice, as the ledger does.
+#[derive(Serialize, Debug, Clone)]
+pub struct ReferenceScriptFee {
+    pub scripts: Vec<ReferenceScriptEntry>,
+    pub languages: BTreeMap<String, LanguageSummary>,
+    pub total_size: u64,
+    pub tiers: Vec<ReferenceScriptTier>,
+    pub fee: u64,
+    pub max_size: u64,
+    pub exceeds_max_size: bool,
+}
+
+/// Totals the reference scripts of the UTxOs a tx spends or references
+/// (Koios `utxo_info` JSON) by language and prices them with the tiered
+/// Conway `min_fee_ref_script_cost_per_byte`.
+#[wasm_bindgen]
+pub fn get_reference_script_fee(tx_hex: &str, utxo_json: &str, protocol_params_json: &str) -> Result<String, JsError> {
+    let tx = Transaction::from_hex(sanitize_hex(tx_hex)?)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding transaction: {:?}", e)))?;
+    let utxos: Vec<UtxoInfoResponse> = if utxo_json.trim().is_empty() {
+        Vec::new()
+    } else {
+        serde_json::from_str(utxo_json).map_err(|e| ErrorCode::InvalidInput.error(&e.to_string()))?
+    };
+    let protocol_params = parse_protocol_params(protocol_params_json)?;
+    let price = protocol_params.min_fee_ref_script_cost_per_byte.unwrap_or_default();
+    to_versioned_json(&reference_script_fee_report(&tx, utxos, price))
+}
+
+pub(crate) fn reference_script_fee_report(tx: &Transaction, utxos: Vec<UtxoInfoResponse>, price_per_byte: f64) -> ReferenceScriptFee {
+    let mut spent = HashSet::new();
+    collect_input_refs(&tx.body().inputs(), &mut spent);
+    let scripts: Vec<ReferenceScriptEntry> = collect_tx_scripts(tx, &used_utxos(tx, utxos))
+        .into_iter()
+        .filter(|script| script.source == "reference")
+        .map(|script| {
+            let utxo = script.utxo.unwrap_or_default();
+            let input_kind = if spent.contains(&utxo) { "input" } else { "reference_input" };
+            ReferenceScriptEntry {
+                input_kind: input_kind.to_string(),
+                utxo,
+                script_hash: script.script_hash,
+                language: script.language,
+                size: script.size,
+            }
+        })
+        .collect();
+
+    let mut languages: BTreeMap<String, LanguageSummary> = BTreeMap::new();
+    for script in &scripts {
+        let summary = languages.entry(script.language.clone()).or_default();
+        summary.count += 1;
+        summary.total_size += script.size;
+    }
+    let total_size: u64 = scripts.iter().map(|script| script.size as u64).sum();
+
+    let mut tiers = Vec::new();
+    let mut remaining = total_size;
+    let mut price = price_per_byte;
+    while remaining > 0 {
+        let bytes = remaining.min(REF_SCRIPT_SIZE_INCREMENT);
+        tiers.push(ReferenceScriptTier { bytes, price_per_byte: price });
+        price *= REF_SCRIPT_MULTIPLIER;
+        remaining -= bytes;
+    }
+
+    ReferenceScriptFee {
+        scripts,
+        languages,
+        total_size,
+        tiers,
+        fee: reference_script_fee(total_size, price_per_byte),
+        max_size: MAX_REF_SCRIPT_SIZE_PER_TX,
+        exceeds_max_size: total_size > MAX_REF_SCRIPT_SIZE_PER_TX,
+    }
+}
+
+/// The UTxOs the tx spends or references, the ones whose reference scripts
+/// count towards its fee.
+pub(crate) fn used_utxos(tx: &Transaction, utxos: Vec<UtxoInfoResponse>) -> Vec<UtxoInfoResponse> {
+    let body = tx.body();
+    let mut used_inputs = HashSet::new();
+    collect_input_refs(&body.inputs(), &mut used_inputs);
+    if let Some(reference_inputs) = body.reference_inputs() {
+        collect_input_refs(&reference_inputs, &mut used_inputs);
+    }
+    utxos
+        .into_iter()
+        .filter(|utxo| used_inputs.contains(&format!("{}#{}", utxo.tx_hash, utxo.tx_index)))
+        .collect()
+}
+
+fn collect_input_refs(inputs: &TransactionInputs, refs: &mut HashSet<String>) {
+    for i in 0..inputs.len() {
+        let input = inputs.get(i);
+        refs.insert(format!("{}#{}", input.transaction_id().to_hex(), input.index()));
+    }
+}
+
+fn to_inventory_entry(script: ExtractedScript, min_fee_a: u64, ref_script_price: f64) -> InventoryEntry {
+    let size = script.size as u64;
+    InventoryEntry {
+        attached_fee: size * min_fee_a,
+        referenced_fee: reference_script_fee(size, ref_script_price),
+        script_hash: script.script_hash,
+        language: script.language,
+        source: script.source,
+        utxo: script.utxo,
+        size: script.size,
+    }
+}
+
+/// Conway min fee of a tx of `size` bytes whose redeemers declare
+/// `ex_units` and whose reference scripts total `reference_size` bytes.
+pub(crate) fn min_fee(pp: &EpochParamResponse, size: u64, ex_units: &EvaluationExUnits, reference_size: u64) -> u64 {
+    pp.min_fee_a.unwrap_or_default() * size
+        + pp.min_fee_b.unwrap_or_default()
+        + ex_unit_fee(pp, ex_units)
+        + reference_script_fee(reference_size, pp.min_fee_ref_script_cost_per_byte.unwrap_or_default())
+}
+
+/// Fee of the redeemer ex units at the memory and step prices, computed
+/// exactly like the ledger with the prices as decimal fractions, rounded up
+/// once at the end.
+pub(crate) fn ex_unit_fee(pp: &EpochParamResponse, ex_units: &EvaluationExUnits) -> u64 {
+    let price_mem = pp.price_mem.unwrap_or_default();
+    let price_step = pp.price_step.unwrap_or_default();
+    exact_ex_unit_fee(ex_units, price_mem, price_step)
+        .unwrap_or_else(|| (ex_units.mem as f64 * price_mem + ex_units.steps as f64 * price_step).ceil() as u64)
+}
+
+fn exact_ex_unit_fee(ex_units: &EvaluationExUnits, price_mem: f64, price_step: f64) -> Option<u64> {
+    let (mem_numerator, mem_denominator) = decimal_fraction(price_mem)?;
+    let (step_numerator, step_denominator) = decimal_fraction(price_step)?;
+    // mem * pm_n / pm_d + steps * ps_n / ps_d over the common denominator pm_d * ps_d
+    let numerator = (ex_units.mem as u128)
+        .checked_mul(mem_numerator)?
+        .checked_mul(step_denominator)?
+        .checked_add((ex_units.steps as u128).checked_mul(step_numerator)?.checked_mul(mem_denominator)?)?;
+    let denominator = mem_denominator.checked_mul(step_denominator)?;
+    u64::try_from(numerator.div_ceil(denominator)).ok()
+}
+
+/// Tiered fee for the total size of all reference scripts of a tx, computed
+/// exactly like the ledger with the price as a decimal fraction and the
+/// 1.2 multiplier as 6/5, floored once at the end.
+pub(crate) fn reference_script_fee(total_size: u64, price_per_byte: f64) -> u64 {
+    exact_reference_script_fee(total_size, price_per_byte).unwrap_or_else(|| {
+        // sizes past the ledger limit overflow the exact sum, approximate them
+        let mut fee = 0.0;
+        let mut price = price_per_byte;
+        let mut remaining = total_size;
+        while remaining > 0 {
+            let chunk = remaining.min(REF_SCRIPT_SIZE_INCREMENT);
+            fee += chunk as f64 * price;
+            price *= REF_SCRIPT_MULTIPLIER;
+            remaining -= chunk;
+        }
+        fee.floor() as u64
+    })
+}
+
+fn exact_reference_script_fee(total_size: u64, price_per_byte: f64) -> Option<u64> {
+    let (price_numerator, price_denominator) = decimal_fraction(price_per_byte)?;
+    let tiers = total_size.div_ceil(REF_SCRIPT_SIZE_INCREMENT) as u32;
+    // sum of chunk * price * 6^k / 5^k over the tiers, over the common denominator 5^tiers
+    let mut numerator: u128 = 0;
+    let mut remaining = total_size;
+    for tier in 0..tiers {
+        let chunk = remaining.min(REF_SCRIPT_SIZE_INCREMENT) as u128;
+        let term = chunk
+            .checked_mul(price_numerator)?
+            .checked_mul(6u128.checked_pow(tier)?)?
+            .checked_mul(5u128.checked_pow(tiers - tier)?)?;
+        numerator = numerator.checked_add(term)?;
+        remaining -= chunk as u64;
+    }
+    let denominator = price_denominator.checked_mul(5u128.checked_pow(tiers)?)?;
+    u64::try_from(numerator / denominator).ok()
+}
+
+/// `value` as numerator and power of ten denominator, from its shortest
+/// decimal form, which is how the params JSON wrote it.
+fn decimal_fraction(value: f64) -> Option<(u128, u128)> {
+    if !value.is_finite() || value < 0.0 {
+        return None;
+    }
+    let text = value.to_string();
+    let (whole, fraction) = text.split_once('.').unwrap_or((&text, ""));
+    let numerator = format!("{}{}", whole, fraction).parse().ok()?;
+    let denominator = 10u128.checked_pow(fraction.len() as u32)?;
+    Some((numerator, denominator))
+}
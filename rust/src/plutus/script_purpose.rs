@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+use cardano_serialization_lib::{
+    Address, BaseAddress, Certificate, Credential, Ed25519KeyHash, EnterpriseAddress, PointerAddress,
+    RedeemerTagKind, RewardAddress, Transaction, TransactionInput,
+};
+use crate::csl_decoders::certificate_form;
+use crate::error::ErrorCode;
+use crate::js_error::JsError;
+use crate::koios_client::models::UtxoInfoResponse;
+
+/// Which on-chain item a redeemer points to, resolved the same way the ledger
+/// does: spend and mint indexes refer to the sorted inputs/policies, reward
+/// indexes to the withdrawals in ledger order, cert indexes to the list as is.
+#[derive(Clone, Debug)]
+pub(crate) struct RedeemerTarget {
+    pub(crate) tag: String,
+    pub(crate) index: u32,
+    pub(crate) description: String,
+    pub(crate) script_hash: Option<String>,
+}
+
+/// The tag names `resolve_redeemer_target` takes, as the evaluator reports them.
+pub(crate) fn redeemer_tag_name(kind: RedeemerTagKind) -> &'static str {
+    match kind {
+        RedeemerTagKind::Spend => "Spend",
+        RedeemerTagKind::Mint => "Mint",
+        RedeemerTagKind::Cert => "Cert",
+        RedeemerTagKind::Reward => "Reward",
+        RedeemerTagKind::Vote => "Vote",
+        RedeemerTagKind::VotingProposal => "Propose",
+    }
+}
+
+/// `tx_hash#index` to the bech32 address of each UTxO.
+pub(crate) fn utxo_addresses(utxos: &[UtxoInfoResponse]) -> HashMap<String, String> {
+    utxos
+        .iter()
+        .map(|u| (format!("{}#{}", u.tx_hash, u.tx_index), u.address.clone()))
+        .collect()
+}
+
+pub(crate) fn sorted_inputs(tx: &Transaction) -> Vec<TransactionInput> {
+    let inputs = tx.body().inputs();
+    let mut sorted = Vec::new();
+    for i in 0..inputs.len() {
+        sorted.push(inputs.get(i));
+    }
+    sorted.sort_by_key(|input| (input.transaction_id().to_bytes(), input.index()));
+    sorted
+}
+
+pub(crate) fn sorted_policy_ids(tx: &Transaction) -> Vec<String> {
+    let mut policies = Vec::new();
+    if let Some(mint) = tx.body().mint() {
+        let keys = mint.keys();
+        for i in 0..keys.len() {
+            policies.push(keys.get(i).to_hex());
+        }
+    }
+    policies.sort();
+    policies
+}
+
+/// Reward accounts in ledger order: script credentials sort before key
+/// credentials, then by hash.
+pub(crate) fn sorted_reward_addresses(tx: &Transaction) -> Vec<RewardAddress> {
+    let mut addresses = Vec::new();
+    if let Some(withdrawals) = tx.body().withdrawals() {
+        let keys = withdrawals.keys();
+        for i in 0..keys.len() {
+            addresses.push(keys.get(i));
+        }
+    }
+    addresses.sort_by_key(|addr| {
+        let cred = addr.payment_cred();
+        (cred.to_scripthash().is_none(), cred.to_bytes())
+    });
+    addresses
+}
+
+pub(crate) fn payment_script_hash(address: &Address) -> Option<String> {
+    let cred = if let Some(base) = BaseAddress::from_address(address) {
+        base.payment_cred()
+    } else if let Some(enterprise) = EnterpriseAddress::from_address(address) {
+        enterprise.payment_cred()
+    } else if let Some(pointer) = PointerAddress::from_address(address) {
+        pointer.payment_cred()
+    } else {
+        return None;
+    };
+    cred.to_scripthash().map(|hash| hash.to_hex())
+}
+
+pub(crate) fn certificate_credential(cert: &Certificate) -> Option<Credential> {
+    if let Some(c) = cert.as_stake_registration() {
+        Some(c.stake_credential())
+    } else if let Some(c) = cert.as_stake_deregistration() {
+        Some(c.stake_credential())
+    } else if let Some(c) = cert.as_stake_delegation() {
+        Some(c.stake_credential())
+    } else if let Some(c) = cert.as_vote_delegation() {
+        Some(c.stake_credential())
+    } else if let Some(c) = cert.as_stake_and_vote_delegation() {
+        Some(c.stake_credential())
+    } else if let Some(c) = cert.as_stake_registration_and_delegation() {
+        Some(c.stake_credential())
+    } else if let Some(c) = cert.as_vote_registration_and_delegation() {
+        Some(c.stake_credential())
+    } else if let Some(c) = cert.as_stake_vote_registration_and_delegation() {
+        Some(c.stake_credential())
+    } else if let Some(c) = cert.as_drep_registration() {
+        Some(c.voting_credential())
+    } else if let Some(c) = cert.as_drep_deregistration() {
+        Some(c.voting_credential())
+    } else if let Some(c) = cert.as_drep_update() {
+        Some(c.voting_credential())
+    } else if let Some(c) = cert.as_committee_hot_auth() {
+        Some(c.committee_cold_credential())
+    } else if let Some(c) = cert.as_committee_cold_resign() {
+        Some(c.committee_cold_credential())
+    } else {
+        None
+    }
+}
+
+/// The pool a certificate delegates to, registers or retires.
+pub(crate) fn certificate_pool(cert: &Certificate) -> Option<Ed25519KeyHash> {
+    if let Some(c) = cert.as_stake_delegation() {
+        Some(c.pool_keyhash())
+    } else if let Some(c) = cert.as_stake_and_vote_delegation() {
+        Some(c.pool_keyhash())
+    } else if let Some(c) = cert.as_stake_registration_and_delegation() {
+        Some(c.pool_keyhash())
+    } else if let Some(c) = cert.as_stake_vote_registration_and_delegation() {
+        Some(c.pool_keyhash())
+    } else if let Some(c) = cert.as_pool_registration() {
+        Some(c.pool_params().operator())
+    } else {
+        cert.as_pool_retirement().map(|c| c.pool_keyhash())
+    }
+}
+
+/// Resolves the target of a redeemer. `utxo_addresses` maps `tx_hash#index`
+/// to the bech32 address of the spent output; spend targets without a known
+/// address are still described but carry no script hash. Vote and propose
+/// redeemers are described by index only.
+pub(crate) fn resolve_redeemer_target(
+    tx: &Transaction,
+    utxo_addresses: &HashMap<String, String>,
+    tag: &str,
+    index: u32,
+) -> RedeemerTarget {
+    let (description, script_hash) = match tag {
+        "Spend" => match sorted_inputs(tx).get(index as usize) {
+            Some(input) => {
+                let input_ref = format!("{}#{}", input.transaction_id().to_hex(), input.index());
+                match utxo_addresses.get(&input_ref) {
+                    Some(address) => (
+                        format!("spends UTxO {} at {}", input_ref, address),
+                        Address::from_bech32(address).ok().and_then(|a| payment_script_hash(&a)),
+                    ),
+                    None => (format!("spends UTxO {}", input_ref), None),
+                }
+            }
+            None => (format!("spend index {} is out of range", index), None),
+        },
+        "Mint" => match sorted_policy_ids(tx).get(index as usize) {
+            Some(policy) => (format!("mints under policy {}", policy), Some(policy.clone())),
+            None => (format!("mint index {} is out of range", index), None),
+        },
+        "Reward" => match sorted_reward_addresses(tx).get(index as usize) {
+            Some(reward_address) => {
+                let bech32 = reward_address
+                    .to_address()
+                    .to_bech32(None)
+                    .unwrap_or_default();
+                (
+                    format!("withdraws rewards from {}", bech32),
+                    reward_address.payment_cred().to_scripthash().map(|h| h.to_hex()),
+                )
+            }
+            None => (format!("reward index {} is out of range", index), None),
+        },
+        "Cert" => match tx.body().certs() {
+            Some(certs) if (index as usize) < certs.len() => {
+                let cert = certs.get(index as usize);
+                (
+                    format!("certificate #{} ({})", index, certificate_form(&cert).name),
+                    certificate_credential(&cert)
+                        .and_then(|c| c.to_scripthash())
+                        .map(|h| h.to_hex()),
+                )
+            }
+            _ => (format!("cert index {} is out of range", index), None),
+        },
+        "Vote" => (format!("vote by voter #{}", index), None),
+        "Propose" => (format!("proposal #{}", index), None),
+        _ => (format!("{} #{}", tag, index), None),
+    };
+
+    RedeemerTarget {
+        tag: tag.to_string(),
+        index,
+        description,
+        script_hash,
+    }
+}
+
+/// Targets of every redeemer of the tx, in witness set order.
+pub(crate) fn redeemer_targets(tx: &Transaction, utxos: &[UtxoInfoResponse]) -> Result<Vec<RedeemerTarget>, JsError> {
+    let Some(redeemers) = tx.witness_set().redeemers() else {
+        return Ok(Vec::new());
+    };
+    let utxo_addresses = utxo_addresses(utxos);
+    let mut targets = Vec::new();
+    for i in 0..redeemers.len() {
+        let redeemer = redeemers.get(i);
+        let tag = redeemer_tag_name(redeemer.tag().kind());
+        let index = redeemer.index().to_str().parse().map_err(|_| {
+            ErrorCode::InvalidInput.error(&format!("Redeemer {}:{} has an out of range index", tag, redeemer.index().to_str()))
+        })?;
+        targets.push(resolve_redeemer_target(tx, &utxo_addresses, tag, index));
+    }
+    Ok(targets)
+}
@@ -0,0 +1,100 @@
+use std::collections::HashSet;
+use cardano_serialization_lib::{FixedTransaction, PlutusScript, PlutusScripts, Transaction};
+use crate::error::{CquisitorError, ErrorCode};
+use crate::js_error::JsError;
+use crate::logger::log_info;
+use crate::koios_client::models::{ScriptInfoResponse, UtxoInfoResponse};
+use crate::plutus::script_purpose::redeemer_targets;
+use crate::plutus::tx_scripts::collect_tx_scripts;
+use crate::provider::Provider;
+use serde_json::json;
+
+/// Hashes of the scripts the redeemers run that neither the witness set nor
+/// the reference scripts of the resolved UTxOs provide.
+pub(crate) fn missing_script_hashes(tx: &Transaction, utxos: &[UtxoInfoResponse]) -> Result<Vec<String>, JsError> {
+    let available: HashSet<String> = collect_tx_scripts(tx, utxos)
+        .into_iter()
+        .map(|script| script.script_hash)
+        .collect();
+
+    let mut missing = Vec::new();
+    for target in redeemer_targets(tx, utxos)? {
+        if let Some(hash) = target.script_hash {
+            if !available.contains(&hash) && !missing.contains(&hash) {
+                missing.push(hash);
+            }
+        }
+    }
+    Ok(missing)
+}
+
+/// Adds scripts to the witness set. The body keeps its original bytes, so the
+/// tx id the scripts see in their context doesn't change.
+pub(crate) fn attach_scripts(tx_bytes: &[u8], scripts: &[ScriptInfoResponse]) -> Result<Vec<u8>, JsError> {
+    let fixed_tx = FixedTransaction::from_bytes(tx_bytes.to_vec())
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding transaction: {:?}", e)))?;
+    let mut witness_set = fixed_tx.witness_set();
+    let mut plutus_scripts = witness_set.plutus_scripts().unwrap_or_else(PlutusScripts::new);
+    for script in scripts {
+        let bytes = script
+            .bytes
+            .as_ref()
+            .ok_or_else(|| ErrorCode::ProviderError.error(&format!("Provider returned no bytes for script {}", script.script_hash)))
+            .and_then(|bytes| hex::decode(bytes).map_err(|e| ErrorCode::InvalidHex.error(&e.to_string())))?;
+        let plutus_script = match script.script_type.as_str() {
+            "plutusV1" => PlutusScript::new(bytes),
+            "plutusV2" => PlutusScript::new_v2(bytes),
+            "plutusV3" => PlutusScript::new_v3(bytes),
+            // native scripts have no redeemers, they never end up here
+            other => {
+                return Err(ErrorCode::DecodeFailure.error(&format!(
+                    "Script {} has unsupported type {}",
+                    script.script_hash, other
+                )))
+            }
+        };
+        plutus_scripts.add(&plutus_script);
+    }
+    witness_set.set_plutus_scripts(&plutus_scripts);
+
+    let patched = match fixed_tx.raw_auxiliary_data() {
+        Some(auxiliary_data) => FixedTransaction::new_with_auxiliary(
+            &fixed_tx.raw_body(),
+            &witness_set.to_bytes(),
+            &auxiliary_data,
+            fixed_tx.is_valid(),
+        ),
+        None => FixedTransaction::new(&fixed_tx.raw_body(), &witness_set.to_bytes(), fixed_tx.is_valid()),
+    }
+    .map_err(|e| ErrorCode::Internal.error(&format!("Error rebuilding transaction: {:?}", e)))?;
+    Ok(patched.to_bytes())
+}
+
+/// Looks up the scripts a tx needs but doesn't carry and attaches them, so
+/// txs built without their scripts (e.g. for fee estimation) still evaluate.
+pub(crate) async fn resolve_missing_scripts<P: Provider>(
+    tx_bytes: Vec<u8>,
+    utxos: &[UtxoInfoResponse],
+    provider: &P,
+) -> Result<Vec<u8>, JsError> {
+    let tx = Transaction::from_bytes(tx_bytes.clone())
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding transaction: {:?}", e)))?;
+    let missing = missing_script_hashes(&tx, utxos)?;
+    if missing.is_empty() {
+        return Ok(tx_bytes);
+    }
+    log_info!("fetching {} scripts missing from the transaction: {}", missing.len(), missing.join(", "));
+    let scripts = provider.get_scripts(&missing).await?;
+    let found: HashSet<&str> = scripts.iter().map(|s| s.script_hash.as_str()).collect();
+    let not_found: Vec<&str> = missing.iter().map(|h| h.as_str()).filter(|h| !found.contains(h)).collect();
+    if !not_found.is_empty() {
+        let message = format!(
+            "Scripts not attached to the transaction and not found by the provider: {}",
+            not_found.join(", ")
+        );
+        return Err(CquisitorError::new(ErrorCode::MissingScript, &message)
+            .with_details(json!({ "scripts": not_found }))
+            .into());
+    }
+    attach_scripts(&tx_bytes, &scripts)
+}
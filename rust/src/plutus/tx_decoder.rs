@@ -0,0 +1,299 @@
+use std::str::FromStr;
+use cardano_serialization_lib::{Certificate, Credential, Ed25519KeyHash, FixedTransaction, PlutusDatumSchema, ScriptHash, Transaction, TransactionInputs};
+use pallas_crypto::hash::Hasher;
+use serde::Serialize;
+use serde_json::Value;
+use crate::bingen::wasm_bindgen;
+use crate::error::ErrorCode;
+use crate::input::sanitize_hex;
+use crate::js_error::JsError;
+use crate::koios_client::models::UtxoInfoResponse;
+use crate::plutus::evaluation_result::EvaluationExUnits;
+use crate::plutus::script_purpose::{certificate_credential, redeemer_tag_name, resolve_redeemer_target, utxo_addresses};
+use crate::schemas::to_versioned_json;
+
+/// A redeemer with what it points to, e.g. `spends UTxO abc#1 at addr1...`
+/// or `mints under policy ...`. `script_hash` is the script that runs for
+/// it, unknown for spends of UTxOs that weren't given.
+#[derive(Serialize, Debug, Clone)]
+pub struct DecodedRedeemer {
+    pub tag: String,
+    pub index: u32,
+    pub target: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub script_hash: Option<String>,
+    pub ex_units: EvaluationExUnits,
+    pub data: Value,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DecodedTransaction {
+    pub tx_hash: String,
+    pub transaction: Value,
+    pub redeemers: Vec<DecodedRedeemer>,
+    pub annotations: Vec<HashAnnotation>,
+}
+
+/// What a 28 or 32 byte field most likely is, going by where it sits in the
+/// transaction: `path` follows the CSL JSON, `bech32` is set where CIP-5 or
+/// CIP-105 has a prefix for the kind.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct HashAnnotation {
+    pub path: String,
+    pub kind: &'static str,
+    pub hex: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bech32: Option<String>,
+}
+
+/// Decodes a transaction to the CSL JSON and describes the target of each
+/// redeemer the way the evaluator does, joining its index against the sorted
+/// inputs, policies, withdrawals and the certificates. The spent UTxOs
+/// (Koios `utxo_info` JSON, may be empty) add the addresses of spend targets.
+#[wasm_bindgen]
+pub fn decode_transaction_with_extended_info(tx_hex: &str, utxo_json: &str) -> Result<String, JsError> {
+    let utxos: Vec<UtxoInfoResponse> = if utxo_json.trim().is_empty() {
+        Vec::new()
+    } else {
+        serde_json::from_str(utxo_json).map_err(|e| ErrorCode::InvalidInput.error(&e.to_string()))?
+    };
+    to_versioned_json(&decode_transaction(tx_hex, &utxos)?)
+}
+
+pub(crate) fn decode_transaction(tx_hex: &str, utxos: &[UtxoInfoResponse]) -> Result<DecodedTransaction, JsError> {
+    let tx_hex = sanitize_hex(tx_hex)?;
+    let fixed_tx = FixedTransaction::from_hex(tx_hex)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding transaction: {:?}", e)))?;
+    let tx = Transaction::from_hex(tx_hex)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding transaction: {:?}", e)))?;
+    let tx_json = tx.to_json()
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error converting transaction to JSON: {:?}", e)))?;
+    let transaction = Value::from_str(&tx_json)
+        .map_err(|e| ErrorCode::Internal.error(&format!("Invalid transaction JSON: {}", e)))?;
+
+    Ok(DecodedTransaction {
+        // of the body bytes as sent, which a CSL round trip may not keep
+        tx_hash: Hasher::<256>::hash(&fixed_tx.raw_body()).to_string(),
+        transaction,
+        redeemers: redeemer_targets(&tx, utxos),
+        annotations: hash_annotations(&tx),
+    })
+}
+
+pub(crate) fn hash_annotations(tx: &Transaction) -> Vec<HashAnnotation> {
+    let mut annotations = Vec::new();
+    let body = tx.body();
+
+    input_annotations(&mut annotations, "body.inputs", &body.inputs());
+    if let Some(collateral) = body.collateral() {
+        input_annotations(&mut annotations, "body.collateral", &collateral);
+    }
+    if let Some(reference_inputs) = body.reference_inputs() {
+        input_annotations(&mut annotations, "body.reference_inputs", &reference_inputs);
+    }
+
+    let outputs = body.outputs();
+    for i in 0..outputs.len() {
+        let output = outputs.get(i);
+        if let Some(multiasset) = output.amount().multiasset() {
+            let policies = multiasset.keys();
+            for j in 0..policies.len() {
+                let path = format!("body.outputs[{}].amount.multiasset", i);
+                annotations.push(script_hash(path, "policy_id", &policies.get(j)));
+            }
+        }
+        if let Some(datum_hash) = output.data_hash() {
+            annotations.push(annotation(format!("body.outputs[{}].plutus_data", i), "datum_hash", datum_hash.to_hex(), None));
+        }
+    }
+
+    if let Some(certs) = body.certs() {
+        for i in 0..certs.len() {
+            certificate_annotations(&mut annotations, &format!("body.certs[{}]", i), &certs.get(i));
+        }
+    }
+
+    if let Some(withdrawals) = body.withdrawals() {
+        let accounts = withdrawals.keys();
+        for i in 0..accounts.len() {
+            let path = format!("body.withdrawals[{}]", i);
+            annotations.push(credential(path, "stake", &accounts.get(i).payment_cred()));
+        }
+    }
+
+    if let Some(hash) = body.auxiliary_data_hash() {
+        annotations.push(annotation("body.auxiliary_data_hash".to_string(), "auxiliary_data_hash", hash.to_hex(), None));
+    }
+
+    if let Some(mint) = body.mint() {
+        let policies = mint.keys();
+        for i in 0..policies.len() {
+            annotations.push(script_hash(format!("body.mint[{}]", i), "policy_id", &policies.get(i)));
+        }
+    }
+
+    if let Some(hash) = body.script_data_hash() {
+        annotations.push(annotation("body.script_data_hash".to_string(), "script_data_hash", hash.to_hex(), None));
+    }
+
+    if let Some(signers) = body.required_signers() {
+        for i in 0..signers.len() {
+            annotations.push(key_hash(format!("body.required_signers[{}]", i), &signers.get(i), "addr_vkh"));
+        }
+    }
+
+    if let Some(voting_procedures) = body.voting_procedures() {
+        let voters = voting_procedures.get_voters();
+        for i in 0..voters.len() {
+            let Some(voter) = voters.get(i) else {
+                continue;
+            };
+            let path = format!("body.voting_procedures[{}].voter", i);
+            if let Some(cred) = voter.to_constitutional_committee_hot_credential() {
+                annotations.push(credential(path, "cc_hot", &cred));
+            } else if let Some(cred) = voter.to_drep_credential() {
+                annotations.push(credential(path, "drep", &cred));
+            } else if let Some(pool) = voter.to_stake_pool_key_hash() {
+                annotations.push(pool_id(path, &pool));
+            }
+            let action_ids = voting_procedures.get_governance_action_ids_by_voter(&voter);
+            for j in 0..action_ids.len() {
+                if let Some(action_id) = action_ids.get(j) {
+                    let path = format!("body.voting_procedures[{}].votes[{}].action_id.transaction_id", i, j);
+                    annotations.push(annotation(path, "tx_id", action_id.transaction_id().to_hex(), None));
+                }
+            }
+        }
+    }
+
+    if let Some(vkeys) = tx.witness_set().vkeys() {
+        for i in 0..vkeys.len() {
+            // the witness carries the key itself, the hash is what the body refers to
+            let key = vkeys.get(i).vkey().public_key();
+            let path = format!("witness_set.vkeys[{}].vkey", i);
+            annotations.push(key_hash(path, &key.hash(), "addr_vkh"));
+        }
+    }
+
+    annotations
+}
+
+fn input_annotations(annotations: &mut Vec<HashAnnotation>, path: &str, inputs: &TransactionInputs) {
+    for i in 0..inputs.len() {
+        let path = format!("{}[{}].transaction_id", path, i);
+        annotations.push(annotation(path, "tx_id", inputs.get(i).transaction_id().to_hex(), None));
+    }
+}
+
+fn certificate_annotations(annotations: &mut Vec<HashAnnotation>, path: &str, cert: &Certificate) {
+    if let Some(cred) = certificate_credential(cert) {
+        let role = if cert.as_drep_registration().is_some()
+            || cert.as_drep_deregistration().is_some()
+            || cert.as_drep_update().is_some()
+        {
+            "drep"
+        } else if cert.as_committee_hot_auth().is_some() || cert.as_committee_cold_resign().is_some() {
+            "cc_cold"
+        } else {
+            "stake"
+        };
+        annotations.push(credential(format!("{}.credential", path), role, &cred));
+    }
+    if let Some(c) = cert.as_committee_hot_auth() {
+        annotations.push(credential(format!("{}.committee_hot_credential", path), "cc_hot", &c.committee_hot_credential()));
+    }
+    let pool = if let Some(c) = cert.as_stake_delegation() {
+        Some(c.pool_keyhash())
+    } else if let Some(c) = cert.as_stake_and_vote_delegation() {
+        Some(c.pool_keyhash())
+    } else if let Some(c) = cert.as_stake_registration_and_delegation() {
+        Some(c.pool_keyhash())
+    } else if let Some(c) = cert.as_stake_vote_registration_and_delegation() {
+        Some(c.pool_keyhash())
+    } else {
+        cert.as_pool_retirement().map(|c| c.pool_keyhash())
+    };
+    if let Some(pool) = pool {
+        annotations.push(pool_id(format!("{}.pool_keyhash", path), &pool));
+    }
+    if let Some(c) = cert.as_pool_registration() {
+        let params = c.pool_params();
+        annotations.push(pool_id(format!("{}.pool_params.operator", path), &params.operator()));
+        let vrf_path = format!("{}.pool_params.vrf_keyhash", path);
+        annotations.push(annotation(vrf_path, "vrf_key_hash", params.vrf_keyhash().to_hex(), None));
+        let owners = params.pool_owners();
+        for i in 0..owners.len() {
+            let owner_path = format!("{}.pool_params.pool_owners[{}]", path, i);
+            annotations.push(key_hash(owner_path, &owners.get(i), "stake_vkh"));
+        }
+    }
+}
+
+/// A credential as the key or script hash it is, with the bech32 prefix of
+/// its role: `stake`, `drep`, `cc_cold` or `cc_hot`.
+fn credential(path: String, role: &str, cred: &Credential) -> HashAnnotation {
+    if let Some(hash) = cred.to_keyhash() {
+        let prefix = match role {
+            "stake" => "stake_vkh".to_string(),
+            _ => role.to_string(),
+        };
+        key_hash(path, &hash, &prefix)
+    } else if let Some(hash) = cred.to_scripthash() {
+        let prefix = match role {
+            "stake" => "script".to_string(),
+            _ => format!("{}_script", role),
+        };
+        let bech32 = hash.to_bech32(&prefix).ok();
+        annotation(path, "script_hash", hash.to_hex(), bech32)
+    } else {
+        annotation(path, "credential", cred.to_hex(), None)
+    }
+}
+
+fn key_hash(path: String, hash: &Ed25519KeyHash, prefix: &str) -> HashAnnotation {
+    annotation(path, "key_hash", hash.to_hex(), hash.to_bech32(prefix).ok())
+}
+
+fn pool_id(path: String, hash: &Ed25519KeyHash) -> HashAnnotation {
+    annotation(path, "pool_id", hash.to_hex(), hash.to_bech32("pool").ok())
+}
+
+fn script_hash(path: String, kind: &'static str, hash: &ScriptHash) -> HashAnnotation {
+    annotation(path, kind, hash.to_hex(), hash.to_bech32("script").ok())
+}
+
+fn annotation(path: String, kind: &'static str, hex: String, bech32: Option<String>) -> HashAnnotation {
+    HashAnnotation { path, kind, hex, bech32 }
+}
+
+pub(crate) fn redeemer_targets(tx: &Transaction, utxos: &[UtxoInfoResponse]) -> Vec<DecodedRedeemer> {
+    let Some(redeemers) = tx.witness_set().redeemers() else {
+        return Vec::new();
+    };
+    let utxo_addresses = utxo_addresses(utxos);
+    let mut decoded = Vec::new();
+    for i in 0..redeemers.len() {
+        let redeemer = redeemers.get(i);
+        let tag = redeemer_tag_name(redeemer.tag().kind());
+        let index = redeemer.index().to_str().parse().unwrap_or_default();
+        let target = resolve_redeemer_target(tx, &utxo_addresses, tag, index);
+        let ex_units = redeemer.ex_units();
+        decoded.push(DecodedRedeemer {
+            tag: target.tag,
+            index,
+            target: target.description,
+            script_hash: target.script_hash,
+            ex_units: EvaluationExUnits {
+                mem: ex_units.mem().to_str().parse().unwrap_or_default(),
+                steps: ex_units.steps().to_str().parse().unwrap_or_default(),
+            },
+            data: redeemer
+                .data()
+                .to_json(PlutusDatumSchema::DetailedSchema)
+                .ok()
+                .and_then(|json| Value::from_str(&json).ok())
+                .unwrap_or(Value::Null),
+        });
+    }
+    decoded
+}
@@ -0,0 +1,803 @@
+use cardano_serialization_lib::{
+    decode_metadatum_to_json_str, encode_json_str_to_metadatum, hash_auxiliary_data, Address,
+    Anchor, AnchorDataHash, AssetName, AuxiliaryData, BigNum, ByronAddress, Certificate, Certificates, Credential,
+    DRep, DRepKind, DataHash, Ed25519KeyHash, Ed25519KeyHashes, ExUnits, GeneralTransactionMetadata,
+    GovernanceActionId, Int, Language, MetadataJsonSchema, Mint, MintAssets, MultiAsset, NativeScript, NativeScripts,
+    NetworkId, NetworkIdKind, PlutusData, PlutusList, PlutusScript, PlutusScripts, Redeemer, RedeemerTag, Redeemers,
+    RewardAddress, ScriptHash, ScriptRef, Transaction, TransactionBody, TransactionHash,
+    TransactionInput, TransactionInputs, TransactionOutput, TransactionOutputs, TransactionWitnessSet, URL,
+    Value as CslValue, VoteKind, Voter, VotingProcedure, VotingProcedures, VotingProposal, VotingProposals,
+    Withdrawals,
+};
+use pallas_crypto::hash::Hasher;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use crate::bingen::wasm_bindgen;
+use crate::csl_decoders::{certificate_form, stake_cred_to_json};
+use crate::error::ErrorCode;
+use crate::input::sanitize_hex;
+use crate::js_error::JsError;
+use crate::koios_client::models::{EpochParamResponse, UtxoInfoResponse};
+use crate::plutus::evaluation_result::EvaluationExUnits;
+use crate::plutus::script_inventory::{min_fee, used_utxos};
+use crate::plutus::script_purpose::{certificate_credential, certificate_pool, redeemer_tag_name};
+use crate::plutus::tx_rebalance::{set_script_data_hash, settle_fee};
+use crate::plutus::tx_summary::{action_words, certificate_drep};
+use crate::plutus::tx_scripts::collect_tx_scripts;
+use crate::plutus::value_flow::{address_text, utxo_value, AssetQuantity, FlowValue};
+use crate::protocol_params::adapters::parse_protocol_params;
+use crate::schemas::to_versioned_json;
+
+/// A UTxO the tx spends or uses as collateral, `address` and `value` are
+/// set when it's among the given UTxOs.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InputIntent {
+    pub utxo: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<FlowValue>,
+}
+
+/// A payment. `inline_datum` is the datum CBOR, `script_ref` the CBOR of
+/// the script reference.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OutputIntent {
+    pub address: String,
+    pub value: FlowValue,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub datum_hash: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inline_datum: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub script_ref: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WithdrawalIntent {
+    pub reward_address: String,
+    pub amount: u64,
+}
+
+/// `action` is the certificate form name (`stake_delegation`, `reg_cert`,
+/// ...), the other fields what it's about. `cbor` is the certificate itself,
+/// the one field read back when rebuilding a tx.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CertificateIntent {
+    pub action: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credential: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pool_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub drep: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deposit: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refund: Option<u64>,
+    pub cbor: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AnchorIntent {
+    pub url: String,
+    pub data_hash: String,
+}
+
+/// `voter_role` is `constitutional_committee`, `drep` or `stake_pool` and
+/// `voter` its credential, `vote` is `yes`, `no` or `abstain`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct VoteIntent {
+    pub voter_role: String,
+    pub voter: Value,
+    pub action_id: String,
+    pub vote: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anchor: Option<AnchorIntent>,
+}
+
+/// `action` names the governance action kind in words, `cbor` is the whole
+/// proposal procedure.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProposalIntent {
+    pub action: String,
+    pub deposit: u64,
+    pub return_address: String,
+    pub anchor: AnchorIntent,
+    pub cbor: String,
+}
+
+/// A witness script. `hex` is the CBOR of a native script and the flat
+/// bytes of a Plutus one, `language` is `Native`, `PlutusV1`, `PlutusV2` or
+/// `PlutusV3`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ScriptIntent {
+    pub language: String,
+    #[serde(default)]
+    pub hash: String,
+    pub hex: String,
+}
+
+/// `purpose` is `Spend`, `Mint`, `Cert`, `Reward`, `Vote` or `Propose`,
+/// `data` the redeemer CBOR.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RedeemerIntent {
+    pub purpose: String,
+    pub index: u64,
+    pub data: String,
+    pub mem: u64,
+    pub steps: u64,
+}
+
+/// A metadatum in the CSL detailed JSON schema.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MetadataIntent {
+    pub label: u64,
+    pub value: Value,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ValidityIntent {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid_from: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid_until: Option<u64>,
+}
+
+/// What a tx does, free of its encoding: what it spends and pays, mints,
+/// withdraws, certifies, votes and proposes, with the scripts, datums and
+/// redeemers it carries. Hashes that follow from the rest (auxiliary data,
+/// script data) are left out, as are signatures.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct TxIntent {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network_id: Option<u8>,
+    pub spend: Vec<InputIntent>,
+    pub pay: Vec<OutputIntent>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub mint: Vec<AssetQuantity>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub withdrawals: Vec<WithdrawalIntent>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub certificates: Vec<CertificateIntent>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub votes: Vec<VoteIntent>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub proposals: Vec<ProposalIntent>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub reference_inputs: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub collateral: Vec<InputIntent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collateral_return: Option<OutputIntent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_collateral: Option<u64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub required_signers: Vec<String>,
+    pub validity: ValidityIntent,
+    pub fee: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub donation: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_treasury_value: Option<u64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub metadata: Vec<MetadataIntent>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub scripts: Vec<ScriptIntent>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub datums: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub redeemers: Vec<RedeemerIntent>,
+}
+
+/// Extracts the intent of a tx as JSON, to compare what two encodings do
+/// or to rebuild the tx with another builder. `utxo_json` holds spent and
+/// collateral UTxOs in the Koios format to fill in their address and value,
+/// it may be empty.
+#[wasm_bindgen]
+pub fn extract_tx_intent(tx_hex: &str, utxo_json: &str) -> Result<String, JsError> {
+    let tx = Transaction::from_hex(sanitize_hex(tx_hex)?)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding transaction: {:?}", e)))?;
+    let utxos: Vec<UtxoInfoResponse> = if utxo_json.trim().is_empty() {
+        Vec::new()
+    } else {
+        serde_json::from_str(utxo_json).map_err(|e| ErrorCode::InvalidInput.error(&e.to_string()))?
+    };
+    to_versioned_json(&tx_intent(&tx, &utxos)?)
+}
+
+pub(crate) fn tx_intent(tx: &Transaction, utxos: &[UtxoInfoResponse]) -> Result<TxIntent, JsError> {
+    let body = tx.body();
+    let mut intent = TxIntent {
+        network_id: body.network_id().map(|id| match id.kind() {
+            NetworkIdKind::Mainnet => 1,
+            NetworkIdKind::Testnet => 0,
+        }),
+        spend: input_intents(&body.inputs(), utxos)?,
+        collateral: match body.collateral() {
+            Some(collateral) => input_intents(&collateral, utxos)?,
+            None => Vec::new(),
+        },
+        collateral_return: body.collateral_return().map(|output| output_intent(&output)),
+        total_collateral: body.total_collateral().map(|c| coin(&c)),
+        validity: ValidityIntent {
+            valid_from: body.validity_start_interval_bignum().map(|s| coin(&s)),
+            valid_until: body.ttl_bignum().map(|s| coin(&s)),
+        },
+        fee: coin(&body.fee()),
+        donation: body.donation().map(|d| coin(&d)),
+        current_treasury_value: body.current_treasury_value().map(|v| coin(&v)),
+        ..TxIntent::default()
+    };
+
+    let outputs = body.outputs();
+    intent.pay = (0..outputs.len()).map(|i| output_intent(&outputs.get(i))).collect();
+
+    if let Some(mint) = body.mint() {
+        let policies = mint.keys();
+        for i in 0..policies.len() {
+            let policy = policies.get(i);
+            let Some(mints_assets) = mint.get(&policy) else {
+                continue;
+            };
+            for j in 0..mints_assets.len() {
+                let Some(assets) = mints_assets.get(j) else {
+                    continue;
+                };
+                let names = assets.keys();
+                for k in 0..names.len() {
+                    let name = names.get(k);
+                    intent.mint.push(AssetQuantity {
+                        policy_id: policy.to_hex(),
+                        asset_name: hex::encode(name.name()),
+                        quantity: assets.get(&name).and_then(|q| q.to_str().parse().ok()).unwrap_or_default(),
+                    });
+                }
+            }
+        }
+    }
+
+    if let Some(withdrawals) = body.withdrawals() {
+        let keys = withdrawals.keys();
+        for i in 0..keys.len() {
+            let reward_address = keys.get(i);
+            intent.withdrawals.push(WithdrawalIntent {
+                amount: withdrawals.get(&reward_address).map(|c| coin(&c)).unwrap_or_default(),
+                reward_address: address_text(&reward_address.to_address()),
+            });
+        }
+    }
+
+    if let Some(certs) = body.certs() {
+        intent.certificates = (0..certs.len()).map(|i| certificate_intent(&certs.get(i))).collect();
+    }
+
+    if let Some(voting_procedures) = body.voting_procedures() {
+        let voters = voting_procedures.get_voters();
+        for i in 0..voters.len() {
+            let Some(voter) = voters.get(i) else {
+                continue;
+            };
+            let action_ids = voting_procedures.get_governance_action_ids_by_voter(&voter);
+            for j in 0..action_ids.len() {
+                let Some(action_id) = action_ids.get(j) else {
+                    continue;
+                };
+                let Some(procedure) = voting_procedures.get(&voter, &action_id) else {
+                    continue;
+                };
+                let (voter_role, voter_credential) = voter_parts(&voter);
+                intent.votes.push(VoteIntent {
+                    voter_role: voter_role.to_string(),
+                    voter: voter_credential,
+                    action_id: format!("{}#{}", action_id.transaction_id().to_hex(), action_id.index()),
+                    vote: match procedure.vote_kind() {
+                        VoteKind::Yes => "yes",
+                        VoteKind::No => "no",
+                        VoteKind::Abstain => "abstain",
+                    }
+                    .to_string(),
+                    anchor: procedure.anchor().map(|anchor| anchor_intent(&anchor)),
+                });
+            }
+        }
+    }
+
+    if let Some(proposals) = body.voting_proposals() {
+        for i in 0..proposals.len() {
+            let proposal = proposals.get(i);
+            intent.proposals.push(ProposalIntent {
+                action: action_words(&format!("{:?}", proposal.governance_action().kind())),
+                deposit: coin(&proposal.deposit()),
+                return_address: address_text(&proposal.reward_account().to_address()),
+                anchor: anchor_intent(&proposal.anchor()),
+                cbor: proposal.to_hex(),
+            });
+        }
+    }
+
+    if let Some(inputs) = body.reference_inputs() {
+        intent.reference_inputs = (0..inputs.len()).map(|i| input_key(&inputs.get(i))).collect();
+    }
+    if let Some(signers) = body.required_signers() {
+        intent.required_signers = (0..signers.len()).map(|i| signers.get(i).to_hex()).collect();
+    }
+
+    if let Some(metadata) = tx.auxiliary_data().and_then(|aux| aux.metadata()) {
+        let labels = metadata.keys();
+        for i in 0..labels.len() {
+            let label = labels.get(i);
+            let Some(metadatum) = metadata.get(&label) else {
+                continue;
+            };
+            let json = decode_metadatum_to_json_str(&metadatum, MetadataJsonSchema::DetailedSchema)
+                .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding metadatum {}: {:?}", label.to_str(), e)))?;
+            intent.metadata.push(MetadataIntent {
+                label: coin(&label),
+                value: serde_json::from_str(&json).map_err(|e| ErrorCode::Internal.error(&e.to_string()))?,
+            });
+        }
+    }
+
+    let witness_set = tx.witness_set();
+    if let Some(scripts) = witness_set.native_scripts() {
+        for i in 0..scripts.len() {
+            let script = scripts.get(i);
+            intent.scripts.push(ScriptIntent {
+                language: "Native".to_string(),
+                hash: script.hash().to_hex(),
+                hex: script.to_hex(),
+            });
+        }
+    }
+    if let Some(scripts) = witness_set.plutus_scripts() {
+        for i in 0..scripts.len() {
+            let script = scripts.get(i);
+            intent.scripts.push(ScriptIntent {
+                language: format!("{:?}", script.language_version().kind()),
+                hash: script.hash().to_hex(),
+                hex: hex::encode(script.bytes()),
+            });
+        }
+    }
+    if let Some(datums) = witness_set.plutus_data() {
+        intent.datums = (0..datums.len()).map(|i| datums.get(i).to_hex()).collect();
+    }
+    if let Some(redeemers) = witness_set.redeemers() {
+        for i in 0..redeemers.len() {
+            let redeemer = redeemers.get(i);
+            let ex_units = redeemer.ex_units();
+            intent.redeemers.push(RedeemerIntent {
+                purpose: redeemer_tag_name(redeemer.tag().kind()).to_string(),
+                index: coin(&redeemer.index()),
+                data: redeemer.data().to_hex(),
+                mem: coin(&ex_units.mem()),
+                steps: coin(&ex_units.steps()),
+            });
+        }
+    }
+    Ok(intent)
+}
+
+fn input_intents(inputs: &TransactionInputs, utxos: &[UtxoInfoResponse]) -> Result<Vec<InputIntent>, JsError> {
+    let mut intents = Vec::new();
+    for i in 0..inputs.len() {
+        let key = input_key(&inputs.get(i));
+        let utxo = utxos.iter().find(|u| format!("{}#{}", u.tx_hash, u.tx_index) == key);
+        intents.push(InputIntent {
+            address: utxo.map(|u| u.address.clone()),
+            value: utxo.map(utxo_value).transpose()?,
+            utxo: key,
+        });
+    }
+    Ok(intents)
+}
+
+fn input_key(input: &TransactionInput) -> String {
+    format!("{}#{}", input.transaction_id().to_hex(), input.index())
+}
+
+fn output_intent(output: &TransactionOutput) -> OutputIntent {
+    let mut value = FlowValue::default();
+    value.add_output(output);
+    OutputIntent {
+        address: address_text(&output.address()),
+        value,
+        datum_hash: output.data_hash().map(|hash| hash.to_hex()),
+        inline_datum: output.plutus_data().map(|data| data.to_hex()),
+        script_ref: output.script_ref().map(|script_ref| script_ref.to_hex()),
+    }
+}
+
+fn certificate_intent(cert: &Certificate) -> CertificateIntent {
+    let form = certificate_form(cert);
+    CertificateIntent {
+        action: form.name.to_string(),
+        credential: certificate_credential(cert).map(|c| stake_cred_to_json(&c)),
+        pool_id: certificate_pool(cert).map(|pool| pool.to_bech32("pool").unwrap_or_else(|_| pool.to_hex())),
+        drep: certificate_drep(cert).map(|drep| drep_json(&drep)),
+        deposit: form.deposit,
+        refund: form.refund,
+        cbor: cert.to_hex(),
+    }
+}
+
+fn drep_json(drep: &DRep) -> Value {
+    match drep.kind() {
+        DRepKind::AlwaysAbstain => json!({ "kind": "always_abstain" }),
+        DRepKind::AlwaysNoConfidence => json!({ "kind": "always_no_confidence" }),
+        DRepKind::KeyHash => json!({ "key_hash": drep.to_key_hash().map(|h| h.to_hex()) }),
+        DRepKind::ScriptHash => json!({ "script_hash": drep.to_script_hash().map(|h| h.to_hex()) }),
+    }
+}
+
+fn voter_parts(voter: &Voter) -> (&'static str, Value) {
+    if let Some(credential) = voter.to_constitutional_committee_hot_credential() {
+        ("constitutional_committee", stake_cred_to_json(&credential))
+    } else if let Some(credential) = voter.to_drep_credential() {
+        ("drep", stake_cred_to_json(&credential))
+    } else {
+        let pool = voter.to_stake_pool_key_hash().map(|p| p.to_hex()).unwrap_or_default();
+        ("stake_pool", json!({ "key_hash": pool }))
+    }
+}
+
+fn anchor_intent(anchor: &Anchor) -> AnchorIntent {
+    AnchorIntent { url: anchor.url().url(), data_hash: anchor.anchor_data_hash().to_hex() }
+}
+
+fn coin(value: &BigNum) -> u64 {
+    value.to_str().parse().unwrap_or_default()
+}
+
+/// An unsigned tx rebuilt from an intent. `min_fee` is the minimum for the
+/// tx as built, without the vkey witnesses it still needs, and is the `fee`
+/// when the intent states none.
+#[derive(Serialize, Debug, Clone)]
+pub struct BuiltTx {
+    pub tx_hex: String,
+    pub tx_hash: String,
+    pub fee: u64,
+    pub min_fee: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auxiliary_data_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub script_data_hash: Option<String>,
+}
+
+/// Builds the unsigned tx of an intent (the output of `extract_tx_intent`)
+/// in canonical order, inputs sorted, with its auxiliary data and script
+/// data hashes computed. Only `pay` and `spend` are required. `utxo_json`
+/// holds the referenced UTxOs in the Koios format, for the languages and
+/// fee of reference scripts, and may be empty. Address and value of spent
+/// UTxOs are ignored, the tx doesn't carry them.
+#[wasm_bindgen]
+pub fn build_tx_from_intent(intent_json: &str, protocol_params_json: &str, utxo_json: &str) -> Result<String, JsError> {
+    let intent: TxIntent = serde_json::from_str(intent_json)
+        .map_err(|e| ErrorCode::InvalidInput.error(&format!("Invalid intent: {}", e)))?;
+    let protocol_params = parse_protocol_params(protocol_params_json)?;
+    let utxos: Vec<UtxoInfoResponse> = if utxo_json.trim().is_empty() {
+        Vec::new()
+    } else {
+        serde_json::from_str(utxo_json).map_err(|e| ErrorCode::InvalidInput.error(&e.to_string()))?
+    };
+    to_versioned_json(&build_tx(&intent, &protocol_params, utxos)?)
+}
+
+pub(crate) fn build_tx(intent: &TxIntent, pp: &EpochParamResponse, utxos: Vec<UtxoInfoResponse>) -> Result<BuiltTx, JsError> {
+    let mut outputs = TransactionOutputs::new();
+    for output in &intent.pay {
+        outputs.add(&build_output(output)?);
+    }
+    let mut body = TransactionBody::new_tx_body(&build_inputs(&intent.spend.iter().map(|i| &i.utxo))?, &outputs, &BigNum::zero());
+
+    if let Some(network_id) = intent.network_id {
+        body.set_network_id(&if network_id == 1 { NetworkId::mainnet() } else { NetworkId::testnet() });
+    }
+    if let Some(valid_from) = intent.validity.valid_from {
+        body.set_validity_start_interval_bignum(BigNum::from(valid_from));
+    }
+    if let Some(valid_until) = intent.validity.valid_until {
+        body.set_ttl(&BigNum::from(valid_until));
+    }
+
+    if !intent.mint.is_empty() {
+        let mut policies: Vec<&str> = Vec::new();
+        for asset in &intent.mint {
+            if !policies.contains(&asset.policy_id.as_str()) {
+                policies.push(&asset.policy_id);
+            }
+        }
+        let mut mint = Mint::new();
+        for policy in policies {
+            let mut assets = MintAssets::new();
+            for asset in intent.mint.iter().filter(|m| m.policy_id == policy) {
+                let quantity = Int::from_str(&asset.quantity.to_string())
+                    .map_err(|e| ErrorCode::InvalidInput.error(&format!("Invalid mint quantity {}: {:?}", asset.quantity, e)))?;
+                assets
+                    .insert(&asset_name(&asset.asset_name)?, &quantity)
+                    .map_err(|e| ErrorCode::InvalidInput.error(&format!("Invalid mint of {}: {:?}", asset.asset_name, e)))?;
+            }
+            mint.insert(&parse_hex(policy, ScriptHash::from_hex, "policy id")?, &assets);
+        }
+        body.set_mint(&mint);
+    }
+
+    if !intent.withdrawals.is_empty() {
+        let mut withdrawals = Withdrawals::new();
+        for withdrawal in &intent.withdrawals {
+            let reward_address = RewardAddress::from_address(&parse_address(&withdrawal.reward_address)?).ok_or_else(|| {
+                ErrorCode::InvalidInput.error(&format!("{} isn't a reward address", withdrawal.reward_address))
+            })?;
+            withdrawals.insert(&reward_address, &BigNum::from(withdrawal.amount));
+        }
+        body.set_withdrawals(&withdrawals);
+    }
+
+    if !intent.certificates.is_empty() {
+        let mut certs = Certificates::new();
+        for cert in &intent.certificates {
+            certs.add(&parse_hex(&cert.cbor, Certificate::from_hex, "certificate")?);
+        }
+        body.set_certs(&certs);
+    }
+
+    if !intent.votes.is_empty() {
+        let mut voting_procedures = VotingProcedures::new();
+        for vote in &intent.votes {
+            let credential = parse_credential(&vote.voter)?;
+            let voter = match vote.voter_role.as_str() {
+                "constitutional_committee" => Voter::new_constitutional_committee_hot_credential(&credential),
+                "drep" => Voter::new_drep_credential(&credential),
+                "stake_pool" => match credential.to_keyhash() {
+                    Some(pool) => Voter::new_stake_pool_key_hash(&pool),
+                    None => return Err(ErrorCode::InvalidInput.error("A stake pool votes with a key hash")),
+                },
+                role => return Err(ErrorCode::InvalidInput.error(&format!("Invalid voter role: {}", role))),
+            };
+            let (tx_hash, index) = parse_utxo_ref(&vote.action_id)?;
+            let kind = match vote.vote.as_str() {
+                "yes" => VoteKind::Yes,
+                "no" => VoteKind::No,
+                "abstain" => VoteKind::Abstain,
+                other => return Err(ErrorCode::InvalidInput.error(&format!("Invalid vote: {}, expected yes, no or abstain", other))),
+            };
+            let procedure = match &vote.anchor {
+                Some(anchor) => VotingProcedure::new_with_anchor(kind, &build_anchor(anchor)?),
+                None => VotingProcedure::new(kind),
+            };
+            voting_procedures.insert(&voter, &GovernanceActionId::new(&tx_hash, index), &procedure);
+        }
+        body.set_voting_procedures(&voting_procedures);
+    }
+
+    if !intent.proposals.is_empty() {
+        let mut proposals = VotingProposals::new();
+        for proposal in &intent.proposals {
+            proposals.add(&parse_hex(&proposal.cbor, VotingProposal::from_hex, "proposal")?);
+        }
+        body.set_voting_proposals(&proposals);
+    }
+
+    if !intent.reference_inputs.is_empty() {
+        body.set_reference_inputs(&build_inputs(intent.reference_inputs.iter())?);
+    }
+    if !intent.collateral.is_empty() {
+        body.set_collateral(&build_inputs(intent.collateral.iter().map(|i| &i.utxo))?);
+    }
+    if let Some(collateral_return) = &intent.collateral_return {
+        body.set_collateral_return(&build_output(collateral_return)?);
+    }
+    if let Some(total_collateral) = intent.total_collateral {
+        body.set_total_collateral(&BigNum::from(total_collateral));
+    }
+    if !intent.required_signers.is_empty() {
+        let mut signers = Ed25519KeyHashes::new();
+        for signer in &intent.required_signers {
+            signers.add(&parse_hex(signer, Ed25519KeyHash::from_hex, "required signer")?);
+        }
+        body.set_required_signers(&signers);
+    }
+    if let Some(donation) = intent.donation {
+        body.set_donation(&BigNum::from(donation));
+    }
+    if let Some(current_treasury_value) = intent.current_treasury_value {
+        body.set_current_treasury_value(&BigNum::from(current_treasury_value));
+    }
+
+    let auxiliary_data = if intent.metadata.is_empty() {
+        None
+    } else {
+        let mut metadata = GeneralTransactionMetadata::new();
+        for entry in &intent.metadata {
+            let metadatum = encode_json_str_to_metadatum(entry.value.to_string(), MetadataJsonSchema::DetailedSchema)
+                .map_err(|e| ErrorCode::InvalidInput.error(&format!("Invalid metadatum {}: {:?}", entry.label, e)))?;
+            metadata.insert(&BigNum::from(entry.label), &metadatum);
+        }
+        let mut auxiliary_data = AuxiliaryData::new();
+        auxiliary_data.set_metadata(&metadata);
+        body.set_auxiliary_data_hash(&hash_auxiliary_data(&auxiliary_data));
+        Some(auxiliary_data)
+    };
+
+    let witness_set = build_witness_set(intent)?;
+    let tx = Transaction::new(&body, &witness_set, auxiliary_data.clone());
+    let utxos = used_utxos(&tx, utxos);
+    let scripts = collect_tx_scripts(&tx, &utxos);
+    let script_data_hash = set_script_data_hash(&mut body, &tx, &scripts, &utxos, pp)?;
+
+    let ex_units = intent.redeemers.iter().fold(EvaluationExUnits { mem: 0, steps: 0 }, |total, r| EvaluationExUnits {
+        mem: total.mem + r.mem,
+        steps: total.steps + r.steps,
+    });
+    let reference_size: usize = scripts.iter().filter(|script| script.source == "reference").map(|script| script.size).sum();
+    let mut min_fee_with = |fee: u64| {
+        body.set_fee(&BigNum::from(fee));
+        let size = Transaction::new(&body, &witness_set, auxiliary_data.clone()).to_bytes().len() as u64;
+        min_fee(pp, size, &ex_units, reference_size as u64)
+    };
+    let (fee, required_fee) = if intent.fee != 0 {
+        (intent.fee, min_fee_with(intent.fee))
+    } else {
+        let fee = settle_fee(0, |fee| Ok(min_fee_with(fee)))?;
+        (fee, fee)
+    };
+    body.set_fee(&BigNum::from(fee));
+
+    let tx = Transaction::new(&body, &witness_set, auxiliary_data);
+    Ok(BuiltTx {
+        tx_hex: tx.to_hex(),
+        tx_hash: Hasher::<256>::hash(&body.to_bytes()).to_string(),
+        fee,
+        min_fee: required_fee,
+        auxiliary_data_hash: body.auxiliary_data_hash().map(|hash| hash.to_hex()),
+        script_data_hash,
+    })
+}
+
+fn build_witness_set(intent: &TxIntent) -> Result<TransactionWitnessSet, JsError> {
+    let mut witness_set = TransactionWitnessSet::new();
+    let mut native_scripts = NativeScripts::new();
+    let mut plutus_scripts = PlutusScripts::new();
+    for script in &intent.scripts {
+        let language = match script.language.as_str() {
+            "Native" => {
+                native_scripts.add(&parse_hex(&script.hex, NativeScript::from_hex, "native script")?);
+                continue;
+            }
+            "PlutusV1" => Language::new_plutus_v1(),
+            "PlutusV2" => Language::new_plutus_v2(),
+            "PlutusV3" => Language::new_plutus_v3(),
+            other => return Err(ErrorCode::InvalidInput.error(&format!("Invalid script language: {}", other))),
+        };
+        let bytes = hex::decode(&script.hex)
+            .map_err(|e| ErrorCode::InvalidInput.error(&format!("Invalid script hex: {}", e)))?;
+        plutus_scripts.add(&PlutusScript::new_with_version(bytes, &language));
+    }
+    if native_scripts.len() > 0 {
+        witness_set.set_native_scripts(&native_scripts);
+    }
+    if plutus_scripts.len() > 0 {
+        witness_set.set_plutus_scripts(&plutus_scripts);
+    }
+
+    if !intent.datums.is_empty() {
+        let mut datums = PlutusList::new();
+        for datum in &intent.datums {
+            datums.add(&parse_hex(datum, PlutusData::from_hex, "datum")?);
+        }
+        witness_set.set_plutus_data(&datums);
+    }
+    if !intent.redeemers.is_empty() {
+        let mut redeemers = Redeemers::new();
+        for redeemer in &intent.redeemers {
+            let tag = match redeemer.purpose.as_str() {
+                "Spend" => RedeemerTag::new_spend(),
+                "Mint" => RedeemerTag::new_mint(),
+                "Cert" => RedeemerTag::new_cert(),
+                "Reward" => RedeemerTag::new_reward(),
+                "Vote" => RedeemerTag::new_vote(),
+                "Propose" => RedeemerTag::new_voting_proposal(),
+                other => return Err(ErrorCode::InvalidInput.error(&format!("Invalid redeemer purpose: {}", other))),
+            };
+            redeemers.add(&Redeemer::new(
+                &tag,
+                &BigNum::from(redeemer.index),
+                &parse_hex(&redeemer.data, PlutusData::from_hex, "redeemer")?,
+                &ExUnits::new(&BigNum::from(redeemer.mem), &BigNum::from(redeemer.steps)),
+            ));
+        }
+        witness_set.set_redeemers(&redeemers);
+    }
+    Ok(witness_set)
+}
+
+/// Inputs in ledger order, by tx hash then index, the order redeemer
+/// indexes refer to.
+fn build_inputs<'a>(utxo_refs: impl Iterator<Item = &'a String>) -> Result<TransactionInputs, JsError> {
+    let mut parsed = Vec::new();
+    for utxo_ref in utxo_refs {
+        let (tx_hash, index) = parse_utxo_ref(utxo_ref)?;
+        parsed.push((tx_hash.to_bytes(), index));
+    }
+    parsed.sort();
+    parsed.dedup();
+    let mut inputs = TransactionInputs::new();
+    for (tx_hash, index) in parsed {
+        let tx_hash = TransactionHash::from_bytes(tx_hash)
+            .map_err(|e| ErrorCode::Internal.error(&format!("{:?}", e)))?;
+        inputs.add(&TransactionInput::new(&tx_hash, index));
+    }
+    Ok(inputs)
+}
+
+fn build_output(intent: &OutputIntent) -> Result<TransactionOutput, JsError> {
+    let value = &intent.value;
+    let mut amount = CslValue::new(&positive(value.lovelace, "lovelace")?);
+    if !value.assets.is_empty() {
+        let mut multiasset = MultiAsset::new();
+        for asset in &value.assets {
+            let policy = parse_hex(&asset.policy_id, ScriptHash::from_hex, "policy id")?;
+            multiasset.set_asset(&policy, &asset_name(&asset.asset_name)?, &positive(asset.quantity, &asset.asset_name)?);
+        }
+        amount.set_multiasset(&multiasset);
+    }
+    let mut output = TransactionOutput::new(&parse_address(&intent.address)?, &amount);
+    if let Some(datum) = &intent.inline_datum {
+        output.set_plutus_data(&parse_hex(datum, PlutusData::from_hex, "inline datum")?);
+    } else if let Some(hash) = &intent.datum_hash {
+        output.set_data_hash(&parse_hex(hash, DataHash::from_hex, "datum hash")?);
+    }
+    if let Some(script_ref) = &intent.script_ref {
+        output.set_script_ref(&parse_hex(script_ref, ScriptRef::from_hex, "script reference")?);
+    }
+    Ok(output)
+}
+
+fn build_anchor(anchor: &AnchorIntent) -> Result<Anchor, JsError> {
+    let url = URL::new(anchor.url.clone())
+        .map_err(|e| ErrorCode::InvalidInput.error(&format!("Invalid anchor url {}: {:?}", anchor.url, e)))?;
+    Ok(Anchor::new(&url, &parse_hex(&anchor.data_hash, AnchorDataHash::from_hex, "anchor data hash")?))
+}
+
+/// `{"key_hash": ...}` or `{"script_hash": ...}`, as `stake_cred_to_json` writes it.
+fn parse_credential(value: &Value) -> Result<Credential, JsError> {
+    if let Some(key_hash) = value.get("key_hash").and_then(Value::as_str) {
+        Ok(Credential::from_keyhash(&parse_hex(key_hash, Ed25519KeyHash::from_hex, "key hash")?))
+    } else if let Some(script_hash) = value.get("script_hash").and_then(Value::as_str) {
+        Ok(Credential::from_scripthash(&parse_hex(script_hash, ScriptHash::from_hex, "script hash")?))
+    } else {
+        Err(ErrorCode::InvalidInput.error(&format!("Invalid credential: {}", value)))
+    }
+}
+
+fn parse_address(address: &str) -> Result<Address, JsError> {
+    Address::from_bech32(address)
+        .or_else(|_| ByronAddress::from_base58(address).map(|byron| byron.to_address()))
+        .map_err(|e| ErrorCode::InvalidInput.error(&format!("Invalid address {}: {:?}", address, e)))
+}
+
+fn parse_utxo_ref(utxo_ref: &str) -> Result<(TransactionHash, u32), JsError> {
+    let invalid = || ErrorCode::InvalidInput.error(&format!("Invalid UTxO reference {}, expected tx_hash#index", utxo_ref));
+    let (tx_hash, index) = utxo_ref.split_once('#').ok_or_else(invalid)?;
+    let tx_hash = TransactionHash::from_hex(tx_hash).map_err(|_| invalid())?;
+    Ok((tx_hash, index.parse().map_err(|_| invalid())?))
+}
+
+fn asset_name(name_hex: &str) -> Result<AssetName, JsError> {
+    hex::decode(name_hex)
+        .map_err(|e| e.to_string())
+        .and_then(|bytes| AssetName::new(bytes).map_err(|e| format!("{:?}", e)))
+        .map_err(|e| ErrorCode::InvalidInput.error(&format!("Invalid asset name {}: {}", name_hex, e)))
+}
+
+fn positive(quantity: i128, unit: &str) -> Result<BigNum, JsError> {
+    u64::try_from(quantity)
+        .map(BigNum::from)
+        .map_err(|_| ErrorCode::InvalidInput.error(&format!("Invalid output quantity {} of {}", quantity, unit)))
+}
+
+fn parse_hex<T, E: std::fmt::Debug>(hex: &str, parse: impl Fn(&str) -> Result<T, E>, what: &str) -> Result<T, JsError> {
+    parse(hex).map_err(|e| ErrorCode::InvalidInput.error(&format!("Invalid {} {}: {:?}", what, hex, e)))
+}
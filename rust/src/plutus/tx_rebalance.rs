@@ -0,0 +1,217 @@
+use cardano_serialization_lib::{
+    BigNum, Redeemers, ScriptDataHash, Transaction, TransactionBody, TransactionOutput, TransactionOutputs,
+    TransactionWitnessSet,
+};
+use serde::{Deserialize, Serialize};
+use crate::bingen::wasm_bindgen;
+use crate::error::ErrorCode;
+use crate::input::sanitize_hex;
+use crate::js_error::JsError;
+use crate::koios_client::models::{EpochParamResponse, UtxoInfoResponse};
+use crate::plutus::evaluation_result::RedeemerEvaluation;
+use crate::plutus::ex_unit_margins::{suggest_ex_units, SuggestedExUnits};
+use crate::plutus::script_integrity::{needed_languages, script_integrity};
+use crate::plutus::script_inventory::{min_fee, used_utxos};
+use crate::plutus::tx_scripts::{collect_tx_scripts, ExtractedScript};
+use crate::protocol_params::adapters::parse_protocol_params;
+use crate::schemas::to_versioned_json;
+
+// the fee is part of the size it pays for, a few rounds settle its encoding
+const MAX_FEE_ROUNDS: usize = 4;
+
+/// `utxos` are the spent and referenced UTxOs (Koios `utxo_info` items), for
+/// the languages and size of reference scripts. With `adjust_change` the fee
+/// difference is taken from or returned to `change_output`, by default the
+/// last output.
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub(crate) struct RebalanceOptions {
+    pub margin_percent: f64,
+    pub adjust_change: bool,
+    pub change_output: Option<u32>,
+    pub utxos: Vec<UtxoInfoResponse>,
+}
+
+/// The patched tx without its vkey and bootstrap witnesses, to be signed
+/// again. The fee counts the `vkey_witnesses` the tx had, so a tx evaluated
+/// before signing pays for none. Collateral isn't touched, a higher fee may
+/// need more of it.
+#[derive(Serialize, Debug, Clone)]
+pub struct RebalancedTx {
+    pub tx_hex: String,
+    pub original_fee: u64,
+    pub fee: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub script_data_hash: Option<String>,
+    pub redeemers: Vec<SuggestedExUnits>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change_output: Option<u32>,
+    pub vkey_witnesses: usize,
+}
+
+#[derive(Deserialize)]
+struct EvaluationResults {
+    results: Vec<RedeemerEvaluation>,
+}
+
+/// Rewrites the redeemer ex units of a tx to those of its evaluation (the
+/// output of any of the `execute_tx_scripts` functions), recomputes the
+/// script data hash and sets the fee to the new minimum. `options_json` is
+/// `RebalanceOptions`, may be empty.
+#[wasm_bindgen]
+pub fn rebalance_tx_budgets(
+    tx_hex: &str,
+    evaluation_json: &str,
+    protocol_params_json: &str,
+    options_json: &str,
+) -> Result<String, JsError> {
+    let tx = Transaction::from_hex(sanitize_hex(tx_hex)?)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding transaction: {:?}", e)))?;
+    let evaluation: EvaluationResults = serde_json::from_str(evaluation_json)
+        .map_err(|e| ErrorCode::InvalidInput.error(&format!("Invalid evaluation: {}", e)))?;
+    let protocol_params = parse_protocol_params(protocol_params_json)?;
+    let options: RebalanceOptions = if options_json.trim().is_empty() {
+        RebalanceOptions::default()
+    } else {
+        serde_json::from_str(options_json)
+            .map_err(|e| ErrorCode::InvalidInput.error(&format!("Invalid rebalance options: {}", e)))?
+    };
+    to_versioned_json(&rebalance(&tx, &evaluation.results, &protocol_params, options)?)
+}
+
+pub(crate) fn rebalance(
+    tx: &Transaction,
+    results: &[RedeemerEvaluation],
+    pp: &EpochParamResponse,
+    options: RebalanceOptions,
+) -> Result<RebalancedTx, JsError> {
+    let suggestions = suggest_ex_units(tx, results, options.margin_percent)?;
+    let redeemers = Redeemers::from_hex(&suggestions.redeemers_cbor)
+        .map_err(|e| ErrorCode::Internal.error(&format!("Error rebuilding redeemers: {:?}", e)))?;
+    let mut witness_set = tx.witness_set();
+    witness_set.set_redeemers(&redeemers);
+
+    let utxos = used_utxos(tx, options.utxos);
+    let scripts = collect_tx_scripts(tx, &utxos);
+    let reference_size: usize = scripts.iter().filter(|script| script.source == "reference").map(|script| script.size).sum();
+
+    let mut body = tx.body();
+    let patched = with_parts(tx, &body, &witness_set);
+    let script_data_hash = set_script_data_hash(&mut body, &patched, &scripts, &utxos, pp)?;
+
+    let outputs = body.outputs();
+    let change_output = match (options.adjust_change, options.change_output) {
+        (false, _) => None,
+        (true, Some(index)) if (index as usize) < outputs.len() => Some(index),
+        (true, Some(index)) => return Err(ErrorCode::InvalidInput.error(&format!("Transaction has no output {}", index))),
+        (true, None) if outputs.len() > 0 => Some(outputs.len() as u32 - 1),
+        (true, None) => return Err(ErrorCode::InvalidInput.error("Transaction has no output to take the fee from")),
+    };
+    let original_fee: u64 = body.fee().to_str().parse().unwrap_or_default();
+    let original_change: u64 = change_output
+        .map(|index| outputs.get(index as usize).amount().coin().to_str().parse().unwrap_or_default())
+        .unwrap_or_default();
+
+    let set_fee = |body: &mut TransactionBody, fee: u64| -> Result<(), JsError> {
+        body.set_fee(&BigNum::from(fee));
+        if let Some(index) = change_output {
+            let change = (original_change + original_fee).checked_sub(fee).ok_or_else(|| {
+                ErrorCode::InvalidInput.error(&format!("Change output {} can't cover a fee of {}", index, fee))
+            })?;
+            body.set_outputs(&with_change(&outputs, index as usize, change));
+        }
+        Ok(())
+    };
+    let fee = settle_fee(original_fee, |fee| {
+        set_fee(&mut body, fee)?;
+        let size = with_parts(tx, &body, &witness_set).to_bytes().len() as u64;
+        Ok(min_fee(pp, size, &suggestions.total, reference_size as u64))
+    })?;
+    // no-op unless the rounds ran out
+    set_fee(&mut body, fee)?;
+
+    let mut unsigned = TransactionWitnessSet::new();
+    if let Some(native_scripts) = witness_set.native_scripts() {
+        unsigned.set_native_scripts(&native_scripts);
+    }
+    if let Some(plutus_scripts) = witness_set.plutus_scripts() {
+        unsigned.set_plutus_scripts(&plutus_scripts);
+    }
+    if let Some(plutus_data) = witness_set.plutus_data() {
+        unsigned.set_plutus_data(&plutus_data);
+    }
+    unsigned.set_redeemers(&redeemers);
+
+    Ok(RebalancedTx {
+        tx_hex: with_parts(tx, &body, &unsigned).to_hex(),
+        original_fee,
+        fee,
+        script_data_hash,
+        redeemers: suggestions.redeemers,
+        change_output,
+        vkey_witnesses: witness_set.vkeys().map_or(0, |vkeys| vkeys.len()),
+    })
+}
+
+/// Sets the script data hash of `body` from the redeemers and datums of
+/// `tx` and the languages of the `scripts` it needs, `None` when it has
+/// neither. `utxos` resolve which scripts the spend redeemers run.
+pub(crate) fn set_script_data_hash(
+    body: &mut TransactionBody,
+    tx: &Transaction,
+    scripts: &[ExtractedScript],
+    utxos: &[UtxoInfoResponse],
+    pp: &EpochParamResponse,
+) -> Result<Option<String>, JsError> {
+    let languages = needed_languages(tx, scripts, utxos)?;
+    let script_data_hash = script_integrity(&tx.to_bytes(), &languages, pp)?.computed_hash;
+    if let Some(hash) = &script_data_hash {
+        let hash = ScriptDataHash::from_hex(hash)
+            .map_err(|e| ErrorCode::Internal.error(&format!("Invalid script data hash: {:?}", e)))?;
+        body.set_script_data_hash(&hash);
+    }
+    Ok(script_data_hash)
+}
+
+/// Settles a fee that pays for its own size, starting from `fee`.
+/// `min_fee_with` puts a fee in the tx and returns the min fee of the result.
+pub(crate) fn settle_fee(mut fee: u64, mut min_fee_with: impl FnMut(u64) -> Result<u64, JsError>) -> Result<u64, JsError> {
+    for _ in 0..MAX_FEE_ROUNDS {
+        let required = min_fee_with(fee)?;
+        if required == fee {
+            break;
+        }
+        fee = required;
+    }
+    Ok(fee)
+}
+
+fn with_parts(tx: &Transaction, body: &TransactionBody, witness_set: &TransactionWitnessSet) -> Transaction {
+    let mut patched = Transaction::new(body, witness_set, tx.auxiliary_data());
+    patched.set_is_valid(tx.is_valid());
+    patched
+}
+
+fn with_change(outputs: &TransactionOutputs, index: usize, change: u64) -> TransactionOutputs {
+    let mut patched = TransactionOutputs::new();
+    for i in 0..outputs.len() {
+        let output = outputs.get(i);
+        if i != index {
+            patched.add(&output);
+            continue;
+        }
+        let mut amount = output.amount();
+        amount.set_coin(&BigNum::from(change));
+        let mut changed = TransactionOutput::new(&output.address(), &amount);
+        if let Some(data) = output.plutus_data() {
+            changed.set_plutus_data(&data);
+        } else if let Some(hash) = output.data_hash() {
+            changed.set_data_hash(&hash);
+        }
+        if let Some(script_ref) = output.script_ref() {
+            changed.set_script_ref(&script_ref);
+        }
+        patched.add(&changed);
+    }
+    patched
+}
@@ -0,0 +1,232 @@
+use std::str::FromStr;
+use cardano_serialization_lib::{
+    hash_plutus_data, NativeScript, PlutusDatumSchema, PlutusScript, Transaction, TransactionOutput,
+    TransactionUnspentOutput,
+};
+use serde::Serialize;
+use serde_json::Value;
+use uplc::ast::{NamedDeBruijn, Program};
+use crate::bingen::wasm_bindgen;
+use crate::error::ErrorCode;
+use crate::input::sanitize_hex;
+use crate::js_error::JsError;
+use crate::schemas::to_versioned_json;
+use crate::koios_client::models::{ScriptInfoResponse, UtxoInfoResponse};
+use crate::plutus::plutus_decoder::decode_program_bytes_unwrapped;
+use crate::smart_data::smart_render;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ExtractedScript {
+    pub source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub utxo: Option<String>,
+    pub script_hash: String,
+    pub language: String,
+    pub size: usize,
+    pub script_hex: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub native_script: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uplc: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decode_error: Option<String>,
+}
+
+/// Lists every script a transaction carries in its witness set and, when the
+/// spent/referenced UTxOs are given (Koios `utxo_info` JSON, may be empty),
+/// the script refs of those outputs. Plutus scripts are decoded to UPLC.
+#[wasm_bindgen]
+pub fn extract_scripts_from_tx(tx_hex: &str, utxo_json: &str) -> Result<String, JsError> {
+    let tx = Transaction::from_hex(sanitize_hex(tx_hex)?)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding transaction: {:?}", e)))?;
+    let utxos: Vec<UtxoInfoResponse> = if utxo_json.trim().is_empty() {
+        Vec::new()
+    } else {
+        serde_json::from_str(utxo_json).map_err(|e| ErrorCode::InvalidInput.error(&e.to_string()))?
+    };
+    let scripts = collect_tx_scripts(&tx, &utxos);
+    to_versioned_json(&scripts)
+}
+
+pub(crate) fn collect_tx_scripts(tx: &Transaction, utxos: &[UtxoInfoResponse]) -> Vec<ExtractedScript> {
+    let mut scripts = Vec::new();
+    let witness_set = tx.witness_set();
+
+    if let Some(native_scripts) = witness_set.native_scripts() {
+        for i in 0..native_scripts.len() {
+            scripts.push(native_to_extracted(&native_scripts.get(i), "witness", None));
+        }
+    }
+    if let Some(plutus_scripts) = witness_set.plutus_scripts() {
+        for i in 0..plutus_scripts.len() {
+            scripts.push(plutus_to_extracted(&plutus_scripts.get(i), "witness", None));
+        }
+    }
+
+    for utxo in utxos {
+        let Some(reference_script) = &utxo.reference_script else {
+            continue;
+        };
+        let utxo_ref = Some(format!("{}#{}", utxo.tx_hash, utxo.tx_index));
+        let bytes = match hex::decode(&reference_script.bytes) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                scripts.push(undecodable_reference(utxo_ref, reference_script, e.to_string()));
+                continue;
+            }
+        };
+        let extracted = match reference_script.script_type.as_str() {
+            "nativeScript" | "timelock" | "multisig" => NativeScript::from_bytes(bytes)
+                .map(|script| native_to_extracted(&script, "reference", utxo_ref.clone()))
+                .map_err(|e| format!("{:?}", e)),
+            "plutusV1" => Ok(plutus_to_extracted(&PlutusScript::new(bytes), "reference", utxo_ref.clone())),
+            "plutusV2" => Ok(plutus_to_extracted(&PlutusScript::new_v2(bytes), "reference", utxo_ref.clone())),
+            "plutusV3" => Ok(plutus_to_extracted(&PlutusScript::new_v3(bytes), "reference", utxo_ref.clone())),
+            other => Err(format!("Unknown script type {}", other)),
+        };
+        match extracted {
+            Ok(script) => scripts.push(script),
+            Err(e) => scripts.push(undecodable_reference(utxo_ref, reference_script, e)),
+        }
+    }
+
+    scripts
+}
+
+/// An output's inline datum in the detailed CSL schema and the smart
+/// rendering, with its hash. `kind` is `hash` when the output only carries a
+/// datum hash, `data` is then unknown.
+#[derive(Serialize, Debug, Clone)]
+pub struct OutputDatum {
+    pub kind: String,
+    pub hash: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cbor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub smart: Option<Value>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct OutputAttachments {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub datum: Option<OutputDatum>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub script: Option<ExtractedScript>,
+}
+
+/// Decodes the datum and reference script of a single output, given as a
+/// `TransactionOutput` or a `TransactionUnspentOutput`, without the tx that
+/// made it. Plutus scripts are decoded to UPLC.
+#[wasm_bindgen]
+pub fn extract_output_datum_and_script(hex: &str) -> Result<String, JsError> {
+    let hex = sanitize_hex(hex)?;
+    let output = match TransactionOutput::from_hex(hex) {
+        Ok(output) => output,
+        Err(_) => TransactionUnspentOutput::from_hex(hex)
+            .map(|utxo| utxo.output())
+            .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Neither an output nor a UTxO: {:?}", e)))?,
+    };
+    to_versioned_json(&output_attachments(&output))
+}
+
+pub(crate) fn output_attachments(output: &TransactionOutput) -> OutputAttachments {
+    let datum = match (output.plutus_data(), output.data_hash()) {
+        (Some(data), _) => Some(OutputDatum {
+            kind: "inline".to_string(),
+            hash: hash_plutus_data(&data).to_hex(),
+            cbor: Some(data.to_hex()),
+            data: data
+                .to_json(PlutusDatumSchema::DetailedSchema)
+                .ok()
+                .and_then(|json| Value::from_str(&json).ok()),
+            smart: Some(smart_render(&data)),
+        }),
+        (None, Some(hash)) => Some(OutputDatum {
+            kind: "hash".to_string(),
+            hash: hash.to_hex(),
+            cbor: None,
+            data: None,
+            smart: None,
+        }),
+        (None, None) => None,
+    };
+    let script = output.script_ref().and_then(|script_ref| match script_ref.native_script() {
+        Some(native) => Some(native_to_extracted(&native, "output", None)),
+        None => script_ref.plutus_script().map(|plutus| plutus_to_extracted(&plutus, "output", None)),
+    });
+    OutputAttachments { datum, script }
+}
+
+/// A Koios `script_info` item as an extracted script, decoded the same way
+/// as the ones a transaction carries.
+pub(crate) fn script_info_to_extracted(info: &ScriptInfoResponse, source: &str) -> Result<ExtractedScript, JsError> {
+    let bytes = info
+        .bytes
+        .as_ref()
+        .ok_or_else(|| ErrorCode::ProviderError.error(&format!("Provider returned no bytes for script {}", info.script_hash)))
+        .and_then(|bytes| hex::decode(bytes).map_err(|e| ErrorCode::InvalidHex.error(&e.to_string())))?;
+    let script = match info.script_type.as_str() {
+        "plutusV1" => plutus_to_extracted(&PlutusScript::new(bytes), source, None),
+        "plutusV2" => plutus_to_extracted(&PlutusScript::new_v2(bytes), source, None),
+        "plutusV3" => plutus_to_extracted(&PlutusScript::new_v3(bytes), source, None),
+        _ => {
+            let native = NativeScript::from_bytes(bytes)
+                .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Invalid native script {}: {:?}", info.script_hash, e)))?;
+            native_to_extracted(&native, source, None)
+        }
+    };
+    Ok(script)
+}
+
+fn native_to_extracted(script: &NativeScript, source: &str, utxo: Option<String>) -> ExtractedScript {
+    ExtractedScript {
+        source: source.to_string(),
+        utxo,
+        script_hash: script.hash().to_hex(),
+        language: "Native".to_string(),
+        size: script.to_bytes().len(),
+        script_hex: script.to_hex(),
+        native_script: script.to_json().ok().and_then(|json| Value::from_str(&json).ok()),
+        uplc: None,
+        decode_error: None,
+    }
+}
+
+fn plutus_to_extracted(script: &PlutusScript, source: &str, utxo: Option<String>) -> ExtractedScript {
+    let script_bytes = script.bytes();
+    let (uplc, decode_error) = match decode_program_bytes_unwrapped(script_bytes.clone()) {
+        Ok((program, _)) => (Some(Program::<NamedDeBruijn>::from(program).to_pretty()), None),
+        Err(e) => (None, Some(e)),
+    };
+    ExtractedScript {
+        source: source.to_string(),
+        utxo,
+        script_hash: script.hash().to_hex(),
+        language: format!("{:?}", script.language_version().kind()),
+        size: script_bytes.len(),
+        script_hex: hex::encode(&script_bytes),
+        native_script: None,
+        uplc,
+        decode_error,
+    }
+}
+
+fn undecodable_reference(
+    utxo: Option<String>,
+    reference_script: &crate::koios_client::models::ReferenceScript,
+    error: String,
+) -> ExtractedScript {
+    ExtractedScript {
+        source: "reference".to_string(),
+        utxo,
+        script_hash: reference_script.hash.clone(),
+        language: reference_script.script_type.clone(),
+        size: reference_script.size as usize,
+        script_hex: reference_script.bytes.clone(),
+        native_script: None,
+        uplc: None,
+        decode_error: Some(error),
+    }
+}
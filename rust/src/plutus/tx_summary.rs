@@ -0,0 +1,415 @@
+use cardano_serialization_lib::{BigNum, Certificate, Credential, DRep, DRepKind, Ed25519KeyHash, Transaction, VoteKind, Voter};
+use serde::Serialize;
+use crate::bingen::wasm_bindgen;
+use crate::csl_decoders::certificate_form;
+use crate::error::ErrorCode;
+use crate::input::sanitize_hex;
+use crate::js_error::JsError;
+use crate::koios_client::models::UtxoInfoResponse;
+use crate::plutus::script_purpose::{
+    certificate_credential, certificate_pool, redeemer_tag_name, resolve_redeemer_target, utxo_addresses,
+};
+use crate::plutus::value_flow::{address_flows, AddressFlow};
+use crate::schemas::to_versioned_json;
+
+/// `summary` is the `sentences` joined. Value flows need every input among
+/// the given UTxOs, otherwise only the outputs are described and
+/// `missing_inputs` lists the unknown ones.
+#[derive(Serialize, Debug, Clone)]
+pub struct TxSummary {
+    pub summary: String,
+    pub sentences: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub missing_inputs: Vec<String>,
+}
+
+/// Describes a tx in a few plain sentences: who pays whom and how much each
+/// address gains or loses, mints and burns, certificates, governance
+/// proposals and votes, the scripts it runs and its fee. `utxo_json` holds
+/// the spent UTxOs in the Koios format and may be empty.
+#[wasm_bindgen]
+pub fn summarize_transaction(tx_hex: &str, utxo_json: &str) -> Result<String, JsError> {
+    let tx = Transaction::from_hex(sanitize_hex(tx_hex)?)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding transaction: {:?}", e)))?;
+    let utxos: Vec<UtxoInfoResponse> = if utxo_json.trim().is_empty() {
+        Vec::new()
+    } else {
+        serde_json::from_str(utxo_json).map_err(|e| ErrorCode::InvalidInput.error(&e.to_string()))?
+    };
+    to_versioned_json(&tx_summary(&tx, &utxos)?)
+}
+
+pub(crate) fn tx_summary(tx: &Transaction, utxos: &[UtxoInfoResponse]) -> Result<TxSummary, JsError> {
+    let body = tx.body();
+    let mut sentences = Vec::new();
+
+    let (flows, missing_inputs) = address_flows(tx, utxos)?;
+    if missing_inputs.is_empty() {
+        sentences.extend(flow_sentences(&flows));
+    } else {
+        sentences.push(output_sentence(tx, &flows));
+    }
+
+    if let Some(withdrawals) = body.withdrawals() {
+        let keys = withdrawals.keys();
+        for i in 0..keys.len() {
+            let reward_address = keys.get(i);
+            let amount = withdrawals.get(&reward_address).map(|c| coin(&c)).unwrap_or_default();
+            let account = reward_address.to_address().to_bech32(None).unwrap_or_default();
+            sentences.push(format!("Withdraws {} of rewards from {}.", ada(amount as u128), abbreviate(&account)));
+        }
+    }
+
+    sentences.extend(mint_sentences(tx));
+
+    if let Some(certs) = body.certs() {
+        for i in 0..certs.len() {
+            sentences.push(certificate_sentence(&certs.get(i)));
+        }
+    }
+
+    if let Some(proposals) = body.voting_proposals() {
+        for i in 0..proposals.len() {
+            let proposal = proposals.get(i);
+            let kind = format!("{:?}", proposal.governance_action().kind());
+            let account = proposal.reward_account().to_address().to_bech32(None).unwrap_or_default();
+            sentences.push(format!(
+                "Proposes {} action with a {} deposit returned to {}.",
+                with_article(&action_words(&kind)),
+                ada(coin(&proposal.deposit()) as u128),
+                abbreviate(&account)
+            ));
+        }
+    }
+
+    if let Some(voting_procedures) = body.voting_procedures() {
+        let voters = voting_procedures.get_voters();
+        for i in 0..voters.len() {
+            let Some(voter) = voters.get(i) else {
+                continue;
+            };
+            let action_ids = voting_procedures.get_governance_action_ids_by_voter(&voter);
+            for j in 0..action_ids.len() {
+                let Some(action_id) = action_ids.get(j) else {
+                    continue;
+                };
+                let Some(procedure) = voting_procedures.get(&voter, &action_id) else {
+                    continue;
+                };
+                let vote = match procedure.vote_kind() {
+                    VoteKind::Yes => "votes yes on",
+                    VoteKind::No => "votes no on",
+                    VoteKind::Abstain => "abstains on",
+                };
+                let action = format!("{}#{}", action_id.transaction_id().to_hex(), action_id.index());
+                sentences.push(format!("{} {} action {}.", voter_label(&voter), vote, abbreviate(&action)));
+            }
+        }
+    }
+
+    sentences.extend(script_sentences(tx, utxos));
+
+    if let Some(donation) = body.donation() {
+        sentences.push(format!("Donates {} to the treasury.", ada(coin(&donation) as u128)));
+    }
+    sentences.push(format!("Pays a fee of {}.", ada(coin(&body.fee()) as u128)));
+
+    Ok(TxSummary {
+        summary: sentences.join(" "),
+        sentences,
+        missing_inputs,
+    })
+}
+
+/// "A pays B." followed by what each address sends and receives on net.
+fn flow_sentences(flows: &[AddressFlow]) -> Vec<String> {
+    let with_role = |roles: [&str; 2]| -> Vec<String> {
+        flows.iter().filter(|f| roles.contains(&f.role)).map(|f| abbreviate(&f.address)).collect()
+    };
+    let payers = with_role(["sender", "both"]);
+    let payees = with_role(["receiver", "both"]);
+
+    let mut sentences = Vec::new();
+    if !payers.is_empty() && !payees.is_empty() {
+        let verb = if payers.len() == 1 { "pays" } else { "pay" };
+        sentences.push(format!("{} {} {}.", join_and(&payers), verb, join_and(&payees)));
+    }
+    for flow in flows {
+        let net = &flow.net;
+        let lost = net.assets.iter().filter(|a| a.quantity < 0).map(|a| (&a.asset_name, -a.quantity));
+        let gained = net.assets.iter().filter(|a| a.quantity > 0).map(|a| (&a.asset_name, a.quantity));
+        let sent = value_text(-net.lovelace.min(0), lost);
+        let received = value_text(net.lovelace.max(0), gained);
+        let address = abbreviate(&flow.address);
+        let sentence = match (sent, received) {
+            (Some(sent), Some(received)) => format!("{} sends {} and receives {}.", address, sent, received),
+            (Some(sent), None) if !flow.change.is_empty() => format!("{} sends {} net of change.", address, sent),
+            (Some(sent), None) => format!("{} sends {}.", address, sent),
+            (None, Some(received)) => format!("{} receives {}.", address, received),
+            (None, None) => continue,
+        };
+        sentences.push(sentence);
+    }
+    sentences
+}
+
+/// Used when inputs are unknown: what the outputs pay to each address.
+fn output_sentence(tx: &Transaction, flows: &[AddressFlow]) -> String {
+    let inputs = plural(tx.body().inputs().len(), "input");
+    let payments: Vec<String> = flows
+        .iter()
+        .filter_map(|f| {
+            let received = &f.received;
+            value_text(received.lovelace, received.assets.iter().map(|a| (&a.asset_name, a.quantity)))
+                .map(|value| format!("{} to {}", value, abbreviate(&f.address)))
+        })
+        .collect();
+    if payments.is_empty() {
+        format!("Spends {}.", inputs)
+    } else {
+        format!("Spends {} and pays {}.", inputs, join_and(&payments))
+    }
+}
+
+fn mint_sentences(tx: &Transaction) -> Vec<String> {
+    let Some(mint) = tx.body().mint() else {
+        return Vec::new();
+    };
+    let mut sentences = Vec::new();
+    let policies = mint.keys();
+    for i in 0..policies.len() {
+        let policy = policies.get(i);
+        let Some(mints_assets) = mint.get(&policy) else {
+            continue;
+        };
+        let mut minted = Vec::new();
+        let mut burned = Vec::new();
+        for j in 0..mints_assets.len() {
+            let Some(assets) = mints_assets.get(j) else {
+                continue;
+            };
+            let names = assets.keys();
+            for k in 0..names.len() {
+                let name = names.get(k);
+                let amount: i128 = assets.get(&name).and_then(|q| q.to_str().parse().ok()).unwrap_or_default();
+                let text = format!("{} {}", amount.unsigned_abs(), asset_label(&hex::encode(name.name())));
+                if amount < 0 {
+                    burned.push(text);
+                } else {
+                    minted.push(text);
+                }
+            }
+        }
+        let policy = abbreviate(&policy.to_hex());
+        if !minted.is_empty() {
+            sentences.push(format!("Mints {} under policy {}.", join_and(&minted), policy));
+        }
+        if !burned.is_empty() {
+            sentences.push(format!("Burns {} under policy {}.", join_and(&burned), policy));
+        }
+    }
+    sentences
+}
+
+fn certificate_sentence(cert: &Certificate) -> String {
+    let form = certificate_form(cert);
+    let credential = certificate_credential(cert).map(|c| credential_label(&c)).unwrap_or_default();
+    let deposit = ada(form.deposit.unwrap_or_default() as u128);
+    let refund = ada(form.refund.unwrap_or_default() as u128);
+    let pool = certificate_pool(cert).map(|p| pool_label(&p)).unwrap_or_default();
+    let drep = certificate_drep(cert).map(|d| drep_label(&d)).unwrap_or_default();
+    match form.name {
+        "stake_registration" => format!("Registers stake credential {}.", credential),
+        "reg_cert" => format!("Registers stake credential {} with a {} deposit.", credential, deposit),
+        "stake_deregistration" => format!("Deregisters stake credential {}.", credential),
+        "unreg_cert" => format!("Deregisters stake credential {} and gets its {} deposit back.", credential, refund),
+        "stake_delegation" => format!("Delegates the stake of {} to pool {}.", credential, pool),
+        "vote_deleg_cert" => format!("Delegates the votes of {} to {}.", credential, drep),
+        "stake_vote_deleg_cert" => {
+            format!("Delegates the stake of {} to pool {} and its votes to {}.", credential, pool, drep)
+        }
+        "stake_reg_deleg_cert" => format!(
+            "Registers stake credential {} with a {} deposit and delegates it to pool {}.",
+            credential, deposit, pool
+        ),
+        "vote_reg_deleg_cert" => format!(
+            "Registers stake credential {} with a {} deposit and delegates its votes to {}.",
+            credential, deposit, drep
+        ),
+        "stake_vote_reg_deleg_cert" => format!(
+            "Registers stake credential {} with a {} deposit, delegates it to pool {} and its votes to {}.",
+            credential, deposit, pool, drep
+        ),
+        "pool_registration" => format!("Registers or updates pool {}.", pool),
+        "pool_retirement" => {
+            let epoch = cert.as_pool_retirement().map(|r| r.epoch()).unwrap_or_default();
+            format!("Retires pool {} in epoch {}.", pool, epoch)
+        }
+        "auth_committee_hot_cert" => format!("Authorizes a hot key for committee member {}.", credential),
+        "resign_committee_cold_cert" => format!("Resigns committee member {}.", credential),
+        "reg_drep_cert" => format!("Registers {} as a DRep with a {} deposit.", credential, deposit),
+        "unreg_drep_cert" => format!("Retires DRep {} and gets its {} deposit back.", credential, refund),
+        "update_drep_cert" => format!("Updates the metadata of DRep {}.", credential),
+        "genesis_key_delegation" => "Delegates a genesis key.".to_string(),
+        _ => "Moves instantaneous rewards.".to_string(),
+    }
+}
+
+/// One sentence per redeemer, plus one for native scripts and reference inputs.
+fn script_sentences(tx: &Transaction, utxos: &[UtxoInfoResponse]) -> Vec<String> {
+    let witness_set = tx.witness_set();
+    let mut sentences = Vec::new();
+    if let Some(redeemers) = witness_set.redeemers() {
+        let addresses = utxo_addresses(utxos);
+        for i in 0..redeemers.len() {
+            let redeemer = redeemers.get(i);
+            let tag = redeemer_tag_name(redeemer.tag().kind());
+            let index = redeemer.index().to_str().parse().unwrap_or_default();
+            let target = resolve_redeemer_target(tx, &addresses, tag, index);
+            let ex_units = redeemer.ex_units();
+            sentences.push(format!(
+                "Runs a Plutus script for {} with a budget of {} memory units and {} steps.",
+                target.description,
+                ex_units.mem().to_str(),
+                ex_units.steps().to_str()
+            ));
+        }
+    }
+    if let Some(scripts) = witness_set.native_scripts() {
+        if scripts.len() > 0 {
+            sentences.push(format!("Checks {}.", plural(scripts.len(), "native script")));
+        }
+    }
+    if let Some(reference_inputs) = tx.body().reference_inputs() {
+        if reference_inputs.len() > 0 {
+            sentences.push(format!("Reads {} without spending them.", plural(reference_inputs.len(), "reference input")));
+        }
+    }
+    sentences
+}
+
+pub(crate) fn certificate_drep(cert: &Certificate) -> Option<DRep> {
+    if let Some(c) = cert.as_vote_delegation() {
+        Some(c.drep())
+    } else if let Some(c) = cert.as_stake_and_vote_delegation() {
+        Some(c.drep())
+    } else if let Some(c) = cert.as_vote_registration_and_delegation() {
+        Some(c.drep())
+    } else {
+        cert.as_stake_vote_registration_and_delegation().map(|c| c.drep())
+    }
+}
+
+fn credential_label(credential: &Credential) -> String {
+    match credential.to_scripthash() {
+        Some(hash) => format!("script {}", abbreviate(&hash.to_hex())),
+        None => credential.to_keyhash().map(|hash| abbreviate(&hash.to_hex())).unwrap_or_default(),
+    }
+}
+
+fn pool_label(pool: &Ed25519KeyHash) -> String {
+    abbreviate(&pool.to_bech32("pool").unwrap_or_else(|_| pool.to_hex()))
+}
+
+fn drep_label(drep: &DRep) -> String {
+    match drep.kind() {
+        DRepKind::AlwaysAbstain => "always abstain".to_string(),
+        DRepKind::AlwaysNoConfidence => "always no confidence".to_string(),
+        DRepKind::KeyHash => format!("DRep {}", drep.to_key_hash().map(|h| abbreviate(&h.to_hex())).unwrap_or_default()),
+        DRepKind::ScriptHash => {
+            format!("DRep script {}", drep.to_script_hash().map(|h| abbreviate(&h.to_hex())).unwrap_or_default())
+        }
+    }
+}
+
+fn voter_label(voter: &Voter) -> String {
+    if let Some(credential) = voter.to_constitutional_committee_hot_credential() {
+        format!("Committee member {}", credential_label(&credential))
+    } else if let Some(credential) = voter.to_drep_credential() {
+        format!("DRep {}", credential_label(&credential))
+    } else {
+        format!("Pool {}", voter.to_stake_pool_key_hash().map(|p| pool_label(&p)).unwrap_or_default())
+    }
+}
+
+/// `TreasuryWithdrawalsAction` to `treasury withdrawals`.
+pub(crate) fn action_words(kind: &str) -> String {
+    let name = kind.strip_suffix("Action").unwrap_or(kind);
+    let mut words = String::new();
+    for c in name.chars() {
+        if c.is_uppercase() && !words.is_empty() {
+            words.push(' ');
+        }
+        words.extend(c.to_lowercase());
+    }
+    words
+}
+
+fn with_article(words: &str) -> String {
+    let article = if words.starts_with(['a', 'e', 'i', 'o', 'u']) { "an" } else { "a" };
+    format!("{} {}", article, words)
+}
+
+/// `12.5 ADA and 3 HOSKY`, `None` for an empty value.
+fn value_text<'a>(lovelace: i128, assets: impl Iterator<Item = (&'a String, i128)>) -> Option<String> {
+    let mut parts = Vec::new();
+    if lovelace > 0 {
+        parts.push(ada(lovelace as u128));
+    }
+    for (name, amount) in assets {
+        parts.push(format!("{} {}", amount, asset_label(name)));
+    }
+    if parts.is_empty() {
+        None
+    } else {
+        Some(join_and(&parts))
+    }
+}
+
+/// The asset name as text when it is printable, else in hex.
+fn asset_label(name_hex: &str) -> String {
+    let text = hex::decode(name_hex).ok().and_then(|bytes| String::from_utf8(bytes).ok());
+    match text {
+        Some(text) if !text.is_empty() && !text.chars().any(char::is_control) => text,
+        _ if name_hex.is_empty() => "unnamed token".to_string(),
+        _ => format!("token {}", abbreviate(name_hex)),
+    }
+}
+
+/// Lovelace as ADA without trailing zeros, e.g. `1.5 ADA`.
+fn ada(lovelace: u128) -> String {
+    let (whole, fraction) = (lovelace / 1_000_000, lovelace % 1_000_000);
+    if fraction == 0 {
+        format!("{} ADA", whole)
+    } else {
+        format!("{}.{} ADA", whole, format!("{:06}", fraction).trim_end_matches('0'))
+    }
+}
+
+/// Keeps the start and end of long addresses and hashes, enough to tell them apart.
+fn abbreviate(text: &str) -> String {
+    if text.len() <= 24 || !text.is_ascii() {
+        return text.to_string();
+    }
+    format!("{}…{}", &text[..12], &text[text.len() - 6..])
+}
+
+fn join_and(parts: &[String]) -> String {
+    match parts.split_last() {
+        None => String::new(),
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} and {}", rest.join(", "), last),
+    }
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("1 {}", noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
+}
+
+fn coin(value: &BigNum) -> u64 {
+    value.to_str().parse().unwrap_or_default()
+}
@@ -0,0 +1,304 @@
+use cardano_serialization_lib::{
+    BootstrapWitnesses, Ed25519Signature, FixedTransaction, NativeScripts, PublicKey, Transaction,
+    TransactionWitnessSet, Vkey, Vkeywitness, Vkeywitnesses,
+};
+use pallas_crypto::hash::Hasher;
+use serde::Serialize;
+use crate::bingen::wasm_bindgen;
+use crate::error::ErrorCode;
+use crate::input::sanitize_hex;
+use crate::js_error::JsError;
+use crate::schemas::to_versioned_json;
+
+/// A witness left out of the merged tx. `source` is `tx` or
+/// `witness_sets[i]`, `reason` is `InvalidSignature`.
+#[derive(Serialize, Debug, Clone)]
+pub struct RejectedWitness {
+    pub source: String,
+    pub key_hash: String,
+    pub reason: String,
+}
+
+/// The tx with the witnesses merged in. `added` lists the key hashes of the
+/// new vkey and bootstrap witnesses, `duplicates` counts those it already
+/// had. `missing_signers` are required signers that still haven't signed.
+#[derive(Serialize, Debug, Clone)]
+pub struct MergedWitnesses {
+    pub tx_hex: String,
+    pub tx_hash: String,
+    pub vkey_witnesses: usize,
+    pub bootstrap_witnesses: usize,
+    pub added: Vec<String>,
+    pub duplicates: usize,
+    pub rejected: Vec<RejectedWitness>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub missing_signers: Vec<String>,
+}
+
+/// Merges witness sets collected from several signers (e.g. the CIP-30
+/// `signTx` results of a multisig) into a tx. Vkey and bootstrap witnesses
+/// are deduplicated by key and checked against the body hash, invalid ones
+/// are dropped, and native scripts are added once. The body and auxiliary
+/// data keep their bytes, so the tx hash doesn't change.
+#[wasm_bindgen]
+pub fn merge_witness_sets(tx_hex: &str, witness_set_hexes: Vec<String>) -> Result<String, JsError> {
+    let mut witness_sets = Vec::new();
+    for (i, witness_set_hex) in witness_set_hexes.iter().enumerate() {
+        let witness_set = TransactionWitnessSet::from_hex(sanitize_hex(witness_set_hex)?).map_err(|e| {
+            ErrorCode::DecodeFailure.error(&format!("Error decoding witness set {}: {:?}", i, e))
+        })?;
+        witness_sets.push(witness_set);
+    }
+    to_versioned_json(&merge_witnesses(tx_hex, &witness_sets)?)
+}
+
+pub(crate) fn merge_witnesses(tx_hex: &str, witness_sets: &[TransactionWitnessSet]) -> Result<MergedWitnesses, JsError> {
+    let tx_hex = sanitize_hex(tx_hex)?;
+    let fixed_tx = FixedTransaction::from_hex(tx_hex)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding transaction: {:?}", e)))?;
+    let tx = Transaction::from_hex(tx_hex)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding transaction: {:?}", e)))?;
+    let body_hash = Hasher::<256>::hash(&fixed_tx.raw_body());
+
+    let witness_set = tx.witness_set();
+    let mut vkeys = Vkeywitnesses::new();
+    let mut bootstraps = BootstrapWitnesses::new();
+    let mut native_scripts = NativeScripts::new();
+    let mut signed_keys: Vec<String> = Vec::new();
+    let mut script_hashes: Vec<String> = Vec::new();
+    let mut added = Vec::new();
+    let mut duplicates = 0;
+    let mut rejected = Vec::new();
+
+    let sources = std::iter::once(("tx".to_string(), witness_set.clone()))
+        .chain(witness_sets.iter().enumerate().map(|(i, set)| (format!("witness_sets[{}]", i), set.clone())));
+    for (source, set) in sources {
+        let from_tx = source == "tx";
+        if let Some(witnesses) = set.vkeys() {
+            for i in 0..witnesses.len() {
+                let witness = witnesses.get(i);
+                let key = witness.vkey().public_key();
+                let key_hash = key.hash().to_hex();
+                if signed_keys.contains(&key_hash) {
+                    duplicates += 1;
+                } else if !key.verify(body_hash.as_ref(), &witness.signature()) {
+                    rejected.push(RejectedWitness { source: source.clone(), key_hash, reason: "InvalidSignature".to_string() });
+                } else {
+                    vkeys.add(&witness);
+                    if !from_tx {
+                        added.push(key_hash.clone());
+                    }
+                    signed_keys.push(key_hash);
+                }
+            }
+        }
+        if let Some(witnesses) = set.bootstraps() {
+            for i in 0..witnesses.len() {
+                let witness = witnesses.get(i);
+                let key = witness.vkey().public_key();
+                let key_hash = key.hash().to_hex();
+                if signed_keys.contains(&key_hash) {
+                    duplicates += 1;
+                } else if !key.verify(body_hash.as_ref(), &witness.signature()) {
+                    rejected.push(RejectedWitness { source: source.clone(), key_hash, reason: "InvalidSignature".to_string() });
+                } else {
+                    bootstraps.add(&witness);
+                    if !from_tx {
+                        added.push(key_hash.clone());
+                    }
+                    signed_keys.push(key_hash);
+                }
+            }
+        }
+        if let Some(scripts) = set.native_scripts() {
+            for i in 0..scripts.len() {
+                let script = scripts.get(i);
+                let hash = script.hash().to_hex();
+                if !script_hashes.contains(&hash) {
+                    native_scripts.add(&script);
+                    script_hashes.push(hash);
+                }
+            }
+        }
+    }
+
+    // rebuilt rather than patched, so rejected witnesses of the tx don't stay
+    let tx_witness_set = witness_set;
+    let mut witness_set = TransactionWitnessSet::new();
+    if vkeys.len() > 0 {
+        witness_set.set_vkeys(&vkeys);
+    }
+    if bootstraps.len() > 0 {
+        witness_set.set_bootstraps(&bootstraps);
+    }
+    if native_scripts.len() > 0 {
+        witness_set.set_native_scripts(&native_scripts);
+    }
+    if let Some(plutus_scripts) = tx_witness_set.plutus_scripts() {
+        witness_set.set_plutus_scripts(&plutus_scripts);
+    }
+    if let Some(plutus_data) = tx_witness_set.plutus_data() {
+        witness_set.set_plutus_data(&plutus_data);
+    }
+    if let Some(redeemers) = tx_witness_set.redeemers() {
+        witness_set.set_redeemers(&redeemers);
+    }
+    let vkey_witnesses = vkeys.len();
+    let bootstrap_witnesses = bootstraps.len();
+    let missing_signers = match tx.body().required_signers() {
+        Some(signers) => (0..signers.len())
+            .map(|i| signers.get(i).to_hex())
+            .filter(|signer| !signed_keys.contains(signer))
+            .collect(),
+        None => Vec::new(),
+    };
+
+    Ok(MergedWitnesses {
+        tx_hex: hex::encode(with_witness_set(&fixed_tx, &witness_set)?),
+        tx_hash: body_hash.to_string(),
+        vkey_witnesses,
+        bootstrap_witnesses,
+        added,
+        duplicates,
+        rejected,
+        missing_signers,
+    })
+}
+
+/// A tx without its signatures, and its parts for signing elsewhere.
+/// `body_hex` is what gets hashed and signed, `tx_hash` its hash.
+#[derive(Serialize, Debug, Clone)]
+pub struct StrippedTx {
+    pub tx_hex: String,
+    pub tx_hash: String,
+    pub body_hex: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auxiliary_data_hex: Option<String>,
+    pub removed_vkey_witnesses: usize,
+    pub removed_bootstrap_witnesses: usize,
+}
+
+/// The tx with one more vkey witness. `replaced` is true when the key had
+/// already signed and its witness was swapped for the new one.
+#[derive(Serialize, Debug, Clone)]
+pub struct AttachedWitness {
+    pub tx_hex: String,
+    pub tx_hash: String,
+    pub key_hash: String,
+    pub vkey_witnesses: usize,
+    pub replaced: bool,
+}
+
+/// Removes the vkey and bootstrap witnesses of a signed tx, for signing it
+/// again on an air-gapped machine. With `keep_scripts` scripts, datums and
+/// redeemers stay, otherwise the witness set is emptied. Body and auxiliary
+/// data keep their bytes.
+#[wasm_bindgen]
+pub fn strip_tx_witnesses(tx_hex: &str, keep_scripts: bool) -> Result<String, JsError> {
+    to_versioned_json(&strip_witnesses(tx_hex, keep_scripts)?)
+}
+
+pub(crate) fn strip_witnesses(tx_hex: &str, keep_scripts: bool) -> Result<StrippedTx, JsError> {
+    let tx_hex = sanitize_hex(tx_hex)?;
+    let fixed_tx = FixedTransaction::from_hex(tx_hex)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding transaction: {:?}", e)))?;
+    let tx = Transaction::from_hex(tx_hex)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding transaction: {:?}", e)))?;
+    let signed = tx.witness_set();
+
+    let mut witness_set = TransactionWitnessSet::new();
+    if keep_scripts {
+        if let Some(native_scripts) = signed.native_scripts() {
+            witness_set.set_native_scripts(&native_scripts);
+        }
+        if let Some(plutus_scripts) = signed.plutus_scripts() {
+            witness_set.set_plutus_scripts(&plutus_scripts);
+        }
+        if let Some(plutus_data) = signed.plutus_data() {
+            witness_set.set_plutus_data(&plutus_data);
+        }
+        if let Some(redeemers) = signed.redeemers() {
+            witness_set.set_redeemers(&redeemers);
+        }
+    }
+
+    let body = fixed_tx.raw_body();
+    Ok(StrippedTx {
+        tx_hex: hex::encode(with_witness_set(&fixed_tx, &witness_set)?),
+        tx_hash: Hasher::<256>::hash(&body).to_string(),
+        body_hex: hex::encode(&body),
+        auxiliary_data_hex: fixed_tx.raw_auxiliary_data().map(hex::encode),
+        removed_vkey_witnesses: signed.vkeys().map_or(0, |vkeys| vkeys.len()),
+        removed_bootstrap_witnesses: signed.bootstraps().map_or(0, |bootstraps| bootstraps.len()),
+    })
+}
+
+/// Adds the signature of one key, made elsewhere over the tx hash, as a vkey
+/// witness. `public_key` is hex or `ed25519_pk` bech32, `signature_hex` the
+/// 64 byte ed25519 signature. A signature that doesn't verify against the
+/// body hash is an error.
+#[wasm_bindgen]
+pub fn attach_vkey_witness(tx_hex: &str, public_key: &str, signature_hex: &str) -> Result<String, JsError> {
+    to_versioned_json(&attach_witness(tx_hex, public_key, signature_hex)?)
+}
+
+pub(crate) fn attach_witness(tx_hex: &str, public_key: &str, signature_hex: &str) -> Result<AttachedWitness, JsError> {
+    let tx_hex = sanitize_hex(tx_hex)?;
+    let fixed_tx = FixedTransaction::from_hex(tx_hex)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding transaction: {:?}", e)))?;
+    let tx = Transaction::from_hex(tx_hex)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding transaction: {:?}", e)))?;
+    let public_key = public_key.trim();
+    let key = if public_key.starts_with("ed25519_pk") {
+        PublicKey::from_bech32(public_key)
+    } else {
+        PublicKey::from_hex(sanitize_hex(public_key)?)
+    }
+    .map_err(|e| ErrorCode::InvalidInput.error(&format!("Invalid public key: {:?}", e)))?;
+    let signature = Ed25519Signature::from_hex(sanitize_hex(signature_hex)?)
+        .map_err(|e| ErrorCode::InvalidInput.error(&format!("Invalid signature: {:?}", e)))?;
+    let body_hash = Hasher::<256>::hash(&fixed_tx.raw_body());
+    if !key.verify(body_hash.as_ref(), &signature) {
+        return Err(ErrorCode::InvalidInput.error(&format!("The signature doesn't verify against the tx body {}", body_hash)));
+    }
+
+    let key_hash = key.hash().to_hex();
+    let mut witness_set = tx.witness_set();
+    let mut vkeys = Vkeywitnesses::new();
+    let mut replaced = false;
+    if let Some(witnesses) = witness_set.vkeys() {
+        for i in 0..witnesses.len() {
+            let witness = witnesses.get(i);
+            if witness.vkey().public_key().hash().to_hex() == key_hash {
+                replaced = true;
+            } else {
+                vkeys.add(&witness);
+            }
+        }
+    }
+    vkeys.add(&Vkeywitness::new(&Vkey::new(&key), &signature));
+    witness_set.set_vkeys(&vkeys);
+
+    Ok(AttachedWitness {
+        tx_hex: hex::encode(with_witness_set(&fixed_tx, &witness_set)?),
+        tx_hash: body_hash.to_string(),
+        key_hash,
+        vkey_witnesses: vkeys.len(),
+        replaced,
+    })
+}
+
+/// The tx with another witness set, its body and auxiliary data bytes kept.
+fn with_witness_set(fixed_tx: &FixedTransaction, witness_set: &TransactionWitnessSet) -> Result<Vec<u8>, JsError> {
+    let patched = match fixed_tx.raw_auxiliary_data() {
+        Some(auxiliary_data) => FixedTransaction::new_with_auxiliary(
+            &fixed_tx.raw_body(),
+            &witness_set.to_bytes(),
+            &auxiliary_data,
+            fixed_tx.is_valid(),
+        ),
+        None => FixedTransaction::new(&fixed_tx.raw_body(), &witness_set.to_bytes(), fixed_tx.is_valid()),
+    }
+    .map_err(|e| ErrorCode::Internal.error(&format!("Error rebuilding transaction: {:?}", e)))?;
+    Ok(patched.to_bytes())
+}
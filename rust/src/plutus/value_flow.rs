@@ -0,0 +1,327 @@
+use cardano_serialization_lib::{Address, BigNum, ByronAddress, Transaction, TransactionOutput};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use crate::bingen::wasm_bindgen;
+use crate::csl_decoders::certificate_form;
+use crate::error::{CquisitorError, ErrorCode};
+use crate::input::sanitize_hex;
+use crate::js_error::JsError;
+use crate::koios_client::models::UtxoInfoResponse;
+use crate::schemas::to_versioned_json;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AssetQuantity {
+    pub policy_id: String,
+    pub asset_name: String,
+    pub quantity: i128,
+}
+
+/// Lovelace and assets, negative in a net change when the address loses them.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct FlowValue {
+    pub lovelace: i128,
+    #[serde(default)]
+    pub assets: Vec<AssetQuantity>,
+}
+
+impl FlowValue {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.lovelace == 0 && self.assets.iter().all(|a| a.quantity == 0)
+    }
+
+    fn add_asset(&mut self, policy_id: &str, asset_name: &str, quantity: i128) {
+        match self.assets.iter_mut().find(|a| a.policy_id == policy_id && a.asset_name == asset_name) {
+            Some(asset) => asset.quantity += quantity,
+            None => self.assets.push(AssetQuantity {
+                policy_id: policy_id.to_string(),
+                asset_name: asset_name.to_string(),
+                quantity,
+            }),
+        }
+    }
+
+    pub(crate) fn add_output(&mut self, output: &TransactionOutput) {
+        self.lovelace += coin(&output.amount().coin()) as i128;
+        let Some(multiasset) = output.amount().multiasset() else {
+            return;
+        };
+        let policies = multiasset.keys();
+        for i in 0..policies.len() {
+            let policy = policies.get(i);
+            let Some(assets) = multiasset.get(&policy) else {
+                continue;
+            };
+            let names = assets.keys();
+            for j in 0..names.len() {
+                let name = names.get(j);
+                let quantity = assets.get(&name).map(|q| coin(&q) as i128).unwrap_or_default();
+                self.add_asset(&policy.to_hex(), &hex::encode(name.name()), quantity);
+            }
+        }
+    }
+
+    /// `(unit, quantity)` pairs, the unit being `lovelace` or `policy_id.asset_name`.
+    fn units(&self) -> Vec<(String, i128)> {
+        let mut units = vec![("lovelace".to_string(), self.lovelace)];
+        units.extend(self.assets.iter().map(|a| (format!("{}.{}", a.policy_id, a.asset_name), a.quantity)));
+        units
+    }
+
+    fn combine(&self, other: &FlowValue, f: impl Fn(i128, i128) -> i128) -> FlowValue {
+        let mut combined = FlowValue { lovelace: f(self.lovelace, other.lovelace), assets: Vec::new() };
+        for asset in self.assets.iter().chain(&other.assets) {
+            if combined.assets.iter().any(|a| a.policy_id == asset.policy_id && a.asset_name == asset.asset_name) {
+                continue;
+            }
+            let quantity = |value: &FlowValue| {
+                value
+                    .assets
+                    .iter()
+                    .find(|a| a.policy_id == asset.policy_id && a.asset_name == asset.asset_name)
+                    .map_or(0, |a| a.quantity)
+            };
+            let result = f(quantity(self), quantity(other));
+            if result != 0 {
+                combined.add_asset(&asset.policy_id, &asset.asset_name, result);
+            }
+        }
+        combined
+    }
+}
+
+/// What an address spends and receives in a tx. `change` is the part of what
+/// it receives that goes back to it, `net` is received minus spent. `role` is
+/// `sender` or `receiver` by the sign of `net`, `both` when it loses some
+/// units and gains others (a swap), `unchanged` when everything comes back.
+#[derive(Serialize, Debug, Clone)]
+pub struct AddressFlow {
+    pub address: String,
+    pub role: &'static str,
+    pub spent: FlowValue,
+    pub received: FlowValue,
+    pub change: FlowValue,
+    pub net: FlowValue,
+}
+
+/// A share of one unit moving between two nodes of the flow graph. Nodes are
+/// addresses, or `fee`, `donation`, `deposits`, `withdrawals`, `refunds`,
+/// `mint` and `burn` for what the tx itself takes or adds.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct FlowEdge {
+    pub from: String,
+    pub to: String,
+    pub unit: String,
+    pub quantity: i128,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ValueFlowReport {
+    pub addresses: Vec<AddressFlow>,
+    pub edges: Vec<FlowEdge>,
+}
+
+/// Computes the net change of every address a tx spends from or pays to,
+/// lovelace and each asset, with the change going back to senders split
+/// out, and the edges of a flow graph between them. The spent UTxOs are
+/// given as Koios `utxo_info` JSON and must include every input. Edges match
+/// what senders lose to what receivers gain unit by unit, in address order;
+/// deposits and refunds are those the tx states, so a legacy stake
+/// registration's deposit has no edge.
+#[wasm_bindgen]
+pub fn get_value_flows(tx_hex: &str, utxo_json: &str) -> Result<String, JsError> {
+    let tx = Transaction::from_hex(sanitize_hex(tx_hex)?)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding transaction: {:?}", e)))?;
+    let utxos: Vec<UtxoInfoResponse> =
+        serde_json::from_str(utxo_json).map_err(|e| ErrorCode::InvalidInput.error(&e.to_string()))?;
+    to_versioned_json(&value_flows(&tx, &utxos)?)
+}
+
+pub(crate) fn value_flows(tx: &Transaction, utxos: &[UtxoInfoResponse]) -> Result<ValueFlowReport, JsError> {
+    let (addresses, missing_inputs) = address_flows(tx, utxos)?;
+    if !missing_inputs.is_empty() {
+        let message = format!("UTxOs missing from the given list: {}", missing_inputs.join(", "));
+        return Err(CquisitorError::new(ErrorCode::MissingUtxo, &message)
+            .with_details(json!({ "utxos": missing_inputs }))
+            .into());
+    }
+    let edges = flow_edges(tx, &addresses);
+    Ok(ValueFlowReport { addresses, edges })
+}
+
+/// Flows of every address in the order they first appear, with the inputs
+/// that aren't among `utxos`. Those count as spending nothing, so `net` and
+/// `change` only hold when none is missing.
+pub(crate) fn address_flows(
+    tx: &Transaction,
+    utxos: &[UtxoInfoResponse],
+) -> Result<(Vec<AddressFlow>, Vec<String>), JsError> {
+    let body = tx.body();
+    let mut flows: Vec<AddressFlow> = Vec::new();
+    let mut missing_inputs = Vec::new();
+
+    let inputs = body.inputs();
+    for i in 0..inputs.len() {
+        let input = inputs.get(i);
+        let key = format!("{}#{}", input.transaction_id().to_hex(), input.index());
+        let Some(utxo) = utxos.iter().find(|u| format!("{}#{}", u.tx_hash, u.tx_index) == key) else {
+            missing_inputs.push(key);
+            continue;
+        };
+        let value = utxo_value(utxo)?;
+        let spent = &mut flow_entry(&mut flows, &utxo.address).spent;
+        spent.lovelace += value.lovelace;
+        for asset in &value.assets {
+            spent.add_asset(&asset.policy_id, &asset.asset_name, asset.quantity);
+        }
+    }
+
+    let outputs = body.outputs();
+    for i in 0..outputs.len() {
+        let output = outputs.get(i);
+        flow_entry(&mut flows, &address_text(&output.address())).received.add_output(&output);
+    }
+
+    for flow in &mut flows {
+        flow.change = flow.spent.combine(&flow.received, i128::min);
+        flow.net = flow.received.combine(&flow.spent, |received, spent| received - spent);
+        let units = flow.net.units();
+        let loses = units.iter().any(|(_, q)| *q < 0);
+        let gains = units.iter().any(|(_, q)| *q > 0);
+        flow.role = match (loses, gains) {
+            (true, true) => "both",
+            (true, false) => "sender",
+            (false, true) => "receiver",
+            (false, false) => "unchanged",
+        };
+    }
+    Ok((flows, missing_inputs))
+}
+
+fn flow_entry<'a>(flows: &'a mut Vec<AddressFlow>, address: &str) -> &'a mut AddressFlow {
+    let index = match flows.iter().position(|f| f.address == address) {
+        Some(index) => index,
+        None => {
+            flows.push(AddressFlow {
+                address: address.to_string(),
+                role: "unchanged",
+                spent: FlowValue::default(),
+                received: FlowValue::default(),
+                change: FlowValue::default(),
+                net: FlowValue::default(),
+            });
+            flows.len() - 1
+        }
+    };
+    &mut flows[index]
+}
+
+/// Matches sources (senders, withdrawals, refunds, mints) to sinks
+/// (receivers, fee, donation, deposits, burns) greedily, one unit at a time.
+fn flow_edges(tx: &Transaction, flows: &[AddressFlow]) -> Vec<FlowEdge> {
+    let body = tx.body();
+    let mut sources: Vec<(String, String, i128)> = Vec::new();
+    let mut sinks: Vec<(String, String, i128)> = Vec::new();
+    for flow in flows {
+        for (unit, quantity) in flow.net.units() {
+            if quantity < 0 {
+                sources.push((flow.address.clone(), unit, -quantity));
+            } else if quantity > 0 {
+                sinks.push((flow.address.clone(), unit, quantity));
+            }
+        }
+    }
+
+    let lovelace = |node: &str, amount: u64| (node.to_string(), "lovelace".to_string(), amount as i128);
+    if let Some(withdrawals) = body.withdrawals() {
+        let keys = withdrawals.keys();
+        let total: u64 = (0..keys.len()).filter_map(|i| withdrawals.get(&keys.get(i))).map(|c| coin(&c)).sum();
+        sources.push(lovelace("withdrawals", total));
+    }
+    let (mut deposits, mut refunds) = (0u64, 0u64);
+    if let Some(certs) = body.certs() {
+        for i in 0..certs.len() {
+            let form = certificate_form(&certs.get(i));
+            deposits += form.deposit.unwrap_or_default();
+            refunds += form.refund.unwrap_or_default();
+        }
+    }
+    if let Some(proposals) = body.voting_proposals() {
+        deposits += (0..proposals.len()).map(|i| coin(&proposals.get(i).deposit())).sum::<u64>();
+    }
+    sources.push(lovelace("refunds", refunds));
+    sinks.push(lovelace("fee", coin(&body.fee())));
+    sinks.push(lovelace("deposits", deposits));
+    sinks.push(lovelace("donation", body.donation().map(|c| coin(&c)).unwrap_or_default()));
+
+    if let Some(mint) = body.mint() {
+        let policies = mint.keys();
+        for i in 0..policies.len() {
+            let policy = policies.get(i);
+            let Some(mints_assets) = mint.get(&policy) else {
+                continue;
+            };
+            for j in 0..mints_assets.len() {
+                let Some(assets) = mints_assets.get(j) else {
+                    continue;
+                };
+                let names = assets.keys();
+                for k in 0..names.len() {
+                    let name = names.get(k);
+                    let amount: i128 = assets.get(&name).and_then(|q| q.to_str().parse().ok()).unwrap_or_default();
+                    let unit = format!("{}.{}", policy.to_hex(), hex::encode(name.name()));
+                    if amount > 0 {
+                        sources.push(("mint".to_string(), unit, amount));
+                    } else if amount < 0 {
+                        sinks.push(("burn".to_string(), unit, -amount));
+                    }
+                }
+            }
+        }
+    }
+
+    let mut edges = Vec::new();
+    for (to, unit, mut needed) in sinks {
+        for (from, source_unit, available) in sources.iter_mut() {
+            if needed == 0 {
+                break;
+            }
+            if *source_unit != unit || *available == 0 {
+                continue;
+            }
+            let quantity = needed.min(*available);
+            *available -= quantity;
+            needed -= quantity;
+            edges.push(FlowEdge { from: from.clone(), to: to.clone(), unit: unit.clone(), quantity });
+        }
+    }
+    edges
+}
+
+pub(crate) fn address_text(address: &Address) -> String {
+    match ByronAddress::from_address(address) {
+        Some(byron) => byron.to_base58(),
+        None => address.to_bech32(None).unwrap_or_else(|_| address.to_hex()),
+    }
+}
+
+/// Lovelace and assets of a Koios `utxo_info` item.
+pub(crate) fn utxo_value(utxo: &UtxoInfoResponse) -> Result<FlowValue, JsError> {
+    let key = format!("{}#{}", utxo.tx_hash, utxo.tx_index);
+    let mut value = FlowValue { lovelace: quantity(&utxo.value, &key)?, assets: Vec::new() };
+    for asset in utxo.asset_list.iter().flatten() {
+        let asset_name = asset.asset_name.as_deref().unwrap_or_default();
+        value.add_asset(&asset.policy_id, asset_name, quantity(&asset.quantity, &key)?);
+    }
+    Ok(value)
+}
+
+fn quantity(value: &str, utxo_ref: &str) -> Result<i128, JsError> {
+    value
+        .parse::<u64>()
+        .map(i128::from)
+        .map_err(|e| ErrorCode::InvalidInput.error(&format!("Invalid value of UTxO {}: {}", utxo_ref, e)))
+}
+
+fn coin(value: &BigNum) -> u64 {
+    value.to_str().parse().unwrap_or_default()
+}
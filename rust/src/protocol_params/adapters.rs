@@ -0,0 +1,358 @@
+use serde_json::Value;
+use crate::error::ErrorCode;
+use crate::js_error::JsError;
+use crate::koios_client::models::{CostModels, EpochParamResponse};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum ProtocolParamsFormat {
+    Koios,
+    Blockfrost,
+    Maestro,
+    Ogmios,
+    CardanoCli,
+}
+
+pub(crate) fn parse_protocol_params(protocol_params_json: &str) -> Result<EpochParamResponse, JsError> {
+    let value: Value = serde_json::from_str(protocol_params_json)
+        .map_err(|e| ErrorCode::InvalidInput.error(&format!("Error parsing protocol params: {}", e)))?;
+    protocol_params_from_value(value)
+}
+
+pub(crate) fn protocol_params_from_value(value: Value) -> Result<EpochParamResponse, JsError> {
+    let value = unwrap_envelope(value)?;
+    match detect_format(&value) {
+        Some(ProtocolParamsFormat::Koios) => serde_json::from_value(value)
+            .map_err(|e| ErrorCode::InvalidInput.error(&format!("Error parsing Koios protocol params: {}", e))),
+        Some(ProtocolParamsFormat::Blockfrost) => Ok(from_blockfrost(&value)),
+        Some(ProtocolParamsFormat::Maestro) => Ok(from_ogmios_like(&value, "_")),
+        Some(ProtocolParamsFormat::Ogmios) => Ok(from_ogmios_like(&value, ":")),
+        Some(ProtocolParamsFormat::CardanoCli) => Ok(from_cardano_cli(&value)),
+        None => Err(ErrorCode::InvalidInput.error(
+            "Unknown protocol params format, expected Koios, Blockfrost, Maestro, Ogmios or cardano-cli JSON",
+        )),
+    }
+}
+
+/// Strips the wrappers providers put around the params object: Koios returns a
+/// one-element array, Maestro a `data` field and Ogmios a JSON-RPC `result`.
+fn unwrap_envelope(value: Value) -> Result<Value, JsError> {
+    match value {
+        Value::Array(mut items) => {
+            if items.is_empty() {
+                return Err(ErrorCode::InvalidInput.error("Protocol params array is empty"));
+            }
+            unwrap_envelope(items.swap_remove(0))
+        }
+        Value::Object(mut obj) => {
+            if obj.contains_key("jsonrpc") {
+                let result = obj.remove("result")
+                    .ok_or(ErrorCode::InvalidInput.error("Ogmios response has no result"))?;
+                return unwrap_envelope(result);
+            }
+            if obj.contains_key("last_updated") {
+                if let Some(data) = obj.remove("data") {
+                    return unwrap_envelope(data);
+                }
+            }
+            Ok(Value::Object(obj))
+        }
+        _ => Err(ErrorCode::InvalidInput.error("Protocol params must be a JSON object")),
+    }
+}
+
+pub(crate) fn detect_format(value: &Value) -> Option<ProtocolParamsFormat> {
+    let obj = value.as_object()?;
+    if obj.contains_key("epoch_no") && obj.contains_key("block_hash") {
+        Some(ProtocolParamsFormat::Koios)
+    } else if obj.contains_key("min_fee_a") && obj.contains_key("epoch") {
+        Some(ProtocolParamsFormat::Blockfrost)
+    } else if obj.contains_key("min_fee_coefficient") {
+        Some(ProtocolParamsFormat::Maestro)
+    } else if obj.contains_key("minFeeCoefficient") {
+        Some(ProtocolParamsFormat::Ogmios)
+    } else if obj.contains_key("txFeePerByte") {
+        Some(ProtocolParamsFormat::CardanoCli)
+    } else {
+        None
+    }
+}
+
+fn from_blockfrost(pp: &Value) -> EpochParamResponse {
+    EpochParamResponse {
+        epoch_no: get_u64(pp, &["epoch"]).unwrap_or_default(),
+        min_fee_a: get_u64(pp, &["min_fee_a"]),
+        min_fee_b: get_u64(pp, &["min_fee_b"]),
+        max_block_size: get_u64(pp, &["max_block_size"]),
+        max_tx_size: get_u64(pp, &["max_tx_size"]),
+        max_bh_size: get_u64(pp, &["max_block_header_size"]),
+        key_deposit: get_string(pp, &["key_deposit"]),
+        pool_deposit: get_string(pp, &["pool_deposit"]),
+        max_epoch: get_u64(pp, &["e_max"]),
+        optimal_pool_count: get_u64(pp, &["n_opt"]),
+        influence: get_f64(pp, &["a0"]),
+        monetary_expand_rate: get_f64(pp, &["rho"]),
+        treasury_growth_rate: get_f64(pp, &["tau"]),
+        decentralisation: get_f64(pp, &["decentralisation_param"]),
+        extra_entropy: get_string(pp, &["extra_entropy"]),
+        protocol_major: get_u64(pp, &["protocol_major_ver"]),
+        protocol_minor: get_u64(pp, &["protocol_minor_ver"]),
+        min_utxo_value: get_string(pp, &["min_utxo"]),
+        min_pool_cost: get_string(pp, &["min_pool_cost"]),
+        nonce: get_string(pp, &["nonce"]),
+        block_hash: String::new(),
+        cost_models: Some(CostModels {
+            plutus_v1: get_cost_model(pp, &["cost_models_raw", "PlutusV1"])
+                .or_else(|| get_cost_model(pp, &["cost_models", "PlutusV1"])),
+            plutus_v2: get_cost_model(pp, &["cost_models_raw", "PlutusV2"])
+                .or_else(|| get_cost_model(pp, &["cost_models", "PlutusV2"])),
+            plutus_v3: get_cost_model(pp, &["cost_models_raw", "PlutusV3"])
+                .or_else(|| get_cost_model(pp, &["cost_models", "PlutusV3"])),
+        }),
+        price_mem: get_f64(pp, &["price_mem"]),
+        price_step: get_f64(pp, &["price_step"]),
+        max_tx_ex_mem: get_u64(pp, &["max_tx_ex_mem"]),
+        max_tx_ex_steps: get_u64(pp, &["max_tx_ex_steps"]),
+        max_block_ex_mem: get_u64(pp, &["max_block_ex_mem"]),
+        max_block_ex_steps: get_u64(pp, &["max_block_ex_steps"]),
+        max_val_size: get_u64(pp, &["max_val_size"]),
+        collateral_percent: get_u64(pp, &["collateral_percent"]),
+        max_collateral_inputs: get_u64(pp, &["max_collateral_inputs"]),
+        coins_per_utxo_size: get_string(pp, &["coins_per_utxo_size"]),
+        pvt_motion_no_confidence: get_f64(pp, &["pvt_motion_no_confidence"]),
+        pvt_committee_normal: get_f64(pp, &["pvt_committee_normal"]),
+        pvt_committee_no_confidence: get_f64(pp, &["pvt_committee_no_confidence"]),
+        pvt_hard_fork_initiation: get_f64(pp, &["pvt_hard_fork_initiation"]),
+        dvt_motion_no_confidence: get_f64(pp, &["dvt_motion_no_confidence"]),
+        dvt_committee_normal: get_f64(pp, &["dvt_committee_normal"]),
+        dvt_committee_no_confidence: get_f64(pp, &["dvt_committee_no_confidence"]),
+        dvt_update_to_constitution: get_f64(pp, &["dvt_update_to_constitution"]),
+        dvt_hard_fork_initiation: get_f64(pp, &["dvt_hard_fork_initiation"]),
+        dvt_p_p_network_group: get_f64(pp, &["dvt_p_p_network_group"]),
+        dvt_p_p_economic_group: get_f64(pp, &["dvt_p_p_economic_group"]),
+        dvt_p_p_technical_group: get_f64(pp, &["dvt_p_p_technical_group"]),
+        dvt_p_p_gov_group: get_f64(pp, &["dvt_p_p_gov_group"]),
+        dvt_treasury_withdrawal: get_f64(pp, &["dvt_treasury_withdrawal"]),
+        committee_min_size: get_u64(pp, &["committee_min_size"]),
+        committee_max_term_length: get_u64(pp, &["committee_max_term_length"]),
+        gov_action_lifetime: get_u64(pp, &["gov_action_lifetime"]),
+        gov_action_deposit: get_string(pp, &["gov_action_deposit"]),
+        drep_deposit: get_string(pp, &["drep_deposit"]),
+        drep_activity: get_u64(pp, &["drep_activity"]),
+        pvtpp_security_group: get_f64(pp, &["pvtpp_security_group"]),
+        min_fee_ref_script_cost_per_byte: get_f64(pp, &["min_fee_ref_script_cost_per_byte"]),
+    }
+}
+
+/// Maestro mirrors the Ogmios v6 schema in snake_case, so both are read by the
+/// same mapping. `sep` is the separator used in the cost model keys
+/// (`plutus_v1` for Maestro, `plutus:v1` for Ogmios).
+fn from_ogmios_like(pp: &Value, sep: &str) -> EpochParamResponse {
+    let camel = sep == ":";
+    let key = |snake: &'static str, camel_case: &'static str| if camel { camel_case } else { snake };
+    let cost_models_key = key("plutus_cost_models", "plutusCostModels");
+    let prices_key = key("script_execution_prices", "scriptExecutionPrices");
+    let tx_units_key = key("max_execution_units_per_transaction", "maxExecutionUnitsPerTransaction");
+    let block_units_key = key("max_execution_units_per_block", "maxExecutionUnitsPerBlock");
+    let thresholds_key = key("delegate_representative_voting_thresholds", "delegateRepresentativeVotingThresholds");
+    let pool_thresholds_key = key("stake_pool_voting_thresholds", "stakePoolVotingThresholds");
+
+    EpochParamResponse {
+        epoch_no: get_u64(pp, &["epoch"]).unwrap_or_default(),
+        min_fee_a: get_u64(pp, &[key("min_fee_coefficient", "minFeeCoefficient")]),
+        min_fee_b: get_u64(pp, &[key("min_fee_constant", "minFeeConstant")]),
+        max_block_size: get_u64(pp, &[key("max_block_body_size", "maxBlockBodySize")]),
+        max_tx_size: get_u64(pp, &[key("max_transaction_size", "maxTransactionSize")]),
+        max_bh_size: get_u64(pp, &[key("max_block_header_size", "maxBlockHeaderSize")]),
+        key_deposit: get_string(pp, &[key("stake_credential_deposit", "stakeCredentialDeposit")]),
+        pool_deposit: get_string(pp, &[key("stake_pool_deposit", "stakePoolDeposit")]),
+        max_epoch: get_u64(pp, &[key("stake_pool_retirement_epoch_bound", "stakePoolRetirementEpochBound")]),
+        optimal_pool_count: get_u64(pp, &[key("desired_number_of_stake_pools", "desiredNumberOfStakePools")]),
+        influence: get_f64(pp, &[key("stake_pool_pledge_influence", "stakePoolPledgeInfluence")]),
+        monetary_expand_rate: get_f64(pp, &[key("monetary_expansion", "monetaryExpansion")]),
+        treasury_growth_rate: get_f64(pp, &[key("treasury_expansion", "treasuryExpansion")]),
+        decentralisation: get_f64(pp, &[key("federated_block_production_ratio", "federatedBlockProductionRatio")]),
+        extra_entropy: get_string(pp, &[key("extra_entropy", "extraEntropy")]),
+        protocol_major: get_u64(pp, &["version", "major"]),
+        protocol_minor: get_u64(pp, &["version", "minor"]),
+        min_utxo_value: get_string(pp, &[key("min_utxo_deposit_constant", "minUtxoDepositConstant")]),
+        min_pool_cost: get_string(pp, &[key("min_stake_pool_cost", "minStakePoolCost")]),
+        nonce: None,
+        block_hash: String::new(),
+        cost_models: Some(CostModels {
+            plutus_v1: get_cost_model(pp, &[cost_models_key, format!("plutus{}v1", sep).as_str()]),
+            plutus_v2: get_cost_model(pp, &[cost_models_key, format!("plutus{}v2", sep).as_str()]),
+            plutus_v3: get_cost_model(pp, &[cost_models_key, format!("plutus{}v3", sep).as_str()]),
+        }),
+        price_mem: get_f64(pp, &[prices_key, "memory"]),
+        price_step: get_f64(pp, &[prices_key, "cpu"]),
+        max_tx_ex_mem: get_u64(pp, &[tx_units_key, "memory"]),
+        max_tx_ex_steps: get_u64(pp, &[tx_units_key, "cpu"]),
+        max_block_ex_mem: get_u64(pp, &[block_units_key, "memory"]),
+        max_block_ex_steps: get_u64(pp, &[block_units_key, "cpu"]),
+        max_val_size: get_u64(pp, &[key("max_value_size", "maxValueSize")]),
+        collateral_percent: get_u64(pp, &[key("collateral_percentage", "collateralPercentage")]),
+        max_collateral_inputs: get_u64(pp, &[key("max_collateral_inputs", "maxCollateralInputs")]),
+        coins_per_utxo_size: get_string(pp, &[key("min_utxo_deposit_coefficient", "minUtxoDepositCoefficient")]),
+        pvt_motion_no_confidence: get_f64(pp, &[pool_thresholds_key, key("no_confidence", "noConfidence")]),
+        pvt_committee_normal: get_f64(pp, &[pool_thresholds_key, key("constitutional_committee", "constitutionalCommittee"), "default"]),
+        pvt_committee_no_confidence: get_f64(pp, &[pool_thresholds_key, key("constitutional_committee", "constitutionalCommittee"), key("state_of_no_confidence", "stateOfNoConfidence")]),
+        pvt_hard_fork_initiation: get_f64(pp, &[pool_thresholds_key, key("hard_fork_initiation", "hardForkInitiation")]),
+        dvt_motion_no_confidence: get_f64(pp, &[thresholds_key, key("no_confidence", "noConfidence")]),
+        dvt_committee_normal: get_f64(pp, &[thresholds_key, key("constitutional_committee", "constitutionalCommittee"), "default"]),
+        dvt_committee_no_confidence: get_f64(pp, &[thresholds_key, key("constitutional_committee", "constitutionalCommittee"), key("state_of_no_confidence", "stateOfNoConfidence")]),
+        dvt_update_to_constitution: get_f64(pp, &[thresholds_key, "constitution"]),
+        dvt_hard_fork_initiation: get_f64(pp, &[thresholds_key, key("hard_fork_initiation", "hardForkInitiation")]),
+        dvt_p_p_network_group: get_f64(pp, &[thresholds_key, key("protocol_parameters_update", "protocolParametersUpdate"), "network"]),
+        dvt_p_p_economic_group: get_f64(pp, &[thresholds_key, key("protocol_parameters_update", "protocolParametersUpdate"), "economic"]),
+        dvt_p_p_technical_group: get_f64(pp, &[thresholds_key, key("protocol_parameters_update", "protocolParametersUpdate"), "technical"]),
+        dvt_p_p_gov_group: get_f64(pp, &[thresholds_key, key("protocol_parameters_update", "protocolParametersUpdate"), "governance"]),
+        dvt_treasury_withdrawal: get_f64(pp, &[thresholds_key, key("treasury_withdrawals", "treasuryWithdrawals")]),
+        committee_min_size: get_u64(pp, &[key("constitutional_committee_min_size", "constitutionalCommitteeMinSize")]),
+        committee_max_term_length: get_u64(pp, &[key("constitutional_committee_max_term_length", "constitutionalCommitteeMaxTermLength")]),
+        gov_action_lifetime: get_u64(pp, &[key("governance_action_lifetime", "governanceActionLifetime")]),
+        gov_action_deposit: get_string(pp, &[key("governance_action_deposit", "governanceActionDeposit")]),
+        drep_deposit: get_string(pp, &[key("delegate_representative_deposit", "delegateRepresentativeDeposit")]),
+        drep_activity: get_u64(pp, &[key("delegate_representative_max_idle_time", "delegateRepresentativeMaxIdleTime")]),
+        pvtpp_security_group: get_f64(pp, &[pool_thresholds_key, key("protocol_parameters_update", "protocolParametersUpdate"), "security"]),
+        min_fee_ref_script_cost_per_byte: get_f64(pp, &[key("min_fee_reference_scripts", "minFeeReferenceScripts"), "base"]),
+    }
+}
+
+fn from_cardano_cli(pp: &Value) -> EpochParamResponse {
+    EpochParamResponse {
+        epoch_no: 0,
+        min_fee_a: get_u64(pp, &["txFeePerByte"]),
+        min_fee_b: get_u64(pp, &["txFeeFixed"]),
+        max_block_size: get_u64(pp, &["maxBlockBodySize"]),
+        max_tx_size: get_u64(pp, &["maxTxSize"]),
+        max_bh_size: get_u64(pp, &["maxBlockHeaderSize"]),
+        key_deposit: get_string(pp, &["stakeAddressDeposit"]),
+        pool_deposit: get_string(pp, &["stakePoolDeposit"]),
+        max_epoch: get_u64(pp, &["poolRetireMaxEpoch"]),
+        optimal_pool_count: get_u64(pp, &["stakePoolTargetNum"]),
+        influence: get_f64(pp, &["poolPledgeInfluence"]),
+        monetary_expand_rate: get_f64(pp, &["monetaryExpansion"]),
+        treasury_growth_rate: get_f64(pp, &["treasuryCut"]),
+        decentralisation: get_f64(pp, &["decentralization"]),
+        extra_entropy: get_string(pp, &["extraPraosEntropy"]),
+        protocol_major: get_u64(pp, &["protocolVersion", "major"]),
+        protocol_minor: get_u64(pp, &["protocolVersion", "minor"]),
+        min_utxo_value: get_string(pp, &["minUTxOValue"]),
+        min_pool_cost: get_string(pp, &["minPoolCost"]),
+        nonce: None,
+        block_hash: String::new(),
+        cost_models: Some(CostModels {
+            plutus_v1: get_cost_model(pp, &["costModels", "PlutusV1"]),
+            plutus_v2: get_cost_model(pp, &["costModels", "PlutusV2"]),
+            plutus_v3: get_cost_model(pp, &["costModels", "PlutusV3"]),
+        }),
+        price_mem: get_f64(pp, &["executionUnitPrices", "priceMemory"]),
+        price_step: get_f64(pp, &["executionUnitPrices", "priceSteps"]),
+        max_tx_ex_mem: get_u64(pp, &["maxTxExecutionUnits", "memory"]),
+        max_tx_ex_steps: get_u64(pp, &["maxTxExecutionUnits", "steps"]),
+        max_block_ex_mem: get_u64(pp, &["maxBlockExecutionUnits", "memory"]),
+        max_block_ex_steps: get_u64(pp, &["maxBlockExecutionUnits", "steps"]),
+        max_val_size: get_u64(pp, &["maxValueSize"]),
+        collateral_percent: get_u64(pp, &["collateralPercentage"]),
+        max_collateral_inputs: get_u64(pp, &["maxCollateralInputs"]),
+        coins_per_utxo_size: get_string(pp, &["utxoCostPerByte"]),
+        pvt_motion_no_confidence: get_f64(pp, &["poolVotingThresholds", "motionNoConfidence"]),
+        pvt_committee_normal: get_f64(pp, &["poolVotingThresholds", "committeeNormal"]),
+        pvt_committee_no_confidence: get_f64(pp, &["poolVotingThresholds", "committeeNoConfidence"]),
+        pvt_hard_fork_initiation: get_f64(pp, &["poolVotingThresholds", "hardForkInitiation"]),
+        dvt_motion_no_confidence: get_f64(pp, &["dRepVotingThresholds", "motionNoConfidence"]),
+        dvt_committee_normal: get_f64(pp, &["dRepVotingThresholds", "committeeNormal"]),
+        dvt_committee_no_confidence: get_f64(pp, &["dRepVotingThresholds", "committeeNoConfidence"]),
+        dvt_update_to_constitution: get_f64(pp, &["dRepVotingThresholds", "updateToConstitution"]),
+        dvt_hard_fork_initiation: get_f64(pp, &["dRepVotingThresholds", "hardForkInitiation"]),
+        dvt_p_p_network_group: get_f64(pp, &["dRepVotingThresholds", "ppNetworkGroup"]),
+        dvt_p_p_economic_group: get_f64(pp, &["dRepVotingThresholds", "ppEconomicGroup"]),
+        dvt_p_p_technical_group: get_f64(pp, &["dRepVotingThresholds", "ppTechnicalGroup"]),
+        dvt_p_p_gov_group: get_f64(pp, &["dRepVotingThresholds", "ppGovGroup"]),
+        dvt_treasury_withdrawal: get_f64(pp, &["dRepVotingThresholds", "treasuryWithdrawal"]),
+        committee_min_size: get_u64(pp, &["committeeMinSize"]),
+        committee_max_term_length: get_u64(pp, &["committeeMaxTermLength"]),
+        gov_action_lifetime: get_u64(pp, &["govActionLifetime"]),
+        gov_action_deposit: get_string(pp, &["govActionDeposit"]),
+        drep_deposit: get_string(pp, &["dRepDeposit"]),
+        drep_activity: get_u64(pp, &["dRepActivity"]),
+        pvtpp_security_group: get_f64(pp, &["poolVotingThresholds", "ppSecurityGroup"]),
+        min_fee_ref_script_cost_per_byte: get_f64(pp, &["minFeeRefScriptCostPerByte"]),
+    }
+}
+
+fn get_path<'a>(value: &'a Value, path: &[&str]) -> Option<&'a Value> {
+    let mut current = value;
+    for key in path {
+        current = current.get(key)?;
+    }
+    match current {
+        Value::Null => None,
+        _ => Some(current),
+    }
+}
+
+/// Providers wrap amounts differently: plain numbers, numeric strings,
+/// `{"ada": {"lovelace": n}}` and `{"bytes": n}` are all accepted.
+fn unwrap_quantity(value: &Value) -> &Value {
+    if let Some(lovelace) = value.get("ada").and_then(|ada| ada.get("lovelace")) {
+        return lovelace;
+    }
+    if let Some(lovelace) = value.get("lovelace") {
+        return lovelace;
+    }
+    if let Some(bytes) = value.get("bytes") {
+        return bytes;
+    }
+    value
+}
+
+fn get_u64(value: &Value, path: &[&str]) -> Option<u64> {
+    match unwrap_quantity(get_path(value, path)?) {
+        Value::Number(n) => n.as_u64().or_else(|| n.as_f64().map(|f| f as u64)),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+fn get_f64(value: &Value, path: &[&str]) -> Option<f64> {
+    match unwrap_quantity(get_path(value, path)?) {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => parse_ratio(s),
+        _ => None,
+    }
+}
+
+fn get_string(value: &Value, path: &[&str]) -> Option<String> {
+    match unwrap_quantity(get_path(value, path)?) {
+        Value::Number(n) => Some(n.to_string()),
+        Value::String(s) => Some(s.clone()),
+        _ => None,
+    }
+}
+
+/// Cost models come either as a plain list or as a map of named parameters.
+/// Named maps are taken in key order, which matches the ledger order for
+/// PlutusV1/V2 but not necessarily for V3, so raw lists should be preferred.
+fn get_cost_model(value: &Value, path: &[&str]) -> Option<Vec<i64>> {
+    match get_path(value, path)? {
+        Value::Array(items) => items.iter().map(|x| x.as_i64()).collect(),
+        Value::Object(named) => named.values().map(|x| x.as_i64()).collect(),
+        _ => None,
+    }
+}
+
+fn parse_ratio(s: &str) -> Option<f64> {
+    match s.split_once('/') {
+        Some((num, den)) => {
+            let num: f64 = num.trim().parse().ok()?;
+            let den: f64 = den.trim().parse().ok()?;
+            if den == 0.0 {
+                None
+            } else {
+                Some(num / den)
+            }
+        }
+        None => s.parse().ok(),
+    }
+}
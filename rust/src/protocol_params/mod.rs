@@ -0,0 +1,2 @@
+pub(crate) mod adapters;
+pub mod presets;
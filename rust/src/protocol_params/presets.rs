@@ -0,0 +1,43 @@
+use crate::bingen::wasm_bindgen;
+use crate::error::ErrorCode;
+use crate::js_error::JsError;
+use crate::schemas::to_versioned_json;
+use crate::koios_client::models::EpochParamResponse;
+use crate::protocol_params::adapters::parse_protocol_params;
+
+/// Protocol parameter snapshots bundled with the crate for offline use. Cost
+/// models are left empty so the evaluator falls back to the uplc built-in
+/// defaults, the rest mirrors the values on-chain at the snapshot epoch.
+pub(crate) const PRESETS: [(&str, &str); 3] = [
+    ("mainnet", include_str!("presets/mainnet.json")),
+    ("preprod", include_str!("presets/preprod.json")),
+    ("preview", include_str!("presets/preview.json")),
+];
+
+pub(crate) fn get_preset(name: &str) -> Result<EpochParamResponse, JsError> {
+    let name = name.trim().to_lowercase();
+    let (_, json) = PRESETS
+        .iter()
+        .find(|(preset_name, _)| *preset_name == name)
+        .ok_or_else(|| ErrorCode::NotFound.error(&format!(
+            "Unknown protocol params preset: {}, expected one of {}",
+            name,
+            preset_names().join(", ")
+        )))?;
+    parse_protocol_params(json)
+}
+
+pub(crate) fn preset_names() -> Vec<&'static str> {
+    PRESETS.iter().map(|(name, _)| *name).collect()
+}
+
+#[wasm_bindgen]
+pub fn get_protocol_params_preset(preset_name: &str) -> Result<String, JsError> {
+    let pp = get_preset(preset_name)?;
+    to_versioned_json(&pp)
+}
+
+#[wasm_bindgen]
+pub fn get_protocol_params_preset_names() -> Vec<String> {
+    preset_names().iter().map(|name| name.to_string()).collect()
+}
@@ -0,0 +1,283 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use crate::era_history::EraSummary;
+use crate::error::ErrorCode;
+use crate::js_error::JsError;
+use crate::logger::log_debug;
+use serde_json::Value;
+use crate::koios_client::models::{AccountInfoResponse, DatumInfoResponse, EpochParamResponse, QueryChainTipResponse, ScriptInfoResponse, TxCborResponse, TxStatusResponse, UtxoInfoResponse};
+use crate::provider::Provider;
+use crate::time_utils::now_ms;
+
+const CHAIN_TIP_TTL_MS: f64 = 20_000.0;
+
+/// Key-value store backing [`CachedProvider`]. Values are opaque strings,
+/// expiry is handled by the cache itself so stores don't need TTL support.
+#[async_trait(?Send)]
+pub(crate) trait CacheStorage {
+    async fn get(&self, key: &str) -> Result<Option<String>, JsError>;
+
+    async fn set(&self, key: &str, value: String) -> Result<(), JsError>;
+}
+
+/// In-memory storage, lives as long as the value holding it.
+#[derive(Default)]
+pub(crate) struct MemoryStorage {
+    entries: RefCell<HashMap<String, String>>,
+}
+
+#[async_trait(?Send)]
+impl CacheStorage for MemoryStorage {
+    async fn get(&self, key: &str) -> Result<Option<String>, JsError> {
+        Ok(self.entries.borrow().get(key).cloned())
+    }
+
+    async fn set(&self, key: &str, value: String) -> Result<(), JsError> {
+        self.entries.borrow_mut().insert(key.to_string(), value);
+        Ok(())
+    }
+}
+
+#[async_trait(?Send)]
+impl<S: CacheStorage> CacheStorage for Rc<S> {
+    async fn get(&self, key: &str) -> Result<Option<String>, JsError> {
+        self.as_ref().get(key).await
+    }
+
+    async fn set(&self, key: &str, value: String) -> Result<(), JsError> {
+        self.as_ref().set(key, value).await
+    }
+}
+
+thread_local! {
+    static INSTANCE_STORAGE: Rc<MemoryStorage> = Rc::new(MemoryStorage::default());
+}
+
+/// Memory storage shared by every call into this (WASM) instance, so repeated
+/// evaluations against the same network reuse the chain tip and epoch params.
+pub(crate) fn instance_storage() -> Rc<MemoryStorage> {
+    INSTANCE_STORAGE.with(Rc::clone)
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry<T> {
+    expires_at: Option<f64>,
+    value: T,
+}
+
+/// Caches what doesn't change under a provider: protocol params per epoch,
+/// datums, scripts and tx CBOR by hash. The chain tip is kept for a short while only
+/// and UTxOs always go to the provider since they can be spent any time.
+/// `namespace` prefixes all keys so several networks can share one storage.
+pub(crate) struct CachedProvider<P: Provider, S: CacheStorage> {
+    inner: P,
+    storage: S,
+    namespace: String,
+    chain_tip_ttl_ms: f64,
+    force_refresh: bool,
+}
+
+impl<P: Provider, S: CacheStorage> CachedProvider<P, S> {
+    pub(crate) fn new(inner: P, storage: S, namespace: &str) -> Self {
+        CachedProvider {
+            inner,
+            storage,
+            namespace: namespace.to_string(),
+            chain_tip_ttl_ms: CHAIN_TIP_TTL_MS,
+            force_refresh: false,
+        }
+    }
+
+    /// Ignores cached entries and goes to the provider for everything,
+    /// overwriting what's cached with the fresh results.
+    pub(crate) fn with_force_refresh(mut self, force_refresh: bool) -> Self {
+        self.force_refresh = force_refresh;
+        self
+    }
+
+    fn key(&self, kind: &str, id: &str) -> String {
+        format!("cquisitor:{}:{}:{}", self.namespace, kind, id)
+    }
+
+    async fn read<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, JsError> {
+        if self.force_refresh {
+            return Ok(None);
+        }
+        let Some(raw) = self.storage.get(key).await? else {
+            return Ok(None);
+        };
+        // entries that don't parse were written by another version, treat as a miss
+        match serde_json::from_str::<CacheEntry<T>>(&raw) {
+            Ok(entry) if entry.expires_at.is_none_or(|expires_at| expires_at > now_ms()) => {
+                Ok(Some(entry.value))
+            }
+            Ok(_) => Ok(None),
+            Err(e) => {
+                log_debug!("ignoring unreadable cache entry {}: {}", key, e);
+                Ok(None)
+            }
+        }
+    }
+
+    async fn write<T: Serialize>(&self, key: &str, value: &T, ttl_ms: Option<f64>) -> Result<(), JsError> {
+        let entry = CacheEntry {
+            expires_at: ttl_ms.map(|ttl| now_ms() + ttl),
+            value,
+        };
+        let raw = serde_json::to_string(&entry).map_err(|e| ErrorCode::Internal.error(&e.to_string()))?;
+        self.storage.set(key, raw).await
+    }
+}
+
+#[async_trait(?Send)]
+impl<P: Provider, S: CacheStorage> Provider for CachedProvider<P, S> {
+    async fn get_utxos(&self, utxo_refs: &Vec<String>) -> Result<Vec<UtxoInfoResponse>, JsError> {
+        self.inner.get_utxos(utxo_refs).await
+    }
+
+    async fn get_datums(&self, datum_hashes: &Vec<String>) -> Result<Vec<DatumInfoResponse>, JsError> {
+        let mut datums = Vec::new();
+        let mut missing = Vec::new();
+        for hash in datum_hashes {
+            match self.read::<DatumInfoResponse>(&self.key("datum", hash)).await? {
+                Some(datum) => datums.push(datum),
+                None => missing.push(hash.clone()),
+            }
+        }
+        if !missing.is_empty() {
+            for datum in self.inner.get_datums(&missing).await? {
+                self.write(&self.key("datum", &datum.datum_hash), &datum, None).await?;
+                datums.push(datum);
+            }
+        }
+        Ok(datums)
+    }
+
+    async fn get_scripts(&self, script_hashes: &Vec<String>) -> Result<Vec<ScriptInfoResponse>, JsError> {
+        let mut scripts = Vec::new();
+        let mut missing = Vec::new();
+        for hash in script_hashes {
+            match self.read::<ScriptInfoResponse>(&self.key("script", hash)).await? {
+                Some(script) => scripts.push(script),
+                None => missing.push(hash.clone()),
+            }
+        }
+        if !missing.is_empty() {
+            for script in self.inner.get_scripts(&missing).await? {
+                self.write(&self.key("script", &script.script_hash), &script, None).await?;
+                scripts.push(script);
+            }
+        }
+        Ok(scripts)
+    }
+
+    async fn get_protocol_params(&self, epoch: u64) -> Result<EpochParamResponse, JsError> {
+        let key = self.key("epoch_params", &epoch.to_string());
+        if let Some(params) = self.read(&key).await? {
+            return Ok(params);
+        }
+        let params = self.inner.get_protocol_params(epoch).await?;
+        self.write(&key, &params, None).await?;
+        Ok(params)
+    }
+
+    async fn get_chain_tip(&self) -> Result<QueryChainTipResponse, JsError> {
+        let key = self.key("tip", "latest");
+        if let Some(tip) = self.read(&key).await? {
+            return Ok(tip);
+        }
+        let tip = self.inner.get_chain_tip().await?;
+        self.write(&key, &tip, Some(self.chain_tip_ttl_ms)).await?;
+        Ok(tip)
+    }
+
+    async fn get_tx_cbor(&self, tx_hashes: &Vec<String>) -> Result<Vec<TxCborResponse>, JsError> {
+        let mut txs = Vec::new();
+        let mut missing = Vec::new();
+        for hash in tx_hashes {
+            match self.read::<TxCborResponse>(&self.key("tx_cbor", hash)).await? {
+                Some(tx) => txs.push(tx),
+                None => missing.push(hash.clone()),
+            }
+        }
+        if !missing.is_empty() {
+            for tx in self.inner.get_tx_cbor(&missing).await? {
+                self.write(&self.key("tx_cbor", &tx.tx_hash), &tx, None).await?;
+                txs.push(tx);
+            }
+        }
+        Ok(txs)
+    }
+
+    async fn get_tx_info(&self, tx_hashes: &Vec<String>) -> Result<Vec<Value>, JsError> {
+        self.inner.get_tx_info(tx_hashes).await
+    }
+
+    async fn get_tx_status(&self, tx_hashes: &Vec<String>) -> Result<Vec<TxStatusResponse>, JsError> {
+        self.inner.get_tx_status(tx_hashes).await
+    }
+
+    async fn get_address_utxos(&self, addresses: &Vec<String>) -> Result<Vec<UtxoInfoResponse>, JsError> {
+        self.inner.get_address_utxos(addresses).await
+    }
+
+    async fn get_address_info(&self, addresses: &Vec<String>) -> Result<Vec<Value>, JsError> {
+        self.inner.get_address_info(addresses).await
+    }
+
+    async fn get_account_info(&self, stake_addresses: &Vec<String>) -> Result<Vec<AccountInfoResponse>, JsError> {
+        self.inner.get_account_info(stake_addresses).await
+    }
+
+    async fn get_account_addresses(&self, stake_addresses: &Vec<String>) -> Result<Vec<Value>, JsError> {
+        self.inner.get_account_addresses(stake_addresses).await
+    }
+
+    async fn get_asset_info(&self, assets: &Vec<(String, String)>) -> Result<Vec<Value>, JsError> {
+        self.inner.get_asset_info(assets).await
+    }
+
+    async fn get_pool_info(&self, pool_ids: &Vec<String>) -> Result<Vec<Value>, JsError> {
+        self.inner.get_pool_info(pool_ids).await
+    }
+
+    async fn get_genesis(&self) -> Result<Value, JsError> {
+        let key = self.key("genesis", "shelley");
+        if let Some(genesis) = self.read(&key).await? {
+            return Ok(genesis);
+        }
+        let genesis = self.inner.get_genesis().await?;
+        self.write(&key, &genesis, None).await?;
+        Ok(genesis)
+    }
+
+    async fn get_era_summaries(&self) -> Result<Vec<EraSummary>, JsError> {
+        let key = self.key("era_summaries", "all");
+        if let Some(eras) = self.read(&key).await? {
+            return Ok(eras);
+        }
+        let eras = self.inner.get_era_summaries().await?;
+        self.write(&key, &eras, None).await?;
+        Ok(eras)
+    }
+
+    async fn get_proposal_list(&self) -> Result<Vec<Value>, JsError> {
+        self.inner.get_proposal_list().await
+    }
+
+    async fn get_proposal_votes(&self, proposal_id: &str) -> Result<Vec<Value>, JsError> {
+        self.inner.get_proposal_votes(proposal_id).await
+    }
+
+    async fn get_drep_info(&self, drep_ids: &Vec<String>) -> Result<Vec<Value>, JsError> {
+        self.inner.get_drep_info(drep_ids).await
+    }
+
+    async fn get_drep_metadata(&self, drep_ids: &Vec<String>) -> Result<Vec<Value>, JsError> {
+        self.inner.get_drep_metadata(drep_ids).await
+    }
+}
@@ -0,0 +1,346 @@
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use async_trait::async_trait;
+use futures::future::{join_all, select, Either};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use crate::bingen::wasm_bindgen;
+use crate::custom_network::ProviderConfig;
+use crate::era_history::{provider_slot_config, EraSummary};
+use crate::error::ErrorCode;
+use crate::js_error::{error_message, JsError};
+use crate::koios_client::error_mapper::{ProviderError, ProviderErrorKind};
+use crate::koios_client::models::{AccountInfoResponse, DatumInfoResponse, EpochParamResponse, QueryChainTipResponse, ScriptInfoResponse, TxCborResponse, TxStatusResponse, UtxoInfoResponse};
+use crate::plutus::evaluation_result::TxEvaluation;
+use crate::plutus::execute_tx_scripts::execute_tx_scripts_with_provider;
+use crate::provider::Provider;
+use crate::schemas::to_versioned_json;
+use crate::time_utils::{now_ms, sleep_ms};
+use crate::abort_signal::{with_abort_signal, AbortSignal};
+
+type Query<'a, T> = Pin<Box<dyn Future<Output = Result<T, JsError>> + 'a>>;
+
+/// Ordered providers to fail over between, e.g.
+///
+/// ```json
+/// {
+///   "providers": [
+///     { "type": "koios", "url": "https://api.koios.rest/api/v1" },
+///     { "type": "ogmios", "url": "http://localhost:1337" }
+///   ],
+///   "timeout_ms": 10000,
+///   "health_check": true
+/// }
+/// ```
+///
+/// With `health_check` the providers failing it are only tried after the
+/// healthy ones.
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct FallbackConfig {
+    pub(crate) providers: Vec<ProviderConfig>,
+    /// Per query and provider, after which the next provider is tried.
+    pub(crate) timeout_ms: Option<u64>,
+    #[serde(default)]
+    pub(crate) health_check: bool,
+}
+
+impl FallbackConfig {
+    pub(crate) fn from_json(config_json: &str) -> Result<Self, JsError> {
+        serde_json::from_str(config_json)
+            .map_err(|e| ErrorCode::InvalidInput.error(&format!("Invalid fallback provider config: {}", e)))
+    }
+}
+
+/// A provider that failed a query before another one answered it.
+#[derive(Serialize, Debug, Clone)]
+pub(crate) struct ProviderFailure {
+    pub(crate) provider: String,
+    /// The error JSON, see `CquisitorError`.
+    pub(crate) error: Value,
+}
+
+/// Which provider answered a query, `None` when all of them failed.
+#[derive(Serialize, Debug, Clone)]
+pub(crate) struct ServedBy {
+    pub(crate) query: String,
+    pub(crate) provider: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) failures: Vec<ProviderFailure>,
+}
+
+/// Outcome of asking a provider for the chain tip.
+#[derive(Serialize, Debug, Clone)]
+pub(crate) struct ProviderHealth {
+    pub(crate) provider: String,
+    pub(crate) healthy: bool,
+    pub(crate) latency_ms: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) tip_slot: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) error: Option<Value>,
+}
+
+/// Provider asking each of its providers in order until one answers, so a
+/// hosted API being down or slow doesn't fail the whole call. Any error moves
+/// on to the next provider, including unsupported queries, which lets e.g.
+/// Ogmios be backed by Koios for datum lookups. Which provider served each
+/// query is recorded, see [`FallbackProvider::served_by`].
+pub(crate) struct FallbackProvider {
+    providers: Vec<(String, Box<dyn Provider>)>,
+    timeout_ms: Option<u64>,
+    served_by: RefCell<Vec<ServedBy>>,
+}
+
+impl FallbackProvider {
+    /// `providers` as `(name, provider)` in the order they are tried.
+    pub(crate) fn new(providers: Vec<(String, Box<dyn Provider>)>, timeout_ms: Option<u64>) -> Result<Self, JsError> {
+        if providers.is_empty() {
+            return Err(ErrorCode::InvalidInput.error("A fallback provider needs at least one provider"));
+        }
+        Ok(FallbackProvider {
+            providers,
+            timeout_ms,
+            served_by: RefCell::new(Vec::new()),
+        })
+    }
+
+    pub(crate) fn from_config(config: &FallbackConfig, api_token: &Option<String>) -> Result<Self, JsError> {
+        let providers = config
+            .providers
+            .iter()
+            .map(|provider| Ok((provider.label(), provider.build(api_token)?)))
+            .collect::<Result<_, JsError>>()?;
+        FallbackProvider::new(providers, config.timeout_ms)
+    }
+
+    pub(crate) fn served_by(&self) -> Vec<ServedBy> {
+        self.served_by.borrow().clone()
+    }
+
+    /// Asks every provider for the chain tip, concurrently.
+    pub(crate) async fn check_health(&self) -> Vec<ProviderHealth> {
+        join_all(self.providers.iter().map(|(name, provider)| async move {
+            let started = now_ms();
+            let tip = self.with_timeout("get_chain_tip", provider.get_chain_tip()).await;
+            let latency_ms = now_ms() - started;
+            match tip {
+                Ok(tip) => ProviderHealth {
+                    provider: name.clone(),
+                    healthy: true,
+                    latency_ms,
+                    tip_slot: Some(tip.abs_slot),
+                    error: None,
+                },
+                Err(err) => ProviderHealth {
+                    provider: name.clone(),
+                    healthy: false,
+                    latency_ms,
+                    tip_slot: None,
+                    error: Some(error_json(&error_message(err))),
+                },
+            }
+        }))
+        .await
+    }
+
+    /// Runs the health check and moves the providers failing it to the end,
+    /// keeping the configured order otherwise.
+    pub(crate) async fn prefer_healthy(&mut self) -> Vec<ProviderHealth> {
+        let health = self.check_health().await;
+        let mut providers: Vec<_> = self.providers.drain(..).zip(health.iter().map(|check| check.healthy)).collect();
+        providers.sort_by_key(|(_, healthy)| !healthy);
+        self.providers = providers.into_iter().map(|(provider, _)| provider).collect();
+        health
+    }
+
+    async fn with_timeout<T>(&self, query: &str, future: Query<'_, T>) -> Result<T, JsError> {
+        let Some(timeout_ms) = self.timeout_ms else {
+            return future.await;
+        };
+        match select(future, Box::pin(sleep_ms(timeout_ms))).await {
+            Either::Left((result, _)) => result,
+            Either::Right(_) => Err(ProviderError::new(
+                ProviderErrorKind::Timeout,
+                query,
+                &format!("No answer within {} ms", timeout_ms),
+            )
+            .to_js_error()),
+        }
+    }
+
+    /// The answer of the first provider that doesn't fail `query`, else the
+    /// error of the last one.
+    async fn first_ok<'a, T, F>(&'a self, query: &str, call: F) -> Result<T, JsError>
+    where
+        F: Fn(&'a dyn Provider) -> Query<'a, T>,
+    {
+        let mut failures = Vec::new();
+        let mut last_error = String::new();
+        for (name, provider) in &self.providers {
+            match self.with_timeout(query, call(provider.as_ref())).await {
+                Ok(result) => {
+                    self.record(query, Some(name.clone()), failures);
+                    return Ok(result);
+                }
+                Err(err) => {
+                    last_error = error_message(err);
+                    failures.push(ProviderFailure {
+                        provider: name.clone(),
+                        error: error_json(&last_error),
+                    });
+                }
+            }
+        }
+        self.record(query, None, failures);
+        Err(JsError::new(&last_error))
+    }
+
+    fn record(&self, query: &str, provider: Option<String>, failures: Vec<ProviderFailure>) {
+        self.served_by.borrow_mut().push(ServedBy {
+            query: query.to_string(),
+            provider,
+            failures,
+        });
+    }
+}
+
+fn error_json(message: &str) -> Value {
+    serde_json::from_str(message).unwrap_or_else(|_| Value::String(message.to_string()))
+}
+
+#[async_trait(?Send)]
+impl Provider for FallbackProvider {
+    async fn get_utxos(&self, utxo_refs: &Vec<String>) -> Result<Vec<UtxoInfoResponse>, JsError> {
+        self.first_ok("utxos", |provider| provider.get_utxos(utxo_refs)).await
+    }
+
+    async fn get_datums(&self, datum_hashes: &Vec<String>) -> Result<Vec<DatumInfoResponse>, JsError> {
+        self.first_ok("datums", |provider| provider.get_datums(datum_hashes)).await
+    }
+
+    async fn get_scripts(&self, script_hashes: &Vec<String>) -> Result<Vec<ScriptInfoResponse>, JsError> {
+        self.first_ok("scripts", |provider| provider.get_scripts(script_hashes)).await
+    }
+
+    async fn get_protocol_params(&self, epoch: u64) -> Result<EpochParamResponse, JsError> {
+        self.first_ok("protocol_params", |provider| provider.get_protocol_params(epoch)).await
+    }
+
+    async fn get_chain_tip(&self) -> Result<QueryChainTipResponse, JsError> {
+        self.first_ok("chain_tip", |provider| provider.get_chain_tip()).await
+    }
+
+    async fn get_tx_cbor(&self, tx_hashes: &Vec<String>) -> Result<Vec<TxCborResponse>, JsError> {
+        self.first_ok("tx_cbor", |provider| provider.get_tx_cbor(tx_hashes)).await
+    }
+
+    async fn get_tx_info(&self, tx_hashes: &Vec<String>) -> Result<Vec<Value>, JsError> {
+        self.first_ok("tx_info", |provider| provider.get_tx_info(tx_hashes)).await
+    }
+
+    async fn get_tx_status(&self, tx_hashes: &Vec<String>) -> Result<Vec<TxStatusResponse>, JsError> {
+        self.first_ok("tx_status", |provider| provider.get_tx_status(tx_hashes)).await
+    }
+
+    async fn get_address_utxos(&self, addresses: &Vec<String>) -> Result<Vec<UtxoInfoResponse>, JsError> {
+        self.first_ok("address_utxos", |provider| provider.get_address_utxos(addresses)).await
+    }
+
+    async fn get_address_info(&self, addresses: &Vec<String>) -> Result<Vec<Value>, JsError> {
+        self.first_ok("address_info", |provider| provider.get_address_info(addresses)).await
+    }
+
+    async fn get_account_info(&self, stake_addresses: &Vec<String>) -> Result<Vec<AccountInfoResponse>, JsError> {
+        self.first_ok("account_info", |provider| provider.get_account_info(stake_addresses)).await
+    }
+
+    async fn get_account_addresses(&self, stake_addresses: &Vec<String>) -> Result<Vec<Value>, JsError> {
+        self.first_ok("account_addresses", |provider| provider.get_account_addresses(stake_addresses)).await
+    }
+
+    async fn get_asset_info(&self, assets: &Vec<(String, String)>) -> Result<Vec<Value>, JsError> {
+        self.first_ok("asset_info", |provider| provider.get_asset_info(assets)).await
+    }
+
+    async fn get_pool_info(&self, pool_ids: &Vec<String>) -> Result<Vec<Value>, JsError> {
+        self.first_ok("pool_info", |provider| provider.get_pool_info(pool_ids)).await
+    }
+
+    async fn get_genesis(&self) -> Result<Value, JsError> {
+        self.first_ok("genesis", |provider| provider.get_genesis()).await
+    }
+
+    async fn get_era_summaries(&self) -> Result<Vec<EraSummary>, JsError> {
+        self.first_ok("era_summaries", |provider| provider.get_era_summaries()).await
+    }
+
+    async fn get_proposal_list(&self) -> Result<Vec<Value>, JsError> {
+        self.first_ok("proposal_list", |provider| provider.get_proposal_list()).await
+    }
+
+    async fn get_proposal_votes(&self, proposal_id: &str) -> Result<Vec<Value>, JsError> {
+        self.first_ok("proposal_votes", |provider| provider.get_proposal_votes(proposal_id)).await
+    }
+
+    async fn get_drep_info(&self, drep_ids: &Vec<String>) -> Result<Vec<Value>, JsError> {
+        self.first_ok("drep_info", |provider| provider.get_drep_info(drep_ids)).await
+    }
+
+    async fn get_drep_metadata(&self, drep_ids: &Vec<String>) -> Result<Vec<Value>, JsError> {
+        self.first_ok("drep_metadata", |provider| provider.get_drep_metadata(drep_ids)).await
+    }
+}
+
+#[derive(Serialize)]
+struct FallbackEvaluation {
+    evaluation: TxEvaluation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    health: Option<Vec<ProviderHealth>>,
+    served_by: Vec<ServedBy>,
+}
+
+/// Same as `execute_tx_scripts_for_specific_network`, failing over between
+/// the providers of `config_json`, see [`FallbackConfig`]. Returns
+/// `{ evaluation, health, served_by }` where `served_by` tells which provider
+/// answered each query and which ones failed it before. The slot config comes
+/// from the first provider with an era history or genesis, unless
+/// `slot_config_json`, `{ zero_time, zero_slot, slot_length }`, is given.
+#[wasm_bindgen(catch)]
+pub async fn execute_tx_scripts_with_fallback(
+    tx_hex: &str,
+    config_json: &str,
+    api_token: Option<String>,
+    slot_config_json: Option<String>,
+    signal: Option<AbortSignal>,
+) -> Result<String, JsError> {
+    with_abort_signal(signal, async move {
+        let config = FallbackConfig::from_json(config_json)?;
+        let mut provider = FallbackProvider::from_config(&config, &api_token)?;
+        let health = if config.health_check { Some(provider.prefer_healthy().await) } else { None };
+        let slot_config = provider_slot_config(&provider, tx_hex, slot_config_json.as_deref()).await?;
+        let evaluation = execute_tx_scripts_with_provider(tx_hex, &provider, &slot_config).await?;
+        to_versioned_json(&FallbackEvaluation {
+            evaluation,
+            health,
+            served_by: provider.served_by(),
+        })
+    })
+    .await
+}
+
+/// Chain tip latency and errors of every provider of `config_json`, as
+/// `{ providers: [{ provider, healthy, latency_ms, tip_slot, error }] }`.
+#[wasm_bindgen(catch)]
+pub async fn check_provider_health(
+    config_json: &str,
+    api_token: Option<String>,
+    signal: Option<AbortSignal>,
+) -> Result<String, JsError> {
+    with_abort_signal(signal, async move {
+        let config = FallbackConfig::from_json(config_json)?;
+        let provider = FallbackProvider::from_config(&config, &api_token)?;
+        to_versioned_json(&json!({ "providers": provider.check_health().await }))
+    })
+    .await
+}
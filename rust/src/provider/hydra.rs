@@ -0,0 +1,255 @@
+use std::collections::BTreeMap;
+use async_trait::async_trait;
+use cardano_serialization_lib::{PlutusData, PlutusDatumSchema, PlutusScript};
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+use crate::bingen::wasm_bindgen;
+use crate::era_history::provider_slot_config;
+use crate::error::ErrorCode;
+use crate::js_error::JsError;
+use crate::koios_client::config::HttpOptions;
+use crate::koios_client::error_mapper::{check_status, to_js_error};
+use crate::koios_client::models::{Asset, DatumInfoResponse, EpochParamResponse, InlineDatum, QueryChainTipResponse, ReferenceScript, ScriptInfoResponse, UtxoInfoResponse};
+use crate::plutus::evaluation_result::TxEvaluation;
+use crate::plutus::execute_tx_scripts::execute_tx_scripts_with_provider;
+use crate::protocol_params::adapters::protocol_params_from_value;
+use crate::provider::ogmios::script_hash;
+use crate::provider::request_log::send_logged;
+use crate::provider::Provider;
+use crate::abort_signal::{with_abort_signal, AbortSignal};
+
+/// Output in the cardano-api JSON the head API answers with.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct HydraTxOut {
+    address: String,
+    value: serde_json::Map<String, Value>,
+    #[serde(rename = "datumhash")]
+    datum_hash: Option<String>,
+    inline_datum: Option<Value>,
+    inline_datum_raw: Option<String>,
+    reference_script: Option<HydraReferenceScript>,
+}
+
+#[derive(Deserialize, Debug)]
+struct HydraReferenceScript {
+    script: HydraScriptEnvelope,
+}
+
+/// Text envelope; Plutus scripts are CBOR-wrapped once more than on chain.
+#[derive(Deserialize, Debug)]
+struct HydraScriptEnvelope {
+    #[serde(rename = "type")]
+    script_type: String,
+    #[serde(rename = "cborHex")]
+    cbor_hex: String,
+}
+
+/// Provider for the HTTP API of a Hydra head, e.g. `http://localhost:4001`.
+/// UTxOs come from the confirmed snapshot and protocol params are the head's
+/// own, typically with zeroed fees, so head transactions are evaluated against
+/// the ledger they are submitted to. A head has no epochs, the tip is epoch 0.
+pub(crate) struct HydraProvider {
+    url: String,
+    http: HttpOptions,
+}
+
+impl HydraProvider {
+    pub(crate) fn new(url: &str) -> Self {
+        HydraProvider {
+            url: url.trim_end_matches('/').to_string(),
+            http: HttpOptions::default(),
+        }
+    }
+
+    pub(crate) fn with_http_options(mut self, http: HttpOptions) -> Self {
+        self.http = http;
+        self
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str, location: &'static str) -> Result<T, JsError> {
+        let client = Client::new();
+        let request = client
+            .get(self.http.url(&format!("{}/{}", self.url, path)))
+            .header("Accept", "application/json");
+        let response = send_logged(&client, self.http.apply(request), location, 1)
+            .await
+            .map_err(|err| to_js_error(err, location))?;
+        check_status(response, location)
+            .await?
+            .json()
+            .await
+            .map_err(|err| to_js_error(err, location))
+    }
+
+    async fn snapshot(&self, location: &'static str) -> Result<BTreeMap<String, HydraTxOut>, JsError> {
+        self.get("snapshot/utxo", location).await
+    }
+}
+
+#[async_trait(?Send)]
+impl Provider for HydraProvider {
+    async fn get_utxos(&self, utxo_refs: &Vec<String>) -> Result<Vec<UtxoInfoResponse>, JsError> {
+        let mut snapshot = self.snapshot("hydra.get_utxos").await?;
+        utxo_refs
+            .iter()
+            .filter_map(|utxo_ref| snapshot.remove_entry(utxo_ref))
+            .map(|(utxo_ref, output)| hydra_utxo_to_koios(&utxo_ref, output))
+            .collect()
+    }
+
+    async fn get_address_utxos(&self, addresses: &Vec<String>) -> Result<Vec<UtxoInfoResponse>, JsError> {
+        let snapshot = self.snapshot("hydra.get_address_utxos").await?;
+        snapshot
+            .into_iter()
+            .filter(|(_, output)| addresses.contains(&output.address))
+            .map(|(utxo_ref, output)| hydra_utxo_to_koios(&utxo_ref, output))
+            .collect()
+    }
+
+    async fn get_datums(&self, _datum_hashes: &Vec<String>) -> Result<Vec<DatumInfoResponse>, JsError> {
+        Err(ErrorCode::Unsupported.error("A Hydra head can't look up datums by hash"))
+    }
+
+    /// Only reference scripts of the snapshot can be found.
+    async fn get_scripts(&self, script_hashes: &Vec<String>) -> Result<Vec<ScriptInfoResponse>, JsError> {
+        let snapshot = self.snapshot("hydra.get_scripts").await?;
+        let mut scripts = Vec::new();
+        for (utxo_ref, output) in &snapshot {
+            let Some(script) = &output.reference_script else {
+                continue;
+            };
+            let script = reference_script(utxo_ref, &script.script)?;
+            if script_hashes.contains(&script.hash) && !scripts.iter().any(|known| known.script_hash == script.hash) {
+                scripts.push(ScriptInfoResponse {
+                    script_hash: script.hash,
+                    creation_tx_hash: utxo_ref.split_once('#').map(|(tx_hash, _)| tx_hash.to_string()),
+                    script_type: script.script_type,
+                    value: None,
+                    size: Some(script.size),
+                    bytes: Some(script.bytes),
+                });
+            }
+        }
+        Ok(scripts)
+    }
+
+    async fn get_protocol_params(&self, _epoch: u64) -> Result<EpochParamResponse, JsError> {
+        let params: Value = self.get("protocol-parameters", "hydra.get_protocol_params").await?;
+        protocol_params_from_value(params)
+    }
+
+    async fn get_chain_tip(&self) -> Result<QueryChainTipResponse, JsError> {
+        Ok(QueryChainTipResponse::default())
+    }
+}
+
+pub(crate) fn hydra_utxo_to_koios(utxo_ref: &str, output: HydraTxOut) -> Result<UtxoInfoResponse, JsError> {
+    let (tx_hash, tx_index) = utxo_ref
+        .split_once('#')
+        .and_then(|(tx_hash, index)| index.parse().ok().map(|index| (tx_hash.to_string(), index)))
+        .ok_or_else(|| ErrorCode::ProviderError.error(&format!("Invalid UTxO reference {} in the head snapshot", utxo_ref)))?;
+
+    let mut lovelace = String::from("0");
+    let mut asset_list = Vec::new();
+    for (policy_id, assets) in &output.value {
+        if policy_id == "lovelace" {
+            lovelace = assets.to_string();
+            continue;
+        }
+        for (asset_name, quantity) in assets.as_object().into_iter().flatten() {
+            asset_list.push(Asset {
+                policy_id: policy_id.clone(),
+                asset_name: Some(asset_name.clone()),
+                fingerprint: String::new(),
+                decimals: 0,
+                quantity: quantity.to_string(),
+            });
+        }
+    }
+
+    // older nodes only send the datum as detailed schema JSON
+    let inline_datum = match (output.inline_datum_raw, &output.inline_datum) {
+        (Some(bytes), _) => Some(bytes),
+        (None, Some(datum)) if !datum.is_null() => Some(
+            PlutusData::from_json(&datum.to_string(), PlutusDatumSchema::DetailedSchema)
+                .map_err(|e| ErrorCode::ProviderError.error(&format!("Invalid inline datum of {}: {:?}", utxo_ref, e)))?
+                .to_hex(),
+        ),
+        _ => None,
+    };
+
+    let reference_script = match &output.reference_script {
+        Some(script) => Some(reference_script(utxo_ref, &script.script)?),
+        None => None,
+    };
+
+    Ok(UtxoInfoResponse {
+        tx_hash,
+        tx_index,
+        address: output.address,
+        value: lovelace,
+        stake_address: None,
+        payment_cred: None,
+        epoch_no: 0,
+        block_height: None,
+        block_time: 0,
+        datum_hash: if inline_datum.is_some() { None } else { output.datum_hash },
+        inline_datum: inline_datum.map(|bytes| InlineDatum {
+            bytes,
+            value: Value::Null,
+        }),
+        reference_script,
+        asset_list: if asset_list.is_empty() { None } else { Some(asset_list) },
+        is_spent: false,
+    })
+}
+
+fn reference_script(utxo_ref: &str, envelope: &HydraScriptEnvelope) -> Result<ReferenceScript, JsError> {
+    let script_type = match envelope.script_type.as_str() {
+        "SimpleScript" | "SimpleScriptV1" | "SimpleScriptV2" => "nativeScript",
+        "PlutusScriptV1" => "plutusV1",
+        "PlutusScriptV2" => "plutusV2",
+        "PlutusScriptV3" => "plutusV3",
+        other => {
+            return Err(ErrorCode::DecodeFailure.error(&format!("Unknown script type {} of {}", other, utxo_ref)));
+        }
+    };
+    let bytes = if script_type == "nativeScript" {
+        envelope.cbor_hex.clone()
+    } else {
+        let wrapped = hex::decode(&envelope.cbor_hex).map_err(|e| ErrorCode::InvalidHex.error(&e.to_string()))?;
+        let script = PlutusScript::from_bytes(wrapped)
+            .map_err(|e| ErrorCode::ProviderError.error(&format!("Invalid script of {}: {:?}", utxo_ref, e)))?;
+        hex::encode(script.bytes())
+    };
+    Ok(ReferenceScript {
+        hash: script_hash(script_type, &bytes)?,
+        size: (bytes.len() / 2) as u64,
+        script_type: script_type.to_string(),
+        bytes,
+        value: None,
+    })
+}
+
+/// Same as `execute_tx_scripts_for_specific_network`, for a transaction
+/// destined for the Hydra head whose API is at `hydra_url`, e.g.
+/// `http://localhost:4001`. Inputs and params come from the head. Validity
+/// intervals are in slots of the layer 1 network, whose slot config the head
+/// doesn't expose: `slot_config_json` is `{ zero_time, zero_slot, slot_length }`.
+#[wasm_bindgen(catch)]
+pub async fn execute_tx_scripts_with_hydra(
+    tx_hex: &str,
+    hydra_url: &str,
+    slot_config_json: &str,
+    signal: Option<AbortSignal>,
+) -> Result<TxEvaluation, JsError> {
+    with_abort_signal(signal, async move {
+        let provider = HydraProvider::new(hydra_url);
+        let slot_config = provider_slot_config(&provider, tx_hex, Some(slot_config_json)).await?;
+        execute_tx_scripts_with_provider(tx_hex, &provider, &slot_config).await
+    })
+    .await
+}
@@ -0,0 +1,174 @@
+use async_trait::async_trait;
+use js_sys::{Function, Promise, JSON};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::JsFuture;
+use crate::bingen::wasm_bindgen;
+use crate::era_history::{provider_slot_config, EraSummary};
+use crate::error::ErrorCode;
+use crate::js_error::JsError;
+use crate::koios_client::models::{AccountInfoResponse, DatumInfoResponse, EpochParamResponse, QueryChainTipResponse, ScriptInfoResponse, TxCborResponse, TxStatusResponse, UtxoInfoResponse};
+use crate::plutus::evaluation_result::TxEvaluation;
+use crate::plutus::execute_tx_scripts::execute_tx_scripts_with_provider;
+use crate::protocol_params::adapters::protocol_params_from_value;
+use crate::provider::Provider;
+use crate::abort_signal::{with_abort_signal, AbortSignal};
+
+/// Provider that delegates every lookup to a JS function
+/// `(method, params) => Promise<result>`. Methods and their params:
+///
+/// - `utxos` `{ utxo_refs: ["<tx_hash>#<index>"] }` -> Koios `utxo_info` items
+/// - `datums` `{ datum_hashes: [...] }` -> Koios `datum_info` items
+/// - `scripts` `{ script_hashes: [...] }` -> Koios `script_info` items
+/// - `protocol_params` `{ epoch }` -> params in any format `parse_protocol_params` accepts
+/// - `chain_tip` `{}` -> Koios `tip` item
+/// - `tx_cbor` `{ tx_hashes: [...] }` -> Koios `tx_cbor` items
+/// - `tx_info` `{ tx_hashes: [...] }` -> Koios `tx_info` items
+/// - `tx_status` `{ tx_hashes: [...] }` -> Koios `tx_status` items
+/// - `address_utxos` `{ addresses: [...] }` -> Koios `address_utxos` items
+/// - `address_info` `{ addresses: [...] }` -> Koios `address_info` items
+/// - `account_info` `{ stake_addresses: [...] }` -> Koios `account_info` items
+/// - `account_addresses` `{ stake_addresses: [...] }` -> Koios `account_addresses` items
+/// - `asset_info` `{ assets: [[policy_id, asset_name_hex], ...] }` -> Koios `asset_info` items
+/// - `pool_info` `{ pool_ids: [...] }` -> Koios `pool_info` items
+/// - `genesis` `{}` -> Koios `genesis` object
+/// - `era_summaries` `{}` -> `[{ start_slot, start_time, slot_length }]`, times in POSIX ms
+/// - `proposal_list` `{}` -> Koios `proposal_list` items
+/// - `proposal_votes` `{ proposal_id }` -> Koios `proposal_votes` items
+/// - `drep_info` / `drep_metadata` `{ drep_ids: [...] }` -> Koios items
+pub(crate) struct JsProvider {
+    resolver: Function,
+}
+
+impl JsProvider {
+    pub(crate) fn new(resolver: Function) -> Self {
+        JsProvider { resolver }
+    }
+
+    async fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T, JsError> {
+        let params = JSON::parse(&params.to_string()).map_err(|e| resolver_error(method, e))?;
+        let returned = self
+            .resolver
+            .call2(&JsValue::NULL, &JsValue::from_str(method), &params)
+            .map_err(|e| resolver_error(method, e))?;
+        let result = JsFuture::from(Promise::resolve(&returned))
+            .await
+            .map_err(|e| resolver_error(method, e))?;
+        let json = if result.is_undefined() || result.is_null() {
+            "null".to_string()
+        } else {
+            String::from(JSON::stringify(&result).map_err(|e| resolver_error(method, e))?)
+        };
+        serde_json::from_str(&json)
+            .map_err(|e| ErrorCode::ProviderError.error(&format!("Invalid {} response from resolver: {}", method, e)))
+    }
+}
+
+fn resolver_error(method: &str, error: JsValue) -> JsError {
+    let message = error.as_string().unwrap_or_else(|| format!("{:?}", error));
+    ErrorCode::ProviderError.error(&format!("Resolver failed on {}: {}", method, message))
+}
+
+#[async_trait(?Send)]
+impl Provider for JsProvider {
+    async fn get_utxos(&self, utxo_refs: &Vec<String>) -> Result<Vec<UtxoInfoResponse>, JsError> {
+        self.call("utxos", json!({ "utxo_refs": utxo_refs })).await
+    }
+
+    async fn get_datums(&self, datum_hashes: &Vec<String>) -> Result<Vec<DatumInfoResponse>, JsError> {
+        self.call("datums", json!({ "datum_hashes": datum_hashes })).await
+    }
+
+    async fn get_scripts(&self, script_hashes: &Vec<String>) -> Result<Vec<ScriptInfoResponse>, JsError> {
+        self.call("scripts", json!({ "script_hashes": script_hashes })).await
+    }
+
+    async fn get_protocol_params(&self, epoch: u64) -> Result<EpochParamResponse, JsError> {
+        let value: Value = self.call("protocol_params", json!({ "epoch": epoch })).await?;
+        protocol_params_from_value(value)
+    }
+
+    async fn get_chain_tip(&self) -> Result<QueryChainTipResponse, JsError> {
+        self.call("chain_tip", json!({})).await
+    }
+
+    async fn get_tx_cbor(&self, tx_hashes: &Vec<String>) -> Result<Vec<TxCborResponse>, JsError> {
+        self.call("tx_cbor", json!({ "tx_hashes": tx_hashes })).await
+    }
+
+    async fn get_tx_info(&self, tx_hashes: &Vec<String>) -> Result<Vec<Value>, JsError> {
+        self.call("tx_info", json!({ "tx_hashes": tx_hashes })).await
+    }
+
+    async fn get_tx_status(&self, tx_hashes: &Vec<String>) -> Result<Vec<TxStatusResponse>, JsError> {
+        self.call("tx_status", json!({ "tx_hashes": tx_hashes })).await
+    }
+
+    async fn get_address_utxos(&self, addresses: &Vec<String>) -> Result<Vec<UtxoInfoResponse>, JsError> {
+        self.call("address_utxos", json!({ "addresses": addresses })).await
+    }
+
+    async fn get_address_info(&self, addresses: &Vec<String>) -> Result<Vec<Value>, JsError> {
+        self.call("address_info", json!({ "addresses": addresses })).await
+    }
+
+    async fn get_account_info(&self, stake_addresses: &Vec<String>) -> Result<Vec<AccountInfoResponse>, JsError> {
+        self.call("account_info", json!({ "stake_addresses": stake_addresses })).await
+    }
+
+    async fn get_account_addresses(&self, stake_addresses: &Vec<String>) -> Result<Vec<Value>, JsError> {
+        self.call("account_addresses", json!({ "stake_addresses": stake_addresses })).await
+    }
+
+    async fn get_asset_info(&self, assets: &Vec<(String, String)>) -> Result<Vec<Value>, JsError> {
+        self.call("asset_info", json!({ "assets": assets })).await
+    }
+
+    async fn get_pool_info(&self, pool_ids: &Vec<String>) -> Result<Vec<Value>, JsError> {
+        self.call("pool_info", json!({ "pool_ids": pool_ids })).await
+    }
+
+    async fn get_genesis(&self) -> Result<Value, JsError> {
+        self.call("genesis", json!({})).await
+    }
+
+    async fn get_era_summaries(&self) -> Result<Vec<EraSummary>, JsError> {
+        self.call("era_summaries", json!({})).await
+    }
+
+    async fn get_proposal_list(&self) -> Result<Vec<Value>, JsError> {
+        self.call("proposal_list", json!({})).await
+    }
+
+    async fn get_proposal_votes(&self, proposal_id: &str) -> Result<Vec<Value>, JsError> {
+        self.call("proposal_votes", json!({ "proposal_id": proposal_id })).await
+    }
+
+    async fn get_drep_info(&self, drep_ids: &Vec<String>) -> Result<Vec<Value>, JsError> {
+        self.call("drep_info", json!({ "drep_ids": drep_ids })).await
+    }
+
+    async fn get_drep_metadata(&self, drep_ids: &Vec<String>) -> Result<Vec<Value>, JsError> {
+        self.call("drep_metadata", json!({ "drep_ids": drep_ids })).await
+    }
+}
+
+/// Same as `execute_tx_scripts_for_specific_network`, but all chain data comes
+/// from the given JS resolver instead of Koios, see [`JsProvider`]. The slot
+/// config comes from the resolver's `era_summaries` or `genesis`, unless
+/// `slot_config_json`, `{ zero_time, zero_slot, slot_length }`, is given.
+#[wasm_bindgen(catch)]
+pub async fn execute_tx_scripts_with_resolver(
+    tx_hex: &str,
+    resolver: Function,
+    slot_config_json: Option<String>,
+    signal: Option<AbortSignal>,
+) -> Result<TxEvaluation, JsError> {
+    with_abort_signal(signal, async move {
+        let provider = JsProvider::new(resolver);
+        let slot_config = provider_slot_config(&provider, tx_hex, slot_config_json.as_deref()).await?;
+        execute_tx_scripts_with_provider(tx_hex, &provider, &slot_config).await
+    })
+    .await
+}
@@ -0,0 +1,84 @@
+use async_trait::async_trait;
+use js_sys::{Function, Object, Promise, Reflect};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use crate::bingen::wasm_bindgen;
+use crate::error::ErrorCode;
+use crate::js_error::JsError;
+use crate::koios_client::config::ClientOptions;
+use crate::netwrok_type::NetworkType;
+use crate::plutus::evaluation_result::TxEvaluation;
+use crate::plutus::execute_tx_scripts::execute_tx_scripts_with_provider;
+use crate::era_history::network_slot_config;
+use crate::provider::cache::{CacheStorage, CachedProvider};
+use crate::provider::koios::KoiosProvider;
+use crate::abort_signal::{with_abort_signal, AbortSignal};
+
+/// Cache storage backed by a JS object with `get(key)` and `set(key, value)`
+/// methods. Both may return promises, so localStorage and IndexedDB wrappers
+/// work alike.
+pub(crate) struct JsStorage {
+    storage: Object,
+}
+
+impl JsStorage {
+    pub(crate) fn new(storage: Object) -> Self {
+        JsStorage { storage }
+    }
+
+    async fn call(&self, method: &str, args: &[JsValue]) -> Result<JsValue, JsError> {
+        let function: Function = Reflect::get(&self.storage, &JsValue::from_str(method))
+            .ok()
+            .and_then(|f| f.dyn_into().ok())
+            .ok_or_else(|| ErrorCode::InvalidInput.error(&format!("Cache storage has no {} function", method)))?;
+        let returned = match args {
+            [key] => function.call1(&self.storage, key),
+            [key, value] => function.call2(&self.storage, key, value),
+            _ => function.call0(&self.storage),
+        }
+        .map_err(|e| storage_error(method, e))?;
+        JsFuture::from(Promise::resolve(&returned))
+            .await
+            .map_err(|e| storage_error(method, e))
+    }
+}
+
+fn storage_error(method: &str, error: JsValue) -> JsError {
+    let message = error.as_string().unwrap_or_else(|| format!("{:?}", error));
+    ErrorCode::Internal.error(&format!("Cache storage failed on {}: {}", method, message))
+}
+
+#[async_trait(?Send)]
+impl CacheStorage for JsStorage {
+    async fn get(&self, key: &str) -> Result<Option<String>, JsError> {
+        Ok(self.call("get", &[JsValue::from_str(key)]).await?.as_string())
+    }
+
+    async fn set(&self, key: &str, value: String) -> Result<(), JsError> {
+        self.call("set", &[JsValue::from_str(key), JsValue::from_str(&value)]).await?;
+        Ok(())
+    }
+}
+
+/// Same as `execute_tx_scripts_for_specific_network_with_options`, caching
+/// protocol params, datums, scripts and the chain tip in `storage`, an object
+/// with `get(key)` and `set(key, value)` functions.
+#[wasm_bindgen(catch)]
+pub async fn execute_tx_scripts_with_cache(
+    tx_hex: &str,
+    network: NetworkType,
+    api_token: Option<String>,
+    options_json: &str,
+    storage: Object,
+    signal: Option<AbortSignal>,
+) -> Result<TxEvaluation, JsError> {
+    with_abort_signal(signal, async move {
+        let options = ClientOptions::from_json(options_json)?;
+        let namespace = network.name();
+        let koios = KoiosProvider::new(network.clone().into(), api_token).with_options(options);
+        let provider = CachedProvider::new(koios, JsStorage::new(storage), namespace);
+        let slot_config = network_slot_config(&provider, &network, tx_hex).await;
+        execute_tx_scripts_with_provider(tx_hex, &provider, &slot_config).await
+    })
+    .await
+}
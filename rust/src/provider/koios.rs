@@ -0,0 +1,116 @@
+use async_trait::async_trait;
+use crate::js_error::JsError;
+use crate::koios_client::account_request::{get_account_addresses, get_account_info};
+use crate::koios_client::asset_request::get_asset_info;
+use crate::koios_client::pool_request::get_pool_info;
+use crate::koios_client::governance_request::{get_drep_info, get_drep_metadata, get_proposal_list, get_proposal_votes};
+use crate::koios_client::genesis_request::get_genesis;
+use crate::koios_client::address_request::{get_address_info, get_address_utxos};
+use crate::koios_client::config::{ClientOptions, KoiosConfig};
+use crate::koios_client::datum_request::get_datums;
+use crate::koios_client::epoch_protocol_params_request::get_epoch_protocol_params;
+use serde_json::Value;
+use crate::koios_client::models::{AccountInfoResponse, DatumInfoResponse, EpochParamResponse, QueryChainTipResponse, ScriptInfoResponse, TxCborResponse, TxStatusResponse, UtxoInfoResponse};
+use crate::koios_client::network_type::NetworkType;
+use crate::koios_client::query_chain_tip_request::get_chain_tip;
+use crate::koios_client::script_request::get_scripts;
+use crate::koios_client::tx_request::{get_tx_cbor, get_tx_info, get_tx_status};
+use crate::koios_client::utxo_request::get_utxos;
+use crate::provider::Provider;
+
+pub(crate) struct KoiosProvider {
+    config: KoiosConfig,
+}
+
+impl KoiosProvider {
+    pub(crate) fn new(network_type: NetworkType, api_token: Option<String>) -> Self {
+        KoiosProvider {
+            config: KoiosConfig::new(network_type, api_token, ClientOptions::default()),
+        }
+    }
+
+    pub(crate) fn with_options(self, options: ClientOptions) -> Self {
+        KoiosProvider {
+            config: KoiosConfig::new(self.config.network_type, self.config.api_token, options),
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl Provider for KoiosProvider {
+    async fn get_utxos(&self, utxo_refs: &Vec<String>) -> Result<Vec<UtxoInfoResponse>, JsError> {
+        get_utxos(utxo_refs, &self.config).await
+    }
+
+    async fn get_datums(&self, datum_hashes: &Vec<String>) -> Result<Vec<DatumInfoResponse>, JsError> {
+        get_datums(datum_hashes, &self.config).await
+    }
+
+    async fn get_scripts(&self, script_hashes: &Vec<String>) -> Result<Vec<ScriptInfoResponse>, JsError> {
+        get_scripts(script_hashes, &self.config).await
+    }
+
+    async fn get_protocol_params(&self, epoch: u64) -> Result<EpochParamResponse, JsError> {
+        get_epoch_protocol_params(epoch, &self.config).await
+    }
+
+    async fn get_chain_tip(&self) -> Result<QueryChainTipResponse, JsError> {
+        get_chain_tip(&self.config).await
+    }
+
+    async fn get_tx_cbor(&self, tx_hashes: &Vec<String>) -> Result<Vec<TxCborResponse>, JsError> {
+        get_tx_cbor(tx_hashes, &self.config).await
+    }
+
+    async fn get_tx_info(&self, tx_hashes: &Vec<String>) -> Result<Vec<Value>, JsError> {
+        get_tx_info(tx_hashes, &self.config).await
+    }
+
+    async fn get_tx_status(&self, tx_hashes: &Vec<String>) -> Result<Vec<TxStatusResponse>, JsError> {
+        get_tx_status(tx_hashes, &self.config).await
+    }
+
+    async fn get_address_utxos(&self, addresses: &Vec<String>) -> Result<Vec<UtxoInfoResponse>, JsError> {
+        get_address_utxos(addresses, &self.config).await
+    }
+
+    async fn get_address_info(&self, addresses: &Vec<String>) -> Result<Vec<Value>, JsError> {
+        get_address_info(addresses, &self.config).await
+    }
+
+    async fn get_account_info(&self, stake_addresses: &Vec<String>) -> Result<Vec<AccountInfoResponse>, JsError> {
+        get_account_info(stake_addresses, &self.config).await
+    }
+
+    async fn get_account_addresses(&self, stake_addresses: &Vec<String>) -> Result<Vec<Value>, JsError> {
+        get_account_addresses(stake_addresses, &self.config).await
+    }
+
+    async fn get_asset_info(&self, assets: &Vec<(String, String)>) -> Result<Vec<Value>, JsError> {
+        get_asset_info(assets, &self.config).await
+    }
+
+    async fn get_pool_info(&self, pool_ids: &Vec<String>) -> Result<Vec<Value>, JsError> {
+        get_pool_info(pool_ids, &self.config).await
+    }
+
+    async fn get_genesis(&self) -> Result<Value, JsError> {
+        get_genesis(&self.config).await
+    }
+
+    async fn get_proposal_list(&self) -> Result<Vec<Value>, JsError> {
+        get_proposal_list(&self.config).await
+    }
+
+    async fn get_proposal_votes(&self, proposal_id: &str) -> Result<Vec<Value>, JsError> {
+        get_proposal_votes(proposal_id, &self.config).await
+    }
+
+    async fn get_drep_info(&self, drep_ids: &Vec<String>) -> Result<Vec<Value>, JsError> {
+        get_drep_info(drep_ids, &self.config).await
+    }
+
+    async fn get_drep_metadata(&self, drep_ids: &Vec<String>) -> Result<Vec<Value>, JsError> {
+        get_drep_metadata(drep_ids, &self.config).await
+    }
+}
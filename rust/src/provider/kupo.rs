@@ -0,0 +1,253 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+use crate::bingen::wasm_bindgen;
+use crate::era_history::{provider_slot_config, EraSummary};
+use crate::error::ErrorCode;
+use crate::js_error::JsError;
+use crate::koios_client::config::HttpOptions;
+use crate::koios_client::error_mapper::{check_status, to_js_error};
+use crate::koios_client::models::{Asset, DatumInfoResponse, EpochParamResponse, InlineDatum, QueryChainTipResponse, ReferenceScript, ScriptInfoResponse, UtxoInfoResponse};
+use crate::plutus::evaluation_result::TxEvaluation;
+use crate::plutus::execute_tx_scripts::execute_tx_scripts_with_provider;
+use crate::provider::ogmios::{parse_utxo_ref, script_hash, script_type_from_language, OgmiosProvider};
+use crate::provider::request_log::send_logged;
+use crate::provider::Provider;
+use crate::abort_signal::{with_abort_signal, AbortSignal};
+
+#[derive(Deserialize, Debug)]
+struct KupoValue {
+    coins: u64,
+    #[serde(default)]
+    assets: serde_json::Map<String, Value>,
+}
+
+#[derive(Deserialize, Debug)]
+struct KupoMatch {
+    transaction_id: String,
+    output_index: u64,
+    address: String,
+    value: KupoValue,
+    datum_hash: Option<String>,
+    datum_type: Option<String>,
+    script_hash: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct KupoDatum {
+    datum: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct KupoScript {
+    language: String,
+    script: String,
+}
+
+/// Provider resolving UTxOs, datums and scripts from a Kupo index. Kupo knows
+/// nothing about protocol params or the chain tip, those come from `params`.
+pub(crate) struct KupoProvider<P: Provider> {
+    url: String,
+    params: P,
+    http: HttpOptions,
+}
+
+impl<P: Provider> KupoProvider<P> {
+    pub(crate) fn new(url: &str, params: P) -> Self {
+        KupoProvider {
+            url: url.trim_end_matches('/').to_string(),
+            params,
+            http: HttpOptions::default(),
+        }
+    }
+
+    pub(crate) fn with_http_options(mut self, http: HttpOptions) -> Self {
+        self.http = http;
+        self
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str, location: &'static str) -> Result<T, JsError> {
+        let request = Client::new()
+            .get(self.http.url(&format!("{}/{}", self.url, path)))
+            .header("Accept", "application/json");
+        let response = send_logged(&Client::new(), self.http.apply(request), location, 1)
+            .await
+            .map_err(|err| to_js_error(err, location))?;
+        check_status(response, location)
+            .await?
+            .json()
+            .await
+            .map_err(|err| to_js_error(err, location))
+    }
+
+    async fn get_datum(&self, datum_hash: &str) -> Result<Option<KupoDatum>, JsError> {
+        self.get(&format!("datums/{}", datum_hash), "kupo.get_datum").await
+    }
+
+    async fn get_script(&self, script_hash: &str) -> Result<Option<KupoScript>, JsError> {
+        self.get(&format!("scripts/{}", script_hash), "kupo.get_script").await
+    }
+
+    async fn match_to_koios(&self, kupo_match: KupoMatch) -> Result<UtxoInfoResponse, JsError> {
+        let utxo_ref = format!("{}#{}", kupo_match.transaction_id, kupo_match.output_index);
+
+        // Kupo only reports the hash of inline datums, the datum itself is a separate lookup
+        let inline_datum = match (&kupo_match.datum_type, &kupo_match.datum_hash) {
+            (Some(datum_type), Some(datum_hash)) if datum_type == "inline" => {
+                let datum = self.get_datum(datum_hash).await?.ok_or_else(|| {
+                    ErrorCode::NotFound.error(&format!("Kupo has no inline datum {} of {}", datum_hash, utxo_ref))
+                })?;
+                Some(InlineDatum {
+                    bytes: datum.datum,
+                    value: Value::Null,
+                })
+            }
+            _ => None,
+        };
+
+        let reference_script = match &kupo_match.script_hash {
+            Some(hash) => {
+                let script = self.get_script(hash).await?.ok_or_else(|| {
+                    ErrorCode::NotFound.error(&format!("Kupo has no script {} of {}", hash, utxo_ref))
+                })?;
+                let script_type = script_type_from_language(&script.language)?;
+                Some(ReferenceScript {
+                    hash: hash.clone(),
+                    size: (script.script.len() / 2) as u64,
+                    script_type: script_type.to_string(),
+                    bytes: script.script,
+                    value: None,
+                })
+            }
+            None => None,
+        };
+
+        let mut asset_list = Vec::new();
+        for (unit, quantity) in &kupo_match.value.assets {
+            let (policy_id, asset_name) = unit.split_once('.').unwrap_or((unit.as_str(), ""));
+            asset_list.push(Asset {
+                policy_id: policy_id.to_string(),
+                asset_name: Some(asset_name.to_string()),
+                fingerprint: String::new(),
+                decimals: 0,
+                quantity: quantity.to_string(),
+            });
+        }
+
+        Ok(UtxoInfoResponse {
+            tx_hash: kupo_match.transaction_id,
+            tx_index: kupo_match.output_index,
+            address: kupo_match.address,
+            value: kupo_match.value.coins.to_string(),
+            stake_address: None,
+            payment_cred: None,
+            epoch_no: 0,
+            block_height: None,
+            block_time: 0,
+            datum_hash: if inline_datum.is_some() { None } else { kupo_match.datum_hash },
+            inline_datum,
+            reference_script,
+            asset_list: if asset_list.is_empty() { None } else { Some(asset_list) },
+            is_spent: false,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl<P: Provider> Provider for KupoProvider<P> {
+    async fn get_utxos(&self, utxo_refs: &Vec<String>) -> Result<Vec<UtxoInfoResponse>, JsError> {
+        let mut utxos = Vec::new();
+        for utxo_ref in utxo_refs {
+            let (tx_hash, index) = parse_utxo_ref(utxo_ref)?;
+            // the output pattern also matches spent outputs as long as Kupo hasn't pruned them
+            let matches: Vec<KupoMatch> = self
+                .get(&format!("matches/{}@{}", index, tx_hash), "kupo.get_utxos")
+                .await?;
+            for kupo_match in matches {
+                utxos.push(self.match_to_koios(kupo_match).await?);
+            }
+        }
+        Ok(utxos)
+    }
+
+    async fn get_address_utxos(&self, addresses: &Vec<String>) -> Result<Vec<UtxoInfoResponse>, JsError> {
+        let mut utxos = Vec::new();
+        for address in addresses {
+            let matches: Vec<KupoMatch> = self
+                .get(&format!("matches/{}?unspent", address), "kupo.get_address_utxos")
+                .await?;
+            for kupo_match in matches {
+                utxos.push(self.match_to_koios(kupo_match).await?);
+            }
+        }
+        Ok(utxos)
+    }
+
+    async fn get_datums(&self, datum_hashes: &Vec<String>) -> Result<Vec<DatumInfoResponse>, JsError> {
+        let mut datums = Vec::new();
+        for datum_hash in datum_hashes {
+            if let Some(datum) = self.get_datum(datum_hash).await? {
+                datums.push(DatumInfoResponse {
+                    datum_hash: datum_hash.clone(),
+                    creation_tx_hash: None,
+                    value: None,
+                    bytes: datum.datum,
+                });
+            }
+        }
+        Ok(datums)
+    }
+
+    async fn get_scripts(&self, script_hashes: &Vec<String>) -> Result<Vec<ScriptInfoResponse>, JsError> {
+        let mut scripts = Vec::new();
+        for hash in script_hashes {
+            if let Some(script) = self.get_script(hash).await? {
+                let script_type = script_type_from_language(&script.language)?;
+                scripts.push(ScriptInfoResponse {
+                    script_hash: script_hash(script_type, &script.script)?,
+                    creation_tx_hash: None,
+                    script_type: script_type.to_string(),
+                    value: None,
+                    size: Some((script.script.len() / 2) as u64),
+                    bytes: Some(script.script),
+                });
+            }
+        }
+        Ok(scripts)
+    }
+
+    async fn get_protocol_params(&self, epoch: u64) -> Result<EpochParamResponse, JsError> {
+        self.params.get_protocol_params(epoch).await
+    }
+
+    async fn get_chain_tip(&self) -> Result<QueryChainTipResponse, JsError> {
+        self.params.get_chain_tip().await
+    }
+
+    async fn get_genesis(&self) -> Result<Value, JsError> {
+        self.params.get_genesis().await
+    }
+
+    async fn get_era_summaries(&self) -> Result<Vec<EraSummary>, JsError> {
+        self.params.get_era_summaries().await
+    }
+}
+
+/// Same as `execute_tx_scripts_for_specific_network`, for a self-hosted
+/// Kupo + Ogmios stack: UTxOs come from Kupo, protocol params from Ogmios.
+#[wasm_bindgen(catch)]
+pub async fn execute_tx_scripts_with_kupo(
+    tx_hex: &str,
+    kupo_url: &str,
+    ogmios_url: &str,
+    signal: Option<AbortSignal>,
+) -> Result<TxEvaluation, JsError> {
+    with_abort_signal(signal, async move {
+        let provider = KupoProvider::new(kupo_url, OgmiosProvider::new(ogmios_url));
+        let slot_config = provider_slot_config(&provider, tx_hex, None).await?;
+        execute_tx_scripts_with_provider(tx_hex, &provider, &slot_config).await
+    })
+    .await
+}
@@ -0,0 +1,184 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::Value;
+use uplc::tx::SlotConfig;
+use crate::bingen::wasm_bindgen;
+use crate::era_history::{era_of_slot, tx_reference_slot, EraSummary};
+use crate::error::ErrorCode;
+use crate::js_error::JsError;
+use crate::koios_client::models::{DatumInfoResponse, EpochParamResponse, QueryChainTipResponse, ScriptInfoResponse, TxCborResponse, UtxoInfoResponse};
+use crate::plutus::evaluation_result::TxEvaluation;
+use crate::plutus::execute_tx_scripts::{execute_tx_scripts_with_provider, SlotConfigDefinition};
+use crate::protocol_params::adapters::protocol_params_from_value;
+use crate::provider::{unsupported, Provider};
+use crate::abort_signal::{with_abort_signal, AbortSignal};
+
+/// Chain data a [`MockProvider`] serves, in the Koios shapes, e.g.
+///
+/// ```json
+/// {
+///   "utxos": [{ "tx_hash": "...", "tx_index": 0, "address": "addr_test1...", "value": "5000000", ... }],
+///   "datums": [{ "datum_hash": "...", "bytes": "d87980", ... }],
+///   "scripts": [{ "script_hash": "...", "type": "plutusV3", "bytes": "...", ... }],
+///   "protocol_params": { "epoch_no": 500, "block_hash": "...", ... },
+///   "chain_tip": { "hash": "...", "epoch_no": 500, "abs_slot": 1000, ... },
+///   "slot_config": { "zero_time": 1666656000000, "zero_slot": 0, "slot_length": 1000 }
+/// }
+/// ```
+///
+/// Every field is optional. `protocol_params` can be in any format
+/// `parse_protocol_params` accepts.
+#[derive(Deserialize, Debug, Default)]
+pub(crate) struct MockFixtures {
+    #[serde(default)]
+    utxos: Vec<UtxoInfoResponse>,
+    #[serde(default)]
+    datums: Vec<DatumInfoResponse>,
+    #[serde(default)]
+    scripts: Vec<ScriptInfoResponse>,
+    protocol_params: Option<Value>,
+    chain_tip: Option<QueryChainTipResponse>,
+    #[serde(default)]
+    tx_cbor: Vec<TxCborResponse>,
+    #[serde(default)]
+    tx_info: Vec<Value>,
+    genesis: Option<Value>,
+    #[serde(default)]
+    era_history: Vec<EraSummary>,
+    slot_config: Option<SlotConfigDefinition>,
+}
+
+/// Provider answering from in-memory fixtures, never touching the network, so
+/// evaluations are reproducible offline and a bug report can carry everything
+/// needed to replay it. Unknown references are left out like Koios does.
+pub(crate) struct MockProvider {
+    fixtures: MockFixtures,
+    protocol_params: Option<EpochParamResponse>,
+}
+
+impl MockProvider {
+    pub(crate) fn new(fixtures: MockFixtures) -> Result<Self, JsError> {
+        let protocol_params = fixtures.protocol_params.clone().map(protocol_params_from_value).transpose()?;
+        Ok(MockProvider { fixtures, protocol_params })
+    }
+
+    pub(crate) fn from_json(fixtures_json: &str) -> Result<Self, JsError> {
+        let fixtures = serde_json::from_str(fixtures_json)
+            .map_err(|e| ErrorCode::InvalidInput.error(&format!("Invalid provider fixtures: {}", e)))?;
+        MockProvider::new(fixtures)
+    }
+
+    /// The fixed slot config, else the era `reference_slot` falls in, else
+    /// the default one.
+    pub(crate) fn slot_config(&self, reference_slot: Option<u64>) -> SlotConfig {
+        if let Some(config) = self.fixtures.slot_config {
+            return SlotConfig::from(config);
+        }
+        let eras = &self.fixtures.era_history;
+        let era = match reference_slot {
+            Some(slot) => era_of_slot(eras, slot),
+            None => eras.iter().max_by_key(|era| era.start_slot),
+        };
+        era.map(EraSummary::slot_config).unwrap_or_default()
+    }
+}
+
+#[async_trait(?Send)]
+impl Provider for MockProvider {
+    async fn get_utxos(&self, utxo_refs: &Vec<String>) -> Result<Vec<UtxoInfoResponse>, JsError> {
+        Ok(self
+            .fixtures
+            .utxos
+            .iter()
+            .filter(|utxo| utxo_refs.contains(&format!("{}#{}", utxo.tx_hash, utxo.tx_index)))
+            .cloned()
+            .collect())
+    }
+
+    async fn get_datums(&self, datum_hashes: &Vec<String>) -> Result<Vec<DatumInfoResponse>, JsError> {
+        Ok(self
+            .fixtures
+            .datums
+            .iter()
+            .filter(|datum| datum_hashes.contains(&datum.datum_hash))
+            .cloned()
+            .collect())
+    }
+
+    async fn get_scripts(&self, script_hashes: &Vec<String>) -> Result<Vec<ScriptInfoResponse>, JsError> {
+        Ok(self
+            .fixtures
+            .scripts
+            .iter()
+            .filter(|script| script_hashes.contains(&script.script_hash))
+            .cloned()
+            .collect())
+    }
+
+    async fn get_protocol_params(&self, _epoch: u64) -> Result<EpochParamResponse, JsError> {
+        self.protocol_params
+            .clone()
+            .ok_or_else(|| ErrorCode::NotFound.error("The provider fixtures have no protocol_params"))
+    }
+
+    async fn get_chain_tip(&self) -> Result<QueryChainTipResponse, JsError> {
+        Ok(self.fixtures.chain_tip.clone().unwrap_or_default())
+    }
+
+    async fn get_tx_cbor(&self, tx_hashes: &Vec<String>) -> Result<Vec<TxCborResponse>, JsError> {
+        Ok(self
+            .fixtures
+            .tx_cbor
+            .iter()
+            .filter(|tx| tx_hashes.contains(&tx.tx_hash))
+            .cloned()
+            .collect())
+    }
+
+    async fn get_tx_info(&self, tx_hashes: &Vec<String>) -> Result<Vec<Value>, JsError> {
+        Ok(self
+            .fixtures
+            .tx_info
+            .iter()
+            .filter(|tx| tx["tx_hash"].as_str().is_some_and(|hash| tx_hashes.iter().any(|wanted| wanted == hash)))
+            .cloned()
+            .collect())
+    }
+
+    async fn get_address_utxos(&self, addresses: &Vec<String>) -> Result<Vec<UtxoInfoResponse>, JsError> {
+        Ok(self
+            .fixtures
+            .utxos
+            .iter()
+            .filter(|utxo| !utxo.is_spent && addresses.contains(&utxo.address))
+            .cloned()
+            .collect())
+    }
+
+    async fn get_genesis(&self) -> Result<Value, JsError> {
+        self.fixtures.genesis.clone().ok_or_else(|| unsupported("genesis"))
+    }
+
+    async fn get_era_summaries(&self) -> Result<Vec<EraSummary>, JsError> {
+        if self.fixtures.era_history.is_empty() {
+            return Err(unsupported("era_summaries"));
+        }
+        Ok(self.fixtures.era_history.clone())
+    }
+}
+
+/// Same as `execute_tx_scripts_for_specific_network`, with all chain data
+/// taken from `fixtures_json` instead of a provider, see [`MockFixtures`].
+#[wasm_bindgen(catch)]
+pub async fn execute_tx_scripts_with_fixtures(
+    tx_hex: &str,
+    fixtures_json: &str,
+    signal: Option<AbortSignal>,
+) -> Result<TxEvaluation, JsError> {
+    with_abort_signal(signal, async move {
+        let provider = MockProvider::from_json(fixtures_json)?;
+        let slot_config = provider.slot_config(tx_reference_slot(tx_hex));
+        execute_tx_scripts_with_provider(tx_hex, &provider, &slot_config).await
+    })
+    .await
+}
@@ -0,0 +1,108 @@
+use async_trait::async_trait;
+use crate::error::ErrorCode;
+use crate::js_error::JsError;
+use serde_json::Value;
+use crate::era_history::EraSummary;
+use crate::koios_client::models::{AccountInfoResponse, DatumInfoResponse, EpochParamResponse, QueryChainTipResponse, ScriptInfoResponse, TxCborResponse, TxStatusResponse, UtxoInfoResponse};
+
+pub(crate) mod koios;
+pub mod ogmios;
+pub mod kupo;
+pub mod utxorpc;
+pub mod hydra;
+pub mod mock;
+pub mod fallback;
+pub(crate) mod cache;
+pub mod request_log;
+#[cfg(all(target_arch = "wasm32", not(target_os = "emscripten")))]
+pub mod js_provider;
+#[cfg(all(target_arch = "wasm32", not(target_os = "emscripten")))]
+pub mod js_storage;
+
+/// Source of the chain data the network-aware functions need. Results use the
+/// Koios models so evaluation doesn't care where the data came from. Lookups
+/// beyond what evaluation needs have defaults that report them unsupported.
+#[async_trait(?Send)]
+pub(crate) trait Provider {
+    /// Resolves `tx_hash#index` references. Unknown references are left out.
+    async fn get_utxos(&self, utxo_refs: &Vec<String>) -> Result<Vec<UtxoInfoResponse>, JsError>;
+
+    async fn get_datums(&self, datum_hashes: &Vec<String>) -> Result<Vec<DatumInfoResponse>, JsError>;
+
+    async fn get_scripts(&self, script_hashes: &Vec<String>) -> Result<Vec<ScriptInfoResponse>, JsError>;
+
+    async fn get_protocol_params(&self, epoch: u64) -> Result<EpochParamResponse, JsError>;
+
+    async fn get_chain_tip(&self) -> Result<QueryChainTipResponse, JsError>;
+
+    async fn get_tx_cbor(&self, _tx_hashes: &Vec<String>) -> Result<Vec<TxCborResponse>, JsError> {
+        Err(unsupported("tx_cbor"))
+    }
+
+    async fn get_tx_info(&self, _tx_hashes: &Vec<String>) -> Result<Vec<Value>, JsError> {
+        Err(unsupported("tx_info"))
+    }
+
+    async fn get_tx_status(&self, _tx_hashes: &Vec<String>) -> Result<Vec<TxStatusResponse>, JsError> {
+        Err(unsupported("tx_status"))
+    }
+
+    /// Unspent outputs at the given bech32 addresses.
+    async fn get_address_utxos(&self, _addresses: &Vec<String>) -> Result<Vec<UtxoInfoResponse>, JsError> {
+        Err(unsupported("address_utxos"))
+    }
+
+    async fn get_address_info(&self, _addresses: &Vec<String>) -> Result<Vec<Value>, JsError> {
+        Err(unsupported("address_info"))
+    }
+
+    async fn get_account_info(&self, _stake_addresses: &Vec<String>) -> Result<Vec<AccountInfoResponse>, JsError> {
+        Err(unsupported("account_info"))
+    }
+
+    /// Koios `account_addresses` items, the addresses seen on chain with
+    /// each stake address.
+    async fn get_account_addresses(&self, _stake_addresses: &Vec<String>) -> Result<Vec<Value>, JsError> {
+        Err(unsupported("account_addresses"))
+    }
+
+    /// Koios `asset_info` items for `(policy_id, asset_name_hex)` pairs.
+    async fn get_asset_info(&self, _assets: &Vec<(String, String)>) -> Result<Vec<Value>, JsError> {
+        Err(unsupported("asset_info"))
+    }
+
+    /// Koios `pool_info` items for bech32 pool ids.
+    async fn get_pool_info(&self, _pool_ids: &Vec<String>) -> Result<Vec<Value>, JsError> {
+        Err(unsupported("pool_info"))
+    }
+
+    /// Koios `genesis` object: network magic, system start, epoch and slot length.
+    async fn get_genesis(&self) -> Result<Value, JsError> {
+        Err(unsupported("genesis"))
+    }
+
+    async fn get_era_summaries(&self) -> Result<Vec<EraSummary>, JsError> {
+        Err(unsupported("era_summaries"))
+    }
+
+    async fn get_proposal_list(&self) -> Result<Vec<Value>, JsError> {
+        Err(unsupported("proposal_list"))
+    }
+
+    async fn get_proposal_votes(&self, _proposal_id: &str) -> Result<Vec<Value>, JsError> {
+        Err(unsupported("proposal_votes"))
+    }
+
+    async fn get_drep_info(&self, _drep_ids: &Vec<String>) -> Result<Vec<Value>, JsError> {
+        Err(unsupported("drep_info"))
+    }
+
+    async fn get_drep_metadata(&self, _drep_ids: &Vec<String>) -> Result<Vec<Value>, JsError> {
+        Err(unsupported("drep_metadata"))
+    }
+}
+
+/// Error for the optional queries a backend can't answer.
+pub(crate) fn unsupported(query: &str) -> JsError {
+    ErrorCode::Unsupported.error(&format!("The selected provider doesn't support {} queries", query))
+}
@@ -0,0 +1,300 @@
+use async_trait::async_trait;
+use cardano_serialization_lib::{NativeScript, PlutusScript};
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use crate::bingen::wasm_bindgen;
+use crate::era_history::{parse_utc_time_ms, provider_slot_config, EraSummary};
+use crate::error::ErrorCode;
+use crate::js_error::JsError;
+use crate::koios_client::config::HttpOptions;
+use crate::koios_client::error_mapper::{to_js_error, ProviderError, ProviderErrorKind};
+use crate::koios_client::models::{Asset, DatumInfoResponse, EpochParamResponse, InlineDatum, QueryChainTipResponse, ReferenceScript, ScriptInfoResponse, UtxoInfoResponse};
+use crate::plutus::evaluation_result::TxEvaluation;
+use crate::plutus::execute_tx_scripts::execute_tx_scripts_with_provider;
+use crate::protocol_params::adapters::protocol_params_from_value;
+use crate::provider::request_log::send_logged;
+use crate::provider::Provider;
+use crate::abort_signal::{with_abort_signal, AbortSignal};
+
+#[derive(Deserialize, Debug)]
+struct OgmiosResponse<T> {
+    result: Option<T>,
+    error: Option<Value>,
+}
+
+#[derive(Deserialize, Debug)]
+struct OgmiosTransactionId {
+    id: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct OgmiosScript {
+    language: String,
+    cbor: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct OgmiosUtxo {
+    transaction: OgmiosTransactionId,
+    index: u64,
+    address: String,
+    value: Value,
+    datum_hash: Option<String>,
+    datum: Option<String>,
+    script: Option<OgmiosScript>,
+}
+
+#[derive(Deserialize, Debug)]
+struct OgmiosEraBound {
+    slot: u64,
+    epoch: u64,
+    time: OgmiosRelativeTime,
+}
+
+/// Era bounds are relative to the system start.
+#[derive(Deserialize, Debug)]
+struct OgmiosRelativeTime {
+    seconds: u64,
+}
+
+#[derive(Deserialize, Debug)]
+struct OgmiosSlotLength {
+    milliseconds: u64,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct OgmiosEraParameters {
+    epoch_length: u64,
+    slot_length: OgmiosSlotLength,
+}
+
+#[derive(Deserialize, Debug)]
+struct OgmiosEraSummary {
+    start: OgmiosEraBound,
+    parameters: OgmiosEraParameters,
+}
+
+#[derive(Deserialize, Debug)]
+struct OgmiosTip {
+    slot: u64,
+    id: String,
+}
+
+/// Provider for a local node through Ogmios (v6) JSON-RPC over HTTP. Ogmios
+/// only answers for the current ledger state, so protocol params are always
+/// the current ones, and it can't look up datums or scripts by hash.
+pub(crate) struct OgmiosProvider {
+    url: String,
+    http: HttpOptions,
+}
+
+impl OgmiosProvider {
+    pub(crate) fn new(url: &str) -> Self {
+        OgmiosProvider {
+            url: url.to_string(),
+            http: HttpOptions::default(),
+        }
+    }
+
+    pub(crate) fn with_http_options(mut self, http: HttpOptions) -> Self {
+        self.http = http;
+        self
+    }
+
+    async fn query<T: DeserializeOwned>(&self, method: &'static str, params: Value) -> Result<T, JsError> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+        });
+        let client = Client::new();
+        let request = client
+            .post(self.http.url(&self.url))
+            .json(&request)
+            .header("Accept", "application/json");
+        let response: OgmiosResponse<T> = send_logged(&client, self.http.apply(request), method, 1)
+            .await
+            .map_err(|err| to_js_error(err, method))?
+            .json()
+            .await
+            .map_err(|err| to_js_error(err, method))?;
+        match (response.result, response.error) {
+            (_, Some(error)) => {
+                let message = error.get("message").and_then(Value::as_str).unwrap_or("Ogmios error");
+                let mut provider_error = ProviderError::new(ProviderErrorKind::Provider, method, message);
+                provider_error.body = Some(error.to_string());
+                Err(provider_error.to_js_error())
+            }
+            (Some(result), None) => Ok(result),
+            (None, None) => Err(ErrorCode::ProviderError.error(&format!("{}, empty Ogmios response", method))),
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl Provider for OgmiosProvider {
+    async fn get_utxos(&self, utxo_refs: &Vec<String>) -> Result<Vec<UtxoInfoResponse>, JsError> {
+        let mut output_references = Vec::new();
+        for utxo_ref in utxo_refs {
+            let (tx_hash, index) = parse_utxo_ref(utxo_ref)?;
+            output_references.push(json!({ "transaction": { "id": tx_hash }, "index": index }));
+        }
+        let utxos: Vec<OgmiosUtxo> = self
+            .query("queryLedgerState/utxo", json!({ "outputReferences": output_references }))
+            .await?;
+        utxos.into_iter().map(ogmios_utxo_to_koios).collect()
+    }
+
+    async fn get_datums(&self, _datum_hashes: &Vec<String>) -> Result<Vec<DatumInfoResponse>, JsError> {
+        Err(ErrorCode::Unsupported.error("Ogmios can't look up datums by hash"))
+    }
+
+    async fn get_scripts(&self, _script_hashes: &Vec<String>) -> Result<Vec<ScriptInfoResponse>, JsError> {
+        Err(ErrorCode::Unsupported.error("Ogmios can't look up scripts by hash"))
+    }
+
+    async fn get_protocol_params(&self, _epoch: u64) -> Result<EpochParamResponse, JsError> {
+        let params: Value = self.query("queryLedgerState/protocolParameters", json!({})).await?;
+        protocol_params_from_value(params)
+    }
+
+    async fn get_chain_tip(&self) -> Result<QueryChainTipResponse, JsError> {
+        let tip: OgmiosTip = self.query("queryNetwork/tip", json!({})).await?;
+        let epoch: u64 = self.query("queryLedgerState/epoch", json!({})).await?;
+        Ok(QueryChainTipResponse {
+            hash: tip.id,
+            epoch_no: epoch,
+            abs_slot: tip.slot,
+            ..QueryChainTipResponse::default()
+        })
+    }
+
+    async fn get_era_summaries(&self) -> Result<Vec<EraSummary>, JsError> {
+        let start_time: String = self.query("queryNetwork/startTime", json!({})).await?;
+        let system_start = parse_utc_time_ms(&start_time)
+            .ok_or_else(|| ErrorCode::ProviderError.error(&format!("Invalid Ogmios start time {}", start_time)))?;
+        let summaries: Vec<OgmiosEraSummary> = self.query("queryLedgerState/eraSummaries", json!({})).await?;
+        Ok(summaries
+            .into_iter()
+            .map(|era| EraSummary {
+                start_slot: era.start.slot,
+                start_time: system_start + era.start.time.seconds * 1000,
+                slot_length: era.parameters.slot_length.milliseconds as u32,
+                start_epoch: Some(era.start.epoch),
+                epoch_length: Some(era.parameters.epoch_length),
+            })
+            .collect())
+    }
+}
+
+pub(crate) fn parse_utxo_ref(utxo_ref: &str) -> Result<(&str, u64), JsError> {
+    utxo_ref
+        .split_once('#')
+        .and_then(|(tx_hash, index)| index.parse().ok().map(|index| (tx_hash, index)))
+        .ok_or_else(|| ErrorCode::InvalidInput.error(&format!("Invalid UTxO reference {}", utxo_ref)))
+}
+
+fn ogmios_utxo_to_koios(utxo: OgmiosUtxo) -> Result<UtxoInfoResponse, JsError> {
+    let mut lovelace = 0;
+    let mut asset_list = Vec::new();
+    if let Value::Object(value) = &utxo.value {
+        for (policy_id, assets) in value {
+            if policy_id == "ada" {
+                lovelace = assets["lovelace"].as_u64().unwrap_or_default();
+                continue;
+            }
+            for (asset_name, quantity) in assets.as_object().into_iter().flatten() {
+                asset_list.push(Asset {
+                    policy_id: policy_id.clone(),
+                    asset_name: Some(asset_name.clone()),
+                    // not part of the ledger state, evaluation doesn't need it
+                    fingerprint: String::new(),
+                    decimals: 0,
+                    quantity: quantity.to_string(),
+                });
+            }
+        }
+    }
+
+    let reference_script = match utxo.script {
+        Some(script) => {
+            let script_type = script_type_from_language(&script.language)?;
+            let bytes = script.cbor.ok_or_else(|| {
+                ErrorCode::ProviderError.error(&format!("Script of {}#{} has no CBOR", utxo.transaction.id, utxo.index))
+            })?;
+            Some(ReferenceScript {
+                hash: script_hash(script_type, &bytes)?,
+                size: (bytes.len() / 2) as u64,
+                script_type: script_type.to_string(),
+                bytes,
+                value: None,
+            })
+        }
+        None => None,
+    };
+
+    Ok(UtxoInfoResponse {
+        tx_hash: utxo.transaction.id,
+        tx_index: utxo.index,
+        address: utxo.address,
+        value: lovelace.to_string(),
+        stake_address: None,
+        payment_cred: None,
+        epoch_no: 0,
+        block_height: None,
+        block_time: 0,
+        datum_hash: utxo.datum_hash,
+        inline_datum: utxo.datum.map(|bytes| InlineDatum {
+            bytes,
+            value: Value::Null,
+        }),
+        reference_script,
+        asset_list: if asset_list.is_empty() { None } else { Some(asset_list) },
+        is_spent: false,
+    })
+}
+
+/// Maps the `native`/`plutus:vN` language names Ogmios and Kupo use to the
+/// Koios script types.
+pub(crate) fn script_type_from_language(language: &str) -> Result<&'static str, JsError> {
+    match language {
+        "native" => Ok("nativeScript"),
+        "plutus:v1" => Ok("plutusV1"),
+        "plutus:v2" => Ok("plutusV2"),
+        "plutus:v3" => Ok("plutusV3"),
+        other => Err(ErrorCode::DecodeFailure.error(&format!("Unknown script language {}", other))),
+    }
+}
+
+pub(crate) fn script_hash(script_type: &str, bytes_hex: &str) -> Result<String, JsError> {
+    let bytes = hex::decode(bytes_hex).map_err(|e| ErrorCode::InvalidHex.error(&e.to_string()))?;
+    let hash = match script_type {
+        "nativeScript" => NativeScript::from_bytes(bytes)
+            .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Invalid native script: {:?}", e)))?
+            .hash(),
+        "plutusV1" => PlutusScript::new(bytes).hash(),
+        "plutusV2" => PlutusScript::new_v2(bytes).hash(),
+        _ => PlutusScript::new_v3(bytes).hash(),
+    };
+    Ok(hash.to_hex())
+}
+
+/// Same as `execute_tx_scripts_for_specific_network`, but resolves everything
+/// from an Ogmios endpoint, e.g. `http://localhost:1337`, instead of Koios.
+#[wasm_bindgen(catch)]
+pub async fn execute_tx_scripts_with_ogmios(
+    tx_hex: &str,
+    ogmios_url: &str,
+    signal: Option<AbortSignal>,
+) -> Result<TxEvaluation, JsError> {
+    with_abort_signal(signal, async move {
+        let provider = OgmiosProvider::new(ogmios_url);
+        let slot_config = provider_slot_config(&provider, tx_hex, None).await?;
+        execute_tx_scripts_with_provider(tx_hex, &provider, &slot_config).await
+    })
+    .await
+}
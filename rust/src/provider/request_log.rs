@@ -0,0 +1,116 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use reqwest::{Client, RequestBuilder, Response, Url};
+use serde::Serialize;
+use crate::time_utils::now_ms;
+use crate::diagnostics::count_round_trip;
+
+// query parameters whose values never reach the logger
+const SECRET_PARAMS: [&str; 5] = ["key", "token", "secret", "auth", "project"];
+
+/// What the logger sees of a provider request. Headers and bodies are left
+/// out since they carry API tokens, the URL has credentials and secret-looking
+/// query parameters redacted.
+#[derive(Serialize, Debug, Clone)]
+pub(crate) struct RequestLog {
+    pub(crate) endpoint: String,
+    pub(crate) method: String,
+    pub(crate) url: String,
+    pub(crate) attempt: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) content_length: Option<u64>,
+    pub(crate) duration_ms: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) error: Option<String>,
+}
+
+type Logger = Rc<dyn Fn(&RequestLog)>;
+
+thread_local! {
+    static LOGGER: RefCell<Option<Logger>> = RefCell::new(None);
+}
+
+#[cfg(all(target_arch = "wasm32", not(target_os = "emscripten")))]
+pub(crate) fn set_logger(logger: Option<Logger>) {
+    LOGGER.with(|current| *current.borrow_mut() = logger);
+}
+
+fn log(entry: &RequestLog) {
+    // cloned out so a logger that sets another logger doesn't hit a borrowed RefCell
+    let logger = LOGGER.with(|current| current.borrow().clone());
+    if let Some(logger) = logger {
+        logger(entry);
+    }
+}
+
+/// Sends `request` and reports it to the logger, if one is set.
+pub(crate) async fn send_logged(
+    client: &Client,
+    request: RequestBuilder,
+    endpoint: &str,
+    attempt: u32,
+) -> Result<Response, reqwest::Error> {
+    let request = request.build()?;
+    let method = request.method().to_string();
+    let url = sanitize_url(request.url());
+    let started = now_ms();
+    let result = client.execute(request).await;
+    count_round_trip();
+    log(&RequestLog {
+        endpoint: endpoint.to_string(),
+        method,
+        url,
+        attempt,
+        status: result.as_ref().ok().map(|response| response.status().as_u16()),
+        content_length: result.as_ref().ok().and_then(Response::content_length),
+        duration_ms: now_ms() - started,
+        error: result.as_ref().err().map(|err| err.to_string()),
+    });
+    result
+}
+
+pub(crate) fn sanitize_url(url: &Url) -> String {
+    let mut url = url.clone();
+    let _ = url.set_username("");
+    let _ = url.set_password(None);
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(name, value)| {
+            let lower = name.to_lowercase();
+            let value = if SECRET_PARAMS.iter().any(|secret| lower.contains(secret)) {
+                "redacted".to_string()
+            } else {
+                value.into_owned()
+            };
+            (name.into_owned(), value)
+        })
+        .collect();
+    if !pairs.is_empty() {
+        url.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+    url.to_string()
+}
+
+/// Calls `callback` with every provider request made from now on: endpoint,
+/// method, sanitized URL, attempt, status, content length, duration and error.
+/// Pass `undefined` to stop logging.
+#[cfg(all(target_arch = "wasm32", not(target_os = "emscripten")))]
+#[wasm_bindgen::prelude::wasm_bindgen]
+pub fn set_request_logger(callback: Option<js_sys::Function>) {
+    use wasm_bindgen::JsValue;
+    set_logger(callback.map(|callback| -> Logger {
+        Rc::new(move |entry: &RequestLog| {
+            let Ok(json) = serde_json::to_string(entry) else {
+                return;
+            };
+            if let Ok(value) = js_sys::JSON::parse(&json) {
+                // a throwing logger must not break the request
+                if let Err(e) = callback.call1(&JsValue::NULL, &value) {
+                    crate::logger::log_error!("request logger threw: {:?}", e);
+                }
+            }
+        })
+    }));
+}
@@ -0,0 +1,562 @@
+use async_trait::async_trait;
+use cardano_serialization_lib::{LanguageKind, TransactionOutput};
+use reqwest::Client;
+use serde_json::Value;
+use crate::bingen::wasm_bindgen;
+use crate::era_history::provider_slot_config;
+use crate::error::ErrorCode;
+use crate::js_error::JsError;
+use crate::koios_client::config::HttpOptions;
+use crate::koios_client::error_mapper::{check_status, to_js_error, ProviderError, ProviderErrorKind};
+use crate::koios_client::models::{
+    Asset, CostModels, DatumInfoResponse, EpochParamResponse, InlineDatum, QueryChainTipResponse, ReferenceScript,
+    ScriptInfoResponse, UtxoInfoResponse,
+};
+use crate::plutus::evaluation_result::TxEvaluation;
+use crate::plutus::execute_tx_scripts::execute_tx_scripts_with_provider;
+use crate::provider::ogmios::parse_utxo_ref;
+use crate::provider::request_log::send_logged;
+use crate::provider::Provider;
+use crate::abort_signal::{with_abort_signal, AbortSignal};
+
+const QUERY_SERVICE: &str = "utxorpc.v1alpha.query.QueryService";
+
+// gRPC-web frame flags
+const DATA_FRAME: u8 = 0x00;
+const TRAILER_FRAME: u8 = 0x80;
+const FRAME_HEADER_LEN: usize = 5;
+
+// protobuf wire types
+const WIRE_VARINT: u8 = 0;
+const WIRE_FIXED64: u8 = 1;
+const WIRE_LEN: u8 = 2;
+const WIRE_FIXED32: u8 = 5;
+
+// field numbers of the v1alpha query and cardano messages
+mod fields {
+    // ReadUtxosRequest, ReadUtxosResponse, AnyUtxoData, TxoRef
+    pub const READ_UTXOS_KEYS: u32 = 1;
+    pub const READ_UTXOS_ITEMS: u32 = 1;
+    pub const UTXO_NATIVE_BYTES: u32 = 1;
+    pub const UTXO_TXO_REF: u32 = 2;
+    pub const TXO_REF_HASH: u32 = 1;
+    pub const TXO_REF_INDEX: u32 = 2;
+    // ReadParamsResponse, AnyChainParams, ChainPoint
+    pub const PARAMS_VALUES: u32 = 1;
+    pub const PARAMS_LEDGER_TIP: u32 = 2;
+    pub const CHAIN_PARAMS_CARDANO: u32 = 1;
+    pub const POINT_SLOT: u32 = 1;
+    pub const POINT_HASH: u32 = 2;
+    pub const POINT_HEIGHT: u32 = 3;
+    // cardano.PParams
+    pub const COINS_PER_UTXO_BYTE: u32 = 1;
+    pub const MAX_TX_SIZE: u32 = 2;
+    pub const MIN_FEE_COEFFICIENT: u32 = 3;
+    pub const MIN_FEE_CONSTANT: u32 = 4;
+    pub const MAX_BLOCK_BODY_SIZE: u32 = 5;
+    pub const MAX_BLOCK_HEADER_SIZE: u32 = 6;
+    pub const STAKE_KEY_DEPOSIT: u32 = 7;
+    pub const POOL_DEPOSIT: u32 = 8;
+    pub const POOL_RETIREMENT_EPOCH_BOUND: u32 = 9;
+    pub const DESIRED_NUMBER_OF_POOLS: u32 = 10;
+    pub const MIN_POOL_COST: u32 = 14;
+    pub const PROTOCOL_VERSION: u32 = 15;
+    pub const MAX_VALUE_SIZE: u32 = 16;
+    pub const COLLATERAL_PERCENTAGE: u32 = 17;
+    pub const MAX_COLLATERAL_INPUTS: u32 = 18;
+    pub const COST_MODELS: u32 = 19;
+    pub const PRICES: u32 = 20;
+    pub const MAX_EXECUTION_UNITS_PER_TRANSACTION: u32 = 21;
+    pub const MAX_EXECUTION_UNITS_PER_BLOCK: u32 = 22;
+    pub const MIN_FEE_SCRIPT_REF_COST_PER_BYTE: u32 = 23;
+    // ProtocolVersion, CostModels, CostModel, ExPrices, ExUnits, RationalNumber
+    pub const VERSION_MAJOR: u32 = 1;
+    pub const VERSION_MINOR: u32 = 2;
+    pub const COST_MODEL_VALUES: u32 = 1;
+    pub const PRICES_STEPS: u32 = 1;
+    pub const PRICES_MEMORY: u32 = 2;
+    pub const EX_UNITS_STEPS: u32 = 1;
+    pub const EX_UNITS_MEMORY: u32 = 2;
+    pub const RATIONAL_NUMERATOR: u32 = 1;
+    pub const RATIONAL_DENOMINATOR: u32 = 2;
+}
+
+/// Provider for a UTxO RPC (v1alpha) endpoint such as Dolos or Demeter, over
+/// gRPC-web so it also works from the browser. It covers UTxO resolution,
+/// protocol params and the tip; the query service can't look up datums or
+/// scripts by hash. Demeter wants its key as a `dmtr-api-key` header.
+pub(crate) struct UtxoRpcProvider {
+    url: String,
+    http: HttpOptions,
+}
+
+impl UtxoRpcProvider {
+    pub(crate) fn new(url: &str) -> Self {
+        UtxoRpcProvider {
+            url: url.trim_end_matches('/').to_string(),
+            http: HttpOptions::default(),
+        }
+    }
+
+    pub(crate) fn with_http_options(mut self, http: HttpOptions) -> Self {
+        self.http = http;
+        self
+    }
+
+    async fn call(&self, method: &'static str, message: Vec<u8>) -> Result<Vec<u8>, JsError> {
+        let client = Client::new();
+        let request = client
+            .post(self.http.url(&format!("{}/{}/{}", self.url, QUERY_SERVICE, method)))
+            .header("Content-Type", "application/grpc-web+proto")
+            .header("Accept", "application/grpc-web+proto")
+            .header("X-Grpc-Web", "1")
+            .body(grpc_web_frame(&message));
+        let response = send_logged(&client, self.http.apply(request), method, 1)
+            .await
+            .map_err(|err| to_js_error(err, method))?;
+        let response = check_status(response, method).await?;
+        // a trailers-only response carries the status in the headers
+        let header_status = grpc_status_from_headers(response.headers());
+        let body = response.bytes().await.map_err(|err| to_js_error(err, method))?;
+        let (message, trailer_status) = parse_grpc_web_response(&body)
+            .map_err(|e| ProviderError::new(ProviderErrorKind::Decode, method, &e).to_js_error())?;
+        match trailer_status.or(header_status) {
+            Some((0, _)) | None => Ok(message),
+            Some((code, message)) => Err(grpc_error(code, method, &message)),
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl Provider for UtxoRpcProvider {
+    async fn get_utxos(&self, utxo_refs: &Vec<String>) -> Result<Vec<UtxoInfoResponse>, JsError> {
+        let mut request = Vec::new();
+        for utxo_ref in utxo_refs {
+            let (tx_hash, index) = parse_utxo_ref(utxo_ref)?;
+            let hash = hex::decode(tx_hash).map_err(|e| ErrorCode::InvalidHex.error(&e.to_string()))?;
+            let mut key = Vec::new();
+            put_bytes(&mut key, fields::TXO_REF_HASH, &hash);
+            put_varint_field(&mut key, fields::TXO_REF_INDEX, index);
+            put_bytes(&mut request, fields::READ_UTXOS_KEYS, &key);
+        }
+        let response = self.call("ReadUtxos", request).await?;
+
+        let mut utxos = Vec::new();
+        for item in message_fields(&response, "ReadUtxosResponse")? {
+            if let (fields::READ_UTXOS_ITEMS, WireValue::Bytes(item)) = item {
+                utxos.push(any_utxo_to_koios(item)?);
+            }
+        }
+        Ok(utxos)
+    }
+
+    async fn get_datums(&self, _datum_hashes: &Vec<String>) -> Result<Vec<DatumInfoResponse>, JsError> {
+        Err(ErrorCode::Unsupported.error("UTxO RPC can't look up datums by hash"))
+    }
+
+    async fn get_scripts(&self, _script_hashes: &Vec<String>) -> Result<Vec<ScriptInfoResponse>, JsError> {
+        Err(ErrorCode::Unsupported.error("UTxO RPC can't look up scripts by hash"))
+    }
+
+    async fn get_protocol_params(&self, _epoch: u64) -> Result<EpochParamResponse, JsError> {
+        let response = self.call("ReadParams", Vec::new()).await?;
+        let values = field_bytes(&response, fields::PARAMS_VALUES, "ReadParamsResponse")?
+            .ok_or_else(|| ErrorCode::ProviderError.error("ReadParams, no params in the response"))?;
+        let cardano = field_bytes(values, fields::CHAIN_PARAMS_CARDANO, "AnyChainParams")?
+            .ok_or_else(|| ErrorCode::ProviderError.error("ReadParams, no Cardano params in the response"))?;
+        pparams_to_koios(cardano)
+    }
+
+    async fn get_chain_tip(&self) -> Result<QueryChainTipResponse, JsError> {
+        let response = self.call("ReadParams", Vec::new()).await?;
+        let point = field_bytes(&response, fields::PARAMS_LEDGER_TIP, "ReadParamsResponse")?
+            .ok_or_else(|| ErrorCode::ProviderError.error("ReadParams, no ledger tip in the response"))?;
+        let mut tip = QueryChainTipResponse::default();
+        for (number, value) in message_fields(point, "ChainPoint")? {
+            match (number, value) {
+                (fields::POINT_SLOT, WireValue::Varint(slot)) => tip.abs_slot = slot,
+                (fields::POINT_HASH, WireValue::Bytes(hash)) => tip.hash = hex::encode(hash),
+                (fields::POINT_HEIGHT, WireValue::Varint(height)) => tip.block_no = Some(height),
+                _ => {}
+            }
+        }
+        Ok(tip)
+    }
+}
+
+/// Same as `execute_tx_scripts_for_specific_network`, resolving everything
+/// from a UTxO RPC endpoint, e.g. a local Dolos at `http://localhost:50051`.
+/// `api_key` is sent as Demeter's `dmtr-api-key` header when given. UTxO RPC
+/// has no era history, `slot_config_json`, `{ zero_time, zero_slot,
+/// slot_length }`, is the slot config of the node's network.
+#[wasm_bindgen(catch)]
+pub async fn execute_tx_scripts_with_utxorpc(
+    tx_hex: &str,
+    utxorpc_url: &str,
+    api_key: Option<String>,
+    slot_config_json: &str,
+    signal: Option<AbortSignal>,
+) -> Result<TxEvaluation, JsError> {
+    with_abort_signal(signal, async move {
+        let mut http = HttpOptions::default();
+        if let Some(api_key) = api_key {
+            http.headers.insert("dmtr-api-key".to_string(), api_key);
+        }
+        let provider = UtxoRpcProvider::new(utxorpc_url).with_http_options(http);
+        let slot_config = provider_slot_config(&provider, tx_hex, Some(slot_config_json)).await?;
+        execute_tx_scripts_with_provider(tx_hex, &provider, &slot_config).await
+    })
+    .await
+}
+
+fn grpc_error(code: u64, method: &str, message: &str) -> JsError {
+    // https://grpc.github.io/grpc/core/md_doc_statuscodes.html
+    let kind = match code {
+        4 => ProviderErrorKind::Timeout,
+        5 => ProviderErrorKind::NotFound,
+        7 | 16 => ProviderErrorKind::Auth,
+        8 => ProviderErrorKind::RateLimited,
+        3 | 9 | 11 => ProviderErrorKind::BadRequest,
+        14 => ProviderErrorKind::Network,
+        _ => ProviderErrorKind::Provider,
+    };
+    ProviderError::new(kind, method, &format!("gRPC status {}: {}", code, message)).to_js_error()
+}
+
+pub(crate) fn grpc_web_frame(message: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(FRAME_HEADER_LEN + message.len());
+    frame.push(DATA_FRAME);
+    frame.extend((message.len() as u32).to_be_bytes());
+    frame.extend(message);
+    frame
+}
+
+/// The message of a unary gRPC-web response and the `grpc-status` and
+/// `grpc-message` of its trailer frame, if it has one.
+pub(crate) fn parse_grpc_web_response(body: &[u8]) -> Result<(Vec<u8>, Option<(u64, String)>), String> {
+    let mut message = Vec::new();
+    let mut status = None;
+    let mut rest = body;
+    while !rest.is_empty() {
+        if rest.len() < FRAME_HEADER_LEN {
+            return Err("truncated gRPC-web frame header".to_string());
+        }
+        let flag = rest[0];
+        let len = u32::from_be_bytes([rest[1], rest[2], rest[3], rest[4]]) as usize;
+        // a hostile length near u32::MAX would overflow usize on wasm32
+        let end = FRAME_HEADER_LEN
+            .checked_add(len)
+            .filter(|end| *end <= rest.len())
+            .ok_or_else(|| "truncated gRPC-web frame".to_string())?;
+        let payload = &rest[FRAME_HEADER_LEN..end];
+        if flag & TRAILER_FRAME != 0 {
+            status = parse_trailers(&String::from_utf8_lossy(payload));
+        } else {
+            message.extend(payload);
+        }
+        rest = &rest[end..];
+    }
+    Ok((message, status))
+}
+
+fn parse_trailers(trailers: &str) -> Option<(u64, String)> {
+    let mut code = None;
+    let mut message = String::new();
+    for line in trailers.split("\r\n") {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        match name.trim().to_ascii_lowercase().as_str() {
+            "grpc-status" => code = value.trim().parse().ok(),
+            "grpc-message" => message = value.trim().to_string(),
+            _ => {}
+        }
+    }
+    code.map(|code| (code, message))
+}
+
+fn grpc_status_from_headers(headers: &reqwest::header::HeaderMap) -> Option<(u64, String)> {
+    let code = headers.get("grpc-status")?.to_str().ok()?.parse().ok()?;
+    let message = headers
+        .get("grpc-message")
+        .and_then(|message| message.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    Some((code, message))
+}
+
+/// A protobuf field value, messages, strings and packed lists are `Bytes`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum WireValue<'a> {
+    Varint(u64),
+    Fixed64(u64),
+    Bytes(&'a [u8]),
+    Fixed32(u32),
+}
+
+/// The fields of a protobuf message in wire order.
+pub(crate) fn decode_message(bytes: &[u8]) -> Result<Vec<(u32, WireValue<'_>)>, String> {
+    let mut fields = Vec::new();
+    let mut position = 0;
+    while position < bytes.len() {
+        let key = read_varint(bytes, &mut position)?;
+        let number = (key >> 3) as u32;
+        let value = match (key & 0x7) as u8 {
+            WIRE_VARINT => WireValue::Varint(read_varint(bytes, &mut position)?),
+            WIRE_FIXED64 => {
+                let raw = bytes.get(position..position + 8).ok_or("truncated fixed64")?;
+                position += 8;
+                WireValue::Fixed64(u64::from_le_bytes(raw.try_into().map_err(|_| "truncated fixed64")?))
+            }
+            WIRE_LEN => {
+                let len = read_varint(bytes, &mut position)? as usize;
+                let raw = bytes.get(position..position + len).ok_or("truncated length-delimited field")?;
+                position += len;
+                WireValue::Bytes(raw)
+            }
+            WIRE_FIXED32 => {
+                let raw = bytes.get(position..position + 4).ok_or("truncated fixed32")?;
+                position += 4;
+                WireValue::Fixed32(u32::from_le_bytes(raw.try_into().map_err(|_| "truncated fixed32")?))
+            }
+            other => return Err(format!("unsupported wire type {} of field {}", other, number)),
+        };
+        fields.push((number, value));
+    }
+    Ok(fields)
+}
+
+fn read_varint(bytes: &[u8], position: &mut usize) -> Result<u64, String> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *bytes.get(*position).ok_or("truncated varint")?;
+        *position += 1;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err("varint longer than 10 bytes".to_string())
+}
+
+fn put_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn put_varint_field(out: &mut Vec<u8>, number: u32, value: u64) {
+    put_varint(out, ((number as u64) << 3) | WIRE_VARINT as u64);
+    put_varint(out, value);
+}
+
+fn put_bytes(out: &mut Vec<u8>, number: u32, bytes: &[u8]) {
+    put_varint(out, ((number as u64) << 3) | WIRE_LEN as u64);
+    put_varint(out, bytes.len() as u64);
+    out.extend(bytes);
+}
+
+fn message_fields<'a>(bytes: &'a [u8], message: &str) -> Result<Vec<(u32, WireValue<'a>)>, JsError> {
+    decode_message(bytes).map_err(|e| ErrorCode::ProviderError.error(&format!("Invalid UTxO RPC {}: {}", message, e)))
+}
+
+fn field_bytes<'a>(bytes: &'a [u8], number: u32, message: &str) -> Result<Option<&'a [u8]>, JsError> {
+    Ok(message_fields(bytes, message)?.into_iter().find_map(|field| match field {
+        (n, WireValue::Bytes(value)) if n == number => Some(value),
+        _ => None,
+    }))
+}
+
+fn field_varint(bytes: &[u8], number: u32, message: &str) -> Result<Option<u64>, JsError> {
+    Ok(message_fields(bytes, message)?.into_iter().find_map(|field| match field {
+        (n, WireValue::Varint(value)) if n == number => Some(value),
+        _ => None,
+    }))
+}
+
+/// An `AnyUtxoData` as Koios `utxo_info`, from the output CBOR in
+/// `native_bytes` rather than the parsed form, which differs between versions.
+fn any_utxo_to_koios(item: &[u8]) -> Result<UtxoInfoResponse, JsError> {
+    let native_bytes = field_bytes(item, fields::UTXO_NATIVE_BYTES, "AnyUtxoData")?
+        .ok_or_else(|| ErrorCode::ProviderError.error("UTxO RPC returned a UTxO without its CBOR"))?;
+    let txo_ref = field_bytes(item, fields::UTXO_TXO_REF, "AnyUtxoData")?
+        .ok_or_else(|| ErrorCode::ProviderError.error("UTxO RPC returned a UTxO without its reference"))?;
+    let tx_hash = field_bytes(txo_ref, fields::TXO_REF_HASH, "TxoRef")?.map(hex::encode).unwrap_or_default();
+    let tx_index = field_varint(txo_ref, fields::TXO_REF_INDEX, "TxoRef")?.unwrap_or_default();
+    output_to_koios(tx_hash, tx_index, native_bytes)
+}
+
+pub(crate) fn output_to_koios(tx_hash: String, tx_index: u64, output_bytes: &[u8]) -> Result<UtxoInfoResponse, JsError> {
+    let output = TransactionOutput::from_bytes(output_bytes.to_vec()).map_err(|e| {
+        ErrorCode::ProviderError.error(&format!("Invalid output CBOR of {}#{}: {:?}", tx_hash, tx_index, e))
+    })?;
+    let address = output.address();
+    let address = address.to_bech32(None).unwrap_or_else(|_| address.to_hex());
+
+    let mut asset_list = Vec::new();
+    if let Some(multiasset) = output.amount().multiasset() {
+        let policies = multiasset.keys();
+        for i in 0..policies.len() {
+            let policy = policies.get(i);
+            let Some(assets) = multiasset.get(&policy) else {
+                continue;
+            };
+            let names = assets.keys();
+            for j in 0..names.len() {
+                let name = names.get(j);
+                asset_list.push(Asset {
+                    policy_id: policy.to_hex(),
+                    asset_name: Some(hex::encode(name.name())),
+                    // not part of the ledger state, evaluation doesn't need it
+                    fingerprint: String::new(),
+                    decimals: 0,
+                    quantity: assets.get(&name).map(|q| q.to_str()).unwrap_or_default(),
+                });
+            }
+        }
+    }
+
+    let reference_script = output.script_ref().and_then(|script_ref| match script_ref.native_script() {
+        Some(native) => Some(ReferenceScript {
+            hash: native.hash().to_hex(),
+            size: native.to_bytes().len() as u64,
+            script_type: "nativeScript".to_string(),
+            bytes: native.to_hex(),
+            value: None,
+        }),
+        None => script_ref.plutus_script().map(|plutus| {
+            let script_type = match plutus.language_version().kind() {
+                LanguageKind::PlutusV1 => "plutusV1",
+                LanguageKind::PlutusV2 => "plutusV2",
+                LanguageKind::PlutusV3 => "plutusV3",
+            };
+            ReferenceScript {
+                hash: plutus.hash().to_hex(),
+                size: plutus.bytes().len() as u64,
+                script_type: script_type.to_string(),
+                bytes: hex::encode(plutus.bytes()),
+                value: None,
+            }
+        }),
+    });
+
+    Ok(UtxoInfoResponse {
+        tx_hash,
+        tx_index,
+        address,
+        value: output.amount().coin().to_str(),
+        stake_address: None,
+        payment_cred: None,
+        epoch_no: 0,
+        block_height: None,
+        block_time: 0,
+        datum_hash: output.data_hash().map(|hash| hash.to_hex()),
+        inline_datum: output.plutus_data().map(|data| InlineDatum {
+            bytes: data.to_hex(),
+            value: Value::Null,
+        }),
+        reference_script,
+        asset_list: if asset_list.is_empty() { None } else { Some(asset_list) },
+        is_spent: false,
+    })
+}
+
+/// `cardano.PParams` in the Koios shape. Fields the message doesn't carry,
+/// like the governance thresholds, stay unset.
+pub(crate) fn pparams_to_koios(bytes: &[u8]) -> Result<EpochParamResponse, JsError> {
+    let mut pp = EpochParamResponse::default();
+    for (number, value) in message_fields(bytes, "PParams")? {
+        match (number, value) {
+            (fields::COINS_PER_UTXO_BYTE, WireValue::Varint(v)) => pp.coins_per_utxo_size = Some(v.to_string()),
+            (fields::MAX_TX_SIZE, WireValue::Varint(v)) => pp.max_tx_size = Some(v),
+            (fields::MIN_FEE_COEFFICIENT, WireValue::Varint(v)) => pp.min_fee_a = Some(v),
+            (fields::MIN_FEE_CONSTANT, WireValue::Varint(v)) => pp.min_fee_b = Some(v),
+            (fields::MAX_BLOCK_BODY_SIZE, WireValue::Varint(v)) => pp.max_block_size = Some(v),
+            (fields::MAX_BLOCK_HEADER_SIZE, WireValue::Varint(v)) => pp.max_bh_size = Some(v),
+            (fields::STAKE_KEY_DEPOSIT, WireValue::Varint(v)) => pp.key_deposit = Some(v.to_string()),
+            (fields::POOL_DEPOSIT, WireValue::Varint(v)) => pp.pool_deposit = Some(v.to_string()),
+            (fields::POOL_RETIREMENT_EPOCH_BOUND, WireValue::Varint(v)) => pp.max_epoch = Some(v),
+            (fields::DESIRED_NUMBER_OF_POOLS, WireValue::Varint(v)) => pp.optimal_pool_count = Some(v),
+            (fields::MIN_POOL_COST, WireValue::Varint(v)) => pp.min_pool_cost = Some(v.to_string()),
+            (fields::PROTOCOL_VERSION, WireValue::Bytes(version)) => {
+                pp.protocol_major = field_varint(version, fields::VERSION_MAJOR, "ProtocolVersion")?;
+                pp.protocol_minor = field_varint(version, fields::VERSION_MINOR, "ProtocolVersion")?;
+            }
+            (fields::MAX_VALUE_SIZE, WireValue::Varint(v)) => pp.max_val_size = Some(v),
+            (fields::COLLATERAL_PERCENTAGE, WireValue::Varint(v)) => pp.collateral_percent = Some(v),
+            (fields::MAX_COLLATERAL_INPUTS, WireValue::Varint(v)) => pp.max_collateral_inputs = Some(v),
+            (fields::COST_MODELS, WireValue::Bytes(models)) => pp.cost_models = Some(cost_models(models)?),
+            (fields::PRICES, WireValue::Bytes(prices)) => {
+                pp.price_step = rational_field(prices, fields::PRICES_STEPS, "ExPrices")?;
+                pp.price_mem = rational_field(prices, fields::PRICES_MEMORY, "ExPrices")?;
+            }
+            (fields::MAX_EXECUTION_UNITS_PER_TRANSACTION, WireValue::Bytes(units)) => {
+                pp.max_tx_ex_steps = field_varint(units, fields::EX_UNITS_STEPS, "ExUnits")?;
+                pp.max_tx_ex_mem = field_varint(units, fields::EX_UNITS_MEMORY, "ExUnits")?;
+            }
+            (fields::MAX_EXECUTION_UNITS_PER_BLOCK, WireValue::Bytes(units)) => {
+                pp.max_block_ex_steps = field_varint(units, fields::EX_UNITS_STEPS, "ExUnits")?;
+                pp.max_block_ex_mem = field_varint(units, fields::EX_UNITS_MEMORY, "ExUnits")?;
+            }
+            (fields::MIN_FEE_SCRIPT_REF_COST_PER_BYTE, WireValue::Bytes(rational)) => {
+                pp.min_fee_ref_script_cost_per_byte = Some(rational_value(rational)?);
+            }
+            _ => {}
+        }
+    }
+    Ok(pp)
+}
+
+fn cost_models(bytes: &[u8]) -> Result<CostModels, JsError> {
+    let mut models = CostModels::default();
+    for (number, value) in message_fields(bytes, "CostModels")? {
+        let WireValue::Bytes(model) = value else {
+            continue;
+        };
+        let values = cost_model_values(model)?;
+        match number {
+            1 => models.plutus_v1 = Some(values),
+            2 => models.plutus_v2 = Some(values),
+            3 => models.plutus_v3 = Some(values),
+            _ => {}
+        }
+    }
+    Ok(models)
+}
+
+/// `repeated int64 values`, packed or not. Negative costs are ten-byte
+/// two's complement varints.
+fn cost_model_values(bytes: &[u8]) -> Result<Vec<i64>, JsError> {
+    let mut values = Vec::new();
+    for (number, value) in message_fields(bytes, "CostModel")? {
+        match (number, value) {
+            (fields::COST_MODEL_VALUES, WireValue::Varint(v)) => values.push(v as i64),
+            (fields::COST_MODEL_VALUES, WireValue::Bytes(packed)) => {
+                let mut position = 0;
+                while position < packed.len() {
+                    let v = read_varint(packed, &mut position)
+                        .map_err(|e| ErrorCode::ProviderError.error(&format!("Invalid UTxO RPC CostModel: {}", e)))?;
+                    values.push(v as i64);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(values)
+}
+
+fn rational_field(bytes: &[u8], number: u32, message: &str) -> Result<Option<f64>, JsError> {
+    field_bytes(bytes, number, message)?.map(rational_value).transpose()
+}
+
+fn rational_value(bytes: &[u8]) -> Result<f64, JsError> {
+    // int32 numerator, negative ones are sign-extended varints
+    let numerator = field_varint(bytes, fields::RATIONAL_NUMERATOR, "RationalNumber")?.unwrap_or_default() as i64;
+    let denominator = field_varint(bytes, fields::RATIONAL_DENOMINATOR, "RationalNumber")?.unwrap_or(1);
+    if denominator == 0 {
+        return Err(ErrorCode::ProviderError.error("UTxO RPC returned a rational with a zero denominator"));
+    }
+    Ok(numerator as f64 / denominator as f64)
+}
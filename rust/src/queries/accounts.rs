@@ -0,0 +1,119 @@
+use cardano_serialization_lib::{Address, BaseAddress, RewardAddress};
+use serde::Serialize;
+use serde_json::Value;
+use crate::bingen::wasm_bindgen;
+use crate::csl_decoders::stake_address_family;
+use crate::error::ErrorCode;
+use crate::js_error::JsError;
+use crate::schemas::to_versioned_json;
+use crate::netwrok_type::NetworkType;
+use crate::provider::koios::KoiosProvider;
+use crate::provider::Provider;
+use crate::abort_signal::{with_abort_signal, AbortSignal};
+
+#[derive(Serialize, Debug, Clone)]
+pub struct AccountSummary {
+    pub stake_address: String,
+    pub registered: bool,
+    /// Lovelace in UTxOs plus available rewards.
+    pub controlled_stake: String,
+    pub withdrawable_rewards: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delegated_pool: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delegated_drep: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deposit: Option<String>,
+}
+
+/// Stake address of a reward or base address, both as bech32.
+pub(crate) fn to_stake_address(address: &str) -> Result<String, JsError> {
+    let parsed = Address::from_bech32(address)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Invalid address {}: {:?}", address, e)))?;
+    if RewardAddress::from_address(&parsed).is_some() {
+        return Ok(address.to_string());
+    }
+    let base = BaseAddress::from_address(&parsed)
+        .ok_or_else(|| ErrorCode::InvalidInput.error(&format!("Address {} has no stake part", address)))?;
+    let network_id = parsed
+        .network_id()
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("{:?}", e)))?;
+    RewardAddress::new(network_id, &base.stake_cred())
+        .to_address()
+        .to_bech32(None)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("{:?}", e)))
+}
+
+/// Stake account state of a reward address, or of the stake part of a base
+/// address: controlled stake, pool and DRep delegation and withdrawable rewards.
+#[wasm_bindgen(catch)]
+pub async fn get_account_info(
+    address: &str,
+    network: NetworkType,
+    api_token: Option<String>,
+    signal: Option<AbortSignal>,
+) -> Result<String, JsError> {
+    with_abort_signal(signal, async move {
+        let stake_address = to_stake_address(address)?;
+        let provider = KoiosProvider::new(network.into(), api_token);
+        let account = provider
+            .get_account_info(&vec![stake_address.clone()])
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| ErrorCode::DecodeFailure.error(&format!("Stake address {} not found", stake_address)))?;
+
+        let summary = AccountSummary {
+            stake_address: account.stake_address,
+            registered: account.status.as_deref() == Some("registered"),
+            controlled_stake: account.total_balance.unwrap_or_else(|| "0".to_string()),
+            withdrawable_rewards: account.rewards_available.unwrap_or_else(|| "0".to_string()),
+            delegated_pool: account.delegated_pool,
+            delegated_drep: account.delegated_drep,
+            deposit: account.deposit,
+        };
+        to_versioned_json(&summary)
+    })
+    .await
+}
+
+/// `expand_stake_credential` on the given network, with the addresses
+/// Koios has seen with the stake credential as `seen_addresses`. Derived
+/// base addresses only cover the payment credentials passed in, the seen
+/// ones also include those nobody knew of.
+#[wasm_bindgen(catch)]
+pub async fn find_stake_credential_addresses(
+    stake: &str,
+    payment_credentials_json: &str,
+    network: NetworkType,
+    api_token: Option<String>,
+    signal: Option<AbortSignal>,
+) -> Result<String, JsError> {
+    with_abort_signal(signal, async move {
+        let payment_credentials: Vec<String> = if payment_credentials_json.trim().is_empty() {
+            Vec::new()
+        } else {
+            serde_json::from_str(payment_credentials_json).map_err(|e| ErrorCode::InvalidInput.error(&e.to_string()))?
+        };
+        let mut family = stake_address_family(stake, &payment_credentials, Some(network.network_id()))?;
+        if family.network_id != network.network_id() {
+            return Err(ErrorCode::InvalidInput.error(&format!("{} isn't a {} address", stake, network.name())));
+        }
+        let reward_address = family.addresses[0].bech32.clone();
+        let provider = KoiosProvider::new(network.into(), api_token);
+        let items = provider.get_account_addresses(&vec![reward_address]).await?;
+        family.seen_addresses = Some(seen_addresses(&items));
+        to_versioned_json(&family)
+    })
+    .await
+}
+
+/// The addresses of Koios `account_addresses` items.
+pub(crate) fn seen_addresses(items: &[Value]) -> Vec<String> {
+    items
+        .iter()
+        .filter_map(|item| item.get("addresses").and_then(Value::as_array))
+        .flatten()
+        .filter_map(|address| address.as_str().map(str::to_string))
+        .collect()
+}
@@ -0,0 +1,47 @@
+use crate::bingen::wasm_bindgen;
+use crate::error::ErrorCode;
+use crate::js_error::JsError;
+use crate::schemas::to_versioned_json;
+use crate::netwrok_type::NetworkType;
+use crate::provider::koios::KoiosProvider;
+use crate::provider::Provider;
+use crate::abort_signal::{with_abort_signal, AbortSignal};
+
+/// All live UTxOs at a bech32 address as JSON, in the Koios `utxo_info`
+/// format the rest of the crate takes as `utxo_json`.
+#[wasm_bindgen(catch)]
+pub async fn get_address_utxos(
+    address: &str,
+    network: NetworkType,
+    api_token: Option<String>,
+    signal: Option<AbortSignal>,
+) -> Result<String, JsError> {
+    with_abort_signal(signal, async move {
+        let provider = KoiosProvider::new(network.into(), api_token);
+        let utxos = provider.get_address_utxos(&vec![address.to_string()]).await?;
+        to_versioned_json(&utxos)
+    })
+    .await
+}
+
+/// Koios `address_info` of a bech32 address: balance, stake address and
+/// whether it's a script address.
+#[wasm_bindgen(catch)]
+pub async fn get_address_info(
+    address: &str,
+    network: NetworkType,
+    api_token: Option<String>,
+    signal: Option<AbortSignal>,
+) -> Result<String, JsError> {
+    with_abort_signal(signal, async move {
+        let provider = KoiosProvider::new(network.into(), api_token);
+        let info = provider
+            .get_address_info(&vec![address.to_string()])
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| ErrorCode::NotFound.error(&format!("No information about address {}", address)))?;
+        to_versioned_json(&info)
+    })
+    .await
+}
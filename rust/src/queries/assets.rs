@@ -0,0 +1,204 @@
+use serde::Serialize;
+use serde_json::Value;
+use crate::bingen::wasm_bindgen;
+use crate::error::ErrorCode;
+use crate::js_error::JsError;
+use crate::schemas::to_versioned_json;
+use crate::netwrok_type::NetworkType;
+use crate::provider::koios::KoiosProvider;
+use crate::provider::Provider;
+use crate::token_registry::{fetch_registry_entry, RegistryEntry, DEFAULT_TOKEN_REGISTRY_URL};
+use crate::abort_signal::{with_abort_signal, AbortSignal};
+
+#[derive(Serialize, Debug, Clone)]
+pub struct MetadataMismatch {
+    pub field: String,
+    pub source: String,
+    pub metadata: String,
+    pub registry: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct AssetSummary {
+    pub policy_id: String,
+    pub asset_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub asset_name_ascii: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_supply: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub registry: Option<RegistryEntry>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cip25_metadata: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cip68_metadata: Option<Value>,
+    /// Fields where the on-chain metadata and the registry disagree.
+    pub mismatches: Vec<MetadataMismatch>,
+}
+
+/// Koios `asset_info` of a native asset combined with its token registry
+/// entry, with the CIP-25/68 metadata cross-checked against the registry.
+/// `registry_url` defaults to the mainnet Cardano token registry.
+#[wasm_bindgen(catch)]
+pub async fn get_asset_info(
+    policy_id: &str,
+    asset_name_hex: &str,
+    network: NetworkType,
+    api_token: Option<String>,
+    registry_url: Option<String>,
+    signal: Option<AbortSignal>,
+) -> Result<String, JsError> {
+    with_abort_signal(signal, async move {
+        let provider = KoiosProvider::new(network.into(), api_token);
+        let info = provider
+            .get_asset_info(&vec![(policy_id.to_string(), asset_name_hex.to_string())])
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| ErrorCode::NotFound.error(&format!("Asset {}.{} not found", policy_id, asset_name_hex)))?;
+
+        let registry_url = registry_url.filter(|url| !url.is_empty());
+        let subject = format!("{}{}", policy_id, asset_name_hex);
+        let registry = fetch_registry_entry(
+            registry_url.as_deref().unwrap_or(DEFAULT_TOKEN_REGISTRY_URL),
+            &subject,
+        )
+        .await?;
+
+        let asset_name_ascii = hex::decode(asset_name_hex)
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok());
+        let cip25_metadata = cip25_entry(&info, policy_id, asset_name_hex, asset_name_ascii.as_deref());
+        let cip68_metadata = info
+            .get("cip68_metadata")
+            .filter(|m| !m.is_null())
+            .cloned();
+
+        let mut mismatches = Vec::new();
+        if let Some(registry) = &registry {
+            if let Some(metadata) = &cip25_metadata {
+                compare_metadata("cip25", &cip25_fields(metadata), registry, &mut mismatches);
+            }
+            if let Some(metadata) = &cip68_metadata {
+                compare_metadata("cip68", &cip68_fields(metadata), registry, &mut mismatches);
+            }
+        }
+
+        let summary = AssetSummary {
+            policy_id: policy_id.to_string(),
+            asset_name: asset_name_hex.to_string(),
+            asset_name_ascii,
+            fingerprint: info.get("fingerprint").and_then(Value::as_str).map(str::to_string),
+            total_supply: info.get("total_supply").and_then(Value::as_str).map(str::to_string),
+            registry,
+            cip25_metadata,
+            cip68_metadata,
+            mismatches,
+        };
+        to_versioned_json(&summary)
+    })
+    .await
+}
+
+/// Token registry entry for `policy_id ++ asset_name_hex`, or `null` when the
+/// token isn't registered.
+#[wasm_bindgen(catch)]
+pub async fn get_token_registry_entry(
+    subject: &str,
+    registry_url: Option<String>,
+    signal: Option<AbortSignal>,
+) -> Result<String, JsError> {
+    with_abort_signal(signal, async move {
+        let registry_url = registry_url.filter(|url| !url.is_empty());
+        let entry = fetch_registry_entry(
+            registry_url.as_deref().unwrap_or(DEFAULT_TOKEN_REGISTRY_URL),
+            subject,
+        )
+        .await?;
+        to_versioned_json(&entry)
+    })
+    .await
+}
+
+/// The asset's entry in the minting tx's 721 metadata. CIP-25 v1 keys assets
+/// by their UTF-8 name, v2 by hex.
+fn cip25_entry(info: &Value, policy_id: &str, asset_name_hex: &str, asset_name_ascii: Option<&str>) -> Option<Value> {
+    let policy = info.get("minting_tx_metadata")?.get("721")?.get(policy_id)?;
+    policy
+        .get(asset_name_hex)
+        .or_else(|| asset_name_ascii.and_then(|name| policy.get(name)))
+        .cloned()
+}
+
+fn cip25_fields(metadata: &Value) -> Vec<(String, String)> {
+    ["name", "ticker", "decimals", "description"]
+        .iter()
+        .filter_map(|field| {
+            let value = metadata.get(*field)?;
+            let value = value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string());
+            Some((field.to_string(), value))
+        })
+        .collect()
+}
+
+/// Koios gives the CIP-68 reference datum in detailed schema JSON, keyed by
+/// token label: `{ "333": { "fields": [{ "map": [{ "k": { "bytes" }, "v": ... }] }, ...] } }`.
+fn cip68_fields(metadata: &Value) -> Vec<(String, String)> {
+    let Some(datum) = metadata.as_object().and_then(|labels| labels.values().next()) else {
+        return Vec::new();
+    };
+    let Some(entries) = datum
+        .get("fields")
+        .and_then(|fields| fields.get(0))
+        .and_then(|map| map.get("map"))
+        .and_then(Value::as_array)
+    else {
+        return Vec::new();
+    };
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let key = decode_utf8(entry.get("k")?.get("bytes")?.as_str()?)?;
+            let value = entry.get("v")?;
+            let value = match (value.get("bytes"), value.get("int")) {
+                (Some(bytes), _) => decode_utf8(bytes.as_str()?)?,
+                (None, Some(int)) => int.to_string(),
+                _ => return None,
+            };
+            Some((key, value))
+        })
+        .collect()
+}
+
+fn decode_utf8(hex_str: &str) -> Option<String> {
+    String::from_utf8(hex::decode(hex_str).ok()?).ok()
+}
+
+fn compare_metadata(
+    source: &str,
+    fields: &[(String, String)],
+    registry: &RegistryEntry,
+    mismatches: &mut Vec<MetadataMismatch>,
+) {
+    for (field, value) in fields {
+        let registry_value = match field.as_str() {
+            "name" => registry.name.clone(),
+            "ticker" => registry.ticker.clone(),
+            "decimals" => registry.decimals.map(|d| d.to_string()),
+            "description" => registry.description.clone(),
+            _ => None,
+        };
+        if let Some(registry_value) = registry_value {
+            if &registry_value != value {
+                mismatches.push(MetadataMismatch {
+                    field: field.clone(),
+                    source: source.to_string(),
+                    metadata: value.clone(),
+                    registry: registry_value,
+                });
+            }
+        }
+    }
+}
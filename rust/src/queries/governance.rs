@@ -0,0 +1,75 @@
+use serde::Serialize;
+use serde_json::Value;
+use crate::bingen::wasm_bindgen;
+use crate::error::ErrorCode;
+use crate::js_error::JsError;
+use crate::schemas::to_versioned_json;
+use crate::netwrok_type::NetworkType;
+use crate::provider::koios::KoiosProvider;
+use crate::provider::Provider;
+use crate::abort_signal::{with_abort_signal, AbortSignal};
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DrepSummary {
+    pub info: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Value>,
+}
+
+/// All governance actions known to Koios, as Koios `proposal_list` items.
+#[wasm_bindgen(catch)]
+pub async fn get_proposal_list(
+    network: NetworkType,
+    api_token: Option<String>,
+    signal: Option<AbortSignal>,
+) -> Result<String, JsError> {
+    with_abort_signal(signal, async move {
+        let provider = KoiosProvider::new(network.into(), api_token);
+        let proposals = provider.get_proposal_list().await?;
+        to_versioned_json(&proposals)
+    })
+    .await
+}
+
+/// Votes cast on a governance action. `proposal_id` is the CIP-129
+/// `gov_action1...` id.
+#[wasm_bindgen(catch)]
+pub async fn get_proposal_votes(
+    proposal_id: &str,
+    network: NetworkType,
+    api_token: Option<String>,
+    signal: Option<AbortSignal>,
+) -> Result<String, JsError> {
+    with_abort_signal(signal, async move {
+        let provider = KoiosProvider::new(network.into(), api_token);
+        let votes = provider.get_proposal_votes(proposal_id).await?;
+        to_versioned_json(&votes)
+    })
+    .await
+}
+
+/// Registration state, deposit and voting power of a DRep together with its
+/// anchored metadata, if Koios has fetched it.
+#[wasm_bindgen(catch)]
+pub async fn get_drep_info(
+    drep_id: &str,
+    network: NetworkType,
+    api_token: Option<String>,
+    signal: Option<AbortSignal>,
+) -> Result<String, JsError> {
+    with_abort_signal(signal, async move {
+        let provider = KoiosProvider::new(network.into(), api_token);
+        let drep_ids = vec![drep_id.to_string()];
+        let info = provider
+            .get_drep_info(&drep_ids)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| ErrorCode::NotFound.error(&format!("DRep {} not found", drep_id)))?;
+        let metadata = provider.get_drep_metadata(&drep_ids).await?.into_iter().next();
+
+        let summary = DrepSummary { info, metadata };
+        to_versioned_json(&summary)
+    })
+    .await
+}
@@ -0,0 +1,8 @@
+pub mod scripts;
+pub mod transactions;
+pub mod tx_status;
+pub mod addresses;
+pub mod accounts;
+pub mod assets;
+pub mod pools;
+pub mod governance;
@@ -0,0 +1,242 @@
+use cardano_serialization_lib::Transaction;
+use pallas_crypto::hash::Hasher;
+use reqwest::Client;
+use serde::Serialize;
+use serde_json::Value;
+use crate::bingen::wasm_bindgen;
+use crate::csl_decoders::certificate_form;
+use crate::error::ErrorCode;
+use crate::input::sanitize_hex;
+use crate::js_error::JsError;
+use crate::plutus::script_purpose::certificate_pool;
+use crate::schemas::to_versioned_json;
+use crate::netwrok_type::NetworkType;
+use crate::provider::koios::KoiosProvider;
+use crate::provider::Provider;
+use crate::abort_signal::{with_abort_signal, AbortSignal};
+
+#[derive(Serialize, Debug, Clone)]
+pub struct PoolMetadataCheck {
+    pub url: String,
+    pub registered_hash: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub computed_hash: Option<String>,
+    pub hash_matches: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct PoolSummary {
+    pub info: Value,
+    /// `None` when the pool registered no metadata.
+    pub metadata_check: Option<PoolMetadataCheck>,
+}
+
+/// Koios `pool_info` of a bech32 pool id, plus a check that the metadata at
+/// the registered URL still hashes to the registered metadata hash. A failed
+/// download is reported in the check rather than failing the query.
+#[wasm_bindgen(catch)]
+pub async fn get_pool_info(
+    pool_id: &str,
+    network: NetworkType,
+    api_token: Option<String>,
+    signal: Option<AbortSignal>,
+) -> Result<String, JsError> {
+    with_abort_signal(signal, async move {
+        let provider = KoiosProvider::new(network.into(), api_token);
+        let info = provider
+            .get_pool_info(&vec![pool_id.to_string()])
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| ErrorCode::NotFound.error(&format!("Pool {} not found", pool_id)))?;
+
+        let url = info.get("meta_url").and_then(Value::as_str);
+        let hash = info.get("meta_hash").and_then(Value::as_str);
+        let metadata_check = match (url, hash) {
+            (Some(url), Some(hash)) => Some(check_pool_metadata(url, hash).await),
+            _ => None,
+        };
+
+        let summary = PoolSummary { info, metadata_check };
+        to_versioned_json(&summary)
+    })
+    .await
+}
+
+/// A certificate of the tx naming a pool, with the pool's ticker, name and
+/// status from `pool_info`. `retirement_epoch` is the epoch a
+/// `pool_retirement` certificate retires it in.
+#[derive(Serialize, Debug, Clone)]
+pub struct PoolCertificate {
+    pub index: usize,
+    pub kind: String,
+    pub pool_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ticker: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retiring_epoch: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retirement_epoch: Option<u64>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct PoolCertificateReport {
+    pub current_epoch: u64,
+    pub certificates: Vec<PoolCertificate>,
+}
+
+/// Decodes the delegation, registration and retirement certificates of a tx
+/// that name a pool and adds the pool's ticker, name and current status.
+/// Delegating to a pool that is retired, retiring or unknown is warned
+/// about, the stake would stop earning rewards.
+#[wasm_bindgen(catch)]
+pub async fn decode_pool_certificates(
+    tx_hex: &str,
+    network: NetworkType,
+    api_token: Option<String>,
+    signal: Option<AbortSignal>,
+) -> Result<String, JsError> {
+    with_abort_signal(signal, async move {
+        let tx = Transaction::from_hex(sanitize_hex(tx_hex)?)
+            .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding transaction: {:?}", e)))?;
+        let provider = KoiosProvider::new(network.into(), api_token);
+        to_versioned_json(&pool_certificates(&tx, &provider).await?)
+    })
+    .await
+}
+
+pub(crate) async fn pool_certificates(tx: &Transaction, provider: &dyn Provider) -> Result<PoolCertificateReport, JsError> {
+    let mut pool_ids: Vec<String> = Vec::new();
+    if let Some(certs) = tx.body().certs() {
+        for i in 0..certs.len() {
+            if let Some(pool_id) = certificate_pool(&certs.get(i)).and_then(|pool| pool.to_bech32("pool").ok()) {
+                if !pool_ids.contains(&pool_id) {
+                    pool_ids.push(pool_id);
+                }
+            }
+        }
+    }
+    let current_epoch = provider.get_chain_tip().await?.epoch_no;
+    let infos = if pool_ids.is_empty() {
+        Vec::new()
+    } else {
+        provider.get_pool_info(&pool_ids).await?
+    };
+    Ok(PoolCertificateReport {
+        current_epoch,
+        certificates: enrich_pool_certificates(tx, &infos, current_epoch),
+    })
+}
+
+pub(crate) fn enrich_pool_certificates(tx: &Transaction, infos: &[Value], current_epoch: u64) -> Vec<PoolCertificate> {
+    let Some(certs) = tx.body().certs() else {
+        return Vec::new();
+    };
+    let mut enriched = Vec::new();
+    for index in 0..certs.len() {
+        let cert = certs.get(index);
+        let Some(pool) = certificate_pool(&cert) else {
+            continue;
+        };
+        let pool_id = pool.to_bech32("pool").unwrap_or_else(|_| pool.to_hex());
+        let info = infos
+            .iter()
+            .find(|info| info.get("pool_id_bech32").and_then(Value::as_str) == Some(pool_id.as_str()));
+        let meta = |field: &str| {
+            info.and_then(|info| info.get("meta_json"))
+                .and_then(|meta| meta.get(field))
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        };
+        let status = info.and_then(|info| info.get("pool_status")).and_then(Value::as_str).map(str::to_string);
+        let retiring_epoch = info.and_then(|info| info.get("retiring_epoch")).and_then(Value::as_u64);
+        let kind = certificate_form(&cert).name;
+        let retirement_epoch = cert.as_pool_retirement().map(|retirement| retirement.epoch() as u64);
+
+        let mut warnings = Vec::new();
+        let delegates = kind != "pool_registration" && kind != "pool_retirement";
+        match (status.as_deref(), delegates) {
+            (None, true) => warnings.push(format!("Pool {} is not registered, the stake earns no rewards", pool_id)),
+            (Some("retired"), true) => {
+                warnings.push(format!("Pool {} is retired, the stake earns no rewards", pool_id));
+            }
+            (Some("retiring"), true) => {
+                let epoch = retiring_epoch.map(|epoch| format!(" in epoch {}", epoch)).unwrap_or_default();
+                warnings.push(format!("Pool {} retires{}, the stake stops earning rewards then", pool_id, epoch));
+            }
+            (None, false) if kind == "pool_retirement" => {
+                warnings.push(format!("Pool {} is not registered, the retirement will be rejected", pool_id));
+            }
+            _ => {}
+        }
+        if let Some(epoch) = retirement_epoch {
+            if epoch <= current_epoch {
+                warnings.push(format!("Retirement epoch {} is not after the current epoch {}", epoch, current_epoch));
+            }
+        }
+
+        enriched.push(PoolCertificate {
+            index,
+            kind: kind.to_string(),
+            pool_id,
+            ticker: meta("ticker"),
+            name: meta("name"),
+            status,
+            retiring_epoch,
+            retirement_epoch,
+            warnings,
+        });
+    }
+    enriched
+}
+
+/// The registered hash is blake2b-256 of the exact bytes served, so the body
+/// is hashed before any JSON parsing.
+async fn check_pool_metadata(url: &str, registered_hash: &str) -> PoolMetadataCheck {
+    let mut check = PoolMetadataCheck {
+        url: url.to_string(),
+        registered_hash: registered_hash.to_string(),
+        computed_hash: None,
+        hash_matches: false,
+        metadata: None,
+        error: None,
+    };
+    match fetch_bytes(url).await {
+        Ok(bytes) => {
+            let computed = pool_metadata_hash(&bytes);
+            check.hash_matches = computed.eq_ignore_ascii_case(registered_hash);
+            check.computed_hash = Some(computed);
+            check.metadata = serde_json::from_slice(&bytes).ok();
+        }
+        Err(err) => check.error = Some(err),
+    }
+    check
+}
+
+pub(crate) fn pool_metadata_hash(bytes: &[u8]) -> String {
+    Hasher::<256>::hash(bytes).to_string()
+}
+
+async fn fetch_bytes(url: &str) -> Result<Vec<u8>, String> {
+    let response = Client::new()
+        .get(url)
+        .send()
+        .await
+        .map_err(|err| format!("pool_metadata.send, {}", err))?
+        .error_for_status()
+        .map_err(|err| format!("pool_metadata.status, {}", err))?;
+    response
+        .bytes()
+        .await
+        .map(|bytes| bytes.to_vec())
+        .map_err(|err| format!("pool_metadata.read, {}", err))
+}
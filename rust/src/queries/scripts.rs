@@ -0,0 +1,129 @@
+use cardano_serialization_lib::Address;
+use serde::Serialize;
+use serde_json::Value;
+use crate::bingen::wasm_bindgen;
+use crate::error::ErrorCode;
+use crate::js_error::JsError;
+use crate::schemas::to_versioned_json;
+use crate::netwrok_type::NetworkType;
+use crate::plutus::script_purpose::payment_script_hash;
+use crate::plutus::tx_scripts::{script_info_to_extracted, ExtractedScript};
+use crate::provider::koios::KoiosProvider;
+use crate::provider::Provider;
+use crate::abort_signal::{with_abort_signal, AbortSignal};
+
+/// Looks up scripts by hash through Koios `script_info`. Every entry has the
+/// script type, size in bytes and the script bytes as hex; unknown hashes are
+/// left out of the result.
+#[wasm_bindgen(catch)]
+pub async fn get_scripts(
+    script_hashes: Vec<String>,
+    network: NetworkType,
+    api_token: Option<String>,
+    signal: Option<AbortSignal>,
+) -> Result<String, JsError> {
+    with_abort_signal(signal, async move {
+        let provider = KoiosProvider::new(network.into(), api_token);
+        let scripts = provider.get_scripts(&script_hashes).await?;
+        to_versioned_json(&scripts)
+    })
+    .await
+}
+
+/// An output of the script's creation tx carrying it as a reference script.
+#[derive(Serialize, Debug, Clone)]
+pub struct ReferenceScriptUtxo {
+    pub utxo: String,
+    pub is_spent: bool,
+}
+
+/// The script locking an address. `referenceable` is whether one of the
+/// `reference_utxos` is unspent, so a tx can use the script without carrying
+/// it. Only the creation tx is searched, copies put on chain later aren't found.
+#[derive(Serialize, Debug, Clone)]
+pub struct AddressScript {
+    pub address: String,
+    pub script: ExtractedScript,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub creation_tx_hash: Option<String>,
+    pub reference_utxos: Vec<ReferenceScriptUtxo>,
+    pub referenceable: bool,
+}
+
+/// Resolves the script of a script address's payment credential: its bytes,
+/// language and decoded UPLC, and the reference-script UTxOs holding it.
+#[wasm_bindgen(catch)]
+pub async fn get_address_script(
+    address: &str,
+    network: NetworkType,
+    api_token: Option<String>,
+    signal: Option<AbortSignal>,
+) -> Result<String, JsError> {
+    with_abort_signal(signal, async move {
+        let provider = KoiosProvider::new(network.into(), api_token);
+        to_versioned_json(&address_script(address, &provider).await?)
+    })
+    .await
+}
+
+pub(crate) async fn address_script<P: Provider>(address: &str, provider: &P) -> Result<AddressScript, JsError> {
+    let parsed = Address::from_bech32(address.trim())
+        .map_err(|e| ErrorCode::InvalidInput.error(&format!("Invalid address {}: {:?}", address, e)))?;
+    let script_hash = payment_script_hash(&parsed)
+        .ok_or_else(|| ErrorCode::InvalidInput.error(&format!("Address {} isn't locked by a script", address)))?;
+    let info = provider
+        .get_scripts(&vec![script_hash.clone()])
+        .await?
+        .into_iter()
+        .find(|info| info.script_hash == script_hash)
+        .ok_or_else(|| ErrorCode::NotFound.error(&format!("Script {} isn't known to the provider", script_hash)))?;
+    let script = script_info_to_extracted(&info, "provider")?;
+
+    let mut reference_utxos = Vec::new();
+    if let Some(creation_tx_hash) = &info.creation_tx_hash {
+        let refs: Vec<String> = provider
+            .get_tx_info(&vec![creation_tx_hash.clone()])
+            .await?
+            .iter()
+            .flat_map(|tx_info| reference_script_outputs(tx_info, &script_hash))
+            .collect();
+        if !refs.is_empty() {
+            let utxos = provider.get_utxos(&refs).await?;
+            for utxo in refs {
+                // utxo_info leaves out what it no longer knows, count those as spent
+                let is_spent = utxos
+                    .iter()
+                    .find(|u| format!("{}#{}", u.tx_hash, u.tx_index) == utxo)
+                    .is_none_or(|u| u.is_spent);
+                reference_utxos.push(ReferenceScriptUtxo { utxo, is_spent });
+            }
+        }
+    }
+
+    Ok(AddressScript {
+        address: address.trim().to_string(),
+        script,
+        creation_tx_hash: info.creation_tx_hash,
+        referenceable: reference_utxos.iter().any(|utxo| !utxo.is_spent),
+        reference_utxos,
+    })
+}
+
+/// `tx_hash#index` of the outputs of a Koios `tx_info` item whose reference
+/// script is `script_hash`.
+pub(crate) fn reference_script_outputs(tx_info: &Value, script_hash: &str) -> Vec<String> {
+    let Some(tx_hash) = tx_info["tx_hash"].as_str() else {
+        return Vec::new();
+    };
+    tx_info["outputs"]
+        .as_array()
+        .map(|outputs| {
+            outputs
+                .iter()
+                .filter(|output| output["reference_script"]["hash"].as_str() == Some(script_hash))
+                .filter_map(|output| output["tx_index"].as_u64())
+                .map(|index| format!("{}#{}", tx_hash, index))
+                .collect()
+        })
+        .unwrap_or_default()
+}
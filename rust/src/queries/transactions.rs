@@ -0,0 +1,51 @@
+use crate::bingen::wasm_bindgen;
+use crate::error::ErrorCode;
+use crate::js_error::JsError;
+use crate::schemas::to_versioned_json;
+use crate::netwrok_type::NetworkType;
+use crate::provider::koios::KoiosProvider;
+use crate::provider::Provider;
+use crate::abort_signal::{with_abort_signal, AbortSignal};
+
+/// CBOR hex of an on-chain transaction, ready for any decoder in the crate.
+#[wasm_bindgen(catch)]
+pub async fn get_tx_cbor(
+    tx_hash: &str,
+    network: NetworkType,
+    api_token: Option<String>,
+    signal: Option<AbortSignal>,
+) -> Result<String, JsError> {
+    with_abort_signal(signal, async move {
+        let provider = KoiosProvider::new(network.into(), api_token);
+        provider
+            .get_tx_cbor(&vec![tx_hash.to_string()])
+            .await?
+            .into_iter()
+            .next()
+            .map(|tx| tx.cbor)
+            .ok_or_else(|| ErrorCode::NotFound.error(&format!("Transaction {} not found", tx_hash)))
+    })
+    .await
+}
+
+/// Koios `tx_info` of a transaction as JSON, with inputs, metadata, assets,
+/// withdrawals, certificates and scripts included.
+#[wasm_bindgen(catch)]
+pub async fn get_tx_info(
+    tx_hash: &str,
+    network: NetworkType,
+    api_token: Option<String>,
+    signal: Option<AbortSignal>,
+) -> Result<String, JsError> {
+    with_abort_signal(signal, async move {
+        let provider = KoiosProvider::new(network.into(), api_token);
+        let tx_info = provider
+            .get_tx_info(&vec![tx_hash.to_string()])
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| ErrorCode::NotFound.error(&format!("Transaction {} not found", tx_hash)))?;
+        to_versioned_json(&tx_info)
+    })
+    .await
+}
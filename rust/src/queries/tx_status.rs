@@ -0,0 +1,180 @@
+use serde::Serialize;
+use crate::bingen::wasm_bindgen;
+use crate::js_error::JsError;
+use crate::schemas::to_versioned_json;
+use crate::netwrok_type::NetworkType;
+use crate::provider::koios::KoiosProvider;
+use crate::provider::Provider;
+use crate::abort_signal::{with_abort_signal, AbortSignal};
+#[cfg(all(target_arch = "wasm32", not(target_os = "emscripten")))]
+use {
+    js_sys::{Function, Object, Reflect},
+    wasm_bindgen::{JsCast, JsValue},
+    crate::error::ErrorCode,
+    crate::time_utils::{now_ms, sleep_ms},
+};
+
+#[cfg(all(target_arch = "wasm32", not(target_os = "emscripten")))]
+const DEFAULT_POLL_INTERVAL_MS: f64 = 5_000.0;
+#[cfg(all(target_arch = "wasm32", not(target_os = "emscripten")))]
+const DEFAULT_TIMEOUT_MS: f64 = 600_000.0;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct TxStatus {
+    pub tx_hash: String,
+    pub included: bool,
+    pub confirmations: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_height: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub absolute_slot: Option<u64>,
+}
+
+pub(crate) async fn fetch_tx_status<P: Provider>(provider: &P, tx_hash: &str) -> Result<TxStatus, JsError> {
+    let hashes = vec![tx_hash.to_string()];
+    let confirmations = provider
+        .get_tx_status(&hashes)
+        .await?
+        .into_iter()
+        .next()
+        .and_then(|status| status.num_confirmations);
+
+    let mut status = TxStatus {
+        tx_hash: tx_hash.to_string(),
+        included: confirmations.is_some(),
+        confirmations: confirmations.unwrap_or_default(),
+        block_hash: None,
+        block_height: None,
+        absolute_slot: None,
+    };
+    if status.included {
+        if let Some(tx) = provider.get_tx_cbor(&hashes).await?.into_iter().next() {
+            status.block_hash = tx.block_hash;
+            status.block_height = tx.block_height;
+            status.absolute_slot = tx.absolute_slot;
+        }
+    }
+    Ok(status)
+}
+
+/// Polls until the tx has `confirmations` confirmations or `timeout_ms`
+/// passes, handing every intermediate status to `on_progress`.
+#[cfg(all(target_arch = "wasm32", not(target_os = "emscripten")))]
+pub(crate) async fn poll_tx_status<P: Provider, F: FnMut(&TxStatus)>(
+    provider: &P,
+    tx_hash: &str,
+    confirmations: u64,
+    poll_interval_ms: u64,
+    timeout_ms: f64,
+    mut on_progress: F,
+) -> Result<TxStatus, JsError> {
+    let deadline = now_ms() + timeout_ms;
+    loop {
+        let status = fetch_tx_status(provider, tx_hash).await?;
+        on_progress(&status);
+        if status.included && status.confirmations >= confirmations {
+            return Ok(status);
+        }
+        if now_ms() + poll_interval_ms as f64 > deadline {
+            return Err(ErrorCode::Timeout.error(&format!(
+                "Timed out waiting for {} confirmations of {}, last seen {}",
+                confirmations, tx_hash, status.confirmations
+            )));
+        }
+        sleep_ms(poll_interval_ms).await;
+    }
+}
+
+/// Whether a transaction made it on chain and how deep it is, as JSON.
+#[wasm_bindgen(catch)]
+pub async fn get_tx_status(
+    tx_hash: &str,
+    network: NetworkType,
+    api_token: Option<String>,
+    signal: Option<AbortSignal>,
+) -> Result<String, JsError> {
+    with_abort_signal(signal, async move {
+        let provider = KoiosProvider::new(network.into(), api_token);
+        let status = fetch_tx_status(&provider, tx_hash).await?;
+        to_versioned_json(&status)
+    })
+    .await
+}
+
+/// Options of `wait_for_tx_status`, every field optional:
+/// `{ confirmations: 1, poll_interval_ms: 5000, timeout_ms: 600000, on_progress, signal }`.
+/// `on_progress` is called with the JSON status after every poll, aborting
+/// `signal`, an `AbortSignal`, stops the polling.
+#[cfg(all(target_arch = "wasm32", not(target_os = "emscripten")))]
+struct WaitOptions {
+    confirmations: u64,
+    poll_interval_ms: u64,
+    timeout_ms: f64,
+    on_progress: Option<Function>,
+    signal: Option<AbortSignal>,
+}
+
+#[cfg(all(target_arch = "wasm32", not(target_os = "emscripten")))]
+impl WaitOptions {
+    fn from_object(options: Option<Object>) -> Result<Self, JsError> {
+        let get = |name: &str| {
+            options
+                .as_ref()
+                .and_then(|options| Reflect::get(options, &JsValue::from_str(name)).ok())
+                .filter(|value| !value.is_undefined() && !value.is_null())
+        };
+        let number = |name: &str, default: f64| match get(name) {
+            None => Ok(default),
+            Some(value) => value
+                .as_f64()
+                .filter(|n| *n >= 0.0)
+                .ok_or_else(|| ErrorCode::InvalidInput.error(&format!("`{}` must be a non-negative number", name))),
+        };
+        let on_progress = match get("on_progress") {
+            None => None,
+            Some(value) => Some(
+                value
+                    .dyn_into::<Function>()
+                    .map_err(|_| ErrorCode::InvalidInput.error("`on_progress` must be a function"))?,
+            ),
+        };
+        Ok(WaitOptions {
+            confirmations: number("confirmations", 1.0)? as u64,
+            poll_interval_ms: number("poll_interval_ms", DEFAULT_POLL_INTERVAL_MS)? as u64,
+            timeout_ms: number("timeout_ms", DEFAULT_TIMEOUT_MS)?,
+            on_progress,
+            signal: get("signal").map(JsCast::unchecked_into),
+        })
+    }
+}
+
+/// Waits for a freshly submitted transaction to reach the confirmations of
+/// `options`, see [`WaitOptions`].
+#[cfg(all(target_arch = "wasm32", not(target_os = "emscripten")))]
+#[wasm_bindgen(catch)]
+pub async fn wait_for_tx_status(
+    tx_hash: &str,
+    network: NetworkType,
+    api_token: Option<String>,
+    options: Option<Object>,
+) -> Result<String, JsError> {
+    let options = WaitOptions::from_object(options)?;
+    let provider = KoiosProvider::new(network.into(), api_token);
+    let on_progress = options.on_progress;
+    let polling = poll_tx_status(
+        &provider,
+        tx_hash,
+        options.confirmations,
+        options.poll_interval_ms,
+        options.timeout_ms,
+        |status| {
+            if let (Some(callback), Ok(json)) = (&on_progress, serde_json::to_string(status)) {
+                let _ = callback.call1(&JsValue::NULL, &JsValue::from_str(&json));
+            }
+        },
+    );
+    let status = with_abort_signal(options.signal, polling).await?;
+    to_versioned_json(&status)
+}
@@ -0,0 +1,49 @@
+use serde::Serialize;
+use serde_json::Value;
+use crate::bingen::wasm_bindgen;
+use crate::error::ErrorCode;
+use crate::js_error::JsError;
+
+/// Version of the JSON shapes the library returns, bumped on any breaking
+/// change to them. Every object output and error carries it as
+/// `schema_version`.
+pub const SCHEMA_VERSION: u32 = 1;
+
+const SCHEMAS: [(&str, &str); 10] = [
+    ("cbor", include_str!("schemas/cbor.json")),
+    ("cbor_diagnostics", include_str!("schemas/cbor_diagnostics.json")),
+    ("decoded_address", include_str!("schemas/decoded_address.json")),
+    ("decoded_plutus_data", include_str!("schemas/decoded_plutus_data.json")),
+    ("decoded_script", include_str!("schemas/decoded_script.json")),
+    ("tx_evaluation", include_str!("schemas/tx_evaluation.json")),
+    ("blueprint_evaluation", include_str!("schemas/blueprint_evaluation.json")),
+    ("preset_evaluation", include_str!("schemas/preset_evaluation.json")),
+    ("decoded_submit_error", include_str!("schemas/decoded_submit_error.json")),
+    ("error", include_str!("schemas/error.json")),
+];
+
+/// Serializes `value`, adding `schema_version` when it's a JSON object.
+pub(crate) fn to_versioned_json<T: Serialize>(value: &T) -> Result<String, JsError> {
+    let mut json = serde_json::to_value(value)
+        .map_err(|e| ErrorCode::Internal.error(&format!("Error serializing result: {}", e)))?;
+    if let Value::Object(obj) = &mut json {
+        obj.insert("schema_version".to_string(), Value::from(SCHEMA_VERSION));
+    }
+    Ok(json.to_string())
+}
+
+/// JSON Schema (draft 2020-12) documents of the decoder and evaluator
+/// outputs and of the error JSON, as
+/// `{"schema_version": n, "schemas": {"tx_evaluation": {...}, ...}}`.
+#[wasm_bindgen]
+pub fn get_output_schemas() -> Result<String, JsError> {
+    let mut schemas = serde_json::Map::new();
+    for (name, schema) in SCHEMAS {
+        let schema: Value = serde_json::from_str(schema)
+            .map_err(|e| ErrorCode::Internal.error(&format!("Invalid bundled schema {}: {}", name, e)))?;
+        schemas.insert(name.to_string(), schema);
+    }
+    let mut obj = serde_json::Map::new();
+    obj.insert("schemas".to_string(), Value::Object(schemas));
+    to_versioned_json(&Value::Object(obj))
+}
@@ -0,0 +1,378 @@
+use cardano_serialization_lib::{
+    BaseAddress, BigNum, Credential, Ed25519KeyHash, EnterpriseAddress, PlutusData, PlutusDatumSchema, ScriptHash,
+};
+use std::collections::BTreeMap;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+const HASH28_LEN: usize = 28;
+const MAX_ASSET_NAME_LEN: usize = 32;
+const MAX_PLACEHOLDER_INDEX: u64 = 64;
+
+/// Renders data for reading rather than round-tripping: bytes get their UTF-8
+/// text when printable, and constructors the Plutus types their shape
+/// matches, `address` (with bech32 for both networks), `asset_class`,
+/// `rational`, or a `label` of the common sum types. Shapes are guesses, a
+/// pair of ints is flagged as a rational whatever it means to the validator.
+pub(crate) fn smart_render(data: &PlutusData) -> Value {
+    if let Some(constr) = data.as_constr_plutus_data() {
+        let index = constr.alternative().to_str().parse::<u64>().unwrap_or_default();
+        let fields = list_items(&constr.data());
+        let mut obj = Map::new();
+        obj.insert("constructor".to_string(), json!(index));
+        obj.insert("fields".to_string(), Value::Array(fields.iter().map(smart_render).collect()));
+        if let Some(label) = constructor_label(index, &fields) {
+            obj.insert("label".to_string(), json!(label));
+        }
+        if let Some(address) = as_address(index, &fields) {
+            obj.insert("address".to_string(), address);
+        } else if let Some(asset_class) = as_asset_class(index, &fields) {
+            obj.insert("asset_class".to_string(), asset_class);
+        } else if let Some(rational) = as_rational(index, &fields) {
+            obj.insert("rational".to_string(), rational);
+        }
+        return Value::Object(obj);
+    }
+    if let Some(map) = data.as_map() {
+        let keys = map.keys();
+        let mut entries = Vec::new();
+        for i in 0..keys.len() {
+            let key = keys.get(i);
+            if let Some(values) = map.get(&key) {
+                for j in 0..values.len() {
+                    if let Some(value) = values.get(j) {
+                        entries.push(json!({ "k": smart_render(&key), "v": smart_render(&value) }));
+                    }
+                }
+            }
+        }
+        return json!({ "map": entries });
+    }
+    if let Some(list) = data.as_list() {
+        return json!({ "list": list_items(&list).iter().map(smart_render).collect::<Vec<_>>() });
+    }
+    if let Some(int) = data.as_integer() {
+        let int = int.to_str();
+        return match int.parse::<i64>() {
+            Ok(value) => json!({ "int": value }),
+            Err(_) => json!({ "int": int }),
+        };
+    }
+    if let Some(bytes) = data.as_bytes() {
+        let mut obj = Map::new();
+        obj.insert("bytes".to_string(), json!(hex::encode(&bytes)));
+        if let Some(text) = printable_text(&bytes) {
+            obj.insert("text".to_string(), json!(text));
+        }
+        return Value::Object(obj);
+    }
+    // unreachable for data CSL decoded, fall back to its own JSON
+    data.to_json(PlutusDatumSchema::DetailedSchema)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or(Value::Null)
+}
+
+/// The bytes as text when they're non-empty UTF-8 without control characters
+/// other than whitespace.
+pub(crate) fn printable_text(bytes: &[u8]) -> Option<String> {
+    let text = std::str::from_utf8(bytes).ok()?;
+    if text.is_empty() || text.chars().any(|c| c.is_control() && !c.is_whitespace()) {
+        return None;
+    }
+    Some(text.to_string())
+}
+
+fn list_items(list: &cardano_serialization_lib::PlutusList) -> Vec<PlutusData> {
+    (0..list.len()).map(|i| list.get(i)).collect()
+}
+
+/// Names of the Plutus prelude types a constructor matches: `Bool` and
+/// `Option` share their nullary constructors, `Option` and `Either` the unary.
+fn constructor_label(index: u64, fields: &[PlutusData]) -> Option<&'static str> {
+    match (index, fields.len()) {
+        (0, 0) => Some("False | Unit"),
+        (1, 0) => Some("True | None"),
+        (0, 1) => Some("Some | Left"),
+        (1, 1) => Some("Right"),
+        (0, 2) => Some("Tuple2"),
+        (0, 3) => Some("Tuple3"),
+        _ => None,
+    }
+}
+
+fn constr_fields(data: &PlutusData) -> Option<(u64, Vec<PlutusData>)> {
+    let constr = data.as_constr_plutus_data()?;
+    let index = constr.alternative().to_str().parse().ok()?;
+    Some((index, list_items(&constr.data())))
+}
+
+/// `PubKeyCredential` (0) or `ScriptCredential` (1) over a 28-byte hash.
+fn as_credential(data: &PlutusData) -> Option<Credential> {
+    let (index, fields) = constr_fields(data)?;
+    let [hash] = fields.as_slice() else {
+        return None;
+    };
+    let hash = hash.as_bytes().filter(|hash| hash.len() == HASH28_LEN)?;
+    match index {
+        0 => Some(Credential::from_keyhash(&Ed25519KeyHash::from_bytes(hash).ok()?)),
+        1 => Some(Credential::from_scripthash(&ScriptHash::from_bytes(hash).ok()?)),
+        _ => None,
+    }
+}
+
+/// `Address { payment_credential, stake_credential: Option<StakingCredential> }`
+/// with an inline or pointer staking credential.
+fn as_address(index: u64, fields: &[PlutusData]) -> Option<Value> {
+    let [payment, staking] = fields else {
+        return None;
+    };
+    if index != 0 {
+        return None;
+    }
+    let payment = as_credential(payment)?;
+    let (staking_index, staking_fields) = constr_fields(staking)?;
+    let stake = match (staking_index, staking_fields.as_slice()) {
+        (1, []) => None,
+        (0, [staking_credential]) => Some(staking_credential),
+        _ => return None,
+    };
+
+    let mut obj = Map::new();
+    obj.insert("payment_cred".to_string(), crate::csl_decoders::stake_cred_to_json(&payment));
+    match stake.map(constr_fields) {
+        None => {
+            for (name, network) in [("mainnet", 1), ("testnet", 0)] {
+                let address = EnterpriseAddress::new(network, &payment).to_address().to_bech32(None).ok()?;
+                obj.insert(name.to_string(), json!(address));
+            }
+        }
+        // StakingHash
+        Some(Some((0, inner))) => {
+            let stake = as_credential(inner.first()?)?;
+            obj.insert("stake_cred".to_string(), crate::csl_decoders::stake_cred_to_json(&stake));
+            for (name, network) in [("mainnet", 1), ("testnet", 0)] {
+                let address = BaseAddress::new(network, &payment, &stake).to_address().to_bech32(None).ok()?;
+                obj.insert(name.to_string(), json!(address));
+            }
+        }
+        // StakingPtr, no bech32 as pointer addresses are deprecated
+        Some(Some((1, inner))) if inner.len() == 3 => {
+            let pointer: Vec<Value> = inner.iter().map(|i| json!(i.as_integer().map(|i| i.to_str()))).collect();
+            obj.insert("stake_pointer".to_string(), Value::Array(pointer));
+        }
+        _ => return None,
+    }
+    Some(Value::Object(obj))
+}
+
+/// `(PolicyId, AssetName)`, the policy empty for ada.
+fn as_asset_class(index: u64, fields: &[PlutusData]) -> Option<Value> {
+    let [policy, name] = fields else {
+        return None;
+    };
+    if index != 0 {
+        return None;
+    }
+    let policy = policy.as_bytes().filter(|p| p.is_empty() || p.len() == HASH28_LEN)?;
+    let name = name.as_bytes().filter(|n| n.len() <= MAX_ASSET_NAME_LEN)?;
+    if policy.is_empty() {
+        return name.is_empty().then(|| json!({ "policy_id": "", "asset_name": "", "ada": true }));
+    }
+    let mut obj = Map::new();
+    obj.insert("policy_id".to_string(), json!(hex::encode(&policy)));
+    obj.insert("asset_name".to_string(), json!(hex::encode(&name)));
+    if let Some(text) = printable_text(&name) {
+        obj.insert("asset_name_text".to_string(), json!(text));
+    }
+    Some(Value::Object(obj))
+}
+
+/// Two ints with a positive denominator, as `Rational` encodes.
+fn as_rational(index: u64, fields: &[PlutusData]) -> Option<Value> {
+    let [numerator, denominator] = fields else {
+        return None;
+    };
+    if index != 0 {
+        return None;
+    }
+    let numerator = numerator.as_integer()?.to_str();
+    let denominator = denominator.as_integer()?.to_str();
+    let positive = BigNum::from_str(&denominator).is_ok_and(|d| !d.is_zero());
+    if !positive {
+        return None;
+    }
+    let ratio = match (numerator.parse::<f64>(), denominator.parse::<f64>()) {
+        (Ok(n), Ok(d)) => n / d,
+        _ => return None,
+    };
+    let text = format!("{}/{}", numerator, denominator);
+    Some(json!({ "numerator": numerator, "denominator": denominator, "text": text, "ratio": ratio }))
+}
+
+/// The shape a piece of data suggests for its on-chain type. Samples that
+/// disagree, e.g. list items of different kinds, widen to `Data`.
+#[derive(Debug, Clone, PartialEq)]
+enum Shape {
+    Int,
+    ByteArray(Option<usize>),
+    List(Box<Shape>),
+    Pairs(Box<Shape>, Box<Shape>),
+    Adt(BTreeMap<u64, Vec<Shape>>),
+    Known(&'static str),
+    // no samples yet, as the items of an empty list
+    Unknown,
+    Data,
+}
+
+fn shape_of(data: &PlutusData) -> Shape {
+    if let Some((index, fields)) = constr_fields(data) {
+        if as_address(index, &fields).is_some() {
+            return Shape::Known("Address");
+        }
+        if as_credential(data).is_some() {
+            return Shape::Known("Credential");
+        }
+        let mut constructors = BTreeMap::new();
+        constructors.insert(index, fields.iter().map(shape_of).collect());
+        return Shape::Adt(constructors);
+    }
+    if let Some(map) = data.as_map() {
+        let keys = map.keys();
+        let (mut key_shape, mut value_shape) = (Shape::Unknown, Shape::Unknown);
+        for i in 0..keys.len() {
+            let key = keys.get(i);
+            key_shape = unify(key_shape, shape_of(&key));
+            if let Some(values) = map.get(&key) {
+                for j in 0..values.len() {
+                    if let Some(value) = values.get(j) {
+                        value_shape = unify(value_shape, shape_of(&value));
+                    }
+                }
+            }
+        }
+        return Shape::Pairs(Box::new(key_shape), Box::new(value_shape));
+    }
+    if let Some(list) = data.as_list() {
+        let item = list_items(&list).iter().map(shape_of).fold(Shape::Unknown, unify);
+        return Shape::List(Box::new(item));
+    }
+    if data.as_integer().is_some() {
+        return Shape::Int;
+    }
+    match data.as_bytes() {
+        Some(bytes) => Shape::ByteArray(Some(bytes.len())),
+        None => Shape::Data,
+    }
+}
+
+fn unify(a: Shape, b: Shape) -> Shape {
+    match (a, b) {
+        (Shape::Unknown, other) | (other, Shape::Unknown) => other,
+        (Shape::ByteArray(a), Shape::ByteArray(b)) => Shape::ByteArray(if a == b { a } else { None }),
+        (Shape::List(a), Shape::List(b)) => Shape::List(Box::new(unify(*a, *b))),
+        (Shape::Pairs(ka, va), Shape::Pairs(kb, vb)) => {
+            Shape::Pairs(Box::new(unify(*ka, *kb)), Box::new(unify(*va, *vb)))
+        }
+        (Shape::Adt(mut a), Shape::Adt(b)) => {
+            for (index, fields) in b {
+                let merged = match a.remove(&index) {
+                    Some(seen) if seen.len() == fields.len() => seen.into_iter().zip(fields).map(|(x, y)| unify(x, y)).collect(),
+                    // same constructor with a different arity isn't one type
+                    Some(_) => return Shape::Data,
+                    None => fields,
+                };
+                a.insert(index, merged);
+            }
+            Shape::Adt(a)
+        }
+        (a, b) if a == b => a,
+        _ => Shape::Data,
+    }
+}
+
+/// Aiken-like type definitions guessed from a datum, the root type named
+/// `Datum` and nested ones `Datum1`, `Datum2`, ... in the order found, their
+/// constructors `DatumConstr0`, ... unless there's only the one.
+/// Constructors are positional since data carries no field names, and those
+/// between the seen indexes are left as placeholders to keep the indexes.
+#[derive(Serialize, Debug, Clone)]
+pub struct TypeGuess {
+    pub root: String,
+    pub definitions: String,
+}
+
+pub(crate) fn guess_type(samples: &[PlutusData]) -> TypeGuess {
+    let shape = samples.iter().map(shape_of).fold(Shape::Unknown, unify);
+    let mut printer = TypePrinter { root: "Datum", definitions: Vec::new() };
+    let root = printer.type_name(&shape);
+    TypeGuess { root, definitions: printer.definitions.join("\n\n") }
+}
+
+struct TypePrinter {
+    root: &'static str,
+    definitions: Vec<String>,
+}
+
+impl TypePrinter {
+    fn type_name(&mut self, shape: &Shape) -> String {
+        match shape {
+            Shape::Int => "Int".to_string(),
+            Shape::ByteArray(_) => "ByteArray".to_string(),
+            Shape::List(item) => format!("List<{}>", self.type_name(item)),
+            Shape::Pairs(key, value) => format!("Pairs<{}, {}>", self.type_name(key), self.type_name(value)),
+            Shape::Known(name) => name.to_string(),
+            Shape::Unknown | Shape::Data => "Data".to_string(),
+            Shape::Adt(constructors) => self.adt_name(constructors),
+        }
+    }
+
+    fn adt_name(&mut self, constructors: &BTreeMap<u64, Vec<Shape>>) -> String {
+        let seen: Vec<(u64, usize)> = constructors.iter().map(|(index, fields)| (*index, fields.len())).collect();
+        match seen.as_slice() {
+            [(0, 0), (1, 0)] => return "Bool".to_string(),
+            [(0, 1), (1, 0)] => return format!("Option<{}>", self.type_name(&constructors[&0][0])),
+            _ => {}
+        }
+
+        // reserve the name before the fields so nested types number after it
+        let name = match self.definitions.len() {
+            0 => self.root.to_string(),
+            n => format!("{}{}", self.root, n),
+        };
+        let slot = self.definitions.len();
+        self.definitions.push(String::new());
+
+        let last = constructors.keys().next_back().copied().unwrap_or_default();
+        // a stray large index would otherwise print thousands of placeholders
+        let indexes: Vec<u64> = if last <= MAX_PLACEHOLDER_INDEX {
+            (0..=last).collect()
+        } else {
+            constructors.keys().copied().collect()
+        };
+        let mut lines = Vec::new();
+        for index in indexes {
+            let constructor = if last == 0 { name.clone() } else { format!("{}Constr{}", name, index) };
+            let line = match constructors.get(&index) {
+                None => format!("  {} // constructor {} not seen", constructor, index),
+                Some(fields) if fields.is_empty() => format!("  {}", constructor),
+                Some(fields) => {
+                    let fields: Vec<String> = fields.iter().map(|field| self.field(field)).collect();
+                    format!("  {}(\n{}\n  )", constructor, fields.join("\n"))
+                }
+            };
+            lines.push(line);
+        }
+        self.definitions[slot] = format!("type {} {{\n{}\n}}", name, lines.join("\n"));
+        name
+    }
+
+    fn field(&mut self, shape: &Shape) -> String {
+        let hint = match shape {
+            Shape::ByteArray(Some(HASH28_LEN)) => " // 28 bytes, a key or script hash",
+            Shape::ByteArray(Some(32)) => " // 32 bytes, a tx id, datum hash or key",
+            _ => "",
+        };
+        format!("    {},{}", self.type_name(shape), hint)
+    }
+}
@@ -0,0 +1,516 @@
+use serde::Serialize;
+use serde_json::Value;
+use crate::bingen::wasm_bindgen;
+use crate::cbor::cbor_decoder::fromhex_to_js_error;
+use crate::cbor::text_strings::decode_cbor_value;
+use crate::input::sanitize_hex;
+use crate::js_error::JsError;
+use crate::schemas::to_versioned_json;
+
+// longest part of the error text quoted back per diagnostic
+const MAX_DETAILS_LEN: usize = 600;
+
+struct LedgerError {
+    name: &'static str,
+    ogmios_code: Option<i64>,
+    explanation: &'static str,
+    tx_fields: &'static [&'static str],
+}
+
+/// Ledger predicate failures by constructor name as the node prints them,
+/// with the Ogmios submit error code where there is a one-to-one match.
+const LEDGER_ERRORS: &[LedgerError] = &[
+    LedgerError {
+        name: "EraMismatch",
+        ogmios_code: Some(3005),
+        explanation: "The transaction was serialized for a different era than the one the node is in.",
+        tx_fields: &[],
+    },
+    LedgerError {
+        name: "BadInputsUTxO",
+        ogmios_code: Some(3117),
+        explanation: "Some inputs are not in the UTxO set: they were already spent, never existed, or belong to another network.",
+        tx_fields: &["inputs", "collateral", "reference_inputs"],
+    },
+    LedgerError {
+        name: "ValueNotConservedUTxO",
+        ogmios_code: Some(3123),
+        explanation: "Consumed value (inputs, withdrawals, deposit refunds, mint) does not equal produced value (outputs, fee, deposits, burn, donation).",
+        tx_fields: &["inputs", "outputs", "fee", "mint", "withdrawals", "certs", "donation"],
+    },
+    LedgerError {
+        name: "FeeTooSmallUTxO",
+        ogmios_code: Some(3122),
+        explanation: "The fee is below the minimum for the tx size, execution units and reference scripts.",
+        tx_fields: &["fee"],
+    },
+    LedgerError {
+        name: "OutsideValidityIntervalUTxO",
+        ogmios_code: Some(3118),
+        explanation: "The current slot is outside the validity interval of the transaction.",
+        tx_fields: &["ttl", "validity_interval_start"],
+    },
+    LedgerError {
+        name: "WrongNetworkWithdrawal",
+        ogmios_code: None,
+        explanation: "A withdrawal is from a reward address of another network.",
+        tx_fields: &["withdrawals"],
+    },
+    LedgerError {
+        name: "WrongNetworkInTxBody",
+        ogmios_code: None,
+        explanation: "The network id in the body is not the network of the node.",
+        tx_fields: &["network_id"],
+    },
+    LedgerError {
+        name: "WrongNetwork",
+        ogmios_code: Some(3124),
+        explanation: "An output address belongs to another network.",
+        tx_fields: &["outputs"],
+    },
+    LedgerError {
+        name: "OutputTooSmallUTxO",
+        ogmios_code: Some(3125),
+        explanation: "An output holds less ada than the minimum for its size.",
+        tx_fields: &["outputs"],
+    },
+    LedgerError {
+        name: "OutputTooBigUTxO",
+        ogmios_code: Some(3120),
+        explanation: "The value of an output is larger than max_value_size.",
+        tx_fields: &["outputs"],
+    },
+    LedgerError {
+        name: "MaxTxSizeUTxO",
+        ogmios_code: Some(3119),
+        explanation: "The serialized transaction is larger than max_tx_size.",
+        tx_fields: &[],
+    },
+    LedgerError {
+        name: "InputSetEmptyUTxO",
+        ogmios_code: Some(3121),
+        explanation: "The transaction has no inputs.",
+        tx_fields: &["inputs"],
+    },
+    LedgerError {
+        name: "InsufficientCollateral",
+        ogmios_code: Some(3128),
+        explanation: "Collateral is below collateral_percentage of the fee.",
+        tx_fields: &["collateral", "collateral_return", "total_collateral"],
+    },
+    LedgerError {
+        name: "ScriptsNotPaidUTxO",
+        ogmios_code: Some(3129),
+        explanation: "Collateral inputs are locked by scripts, collateral must be locked by keys.",
+        tx_fields: &["collateral"],
+    },
+    LedgerError {
+        name: "TooManyCollateralInputs",
+        ogmios_code: Some(3131),
+        explanation: "There are more collateral inputs than max_collateral_inputs.",
+        tx_fields: &["collateral"],
+    },
+    LedgerError {
+        name: "NoCollateralInputs",
+        ogmios_code: Some(3132),
+        explanation: "The transaction runs scripts but has no collateral inputs.",
+        tx_fields: &["collateral"],
+    },
+    LedgerError {
+        name: "CollateralContainsNonADA",
+        ogmios_code: Some(3133),
+        explanation: "Collateral holds native assets that are not sent back through collateral_return.",
+        tx_fields: &["collateral", "collateral_return"],
+    },
+    LedgerError {
+        name: "IncorrectTotalCollateralField",
+        ogmios_code: Some(3135),
+        explanation: "total_collateral does not equal collateral inputs minus collateral_return.",
+        tx_fields: &["total_collateral", "collateral_return"],
+    },
+    LedgerError {
+        name: "ExUnitsTooBigUTxO",
+        ogmios_code: Some(3134),
+        explanation: "The summed execution units of all redeemers exceed the per-transaction limit.",
+        tx_fields: &["witness_set.redeemers"],
+    },
+    LedgerError {
+        name: "MissingScriptWitnessesUTXOW",
+        ogmios_code: Some(3102),
+        explanation: "A script needed to validate the transaction is neither attached nor in a reference input.",
+        tx_fields: &["witness_set", "reference_inputs"],
+    },
+    LedgerError {
+        name: "ExtraneousScriptWitnessesUTXOW",
+        ogmios_code: Some(3104),
+        explanation: "A script is attached that no input, mint, certificate or withdrawal needs.",
+        tx_fields: &["witness_set"],
+    },
+    LedgerError {
+        name: "MissingVKeyWitnessesUTXOW",
+        ogmios_code: Some(3101),
+        explanation: "A signature from a spending, certificate, withdrawal or required signer key is missing.",
+        tx_fields: &["witness_set.vkeywitnesses", "required_signers"],
+    },
+    LedgerError {
+        name: "InvalidWitnessesUTXOW",
+        ogmios_code: Some(3100),
+        explanation: "A signature does not verify against the transaction body hash, usually because the body changed after signing.",
+        tx_fields: &["witness_set.vkeywitnesses"],
+    },
+    LedgerError {
+        name: "ScriptWitnessNotValidatingUTXOW",
+        ogmios_code: Some(3103),
+        explanation: "A native script evaluated to false: missing signature or outside its time lock.",
+        tx_fields: &["witness_set.native_scripts", "required_signers", "ttl", "validity_interval_start"],
+    },
+    LedgerError {
+        name: "MissingTxBodyMetadataHash",
+        ogmios_code: Some(3105),
+        explanation: "Auxiliary data is attached but the body has no auxiliary_data_hash.",
+        tx_fields: &["auxiliary_data_hash"],
+    },
+    LedgerError {
+        name: "MissingTxMetadata",
+        ogmios_code: Some(3106),
+        explanation: "The body has an auxiliary_data_hash but no auxiliary data is attached.",
+        tx_fields: &["auxiliary_data"],
+    },
+    LedgerError {
+        name: "ConflictingMetadataHash",
+        ogmios_code: Some(3107),
+        explanation: "auxiliary_data_hash does not match the attached auxiliary data.",
+        tx_fields: &["auxiliary_data_hash", "auxiliary_data"],
+    },
+    LedgerError {
+        name: "MissingRedeemers",
+        ogmios_code: Some(3109),
+        explanation: "A Plutus script needs a redeemer that the transaction does not have.",
+        tx_fields: &["witness_set.redeemers"],
+    },
+    LedgerError {
+        name: "ExtraRedeemers",
+        ogmios_code: Some(3110),
+        explanation: "A redeemer points to an input, policy, certificate or withdrawal that is not run by a Plutus script.",
+        tx_fields: &["witness_set.redeemers"],
+    },
+    LedgerError {
+        name: "MissingRequiredDatums",
+        ogmios_code: Some(3111),
+        explanation: "A spent script output has a datum hash but the datum is not in the witness set.",
+        tx_fields: &["witness_set.plutus_data"],
+    },
+    LedgerError {
+        name: "NotAllowedSupplementalDatums",
+        ogmios_code: Some(3112),
+        explanation: "The witness set has datums that no input or output refers to.",
+        tx_fields: &["witness_set.plutus_data"],
+    },
+    LedgerError {
+        name: "PPViewHashesDontMatch",
+        ogmios_code: Some(3113),
+        explanation: "script_data_hash does not match the redeemers, datums and cost models of the languages used.",
+        tx_fields: &["script_data_hash", "witness_set.redeemers", "witness_set.plutus_data"],
+    },
+    LedgerError {
+        name: "UnspendableUTxONoDatumHash",
+        ogmios_code: Some(3114),
+        explanation: "A script-locked input has no datum, it can't be spent by a Plutus V1/V2 script.",
+        tx_fields: &["inputs"],
+    },
+    LedgerError {
+        name: "ValidationTagMismatch",
+        ogmios_code: None,
+        explanation: "A Plutus script failed, or the tx is marked invalid while all scripts pass. Evaluate the redeemers to see which one fails.",
+        tx_fields: &["witness_set.redeemers", "is_valid"],
+    },
+    LedgerError {
+        name: "CollectErrors",
+        ogmios_code: None,
+        explanation: "The ledger could not build a script context, e.g. a redeemer points to nothing or a script is missing.",
+        tx_fields: &["witness_set.redeemers"],
+    },
+    LedgerError {
+        name: "StakeKeyRegisteredDELEG",
+        ogmios_code: Some(3145),
+        explanation: "A stake credential is registered again while already registered.",
+        tx_fields: &["certs"],
+    },
+    LedgerError {
+        name: "StakeKeyNotRegisteredDELEG",
+        ogmios_code: Some(3146),
+        explanation: "A certificate or withdrawal uses a stake credential that is not registered.",
+        tx_fields: &["certs", "withdrawals"],
+    },
+    LedgerError {
+        name: "WithdrawalsNotInRewardsCERTS",
+        ogmios_code: Some(3141),
+        explanation: "A withdrawal amount is not the full reward balance of the account, or the account does not exist.",
+        tx_fields: &["withdrawals"],
+    },
+    LedgerError {
+        name: "ConwayDRepAlreadyRegistered",
+        ogmios_code: Some(3152),
+        explanation: "The DRep is already registered.",
+        tx_fields: &["certs"],
+    },
+    LedgerError {
+        name: "ConwayDRepNotRegistered",
+        ogmios_code: Some(3153),
+        explanation: "The DRep is not registered.",
+        tx_fields: &["certs"],
+    },
+    LedgerError {
+        name: "DisallowedVoters",
+        ogmios_code: Some(3137),
+        explanation: "A voter is not allowed to vote on the governance action type.",
+        tx_fields: &["voting_procedures"],
+    },
+    LedgerError {
+        name: "GovActionsDoNotExist",
+        ogmios_code: Some(3138),
+        explanation: "A vote refers to a governance action that does not exist or has expired.",
+        tx_fields: &["voting_procedures"],
+    },
+    LedgerError {
+        name: "ProposalDepositIncorrect",
+        ogmios_code: Some(3155),
+        explanation: "The proposal deposit differs from gov_action_deposit.",
+        tx_fields: &["proposal_procedures"],
+    },
+    LedgerError {
+        name: "ConwayTreasuryValueMismatch",
+        ogmios_code: Some(3158),
+        explanation: "current_treasury_value differs from the actual treasury.",
+        tx_fields: &["current_treasury_value"],
+    },
+    LedgerError {
+        name: "BabbageNonDisjointRefInputs",
+        ogmios_code: Some(3164),
+        explanation: "An output is both spent and used as a reference input.",
+        tx_fields: &["inputs", "reference_inputs"],
+    },
+    LedgerError {
+        name: "ConwayTxRefScriptsSizeTooBig",
+        ogmios_code: Some(3166),
+        explanation: "The reference scripts of the transaction exceed the total size limit.",
+        tx_fields: &["reference_inputs"],
+    },
+];
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SubmitErrorDiagnostic {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<i64>,
+    pub explanation: String,
+    /// Transaction fields to look at, in CDDL naming.
+    pub tx_fields: Vec<String>,
+    /// `tx_hash#index` of the outputs the error is about, if it names any.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub utxo_refs: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DecodedSubmitError {
+    /// `ogmios`, `node_text` or `cbor`.
+    pub format: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub era: Option<String>,
+    pub diagnostics: Vec<SubmitErrorDiagnostic>,
+    pub raw: Value,
+}
+
+/// Turns the error a submit API returned into a list of ledger failures with
+/// an explanation and the tx fields involved. Takes an Ogmios JSON-RPC error,
+/// the text cardano-submit-api, Koios or Blockfrost pass through from the node,
+/// or CBOR hex from local tx submission. CBOR errors don't carry constructor
+/// names, those come back decoded to JSON without diagnostics.
+#[wasm_bindgen]
+pub fn decode_submit_error(error: &str) -> Result<String, JsError> {
+    let decoded = decode_submit_error_value(error)?;
+    to_versioned_json(&decoded)
+}
+
+pub(crate) fn decode_submit_error_value(error: &str) -> Result<DecodedSubmitError, JsError> {
+    if let Ok(hex) = sanitize_hex(error) {
+        let cbor = hex::decode(hex).map_err(fromhex_to_js_error)?;
+        return Ok(DecodedSubmitError {
+            format: "cbor".to_string(),
+            era: None,
+            diagnostics: Vec::new(),
+            raw: decode_cbor_value(&cbor)?,
+        });
+    }
+
+    let error = error.trim();
+    let raw: Value = serde_json::from_str(error).unwrap_or_else(|_| Value::String(error.to_string()));
+    if let Some(rpc_error) = ogmios_error(&raw) {
+        return Ok(decode_ogmios_error(rpc_error, raw.clone()));
+    }
+
+    let text = match &raw {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    };
+    Ok(DecodedSubmitError {
+        format: "node_text".to_string(),
+        era: find_era(&text),
+        diagnostics: scan_node_text(&text),
+        raw,
+    })
+}
+
+/// A JSON-RPC error object, either the whole response or just its `error`.
+fn ogmios_error(raw: &Value) -> Option<&Value> {
+    let error = raw.get("error").filter(|e| e.is_object()).unwrap_or(raw);
+    error.get("code")?.as_i64()?;
+    error.get("message")?.as_str()?;
+    Some(error)
+}
+
+fn decode_ogmios_error(error: &Value, raw: Value) -> DecodedSubmitError {
+    let code = error.get("code").and_then(Value::as_i64);
+    let message = error.get("message").and_then(Value::as_str).unwrap_or_default();
+    let mut utxo_refs = Vec::new();
+    if let Some(data) = error.get("data") {
+        collect_ogmios_refs(data, &mut utxo_refs);
+    }
+
+    let known = LEDGER_ERRORS.iter().find(|e| e.ogmios_code.is_some() && e.ogmios_code == code);
+    let diagnostic = SubmitErrorDiagnostic {
+        name: known.map_or("Unknown", |e| e.name).to_string(),
+        code,
+        explanation: known.map_or(message, |e| e.explanation).to_string(),
+        tx_fields: known.map_or(Vec::new(), |e| to_strings(e.tx_fields)),
+        utxo_refs,
+        details: Some(message.to_string()),
+    };
+    DecodedSubmitError {
+        format: "ogmios".to_string(),
+        era: None,
+        diagnostics: vec![diagnostic],
+        raw,
+    }
+}
+
+/// Ogmios lists output references as `{ "transaction": { "id" }, "index" }`.
+fn collect_ogmios_refs(value: &Value, refs: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            let id = map.get("transaction").and_then(|t| t.get("id")).and_then(Value::as_str);
+            let index = map.get("index").and_then(Value::as_u64);
+            if let (Some(id), Some(index)) = (id, index) {
+                refs.push(format!("{}#{}", id, index));
+            } else {
+                map.values().for_each(|v| collect_ogmios_refs(v, refs));
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|v| collect_ogmios_refs(v, refs)),
+        _ => {}
+    }
+}
+
+/// The node shows failures with Haskell `show`, so constructor names appear
+/// verbatim. Diagnostics come in the order the failures appear in the text.
+fn scan_node_text(text: &str) -> Vec<SubmitErrorDiagnostic> {
+    let mut found: Vec<(usize, SubmitErrorDiagnostic)> = Vec::new();
+    for known in LEDGER_ERRORS {
+        let Some(position) = find_constructor(text, known.name) else {
+            continue;
+        };
+        let details = constructor_snippet(text, position);
+        let utxo_refs = if known.name == "BadInputsUTxO" {
+            find_tx_ins(&details)
+        } else {
+            Vec::new()
+        };
+        found.push((
+            position,
+            SubmitErrorDiagnostic {
+                name: known.name.to_string(),
+                code: None,
+                explanation: known.explanation.to_string(),
+                tx_fields: to_strings(known.tx_fields),
+                utxo_refs,
+                details: Some(details),
+            },
+        ));
+    }
+    found.sort_by_key(|(position, _)| *position);
+    found.into_iter().map(|(_, diagnostic)| diagnostic).collect()
+}
+
+/// First occurrence of `name` as a whole identifier, so `WrongNetwork` doesn't
+/// match inside `WrongNetworkWithdrawal`.
+fn find_constructor(text: &str, name: &str) -> Option<usize> {
+    let is_ident = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    text.match_indices(name).map(|(i, _)| i).find(|&i| {
+        let before = text[..i].chars().next_back().is_none_or(|c| !is_ident(c));
+        let after = text[i + name.len()..].chars().next().is_none_or(|c| !is_ident(c));
+        before && after
+    })
+}
+
+/// The constructor and its arguments: everything up to the parenthesis that
+/// closes the enclosing expression.
+fn constructor_snippet(text: &str, start: usize) -> String {
+    let mut depth = 0i32;
+    let mut end = text.len();
+    for (i, c) in text[start..].char_indices() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => {
+                depth -= 1;
+                if depth < 0 {
+                    end = start + i;
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    let snippet: String = text[start..end].trim_end_matches([',', ' ']).chars().take(MAX_DETAILS_LEN).collect();
+    snippet
+}
+
+/// `TxIn (TxId {unTxId = SafeHash "<hash>"}) (TxIx 0)`, older and newer node
+/// versions differ in the quoting and in how `TxIx` is printed.
+fn find_tx_ins(text: &str) -> Vec<String> {
+    let mut refs = Vec::new();
+    let mut rest = text;
+    while let Some(position) = rest.find("SafeHash") {
+        rest = &rest[position + "SafeHash".len()..];
+        let hash_start = rest.find(|c: char| c.is_ascii_hexdigit()).unwrap_or(rest.len());
+        let hash: String = rest[hash_start..].chars().take_while(|c| c.is_ascii_hexdigit()).collect();
+        if hash.len() != 64 {
+            continue;
+        }
+        let Some(ix_position) = rest.find("TxIx") else {
+            break;
+        };
+        let after = &rest[ix_position + "TxIx".len()..];
+        let digits_start = after.find(|c: char| c.is_ascii_digit()).unwrap_or(after.len());
+        let index: String = after[digits_start..].chars().take_while(|c| c.is_ascii_digit()).collect();
+        if !index.is_empty() {
+            refs.push(format!("{}#{}", hash, index));
+        }
+    }
+    refs
+}
+
+fn find_era(text: &str) -> Option<String> {
+    let position = text.find("ShelleyBasedEra")? + "ShelleyBasedEra".len();
+    let era: String = text[position..].chars().take_while(|c| c.is_ascii_alphabetic()).collect();
+    if era.is_empty() {
+        None
+    } else {
+        Some(era)
+    }
+}
+
+fn to_strings(fields: &[&str]) -> Vec<String> {
+    fields.iter().map(|f| f.to_string()).collect()
+}
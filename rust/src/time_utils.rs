@@ -0,0 +1,38 @@
+#[cfg(all(target_arch = "wasm32", not(target_os = "emscripten")))]
+pub(crate) fn now_ms() -> f64 {
+    js_sys::Date::now()
+}
+
+// std::time::Instant is not available on wasm32-unknown-unknown, so the native
+// clock is wall time as well to keep both targets comparable
+#[cfg(not(all(target_arch = "wasm32", not(target_os = "emscripten"))))]
+pub(crate) fn now_ms() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs_f64() * 1000.0)
+        .unwrap_or_default()
+}
+
+#[cfg(all(feature = "providers", target_arch = "wasm32", not(target_os = "emscripten")))]
+pub(crate) async fn sleep_ms(ms: u64) {
+    use wasm_bindgen::{JsCast, JsValue};
+    let promise = js_sys::Promise::new(&mut |resolve, _| {
+        let set_timeout = js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("setTimeout"))
+            .ok()
+            .and_then(|f| f.dyn_into::<js_sys::Function>().ok());
+        match set_timeout {
+            Some(set_timeout) => {
+                let _ = set_timeout.call2(&JsValue::NULL, &resolve, &JsValue::from_f64(ms as f64));
+            }
+            None => {
+                let _ = resolve.call0(&JsValue::NULL);
+            }
+        }
+    });
+    let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+}
+
+#[cfg(all(feature = "providers", not(all(target_arch = "wasm32", not(target_os = "emscripten")))))]
+pub(crate) async fn sleep_ms(ms: u64) {
+    tokio::time::sleep(std::time::Duration::from_millis(ms)).await;
+}
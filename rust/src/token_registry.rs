@@ -0,0 +1,64 @@
+use reqwest::{Client, StatusCode};
+use serde::Serialize;
+use serde_json::Value;
+use crate::js_error::JsError;
+use crate::koios_client::error_mapper::{check_status, to_js_error};
+
+pub(crate) const DEFAULT_TOKEN_REGISTRY_URL: &str = "https://tokens.cardano.org";
+
+/// The parts of an off-chain token registry entry that matter for display.
+#[derive(Serialize, Debug, Clone)]
+pub struct RegistryEntry {
+    pub subject: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ticker: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decimals: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    pub has_logo: bool,
+}
+
+impl RegistryEntry {
+    /// Registry properties are `{ "value": ..., "signatures": [...] }` objects.
+    pub(crate) fn from_value(subject: &str, value: &Value) -> Self {
+        let property = |name: &str| value.get(name).and_then(|p| p.get("value"));
+        let string = |name: &str| property(name).and_then(Value::as_str).map(str::to_string);
+        RegistryEntry {
+            subject: subject.to_string(),
+            name: string("name"),
+            ticker: string("ticker"),
+            decimals: property("decimals").and_then(Value::as_u64),
+            description: string("description"),
+            url: string("url"),
+            has_logo: property("logo")
+                .and_then(Value::as_str)
+                .is_some_and(|logo| !logo.is_empty()),
+        }
+    }
+}
+
+/// Looks up `policy_id ++ asset_name_hex` in a CIP-26 token registry.
+/// Unregistered tokens are `None`, not an error.
+pub(crate) async fn fetch_registry_entry(registry_url: &str, subject: &str) -> Result<Option<RegistryEntry>, JsError> {
+    let url = format!("{}/metadata/{}", registry_url.trim_end_matches('/'), subject);
+    let response = Client::new()
+        .get(url)
+        .header("Accept", "application/json")
+        .send()
+        .await
+        .map_err(|err| to_js_error(err, "token_registry.send"))?;
+    if response.status() == StatusCode::NOT_FOUND || response.status() == StatusCode::NO_CONTENT {
+        return Ok(None);
+    }
+    let value: Value = check_status(response, "token_registry.status")
+        .await?
+        .json()
+        .await
+        .map_err(|err| to_js_error(err, "token_registry.parse"))?;
+    Ok(Some(RegistryEntry::from_value(subject, &value)))
+}
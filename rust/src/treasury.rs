@@ -0,0 +1,86 @@
+use serde::Serialize;
+use crate::bingen::wasm_bindgen;
+use crate::cbor::cbor_decoder::{minicbor_to_js_error, visit_tx_body};
+use crate::error::ErrorCode;
+use crate::input::decode_hex;
+use crate::js_error::JsError;
+use crate::schemas::to_versioned_json;
+
+// Conway transaction body keys
+const CURRENT_TREASURY_VALUE_KEY: u64 = 21;
+const DONATION_KEY: u64 = 22;
+
+/// A treasury field the ledger would reject, `rule` names the ledger check.
+#[derive(Serialize, Debug, Clone)]
+pub struct TreasuryIssue {
+    pub rule: String,
+    pub message: String,
+}
+
+/// The Conway treasury fields of a tx body. `donation` is lovelace moved to
+/// the treasury at the end of the epoch, it is produced value like the fee.
+/// `current_treasury_value` asserts the treasury as seen by the tx, so a
+/// treasury withdrawal can't be submitted against a different treasury.
+#[derive(Serialize, Debug, Clone)]
+pub struct TreasuryFields {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub donation: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_treasury_value: Option<u64>,
+    pub issues: Vec<TreasuryIssue>,
+}
+
+/// Decodes the `donation` and `current_treasury_value` fields of a Conway tx
+/// and checks them: a donation must be positive, and when the actual
+/// treasury (lovelace, e.g. from Koios `totals`) is given, the stated treasury
+/// value must equal it. The fields are read from the raw body, so a tx that
+/// full decoders reject for a zero donation is still reported.
+#[wasm_bindgen]
+pub fn decode_treasury_fields(tx_hex: &str, actual_treasury: Option<String>) -> Result<String, JsError> {
+    let tx_bytes = decode_hex(tx_hex)?;
+    let actual_treasury = actual_treasury
+        .map(|value| {
+            value
+                .trim()
+                .parse::<u64>()
+                .map_err(|e| ErrorCode::InvalidInput.error(&format!("Invalid treasury value: {}", e)))
+        })
+        .transpose()?;
+    to_versioned_json(&treasury_fields(&tx_bytes, actual_treasury)?)
+}
+
+pub(crate) fn treasury_fields(tx_bytes: &[u8], actual_treasury: Option<u64>) -> Result<TreasuryFields, JsError> {
+    let (donation, current_treasury_value) = read_treasury_fields(tx_bytes).map_err(minicbor_to_js_error)?;
+
+    let mut issues = Vec::new();
+    if donation == Some(0) {
+        issues.push(TreasuryIssue {
+            rule: "ZeroTreasuryDonation".to_string(),
+            message: "The donation field is present with 0 lovelace, it must be positive or left out.".to_string(),
+        });
+    }
+    if let (Some(stated), Some(actual)) = (current_treasury_value, actual_treasury) {
+        if stated != actual {
+            issues.push(TreasuryIssue {
+                rule: "CurrentTreasuryValueIncorrect".to_string(),
+                message: format!("The tx states a treasury of {} lovelace, the actual treasury is {}.", stated, actual),
+            });
+        }
+    }
+
+    Ok(TreasuryFields { donation, current_treasury_value, issues })
+}
+
+fn read_treasury_fields(tx_bytes: &[u8]) -> Result<(Option<u64>, Option<u64>), minicbor::decode::Error> {
+    let mut donation = None;
+    let mut current_treasury_value = None;
+    visit_tx_body(tx_bytes, |key, decoder| {
+        match key {
+            DONATION_KEY => donation = Some(decoder.u64()?),
+            CURRENT_TREASURY_VALUE_KEY => current_treasury_value = Some(decoder.u64()?),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    })?;
+    Ok((donation, current_treasury_value))
+}
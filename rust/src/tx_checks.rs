@@ -0,0 +1,222 @@
+use minicbor::data::Type;
+use minicbor::Decoder;
+use serde::Serialize;
+use crate::bingen::wasm_bindgen;
+use crate::cbor::cbor_decoder::{minicbor_to_js_error, visit_tx_body};
+#[cfg(feature = "csl-decoders")]
+use crate::error::ErrorCode;
+#[cfg(feature = "csl-decoders")]
+use crate::input::sanitize_hex;
+use crate::input::decode_hex;
+use crate::js_error::JsError;
+use crate::schemas::to_versioned_json;
+#[cfg(feature = "providers")]
+use crate::NetworkType;
+
+// Conway transaction body keys
+const INPUTS_KEY: u64 = 0;
+const COLLATERAL_KEY: u64 = 13;
+const REFERENCE_INPUTS_KEY: u64 = 18;
+
+/// A ledger rule the tx breaks, `rule` is the name of the ledger failure and
+/// `refs` the offending `tx_hash#index` input refs or output indexes.
+#[derive(Serialize, Debug, Clone)]
+pub struct TxCheckIssue {
+    pub rule: String,
+    pub message: String,
+    pub refs: Vec<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct TxCheckReport {
+    pub valid: bool,
+    pub issues: Vec<TxCheckIssue>,
+}
+
+impl TxCheckReport {
+    pub(crate) fn new(issues: Vec<TxCheckIssue>) -> Self {
+        TxCheckReport { valid: issues.is_empty(), issues }
+    }
+}
+
+/// Checks the input sets of a tx the way Conway does before any script runs:
+/// `inputs` can't be empty, no set may list an input twice, and an input
+/// can't be both spent and referenced. The sets are read from the raw body,
+/// full decoders silently drop duplicates.
+#[wasm_bindgen]
+pub fn check_input_sets(tx_hex: &str) -> Result<String, JsError> {
+    let tx_bytes = decode_hex(tx_hex)?;
+    to_versioned_json(&TxCheckReport::new(input_set_issues(&tx_bytes)?))
+}
+
+pub(crate) fn input_set_issues(tx_bytes: &[u8]) -> Result<Vec<TxCheckIssue>, JsError> {
+    let mut inputs = Vec::new();
+    let mut reference_inputs = Vec::new();
+    let mut collateral = Vec::new();
+    visit_tx_body(tx_bytes, |key, decoder| {
+        match key {
+            INPUTS_KEY => inputs = read_input_set(decoder)?,
+            REFERENCE_INPUTS_KEY => reference_inputs = read_input_set(decoder)?,
+            COLLATERAL_KEY => collateral = read_input_set(decoder)?,
+            _ => return Ok(false),
+        }
+        Ok(true)
+    })
+    .map_err(minicbor_to_js_error)?;
+
+    let mut issues = Vec::new();
+    if inputs.is_empty() {
+        issues.push(TxCheckIssue {
+            rule: "InputSetEmptyUTxO".to_string(),
+            message: "The tx spends no inputs.".to_string(),
+            refs: Vec::new(),
+        });
+    }
+    for (name, set) in [("inputs", &inputs), ("reference_inputs", &reference_inputs), ("collateral", &collateral)] {
+        let duplicates = duplicates(set);
+        if !duplicates.is_empty() {
+            issues.push(TxCheckIssue {
+                rule: "DuplicateInputs".to_string(),
+                message: format!("{} lists the same input more than once.", name),
+                refs: duplicates,
+            });
+        }
+    }
+    let mut overlap: Vec<String> = Vec::new();
+    for input in &inputs {
+        if reference_inputs.contains(input) && !overlap.contains(input) {
+            overlap.push(input.clone());
+        }
+    }
+    if !overlap.is_empty() {
+        issues.push(TxCheckIssue {
+            rule: "BabbageNonDisjointRefInputs".to_string(),
+            message: "Inputs are both spent and referenced, Conway requires the sets to be disjoint.".to_string(),
+            refs: overlap,
+        });
+    }
+    Ok(issues)
+}
+
+/// Input refs of a set, with or without the Conway set tag.
+fn read_input_set(decoder: &mut Decoder) -> Result<Vec<String>, minicbor::decode::Error> {
+    if decoder.datatype()? == Type::Tag {
+        decoder.tag()?;
+    }
+    let len = decoder.array()?;
+    let mut refs = Vec::new();
+    loop {
+        match len {
+            Some(len) if refs.len() as u64 >= len => break,
+            None if decoder.datatype()? == Type::Break => {
+                decoder.set_position(decoder.position() + 1);
+                break;
+            }
+            _ => {}
+        }
+        decoder.array()?;
+        let tx_hash = hex::encode(decoder.bytes()?);
+        let index = decoder.u64()?;
+        refs.push(format!("{}#{}", tx_hash, index));
+    }
+    Ok(refs)
+}
+
+/// Refs listed more than once, each reported once in first-seen order.
+fn duplicates(refs: &[String]) -> Vec<String> {
+    let mut duplicates: Vec<String> = Vec::new();
+    for (i, r) in refs.iter().enumerate() {
+        if refs[..i].contains(r) && !duplicates.contains(r) {
+            duplicates.push(r.clone());
+        }
+    }
+    duplicates
+}
+
+/// Checks that every output, the collateral return and every withdrawal
+/// belong to one network: `expected_network_id` (0 testnets, 1 mainnet) when
+/// given, else the network id of the body, else the network of the first
+/// output. A body network id different from the expected one is reported too.
+#[cfg(feature = "csl-decoders")]
+#[wasm_bindgen]
+pub fn check_output_networks(tx_hex: &str, expected_network_id: Option<u8>) -> Result<String, JsError> {
+    let tx = cardano_serialization_lib::Transaction::from_hex(sanitize_hex(tx_hex)?)
+        .map_err(|e| ErrorCode::DecodeFailure.error(&format!("Error decoding transaction: {:?}", e)))?;
+    to_versioned_json(&TxCheckReport::new(network_issues(&tx, expected_network_id)))
+}
+
+/// Same as `check_output_networks` against the network of the selected provider.
+#[cfg(feature = "providers")]
+#[wasm_bindgen]
+pub fn check_output_networks_for_network(tx_hex: &str, network: NetworkType) -> Result<String, JsError> {
+    check_output_networks(tx_hex, Some(network.network_id()))
+}
+
+#[cfg(feature = "csl-decoders")]
+pub(crate) fn network_issues(tx: &cardano_serialization_lib::Transaction, expected_network_id: Option<u8>) -> Vec<TxCheckIssue> {
+    use cardano_serialization_lib::NetworkIdKind;
+
+    let body = tx.body();
+    let body_network_id = body.network_id().map(|id| match id.kind() {
+        NetworkIdKind::Mainnet => 1,
+        NetworkIdKind::Testnet => 0,
+    });
+
+    let mut addresses = Vec::new();
+    let outputs = body.outputs();
+    for i in 0..outputs.len() {
+        addresses.push((format!("outputs[{}]", i), outputs.get(i).address()));
+    }
+    if let Some(collateral_return) = body.collateral_return() {
+        addresses.push(("collateral_return".to_string(), collateral_return.address()));
+    }
+    if let Some(withdrawals) = body.withdrawals() {
+        let keys = withdrawals.keys();
+        for i in 0..keys.len() {
+            addresses.push((format!("withdrawals[{}]", i), keys.get(i).to_address()));
+        }
+    }
+
+    let mut issues = Vec::new();
+    if let (Some(expected), Some(body_id)) = (expected_network_id, body_network_id) {
+        if expected != body_id {
+            issues.push(TxCheckIssue {
+                rule: "WrongNetworkInTxBody".to_string(),
+                message: format!("The body network id is {} ({}), expected {} ({}).", body_id, network_name(body_id), expected, network_name(expected)),
+                refs: vec!["network_id".to_string()],
+            });
+        }
+    }
+
+    let reference = expected_network_id
+        .or(body_network_id)
+        .or_else(|| addresses.first().and_then(|(_, address)| address.network_id().ok()));
+    let Some(reference) = reference else {
+        return issues;
+    };
+    let mismatched: Vec<String> = addresses
+        .into_iter()
+        .filter(|(_, address)| address.network_id().is_ok_and(|id| id != reference))
+        .map(|(location, _)| location)
+        .collect();
+    if !mismatched.is_empty() {
+        issues.push(TxCheckIssue {
+            rule: "WrongNetwork".to_string(),
+            message: format!(
+                "Addresses of another network than {} ({}), funds sent there can't be spent on this network.",
+                reference,
+                network_name(reference)
+            ),
+            refs: mismatched,
+        });
+    }
+    issues
+}
+
+#[cfg(feature = "csl-decoders")]
+fn network_name(network_id: u8) -> &'static str {
+    match network_id {
+        1 => "mainnet",
+        _ => "testnet",
+    }
+}
@@ -1,6 +1,6 @@
 import {Box, Button, Input, MenuItem, Select, TextField, Typography} from "@mui/material";
 import {useEffect, useRef, useState} from "react";
-import {execute_tx_scripts_for_specific_network, get_utxo_list_from_tx, NetworkType} from "cquisitor_wasm"
+import {execute_tx_scripts_with_abort_signal, get_utxo_list_from_tx, NetworkType} from "cquisitor_wasm"
 
 function mapNetworkTypeToUrl(networkType) {
     switch (networkType) {
@@ -15,23 +15,38 @@ function mapNetworkTypeToUrl(networkType) {
     }
 }
 
+// errors come as JSON with a code, provider failures carry kind, status and
+// retryable in details
+function providerErrorOrMessage(error) {
+    try {
+        return JSON.parse(error.message);
+    } catch {
+        return error.toString();
+    }
+}
+
 export const PlutusExecutorMenu = ({show, cborHex, onResult}) => {
     const [networkType, setNetworkType] = useState("");
-    const [apiToken, setApiToken] = useState(localStorage.getItem("apiToken") || "Kios API key");
+    const [apiToken, setApiToken] = useState(localStorage.getItem("apiToken") || "");
     const [executing, setExecuting] = useState(false);
     const availableNetworks = ['mainnet', 'preprod', 'preview'];
+    const abortController = useRef(null);
+
+    // stop pending Koios requests when the menu goes away
+    useEffect(() => () => abortController.current?.abort(), []);
 
     const executeScripts = async (executionState) => {
         if (!executionState && show) {
             setExecuting(true);
             try {
                 onResult("Executing...");
-                const result = await execute_tx_scripts_for_specific_network(cborHex, mapNetworkTypeToUrl(networkType), apiToken);
+                abortController.current = new AbortController();
+                const result = await execute_tx_scripts_with_abort_signal(cborHex, mapNetworkTypeToUrl(networkType), apiToken || undefined, abortController.current.signal);
                 console.log("result", result)
-                onResult(JSON.parse(result));
+                onResult(result);
             } catch (e) {
                 console.error("error", e)
-                onResult({error: e.toString()});
+                onResult({error: providerErrorOrMessage(e)});
             }
             setExecuting(false);
         }
@@ -65,7 +80,7 @@ export const PlutusExecutorMenu = ({show, cborHex, onResult}) => {
                 ))}
             </Select>
             <Box sx={{ flexGrow: 1 }} />
-            <Input defaultValue={apiToken} onInput={
+            <Input defaultValue={apiToken} placeholder="Koios API key (optional)" onInput={
                 (e) => {
                     setApiToken(e.target.value);
                     localStorage.setItem("apiToken", e.target.value);